@@ -1,19 +1,37 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use std::{
+    path::{MAIN_SEPARATOR, Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use clap::{Args, Parser, Subcommand};
 use picasa_core::{
-    config::Config,
-    database,
-    models::Photo,
+    config::{Config, EmbeddingsConfig, ScannerConfig},
+    database::{self, DbPool},
+    models::{
+        Job, JobStage, JobStageSummary, JobStatus, Library, MediaType, NewJob, NewLibrary, Photo,
+        PhotoErrorWithPath,
+    },
     repositories::{
-        PgGeoRepository, PgPhotoRepository, face::repository::PgFaceRepository,
-        person::repository::PgPersonRepository,
+        JobFindFilters, PgGeoRepository, PgJobRepository, PgLibraryRepository,
+        PgPhotoErrorRepository, PgPhotoRepository, PhotoErrorFindFilters, PhotoRepository,
+        ValueRange, face::repository::PgFaceRepository, person::repository::PgPersonRepository,
     },
     services::{
-        FaceDetectionService, FaceRecognitionService, PhotoEmbedderService, PhotoSearchParams,
-        PhotoSearchService,
-        embedders::{ClipImageEmbedder, ClipTextEmbedder},
+        DuplicateService, FaceDetectionService, FaceRecognitionService, JobService, LibraryService,
+        NearDuplicateService, PhotoEmbedderService, PhotoErrorService, PhotoSearchParams,
+        PhotoSearchService, PhotoWatcher, PipelineJobHandler, SimilarTo, XmpExportService,
+        duplicates::{
+            DuplicateAction, DuplicateGroup, DuplicateSummary, KeeperPreference, KeeperStrategy,
+        },
+        embedders::{ClipImageEmbedder, load_text_embedder},
         face_recognition::{RecognitionAction, RecognitionConfig, RecognitionResult},
-        photo_scanner,
+        near_duplicates::{NearDuplicateCluster, NearDuplicateConfig},
+        photo_import::{self, ImportOptions, ImportSummary},
+        photo_scanner::{self, ScanOptions, ScanSummary},
+        thumbnails::{ThumbnailCache, ThumbnailService, ThumbnailSummary},
+        xmp_export::XmpExportSummary,
     },
 };
 use tabled::{Table, Tabled, settings::Style};
@@ -29,6 +47,63 @@ pub struct Cli {
     command: Commands,
 }
 
+/// Rules selecting the files to scan, added to the `[scanner]` configuration.
+#[derive(Args)]
+struct ScanRuleArgs {
+    /// Glob patterns of files to scan
+    #[arg(
+        long = "include",
+        value_name = "GLOB",
+        help = "Only scan files matching this glob pattern (can be repeated)"
+    )]
+    include: Vec<String>,
+
+    /// Glob patterns of files and directories to skip
+    #[arg(
+        long = "exclude",
+        value_name = "GLOB",
+        help = "Skip files and directories matching this glob pattern (can be repeated)"
+    )]
+    exclude: Vec<String>,
+
+    /// Minimum file size in bytes
+    #[arg(
+        long = "min-file-size",
+        help = "Skip files smaller than this size in bytes"
+    )]
+    min_file_size: Option<u64>,
+
+    /// Minimum photo width in pixels
+    #[arg(
+        long = "min-width",
+        help = "Skip photos narrower than this width in pixels"
+    )]
+    min_width: Option<u32>,
+
+    /// Minimum photo height in pixels
+    #[arg(
+        long = "min-height",
+        help = "Skip photos shorter than this height in pixels"
+    )]
+    min_height: Option<u32>,
+}
+
+impl ScanRuleArgs {
+    /// Builds scan options from the configured rules, extended or overridden by the arguments.
+    fn into_scan_options(self, config: &ScannerConfig) -> ScanOptions {
+        ScanOptions {
+            workers: config.workers,
+            include: config.include.iter().cloned().chain(self.include).collect(),
+            exclude: config.exclude.iter().cloned().chain(self.exclude).collect(),
+            min_file_size: self.min_file_size.unwrap_or(config.min_file_size),
+            min_width: self.min_width.unwrap_or(config.min_width),
+            min_height: self.min_height.unwrap_or(config.min_height),
+            default_timezone: config.default_timezone.clone(),
+            ..Default::default()
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Scan photos in a directory
@@ -37,22 +112,164 @@ enum Commands {
         #[arg(help = "The root directory path to scan for photos")]
         root_directory: String,
 
-        /// Extract EXIF data from photos (default: false)
-        #[arg(long = "with-exif", help = "Enable EXIF data extraction")]
+        /// Extract EXIF, XMP and IPTC metadata from photos (default: false)
+        #[arg(
+            long = "with-exif",
+            help = "Enable EXIF, XMP and IPTC metadata extraction"
+        )]
+        with_exif: bool,
+
+        /// Compute file hashes (default: false)
+        #[arg(long = "with-hash", help = "Enable file hash computation")]
+        with_hash: bool,
+
+        /// Remove photos whose file no longer exists (default: false)
+        #[arg(
+            long = "prune",
+            help = "Remove indexed photos whose file no longer exists under the root directory"
+        )]
+        prune: bool,
+
+        /// Rescan files that did not change since the last scan (default: false)
+        #[arg(
+            long = "force",
+            help = "Re-extract metadata and recompute hashes of unchanged files"
+        )]
+        force: bool,
+
+        /// Number of photos committed at once
+        #[arg(
+            long = "batch-size",
+            help = "Number of photos committed to the database at once",
+            default_value = "500"
+        )]
+        batch_size: usize,
+
+        #[command(flatten)]
+        rules: ScanRuleArgs,
+    },
+    /// Watch a directory and keep the index up to date
+    Watch {
+        /// The root directory path to watch for photos
+        #[arg(help = "The root directory path to watch for photos")]
+        root_directory: String,
+
+        /// Extract EXIF, XMP and IPTC metadata from photos (default: false)
+        #[arg(
+            long = "with-exif",
+            help = "Enable EXIF, XMP and IPTC metadata extraction"
+        )]
         with_exif: bool,
 
         /// Compute file hashes (default: false)
         #[arg(long = "with-hash", help = "Enable file hash computation")]
         with_hash: bool,
+
+        /// Number of photos committed at once
+        #[arg(
+            long = "batch-size",
+            help = "Number of photos committed to the database at once",
+            default_value = "500"
+        )]
+        batch_size: usize,
+
+        /// Delay to wait for changes to settle, in milliseconds
+        #[arg(
+            long = "debounce",
+            help = "Delay in milliseconds to wait for changes to settle before indexing",
+            default_value = "2000"
+        )]
+        debounce: u64,
+
+        #[command(flatten)]
+        rules: ScanRuleArgs,
+    },
+    /// Copy new photos from a directory, such as a memory card, into a library
+    Import {
+        /// The directory path to import photos from
+        #[arg(help = "The directory path to import photos from")]
+        source: String,
+
+        /// The name of the library to import into
+        #[arg(
+            long = "library",
+            value_name = "NAME",
+            help = "The name of the library to import photos into"
+        )]
+        library: String,
+
+        /// Layout of imported photos below the library root
+        #[arg(
+            long = "template",
+            help = "Directory layout below the library root, such as {year}/{month}/{date}_{camera} (default: from config)"
+        )]
+        template: Option<String>,
+
+        /// List photos to import without copying them (default: false)
+        #[arg(long = "dry-run", help = "List photos to import without copying them")]
+        dry_run: bool,
     },
     /// Generate image embeddings for photos
-    Embed,
+    Embed {
+        /// Id of the model in the registry
+        #[arg(
+            long = "model",
+            help = "Id of the model computing the embeddings (default: the active model)"
+        )]
+        model: Option<String>,
+
+        /// Compute the embeddings of every photo again (default: false)
+        #[arg(
+            long = "reembed",
+            help = "Compute the embeddings of every photo again, replacing the existing ones"
+        )]
+        reembed: bool,
+
+        /// Number of photos embedded at once
+        #[arg(
+            long = "batch-size",
+            help = "Number of photos embedded at once (default: from config)"
+        )]
+        batch_size: Option<usize>,
+
+        /// Number of threads running the model on the CPU
+        #[arg(
+            long = "threads",
+            help = "Number of threads running the model on the CPU, one per CPU when 0 (default: from config)"
+        )]
+        threads: Option<usize>,
+    },
+    /// Pre-generate thumbnails of indexed photos and remove stale ones
+    Thumbnails {
+        /// Number of parallel workers
+        #[arg(
+            long = "workers",
+            help = "Number of parallel workers, one per CPU when 0 (default: from config)"
+        )]
+        workers: Option<usize>,
+    },
     /// Search photos
     Search {
         /// The query string to search for
         #[arg(long = "text", help = "The semantic query string to search for photos")]
         text: Option<String>,
 
+        /// Id of a photo whose similar photos are searched
+        #[arg(
+            long = "similar-to",
+            conflicts_with_all = ["text", "similar_to_image"],
+            help = "Search photos similar to the photo with this id"
+        )]
+        similar_to: Option<i32>,
+
+        /// Path of an image file whose similar photos are searched
+        #[arg(
+            long = "similar-to-image",
+            conflicts_with = "text",
+            help = "Search photos similar to an image file, which doesn't need to be indexed"
+        )]
+        similar_to_image: Option<String>,
+
         /// Optional similarity threshold
         #[arg(long = "threshold", help = "Similarity threshold for search results")]
         threshold: Option<f32>,
@@ -79,6 +296,108 @@ enum Commands {
         )]
         date_to: Option<String>,
 
+        /// Filter by media type
+        #[arg(
+            long = "media-type",
+            help = "Filter results by media type (photo or video)"
+        )]
+        media_type: Option<MediaType>,
+
+        /// Minimum aperture f-number
+        #[arg(
+            long = "aperture-min",
+            help = "Filter photos with an f-number of at least this value"
+        )]
+        aperture_min: Option<f32>,
+
+        /// Maximum aperture f-number
+        #[arg(
+            long = "aperture-max",
+            help = "Filter photos with an f-number of at most this value"
+        )]
+        aperture_max: Option<f32>,
+
+        /// Minimum exposure time in seconds
+        #[arg(
+            long = "exposure-min",
+            value_parser = parse_exposure_time,
+            help = "Filter photos exposed for at least this time (e.g., 1/250 or 0.5)"
+        )]
+        exposure_min: Option<f32>,
+
+        /// Maximum exposure time in seconds
+        #[arg(
+            long = "exposure-max",
+            value_parser = parse_exposure_time,
+            help = "Filter photos exposed for at most this time (e.g., 1/250 or 0.5)"
+        )]
+        exposure_max: Option<f32>,
+
+        /// Minimum ISO sensitivity
+        #[arg(
+            long = "iso-min",
+            help = "Filter photos with an ISO of at least this value"
+        )]
+        iso_min: Option<i32>,
+
+        /// Maximum ISO sensitivity
+        #[arg(
+            long = "iso-max",
+            help = "Filter photos with an ISO of at most this value"
+        )]
+        iso_max: Option<i32>,
+
+        /// Minimum focal length in millimeters
+        #[arg(
+            long = "focal-length-min",
+            help = "Filter photos with a focal length of at least this value in mm"
+        )]
+        focal_length_min: Option<f32>,
+
+        /// Maximum focal length in millimeters
+        #[arg(
+            long = "focal-length-max",
+            help = "Filter photos with a focal length of at most this value in mm"
+        )]
+        focal_length_max: Option<f32>,
+
+        /// Minimum 35mm equivalent focal length
+        #[arg(
+            long = "focal-length-35mm-min",
+            help = "Filter photos with a 35mm equivalent focal length of at least this value"
+        )]
+        focal_length_35mm_min: Option<i32>,
+
+        /// Maximum 35mm equivalent focal length
+        #[arg(
+            long = "focal-length-35mm-max",
+            help = "Filter photos with a 35mm equivalent focal length of at most this value"
+        )]
+        focal_length_35mm_max: Option<i32>,
+
+        /// Minimum altitude in meters
+        #[arg(
+            long = "altitude-min",
+            allow_negative_numbers = true,
+            help = "Filter photos taken at an altitude of at least this value in meters"
+        )]
+        altitude_min: Option<f32>,
+
+        /// Maximum altitude in meters
+        #[arg(
+            long = "altitude-max",
+            allow_negative_numbers = true,
+            help = "Filter photos taken at an altitude of at most this value in meters"
+        )]
+        altitude_max: Option<f32>,
+
+        /// Whether the flash fired
+        #[arg(
+            long = "flash",
+            help = "Filter photos by whether the flash fired (true or false)"
+        )]
+        flash: Option<bool>,
+
         /// Page number for pagination
         #[arg(
             long = "page",
@@ -98,6 +417,323 @@ enum Commands {
     /// Face detection and recognition
     #[command(subcommand)]
     Face(FaceCommands),
+    /// Library roots management
+    #[command(subcommand)]
+    Library(LibraryCommands),
+    /// Background jobs of the indexing pipeline
+    #[command(subcommand)]
+    Jobs(JobCommands),
+    /// Photos on which a stage of the indexing pipeline failed
+    #[command(subcommand)]
+    Errors(ErrorCommands),
+    /// Find photos with identical content and clean up the extra copies
+    Duplicates {
+        /// Rule used to suggest the photo to keep in each group
+        #[arg(
+            long = "keep",
+            help = "Keep the largest or the oldest photo of each group (largest or oldest)",
+            default_value = "largest"
+        )]
+        keep: KeeperStrategy,
+
+        /// Library whose photos are kept first
+        #[arg(
+            long = "prefer-library",
+            value_name = "NAME",
+            help = "Keep photos of this library over photos of other libraries"
+        )]
+        prefer_library: Option<String>,
+
+        /// Directory the extra copies are moved to
+        #[arg(
+            long = "move-to",
+            value_name = "DIR",
+            conflicts_with = "delete",
+            help = "Move the extra copies to this quarantine directory"
+        )]
+        move_to: Option<PathBuf>,
+
+        /// Delete the extra copies (default: false)
+        #[arg(long = "delete", help = "Delete the extra copies from disk")]
+        delete: bool,
+    },
+    /// Find resized or edited copies and bursts, with the best shot of each
+    NearDuplicates {
+        /// Maximum number of differing bits between perceptual hashes
+        #[arg(
+            long = "max-hash-distance",
+            help = "Maximum number of differing bits between perceptual hashes of copies"
+        )]
+        max_hash_distance: Option<u32>,
+
+        /// Minimum embedding similarity of copies
+        #[arg(
+            long = "min-similarity",
+            help = "Minimum embedding similarity between copies"
+        )]
+        min_similarity: Option<f32>,
+
+        /// Maximum delay between shots of a burst, in seconds
+        #[arg(
+            long = "burst-interval",
+            help = "Maximum delay in seconds between consecutive shots of a burst"
+        )]
+        burst_interval: Option<i64>,
+
+        /// Minimum embedding similarity of shots of a burst
+        #[arg(
+            long = "min-burst-similarity",
+            help = "Minimum embedding similarity between consecutive shots of a burst"
+        )]
+        min_burst_similarity: Option<f32>,
+    },
+}
+
+#[derive(Subcommand)]
+enum LibraryCommands {
+    /// Add a library root
+    Add {
+        /// The name of the library
+        #[arg(help = "The name of the library")]
+        name: String,
+
+        /// The root directory of the library
+        #[arg(help = "The root directory of the library")]
+        root_directory: String,
+
+        /// Extract EXIF, XMP and IPTC metadata when scanning the library (default: false)
+        #[arg(
+            long = "with-exif",
+            help = "Enable EXIF, XMP and IPTC metadata extraction"
+        )]
+        with_exif: bool,
+
+        /// Compute file hashes when scanning the library (default: false)
+        #[arg(long = "with-hash", help = "Enable file hash computation")]
+        with_hash: bool,
+
+        /// Glob patterns of files to scan
+        #[arg(
+            long = "include",
+            value_name = "GLOB",
+            help = "Only scan files matching this glob pattern (can be repeated)"
+        )]
+        include: Vec<String>,
+
+        /// Glob patterns of files and directories to skip
+        #[arg(
+            long = "exclude",
+            value_name = "GLOB",
+            help = "Skip files and directories matching this glob pattern (can be repeated)"
+        )]
+        exclude: Vec<String>,
+
+        /// Add the library without scanning it (default: false)
+        #[arg(
+            long = "disabled",
+            help = "Skip the library when scanning all libraries"
+        )]
+        disabled: bool,
+
+        /// Allow writing metadata back to XMP sidecars (default: false)
+        #[arg(
+            long = "write-xmp",
+            help = "Allow exporting people, keywords and places to XMP sidecars"
+        )]
+        write_xmp: bool,
+
+        /// Remove photos whose file no longer exists when scanning (default: false)
+        #[arg(
+            long = "prune",
+            help = "Remove indexed photos whose file no longer exists when scanning the library"
+        )]
+        prune: bool,
+    },
+    /// List libraries
+    List,
+    /// Scan libraries with their own settings
+    Scan {
+        /// The name of the library to scan
+        #[arg(help = "The name of the library to scan, or all enabled libraries if omitted")]
+        name: Option<String>,
+
+        /// Remove photos whose file no longer exists, even if the library does not (default: false)
+        #[arg(
+            long = "prune",
+            help = "Remove indexed photos whose file no longer exists under the library root"
+        )]
+        prune: bool,
+
+        /// Rescan files that did not change since the last scan (default: false)
+        #[arg(
+            long = "force",
+            help = "Re-extract metadata and recompute hashes of unchanged files"
+        )]
+        force: bool,
+
+        /// Number of photos committed at once
+        #[arg(
+            long = "batch-size",
+            help = "Number of photos committed to the database at once",
+            default_value = "500"
+        )]
+        batch_size: usize,
+    },
+    /// Move a library to a new root without scanning it again
+    Relocate {
+        /// The name of the library
+        #[arg(help = "The name of the library")]
+        name: String,
+
+        /// The new root directory of the library
+        #[arg(help = "The new root directory of the library")]
+        root_directory: String,
+    },
+    /// Include a library when scanning all libraries
+    Enable {
+        /// The name of the library
+        #[arg(help = "The name of the library")]
+        name: String,
+    },
+    /// Skip a library when scanning all libraries
+    Disable {
+        /// The name of the library
+        #[arg(help = "The name of the library")]
+        name: String,
+    },
+    /// Allow writing metadata back to the XMP sidecars of a library
+    EnableXmp {
+        /// The name of the library
+        #[arg(help = "The name of the library")]
+        name: String,
+    },
+    /// Stop writing metadata back to the XMP sidecars of a library
+    DisableXmp {
+        /// The name of the library
+        #[arg(help = "The name of the library")]
+        name: String,
+    },
+    /// Write people, keywords and places to XMP sidecars, leaving photos untouched
+    ExportXmp {
+        /// The name of the library to export
+        #[arg(
+            help = "The name of the library to export, or all libraries with XMP write-back if omitted"
+        )]
+        name: Option<String>,
+
+        /// List the sidecars that would be written without writing them (default: false)
+        #[arg(
+            long = "dry-run",
+            help = "List the sidecars that would be written without writing them"
+        )]
+        dry_run: bool,
+    },
+    /// Remove a library and its photos from the index
+    Remove {
+        /// The name of the library
+        #[arg(help = "The name of the library")]
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum JobCommands {
+    /// Enqueue jobs of a pipeline stage
+    Enqueue {
+        /// The stage to run
+        #[arg(
+            long = "stage",
+            help = "The stage to run: scan, hash, exif, embed, detect-faces or recognize",
+            default_value = "scan"
+        )]
+        stage: JobStage,
+
+        /// The name of the library
+        #[arg(
+            long = "library",
+            value_name = "NAME",
+            help = "Only enqueue jobs for this library (default: all enabled libraries)"
+        )]
+        library: Option<String>,
+    },
+    /// Run enqueued jobs, waiting for new ones
+    Work {
+        /// Stop once no job is due (default: false)
+        #[arg(long = "until-idle", help = "Stop once no job is due")]
+        until_idle: bool,
+    },
+    /// Show the number of jobs of each stage by status
+    Status,
+    /// List jobs
+    List {
+        /// Filter by stage
+        #[arg(long = "stage", help = "Only list jobs of this stage")]
+        stage: Option<JobStage>,
+
+        /// Filter by status
+        #[arg(
+            long = "status",
+            help = "Only list jobs with this status (pending, running, done or failed)"
+        )]
+        status: Option<JobStatus>,
+
+        /// Filter by photo
+        #[arg(long = "photo-id", help = "Only list jobs of this photo")]
+        photo_id: Option<i32>,
+
+        /// Maximum number of listed jobs
+        #[arg(
+            long = "limit",
+            help = "Maximum number of listed jobs",
+            default_value = "50"
+        )]
+        limit: i64,
+    },
+    /// Run failed jobs again
+    Retry {
+        /// Filter by stage
+        #[arg(long = "stage", help = "Only retry jobs of this stage")]
+        stage: Option<JobStage>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ErrorCommands {
+    /// List photos on which a stage failed
+    List {
+        /// Filter by stage
+        #[arg(long = "stage", help = "Only list errors of this stage")]
+        stage: Option<JobStage>,
+
+        /// Filter by photo
+        #[arg(long = "photo-id", help = "Only list errors of this photo")]
+        photo_id: Option<i32>,
+
+        /// Only list skipped photos (default: false)
+        #[arg(
+            long = "skipped",
+            help = "Only list photos skipped after failing the maximum number of attempts"
+        )]
+        skipped: bool,
+
+        /// Maximum number of listed errors
+        #[arg(
+            long = "limit",
+            help = "Maximum number of listed errors",
+            default_value = "50"
+        )]
+        limit: i64,
+    },
+    /// Clear errors so that failed photos are processed again by the next runs
+    Retry {
+        /// Filter by stage
+        #[arg(long = "stage", help = "Only retry errors of this stage")]
+        stage: Option<JobStage>,
+
+        /// Filter by photo
+        #[arg(long = "photo-id", help = "Only retry errors of this photo")]
+        photo_id: Option<i32>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -170,6 +806,192 @@ impl From<Photo> for PhotoRow {
     }
 }
 
+#[derive(Tabled)]
+struct LibraryRow {
+    #[tabled(rename = "Name")]
+    pub name: String,
+    #[tabled(rename = "Root")]
+    pub root_path: String,
+    #[tabled(rename = "Enabled")]
+    pub enabled: bool,
+    #[tabled(rename = "Scan options")]
+    pub scan_options: String,
+    #[tabled(rename = "XMP write-back")]
+    pub write_xmp: bool,
+}
+
+impl From<Library> for LibraryRow {
+    fn from(library: Library) -> Self {
+        let mut scan_options = Vec::new();
+        if library.with_exif {
+            scan_options.push("exif".to_string());
+        }
+        if library.with_hash {
+            scan_options.push("hash".to_string());
+        }
+        if library.prune {
+            scan_options.push("prune".to_string());
+        }
+        scan_options.extend(library.include.iter().map(|p| format!("include {}", p)));
+        scan_options.extend(library.exclude.iter().map(|p| format!("exclude {}", p)));
+
+        Self {
+            name: library.name,
+            root_path: library.root_path,
+            enabled: library.enabled,
+            scan_options: scan_options.join("\n"),
+            write_xmp: library.write_xmp,
+        }
+    }
+}
+
+#[derive(Tabled)]
+struct JobStageRow {
+    #[tabled(rename = "Stage")]
+    pub stage: String,
+    #[tabled(rename = "Pending")]
+    pub pending: i64,
+    #[tabled(rename = "Running")]
+    pub running: i64,
+    #[tabled(rename = "Done")]
+    pub done: i64,
+    #[tabled(rename = "Failed")]
+    pub failed: i64,
+}
+
+impl From<JobStageSummary> for JobStageRow {
+    fn from(summary: JobStageSummary) -> Self {
+        Self {
+            stage: summary.stage.to_string(),
+            pending: summary.pending,
+            running: summary.running,
+            done: summary.done,
+            failed: summary.failed,
+        }
+    }
+}
+
+#[derive(Tabled)]
+struct JobRow {
+    #[tabled(rename = "ID")]
+    pub id: i32,
+    #[tabled(rename = "Stage")]
+    pub stage: String,
+    #[tabled(rename = "Target")]
+    pub target: String,
+    #[tabled(rename = "Status")]
+    pub status: String,
+    #[tabled(rename = "Attempts")]
+    pub attempts: i32,
+    #[tabled(rename = "Updated")]
+    pub updated_at: String,
+    #[tabled(rename = "Error")]
+    pub last_error: String,
+}
+
+impl From<Job> for JobRow {
+    fn from(job: Job) -> Self {
+        let target = match (job.library_id, job.photo_id) {
+            (_, Some(photo_id)) => format!("photo {}", photo_id),
+            (Some(library_id), None) => format!("library {}", library_id),
+            (None, None) => "all".to_string(),
+        };
+
+        Self {
+            id: job.id,
+            stage: job.stage.to_string(),
+            target,
+            status: job.status.to_string(),
+            attempts: job.attempts,
+            updated_at: job.updated_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            last_error: job.last_error.unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Tabled)]
+struct PhotoErrorRow {
+    #[tabled(rename = "Photo ID")]
+    pub photo_id: i32,
+    #[tabled(rename = "Path")]
+    pub path: String,
+    #[tabled(rename = "Stage")]
+    pub stage: String,
+    #[tabled(rename = "Attempts")]
+    pub attempts: i32,
+    #[tabled(rename = "Last Attempt")]
+    pub last_attempt_at: String,
+    #[tabled(rename = "Error")]
+    pub error: String,
+}
+
+impl From<PhotoErrorWithPath> for PhotoErrorRow {
+    fn from(result: PhotoErrorWithPath) -> Self {
+        Self {
+            photo_id: result.photo_error.photo_id,
+            path: result.photo_path,
+            stage: result.photo_error.stage.to_string(),
+            attempts: result.photo_error.attempts,
+            last_attempt_at: result
+                .photo_error
+                .last_attempt_at
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+            error: result.photo_error.error,
+        }
+    }
+}
+
+#[derive(Tabled)]
+struct DuplicateGroupRow {
+    #[tabled(rename = "Hash")]
+    pub hash: String,
+    #[tabled(rename = "Keep")]
+    pub keeper: String,
+    #[tabled(rename = "Duplicates")]
+    pub duplicates: String,
+}
+
+impl From<&DuplicateGroup> for DuplicateGroupRow {
+    fn from(group: &DuplicateGroup) -> Self {
+        Self {
+            hash: group.hash.chars().take(12).collect(),
+            keeper: group.keeper.path.clone(),
+            duplicates: group
+                .duplicates
+                .iter()
+                .map(|photo| photo.path.as_str())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+#[derive(Tabled)]
+struct NearDuplicateClusterRow {
+    #[tabled(rename = "Kind")]
+    pub kind: String,
+    #[tabled(rename = "Best shot")]
+    pub best_shot: String,
+    #[tabled(rename = "Others")]
+    pub others: String,
+}
+
+impl From<NearDuplicateCluster> for NearDuplicateClusterRow {
+    fn from(cluster: NearDuplicateCluster) -> Self {
+        Self {
+            kind: cluster.kind.to_string(),
+            best_shot: cluster.best_shot.path,
+            others: cluster
+                .others
+                .into_iter()
+                .map(|photo| photo.path)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
 #[derive(Tabled)]
 struct RecognitionResultRow {
     #[tabled(rename = "ID")]
@@ -222,38 +1044,161 @@ impl Cli {
                 root_directory,
                 with_exif,
                 with_hash,
+                prune,
+                force,
+                batch_size,
+                rules,
             } => {
                 let progress_reporter = CliProgressReporter::new();
+                let (root_directory, library) = resolve_library(&pool, root_directory)?;
 
-                let _ = photo_scanner::scan(
+                let summary = photo_scanner::scan(
                     &root_directory,
                     &mut photo_repository,
+                    &ScanOptions {
+                        with_exif,
+                        with_hash,
+                        prune,
+                        force,
+                        batch_size,
+                        library,
+                        ..rules.into_scan_options(&config.scanner)
+                    },
+                    &progress_reporter,
+                )?;
+
+                print_scan_summary(&summary);
+
+                Ok(())
+            }
+            Commands::Watch {
+                root_directory,
+                with_exif,
+                with_hash,
+                batch_size,
+                debounce,
+                rules,
+            } => {
+                let (root_directory, library) = resolve_library(&pool, root_directory)?;
+                let scan_options = ScanOptions {
                     with_exif,
                     with_hash,
-                    &progress_reporter,
-                );
+                    prune: true,
+                    force: false,
+                    batch_size,
+                    library,
+                    ..rules.into_scan_options(&config.scanner)
+                };
+                let watcher = PhotoWatcher::new(&root_directory, Duration::from_millis(debounce))?;
+
+                println!("Watching {} for changes", root_directory);
+
+                // Photos deleted while not watching are left for `scan --prune`
+                let mut changed_paths = None;
+
+                loop {
+                    if let Err(err) = index_changes(
+                        &root_directory,
+                        changed_paths.as_deref(),
+                        &pool,
+                        &config,
+                        &scan_options,
+                    ) {
+                        eprintln!("✗ {:#}", err);
+                    }
+
+                    let paths = watcher.next_changes()?;
+                    println!("Detected {} changed paths", paths.len());
+                    changed_paths = Some(paths);
+                }
+            }
+            Commands::Import {
+                source,
+                library,
+                template,
+                dry_run,
+            } => {
+                let library =
+                    LibraryService::new(PgLibraryRepository::new(pool.clone())).get(&library)?;
+
+                let summary = photo_import::import(
+                    &source,
+                    &library,
+                    &mut photo_repository,
+                    &ImportOptions {
+                        template: template.unwrap_or(config.import.template.clone()),
+                        dry_run,
+                        scan: ScanOptions {
+                            with_exif: library.with_exif,
+                            workers: config.scanner.workers,
+                            default_timezone: config.scanner.default_timezone.clone(),
+                            ..Default::default()
+                        },
+                    },
+                    &CliProgressReporter::new(),
+                )?;
+
+                print_import_summary(&summary, dry_run);
 
                 Ok(())
             }
-            Commands::Embed {} => {
+            Commands::Embed {
+                model,
+                reembed,
+                batch_size,
+                threads,
+            } => {
                 let progress_reporter = CliProgressReporter::new();
-                let image_embedder = ClipImageEmbedder::new(&config.clip_model)?;
-                let mut photo_embedder =
-                    PhotoEmbedderService::new(photo_repository, image_embedder, progress_reporter);
+                let embeddings_config = EmbeddingsConfig {
+                    threads: threads.unwrap_or(config.embeddings.threads),
+                    ..config.embeddings.clone()
+                };
+                let image_embedder = ClipImageEmbedder::new(
+                    &embeddings_config,
+                    model.as_deref().unwrap_or(&config.embeddings.model),
+                )?;
+                let mut photo_embedder = PhotoEmbedderService::new(
+                    photo_repository,
+                    PgPhotoErrorRepository::new(pool.clone()),
+                    image_embedder,
+                    progress_reporter,
+                    config.errors.max_attempts,
+                    batch_size.unwrap_or(config.embeddings.batch_size),
+                );
+
+                if reembed {
+                    photo_embedder.reembed()?;
+                } else {
+                    photo_embedder.embed()?;
+                }
 
-                photo_embedder.embed()?;
+                Ok(())
+            }
+            Commands::Thumbnails { workers } => {
+                let mut thumbnail_service = ThumbnailService::new(
+                    photo_repository,
+                    ThumbnailCache::new(&config.thumbnails),
+                    CliProgressReporter::new(),
+                );
+
+                let summary =
+                    thumbnail_service.generate_all(workers.unwrap_or(config.scanner.workers))?;
+
+                print_thumbnail_summary(&summary);
 
                 Ok(())
             }
             Commands::Face(face_command) => match face_command {
                 FaceCommands::Detect {} => {
                     let progress_reporter = CliProgressReporter::new();
-                    let face_repository = PgFaceRepository::new(pool);
+                    let face_repository = PgFaceRepository::new(pool.clone());
                     let mut face_detection_service = FaceDetectionService::new(
                         photo_repository,
                         face_repository,
+                        PgPhotoErrorRepository::new(pool),
                         progress_reporter,
                         &config.face_detection_server,
+                        config.errors.max_attempts,
                     );
 
                     face_detection_service.detect_faces()?;
@@ -298,33 +1243,455 @@ impl Cli {
                     Ok(())
                 }
             },
+            Commands::Library(library_command) => {
+                let mut library_service =
+                    LibraryService::new(PgLibraryRepository::new(pool.clone()));
+
+                match library_command {
+                    LibraryCommands::Add {
+                        name,
+                        root_directory,
+                        with_exif,
+                        with_hash,
+                        include,
+                        exclude,
+                        disabled,
+                        write_xmp,
+                        prune,
+                    } => {
+                        let library = library_service.add(NewLibrary {
+                            name,
+                            root_path: root_directory,
+                            enabled: !disabled,
+                            with_exif,
+                            with_hash,
+                            include,
+                            exclude,
+                            write_xmp,
+                            prune,
+                        })?;
+
+                        println!("Added library {} at {}", library.name, library.root_path);
+                    }
+                    LibraryCommands::List => {
+                        let library_rows: Vec<LibraryRow> = library_service
+                            .list()?
+                            .into_iter()
+                            .map(|l| l.into())
+                            .collect();
+
+                        let mut table = Table::new(library_rows);
+                        table.with(Style::rounded());
+                        println!("{}", table);
+                    }
+                    LibraryCommands::Scan {
+                        name,
+                        prune,
+                        force,
+                        batch_size,
+                    } => {
+                        let libraries = match name {
+                            Some(name) => vec![library_service.get(&name)?],
+                            None => library_service
+                                .list()?
+                                .into_iter()
+                                .filter(|library| library.enabled)
+                                .collect(),
+                        };
+
+                        for library in libraries {
+                            println!("Scanning library {} at {}", library.name, library.root_path);
+
+                            let root_directory = library.root_path.clone();
+                            let summary = photo_scanner::scan(
+                                &root_directory,
+                                &mut photo_repository,
+                                &ScanOptions {
+                                    prune: prune || library.prune,
+                                    force,
+                                    batch_size,
+                                    ..ScanOptions::for_library(library, &config.scanner)
+                                },
+                                &CliProgressReporter::new(),
+                            )?;
+
+                            print_scan_summary(&summary);
+                        }
+                    }
+                    LibraryCommands::Relocate {
+                        name,
+                        root_directory,
+                    } => {
+                        let library = library_service.relocate(&name, &root_directory)?;
+
+                        println!(
+                            "Relocated library {} to {}",
+                            library.name, library.root_path
+                        );
+                    }
+                    LibraryCommands::Enable { name } => {
+                        library_service.set_enabled(&name, true)?;
+
+                        println!("Enabled library {}", name);
+                    }
+                    LibraryCommands::Disable { name } => {
+                        library_service.set_enabled(&name, false)?;
+
+                        println!("Disabled library {}", name);
+                    }
+                    LibraryCommands::EnableXmp { name } => {
+                        library_service.set_write_xmp(&name, true)?;
+
+                        println!("Enabled XMP write-back for library {}", name);
+                    }
+                    LibraryCommands::DisableXmp { name } => {
+                        library_service.set_write_xmp(&name, false)?;
+
+                        println!("Disabled XMP write-back for library {}", name);
+                    }
+                    LibraryCommands::ExportXmp { name, dry_run } => {
+                        let libraries = match name {
+                            Some(name) => vec![library_service.get(&name)?],
+                            None => library_service
+                                .list()?
+                                .into_iter()
+                                .filter(|library| library.write_xmp)
+                                .collect(),
+                        };
+
+                        let mut xmp_export_service = XmpExportService::new(
+                            PgPhotoRepository::new(pool.clone()),
+                            CliProgressReporter::new(),
+                        );
+
+                        for library in libraries {
+                            println!("Exporting library {} to XMP sidecars", library.name);
+
+                            let summary = xmp_export_service.export(&library, dry_run)?;
+                            print_xmp_export_summary(&summary, dry_run);
+                        }
+                    }
+                    LibraryCommands::Remove { name } => {
+                        let library = library_service.remove(&name)?;
+
+                        println!("Removed library {} and its photos", library.name);
+                    }
+                }
+
+                Ok(())
+            }
+            Commands::Jobs(job_command) => {
+                let mut job_service = JobService::new(
+                    PgJobRepository::new(pool.clone()),
+                    CliProgressReporter::new(),
+                    &config.jobs,
+                );
+
+                match job_command {
+                    JobCommands::Enqueue { stage, library } => {
+                        let mut library_service =
+                            LibraryService::new(PgLibraryRepository::new(pool.clone()));
+                        let libraries = match &library {
+                            Some(name) => vec![library_service.get(name)?],
+                            None => library_service
+                                .list()?
+                                .into_iter()
+                                .filter(|library| library.enabled)
+                                .collect(),
+                        };
+
+                        let new_jobs = match stage {
+                            JobStage::Scan => libraries
+                                .iter()
+                                .map(|library| NewJob::library(stage, library.id))
+                                .collect(),
+                            JobStage::Recognize => vec![NewJob::global(stage)],
+                            _ => {
+                                let path_prefixes = match library {
+                                    Some(_) => libraries
+                                        .iter()
+                                        .map(|library| {
+                                            format!("{}{}", library.root_path, MAIN_SEPARATOR)
+                                        })
+                                        .collect(),
+                                    None => vec![String::new()],
+                                };
+
+                                let mut new_jobs = Vec::new();
+                                for path_prefix in path_prefixes {
+                                    new_jobs.extend(
+                                        photo_repository
+                                            .find_files(&path_prefix)?
+                                            .into_iter()
+                                            .map(|file| NewJob::photo(stage, file.id)),
+                                    );
+                                }
+                                new_jobs
+                            }
+                        };
+
+                        let count = job_service.enqueue(new_jobs)?;
+                        println!("Enqueued {} {} jobs", count, stage);
+                    }
+                    JobCommands::Work { until_idle } => {
+                        let handler = PipelineJobHandler::new(
+                            pool.clone(),
+                            &config.scanner,
+                            &config.face_detection_server,
+                            &config.errors,
+                            ClipImageEmbedder::new(&config.embeddings, &config.embeddings.model)?,
+                        );
+
+                        let summary = job_service.run(&handler, until_idle)?;
+
+                        println!(
+                            "Completed: {}, retried: {}, failed: {}",
+                            summary.completed, summary.retried, summary.failed
+                        );
+                    }
+                    JobCommands::Status => {
+                        let rows: Vec<JobStageRow> = job_service
+                            .summarize()?
+                            .into_iter()
+                            .map(|summary| summary.into())
+                            .collect();
+
+                        let mut table = Table::new(rows);
+                        table.with(Style::rounded());
+                        println!("{}", table);
+                    }
+                    JobCommands::List {
+                        stage,
+                        status,
+                        photo_id,
+                        limit,
+                    } => {
+                        let rows: Vec<JobRow> = job_service
+                            .list(
+                                JobFindFilters {
+                                    stage,
+                                    status,
+                                    photo_id,
+                                },
+                                limit,
+                            )?
+                            .into_iter()
+                            .map(|job| job.into())
+                            .collect();
+
+                        if rows.is_empty() {
+                            println!("No jobs found");
+                        } else {
+                            let mut table = Table::new(rows);
+                            table.with(Style::rounded());
+                            println!("{}", table);
+                        }
+                    }
+                    JobCommands::Retry { stage } => {
+                        let count = job_service.retry_failed(stage)?;
+                        println!("Retrying {} failed jobs", count);
+                    }
+                }
+
+                Ok(())
+            }
+            Commands::Errors(error_command) => {
+                let mut photo_error_service =
+                    PhotoErrorService::new(PgPhotoErrorRepository::new(pool.clone()));
+
+                match error_command {
+                    ErrorCommands::List {
+                        stage,
+                        photo_id,
+                        skipped,
+                        limit,
+                    } => {
+                        let rows: Vec<PhotoErrorRow> = photo_error_service
+                            .list(
+                                PhotoErrorFindFilters {
+                                    stage,
+                                    photo_id,
+                                    min_attempts: skipped.then_some(config.errors.max_attempts),
+                                },
+                                limit,
+                            )?
+                            .into_iter()
+                            .map(|photo_error| photo_error.into())
+                            .collect();
+
+                        if rows.is_empty() {
+                            println!("No photo errors found");
+                        } else {
+                            let mut table = Table::new(rows);
+                            table.with(Style::rounded());
+                            println!("{}", table);
+                        }
+                    }
+                    ErrorCommands::Retry { stage, photo_id } => {
+                        let count = photo_error_service.retry(PhotoErrorFindFilters {
+                            stage,
+                            photo_id,
+                            ..Default::default()
+                        })?;
+                        println!(
+                            "Cleared {} photo errors, run the failed stages again to retry them",
+                            count
+                        );
+                    }
+                }
+
+                Ok(())
+            }
+            Commands::Duplicates {
+                keep,
+                prefer_library,
+                move_to,
+                delete,
+            } => {
+                let preferred_library_id = match prefer_library {
+                    Some(name) => {
+                        let mut library_service =
+                            LibraryService::new(PgLibraryRepository::new(pool.clone()));
+                        Some(library_service.get(&name)?.id)
+                    }
+                    None => None,
+                };
+                let action = match (move_to, delete) {
+                    (Some(quarantine_dir), _) => DuplicateAction::Move(quarantine_dir),
+                    (None, true) => DuplicateAction::Delete,
+                    (None, false) => DuplicateAction::Report,
+                };
+
+                let mut duplicate_service =
+                    DuplicateService::new(photo_repository, CliProgressReporter::new());
+                let groups = duplicate_service.find_groups(&KeeperPreference {
+                    strategy: keep,
+                    preferred_library_id,
+                })?;
+
+                if groups.is_empty() {
+                    println!("No duplicate photos found.");
+                    return Ok(());
+                }
+
+                let duplicate_group_rows: Vec<DuplicateGroupRow> =
+                    groups.iter().map(|g| g.into()).collect();
+                let mut table = Table::new(duplicate_group_rows);
+                table.with(Style::rounded());
+                println!("{}", table);
+
+                let summary = duplicate_service.resolve(&groups, &action)?;
+                print_duplicate_summary(&summary, &action);
+
+                Ok(())
+            }
+            Commands::NearDuplicates {
+                max_hash_distance,
+                min_similarity,
+                burst_interval,
+                min_burst_similarity,
+            } => {
+                let embedding_model = config.embeddings.model.clone();
+                let defaults = NearDuplicateConfig::default();
+                let config = NearDuplicateConfig {
+                    max_hash_distance: max_hash_distance.unwrap_or(defaults.max_hash_distance),
+                    min_similarity: min_similarity.unwrap_or(defaults.min_similarity),
+                    burst_interval: burst_interval.unwrap_or(defaults.burst_interval),
+                    min_burst_similarity: min_burst_similarity
+                        .unwrap_or(defaults.min_burst_similarity),
+                };
+
+                let mut near_duplicate_service =
+                    NearDuplicateService::new(photo_repository, &embedding_model, Some(config));
+                let clusters = near_duplicate_service.find_clusters()?;
+
+                if clusters.is_empty() {
+                    println!("No near-duplicate photos or bursts found.");
+                    return Ok(());
+                }
+
+                let cluster_count = clusters.len();
+                let near_duplicate_cluster_rows: Vec<NearDuplicateClusterRow> =
+                    clusters.into_iter().map(|c| c.into()).collect();
+                let mut table = Table::new(near_duplicate_cluster_rows);
+                table.with(Style::rounded());
+                println!("{}", table);
+                println!("\n{} clusters found", cluster_count);
+
+                Ok(())
+            }
             Commands::Search {
                 text,
+                similar_to,
+                similar_to_image,
                 threshold,
                 country,
                 city,
                 date_from,
                 date_to,
+                media_type,
+                aperture_min,
+                aperture_max,
+                exposure_min,
+                exposure_max,
+                iso_min,
+                iso_max,
+                focal_length_min,
+                focal_length_max,
+                focal_length_35mm_min,
+                focal_length_35mm_max,
+                altitude_min,
+                altitude_max,
+                flash,
                 page,
                 per_page,
             } => {
                 let face_repository = PgFaceRepository::new(pool.clone());
-                let text_embedder = ClipTextEmbedder::new(&config.clip_model)?;
                 let mut photo_search = PhotoSearchService::new(
                     photo_repository,
                     geo_repository,
                     person_repository,
                     face_repository,
-                    text_embedder,
+                    &config.embeddings.model,
                 );
 
+                // Texts are only embedded when searching photos matching a text
+                if text.is_some() {
+                    photo_search =
+                        photo_search.with_text_embedder(load_text_embedder(&config.embeddings)?);
+                }
+
+                let similar_to = match (similar_to, similar_to_image) {
+                    (Some(id), _) => Some(SimilarTo::Photo(id)),
+                    (None, Some(path)) => {
+                        // Images are only embedded when searching photos similar to a file
+                        photo_search = photo_search.with_image_embedder(Box::new(
+                            ClipImageEmbedder::new(&config.embeddings, &config.embeddings.model)?,
+                        ));
+                        Some(SimilarTo::Image(path))
+                    }
+                    (None, None) => None,
+                };
+
                 let search_params = PhotoSearchParams {
                     text,
+                    similar_to,
                     threshold,
                     country,
                     city,
                     date_from,
                     date_to,
+                    media_type,
+                    aperture: ValueRange::new(aperture_min, aperture_max),
+                    exposure_time: ValueRange::new(exposure_min, exposure_max),
+                    iso: ValueRange::new(iso_min, iso_max),
+                    focal_length: ValueRange::new(focal_length_min, focal_length_max),
+                    focal_length_35mm: ValueRange::new(
+                        focal_length_35mm_min,
+                        focal_length_35mm_max,
+                    ),
+                    altitude: ValueRange::new(altitude_min, altitude_max),
+                    flash,
                     page,
                     per_page,
                     ..PhotoSearchParams::default()
@@ -358,3 +1725,173 @@ impl Cli {
         }
     }
 }
+
+/// Parses an exposure time given in seconds, either as a fraction such as 1/250 or a decimal.
+fn parse_exposure_time(value: &str) -> Result<f32, String> {
+    let parsed = match value.split_once('/') {
+        Some((numerator, denominator)) => numerator
+            .trim()
+            .parse::<f32>()
+            .ok()
+            .zip(denominator.trim().parse::<f32>().ok())
+            .filter(|(_, denominator)| *denominator != 0.0)
+            .map(|(numerator, denominator)| numerator / denominator),
+        None => value.trim().parse::<f32>().ok(),
+    };
+
+    parsed.ok_or_else(|| format!("Invalid exposure time: {}", value))
+}
+
+/// Finds the library containing a directory, resolving the directory to its absolute path
+/// so that photos are stored relative to the library root.
+fn resolve_library(pool: &DbPool, root_directory: String) -> Result<(String, Option<Library>)> {
+    let mut library_service = LibraryService::new(PgLibraryRepository::new(pool.clone()));
+
+    match library_service.find_for_path(&root_directory)? {
+        Some(library) => {
+            let root_directory = Path::new(&root_directory).canonicalize()?;
+            Ok((root_directory.to_string_lossy().into_owned(), Some(library)))
+        }
+        None => Ok((root_directory, None)),
+    }
+}
+
+fn print_scan_summary(summary: &ScanSummary) {
+    println!(
+        "Added: {}, updated: {}, unchanged: {}, moved: {}, removed: {}, missing: {}, skipped: {}, failed: {}",
+        summary.added,
+        summary.updated,
+        summary.unchanged,
+        summary.moved,
+        summary.removed,
+        summary.missing,
+        summary.skipped,
+        summary.failed
+    );
+}
+
+fn print_import_summary(summary: &ImportSummary, dry_run: bool) {
+    let verb = if dry_run { "Would import" } else { "Imported" };
+    for path in &summary.imported_paths {
+        println!("{} {}", verb, path);
+    }
+
+    println!(
+        "Imported: {}, skipped: {}, failed: {}",
+        summary.imported, summary.skipped, summary.failed
+    );
+    if !dry_run {
+        print_scan_summary(&summary.scan);
+    }
+}
+
+fn print_thumbnail_summary(summary: &ThumbnailSummary) {
+    println!(
+        "Generated: {}, existing: {}, failed: {}, removed: {}",
+        summary.generated, summary.existing, summary.failed, summary.removed
+    );
+}
+
+fn print_xmp_export_summary(summary: &XmpExportSummary, dry_run: bool) {
+    let verb = if dry_run { "Would write" } else { "Wrote" };
+    for path in &summary.changed_paths {
+        println!("{} {}", verb, path);
+    }
+
+    println!(
+        "Created: {}, updated: {}, unchanged: {}, failed: {}",
+        summary.created, summary.updated, summary.unchanged, summary.failed
+    );
+}
+
+fn print_duplicate_summary(summary: &DuplicateSummary, action: &DuplicateAction) {
+    println!(
+        "Groups: {}, duplicates: {}, reclaimable: {:.1} MB",
+        summary.groups,
+        summary.duplicates,
+        summary.reclaimable_bytes as f64 / 1_000_000.0
+    );
+
+    match action {
+        DuplicateAction::Report => {}
+        DuplicateAction::Move(quarantine_dir) => println!(
+            "Moved to {}: {}, skipped: {}, failed: {}",
+            quarantine_dir.display(),
+            summary.moved,
+            summary.skipped,
+            summary.failed
+        ),
+        DuplicateAction::Delete => println!(
+            "Deleted: {}, skipped: {}, failed: {}",
+            summary.deleted, summary.skipped, summary.failed
+        ),
+    }
+}
+
+/// Scans the changed paths of a root directory, or the whole root on the first run, then
+/// enqueues the embedding and face detection jobs of new or modified photos.
+fn index_changes(
+    root_directory: &str,
+    changed_paths: Option<&[PathBuf]>,
+    pool: &DbPool,
+    config: &Config,
+    scan_options: &ScanOptions,
+) -> Result<()> {
+    let mut photo_repository = PgPhotoRepository::new(pool.clone());
+    let start = Utc::now();
+
+    let summary = match changed_paths {
+        Some(changed_paths) => photo_scanner::scan_changes(
+            root_directory,
+            changed_paths,
+            &mut photo_repository,
+            scan_options,
+            &CliProgressReporter::new(),
+        )?,
+        None => photo_scanner::scan(
+            root_directory,
+            &mut photo_repository,
+            &ScanOptions {
+                prune: false,
+                ..scan_options.clone()
+            },
+            &CliProgressReporter::new(),
+        )?,
+    };
+
+    println!(
+        "Added: {}, updated: {}, moved: {}, removed: {}, missing: {}, failed: {}",
+        summary.added,
+        summary.updated,
+        summary.moved,
+        summary.removed,
+        summary.missing,
+        summary.failed
+    );
+
+    if summary.added + summary.updated == 0 {
+        return Ok(());
+    }
+
+    let changed_ids = photo_repository
+        .find_ids_indexed_since(&photo_scanner::root_prefix(root_directory), start)
+        .context("Failed to fetch scanned photos")?;
+
+    let enqueued = JobService::new(
+        PgJobRepository::new(pool.clone()),
+        CliProgressReporter::new(),
+        &config.jobs,
+    )
+    .enqueue(
+        changed_ids
+            .into_iter()
+            .map(|photo_id| NewJob::photo(JobStage::Embed, photo_id))
+            .collect(),
+    )?;
+    println!(
+        "Enqueued {} jobs, run `jobs work` to process them",
+        enqueued
+    );
+
+    Ok(())
+}
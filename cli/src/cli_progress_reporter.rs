@@ -27,6 +27,10 @@ impl ProgressReporter for CliProgressReporter {
         self.spinner.set_message(message);
     }
 
+    fn report_error(&self, message: String) {
+        self.spinner.println(format!("✗ {}", message));
+    }
+
     fn finish_with_message(&self, message: String) {
         self.spinner.finish_with_message(message);
     }
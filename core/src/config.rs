@@ -1,13 +1,24 @@
-use std::path::PathBuf;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
+use anyhow::anyhow;
 use config::{Config as ConfigBuilder, ConfigError, Environment, File};
 use serde::{Deserialize, Serialize};
 
+use crate::models::JobStage;
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Config {
     pub database: DatabaseConfig,
-    pub clip_model: ClipModelConfig,
+    pub embeddings: EmbeddingsConfig,
     pub face_detection_server: FaceDetectionServerConfig,
+    pub scanner: ScannerConfig,
+    pub import: ImportConfig,
+    pub thumbnails: ThumbnailsConfig,
+    pub jobs: JobsConfig,
+    pub errors: ErrorsConfig,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -21,11 +32,88 @@ pub struct DatabaseConfig {
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct ClipModelConfig {
+pub struct EmbeddingsConfig {
+    /// Id of the active model, whose embeddings are searched.
+    pub model: String,
+    /// Id of the multilingual text encoder of searches, the text encoder of the active model
+    /// when not set.
+    pub text_encoder: Option<String>,
     pub dir: String,
+    /// Device running the models: `cpu` or `cuda`.
+    pub device: String,
+    /// Precision of the model weights.
+    pub dtype: ModelDType,
+    /// Number of photos embedded at once.
+    pub batch_size: usize,
+    /// Number of threads loading images and running the models on the CPU, one per CPU
+    /// when 0.
+    pub threads: usize,
+    /// Registry of the embedding models, by id.
+    pub models: HashMap<String, EmbeddingModelConfig>,
+    /// Registry of the multilingual text encoders, by id.
+    #[serde(default)]
+    pub text_encoders: HashMap<String, TextEncoderConfig>,
+}
+
+impl EmbeddingsConfig {
+    /// Returns the configuration of a registered model.
+    pub fn get(&self, model_id: &str) -> anyhow::Result<&EmbeddingModelConfig> {
+        self.models
+            .get(model_id)
+            .ok_or_else(|| anyhow!("Unknown embedding model: {}", model_id))
+    }
+
+    /// Returns the configuration of a registered multilingual text encoder.
+    pub fn get_text_encoder(&self, text_encoder_id: &str) -> anyhow::Result<&TextEncoderConfig> {
+        self.text_encoders
+            .get(text_encoder_id)
+            .ok_or_else(|| anyhow!("Unknown text encoder: {}", text_encoder_id))
+    }
+
+    /// Returns the path of a model file, relative to the models directory.
+    pub fn path(&self, file: &str) -> PathBuf {
+        Path::new(&self.dir).join(file)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct EmbeddingModelConfig {
+    pub architecture: ClipArchitecture,
     pub safetensors_file: String,
     pub tokenizer_file: String,
-    pub device: String,
+}
+
+/// Multilingual text encoder trained to match the image embeddings of a CLIP model, such
+/// as the M-CLIP encoders: an XLM-RoBERTa transformer followed by a linear projection.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TextEncoderConfig {
+    /// Id of the model whose image embeddings the encoder is aligned with.
+    pub model: String,
+    pub safetensors_file: String,
+    pub tokenizer_file: String,
+    /// Configuration of the XLM-RoBERTa transformer.
+    pub config_file: String,
+}
+
+/// Variant of the CLIP architecture of an embedding model.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClipArchitecture {
+    VitBasePatch32,
+    VitBasePatch16,
+    VitLargePatch14,
+    #[serde(rename = "vit-large-patch14-336")]
+    VitLargePatch14_336,
+}
+
+/// Precision of model weights. Half precision weights take half the memory and are faster on
+/// GPUs and CPUs with native support, at the cost of slightly different embeddings.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelDType {
+    F32,
+    F16,
+    Bf16,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -34,6 +122,81 @@ pub struct FaceDetectionServerConfig {
     pub port: u16,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ScannerConfig {
+    pub workers: usize,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub min_file_size: u64,
+    pub min_width: u32,
+    pub min_height: u32,
+    pub default_timezone: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ImportConfig {
+    pub template: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ThumbnailsConfig {
+    pub cache_dir: String,
+    pub sizes: Vec<u32>,
+}
+
+impl ThumbnailsConfig {
+    /// Returns the thumbnail cache directory, in the user cache directory unless configured.
+    pub fn cache_path(&self) -> PathBuf {
+        if self.cache_dir.is_empty() {
+            dirs::cache_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("picasa-rs")
+                .join("thumbnails")
+        } else {
+            PathBuf::from(&self.cache_dir)
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct JobsConfig {
+    pub max_attempts: i32,
+    pub retry_delay: u64,
+    pub poll_interval: u64,
+    pub stale_after: u64,
+    pub concurrency: JobConcurrencyConfig,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct JobConcurrencyConfig {
+    pub scan: usize,
+    pub hash: usize,
+    pub exif: usize,
+    pub embed: usize,
+    pub detect_faces: usize,
+    pub recognize: usize,
+}
+
+impl JobConcurrencyConfig {
+    /// Returns the number of jobs of a stage run at the same time, at least one.
+    pub fn get(&self, stage: JobStage) -> usize {
+        let concurrency = match stage {
+            JobStage::Scan => self.scan,
+            JobStage::Hash => self.hash,
+            JobStage::Exif => self.exif,
+            JobStage::Embed => self.embed,
+            JobStage::DetectFaces => self.detect_faces,
+            JobStage::Recognize => self.recognize,
+        };
+        concurrency.max(1)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ErrorsConfig {
+    pub max_attempts: i32,
+}
+
 impl Config {
     pub fn load() -> Result<Self, ConfigError> {
         let builder = ConfigBuilder::builder()
@@ -97,6 +97,47 @@ diesel::table! {
         person_id -> Nullable<Int4>,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        source -> Text,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use pgvector::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    jobs (id) {
+        id -> Int4,
+        stage -> Text,
+        library_id -> Nullable<Int4>,
+        photo_id -> Nullable<Int4>,
+        status -> Text,
+        attempts -> Int4,
+        last_error -> Nullable<Text>,
+        run_after -> Timestamptz,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use pgvector::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    libraries (id) {
+        id -> Int4,
+        name -> Text,
+        root_path -> Text,
+        enabled -> Bool,
+        with_exif -> Bool,
+        with_hash -> Bool,
+        include -> Array<Text>,
+        exclude -> Array<Text>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        write_xmp -> Bool,
+        prune -> Bool,
     }
 }
 
@@ -114,6 +155,35 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use pgvector::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    photo_embeddings (photo_id, model) {
+        photo_id -> Int4,
+        model -> Text,
+        embedding -> Vector,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use pgvector::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    photo_errors (id) {
+        id -> Int4,
+        photo_id -> Int4,
+        stage -> Text,
+        error -> Text,
+        attempts -> Int4,
+        last_attempt_at -> Timestamptz,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use pgvector::sql_types::*;
@@ -136,11 +206,30 @@ diesel::table! {
         gps_location -> Nullable<Geometry>,
         image_width -> Nullable<Int4>,
         image_height -> Nullable<Int4>,
-        embedding -> Nullable<Vector>,
         face_detection_completed -> Bool,
         country_id -> Nullable<Int4>,
         city_id -> Nullable<Int4>,
         indexed_at -> Timestamptz,
+        media_type -> Text,
+        library_id -> Nullable<Int4>,
+        relative_path -> Nullable<Text>,
+        aperture -> Nullable<Float4>,
+        exposure_time -> Nullable<Float4>,
+        iso -> Nullable<Int4>,
+        focal_length -> Nullable<Float4>,
+        focal_length_35mm -> Nullable<Int4>,
+        flash -> Nullable<Bool>,
+        altitude -> Nullable<Float4>,
+        gps_heading -> Nullable<Float4>,
+        offset_time -> Nullable<Text>,
+        date_taken_timezone -> Nullable<Text>,
+        date_taken_source -> Nullable<Text>,
+        keywords -> Array<Text>,
+        rating -> Nullable<Int4>,
+        title -> Nullable<Text>,
+        caption -> Nullable<Text>,
+        face_regions -> Nullable<Jsonb>,
+        perceptual_hash -> Nullable<Int8>,
     }
 }
 
@@ -163,14 +252,23 @@ diesel::table! {
 
 diesel::joinable!(faces -> people (person_id));
 diesel::joinable!(faces -> photos (photo_id));
+diesel::joinable!(jobs -> libraries (library_id));
+diesel::joinable!(jobs -> photos (photo_id));
+diesel::joinable!(photo_embeddings -> photos (photo_id));
+diesel::joinable!(photo_errors -> photos (photo_id));
 diesel::joinable!(photos -> cities (city_id));
 diesel::joinable!(photos -> countries (country_id));
+diesel::joinable!(photos -> libraries (library_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     cities,
     countries,
     faces,
+    jobs,
+    libraries,
     people,
+    photo_embeddings,
+    photo_errors,
     photos,
     spatial_ref_sys,
 );
@@ -0,0 +1,84 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use image::{DynamicImage, RgbImage};
+use libheif_rs::{ColorSpace, HeifContext, ImageHandle, LibHeif, RgbChroma};
+
+/// Decodes the primary image of a HEIF container, scaled down so that its
+/// shorter side is `min_size` pixels.
+///
+/// libheif cannot decode at a reduced scale, as HEVC images are always decoded at full
+/// resolution. When the container embeds a thumbnail whose shorter side is at least
+/// `min_size` pixels, the smallest such thumbnail is decoded instead of the primary
+/// image. Otherwise the primary image is decoded in full, then scaled down.
+///
+/// libheif rotates and mirrors the image as recorded in the container.
+pub(super) fn decode(path: &Path, min_size: Option<u32>) -> Result<DynamicImage> {
+    let lib_heif = LibHeif::new();
+    let ctx = HeifContext::read_from_file(
+        path.to_str()
+            .ok_or_else(|| anyhow::anyhow!("Invalid path"))?,
+    )
+    .context("Failed to read HEIC file")?;
+    let handle = ctx
+        .primary_image_handle()
+        .context("Failed to get primary image handle")?;
+    let handle = match min_size {
+        Some(min_size) => find_thumbnail(&handle, min_size).unwrap_or(handle),
+        None => handle,
+    };
+
+    let mut image = lib_heif
+        .decode(&handle, ColorSpace::Rgb(RgbChroma::Rgb), None)
+        .context("Failed to decode HEIC image")?;
+
+    let (width, height) = (image.width(), image.height());
+    if let Some(min_size) = min_size
+        && width.min(height) > min_size
+    {
+        let scale = min_size as f64 / width.min(height) as f64;
+        image = image
+            .scale(
+                (width as f64 * scale).ceil() as u32,
+                (height as f64 * scale).ceil() as u32,
+                None,
+            )
+            .context("Failed to resize HEIC image")?;
+    }
+
+    let (width, height) = (image.width(), image.height());
+    let planes = image.planes();
+    let interleaved_plane = planes
+        .interleaved
+        .ok_or_else(|| anyhow::anyhow!("No interleaved plane available"))?;
+
+    // Rows may be padded, so only the pixels of each row are copied
+    let row_size = width as usize * 3;
+    let image_data: Vec<u8> = interleaved_plane
+        .data
+        .chunks(interleaved_plane.stride)
+        .take(height as usize)
+        .flat_map(|row| &row[..row_size])
+        .copied()
+        .collect();
+
+    let image_buffer = RgbImage::from_raw(width, height, image_data)
+        .ok_or_else(|| anyhow::anyhow!("Failed to create image buffer from HEIF data"))?;
+
+    Ok(DynamicImage::ImageRgb8(image_buffer))
+}
+
+/// Finds the smallest embedded thumbnail whose shorter side is at least `min_size` pixels.
+///
+/// Handle dimensions already account for the rotation recorded in the container.
+fn find_thumbnail(handle: &ImageHandle, min_size: u32) -> Option<ImageHandle> {
+    let mut thumbnail_ids = vec![0; handle.number_of_thumbnails()];
+    let count = handle.thumbnail_ids(&mut thumbnail_ids);
+    thumbnail_ids.truncate(count);
+
+    thumbnail_ids
+        .into_iter()
+        .filter_map(|thumbnail_id| handle.thumbnail(thumbnail_id).ok())
+        .filter(|thumbnail| thumbnail.width().min(thumbnail.height()) >= min_size)
+        .min_by_key(|thumbnail| thumbnail.width() as u64 * thumbnail.height() as u64)
+}
@@ -0,0 +1,49 @@
+use std::io::Cursor;
+
+use anyhow::{Context, Result};
+use image::{DynamicImage, GrayImage, ImageFormat, RgbImage};
+use jpeg_decoder::{Decoder, PixelFormat};
+
+/// Decodes a JPEG stream, using DCT scaling to skip the details that would be lost once
+/// its shorter side is reduced to `min_size` pixels.
+/// The image is returned as stored, without applying its orientation.
+pub(super) fn decode(data: &[u8], min_size: Option<u32>) -> Result<DynamicImage> {
+    let mut decoder = Decoder::new(Cursor::new(data));
+    decoder.read_info().context("Failed to read JPEG header")?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| anyhow::anyhow!("Missing JPEG header"))?;
+
+    // The 8-bit decoder of the image crate also handles CMYK and 16-bit grayscale
+    if !matches!(info.pixel_format, PixelFormat::L8 | PixelFormat::RGB24) {
+        return image::load_from_memory_with_format(data, ImageFormat::Jpeg)
+            .context("Failed to decode JPEG image");
+    }
+
+    if let Some(min_size) = min_size {
+        // The decoder picks the smallest scale reaching either size, so only the shorter
+        // side is requested
+        let min_size = min_size.min(u16::MAX as u32) as u16;
+        let (width, height) = if info.width <= info.height {
+            (min_size, u16::MAX)
+        } else {
+            (u16::MAX, min_size)
+        };
+        decoder
+            .scale(width, height)
+            .context("Failed to scale JPEG image")?;
+    }
+
+    let pixels = decoder.decode().context("Failed to decode JPEG image")?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| anyhow::anyhow!("Missing JPEG header"))?;
+    let (width, height) = (info.width as u32, info.height as u32);
+
+    let image = match info.pixel_format {
+        PixelFormat::L8 => GrayImage::from_raw(width, height, pixels).map(DynamicImage::ImageLuma8),
+        _ => RgbImage::from_raw(width, height, pixels).map(DynamicImage::ImageRgb8),
+    };
+
+    image.ok_or_else(|| anyhow::anyhow!("Invalid JPEG pixel data"))
+}
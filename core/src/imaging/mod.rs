@@ -0,0 +1,167 @@
+//! Decoding of photos and video frames into images in their displayed orientation.
+
+mod heif;
+mod jpeg;
+mod raw;
+mod video;
+
+use std::{fs, io::Cursor, path::Path};
+
+use anyhow::{Context, Result};
+use image::{DynamicImage, ImageDecoder, ImageReader, metadata::Orientation};
+use nom_exif::ExifTag;
+
+use crate::utils::{self, media::MediaFormat};
+
+/// Decodes a photo, or a representative frame of a video, in its displayed orientation.
+pub fn load_image<P: AsRef<Path>>(path: P) -> Result<DynamicImage> {
+    load(path.as_ref(), None)
+}
+
+/// Decodes a photo, or a representative frame of a video, in its displayed orientation and
+/// scaled down while decoding when the format allows it, such as JPEG, HEIC and RAW files.
+///
+/// The shorter side of the image is kept at least `min_size` pixels long, unless the
+/// original is smaller, so that it can then be resized to the exact size needed.
+pub fn load_image_scaled<P: AsRef<Path>>(path: P, min_size: u32) -> Result<DynamicImage> {
+    load(path.as_ref(), Some(min_size))
+}
+
+/// Returns the dimensions of an image once its EXIF orientation is applied, swapping the
+/// width and height of rotated images.
+pub fn displayed_dimensions(width: i32, height: i32, orientation: Option<i32>) -> (i32, i32) {
+    match orientation {
+        Some(5..=8) => (height, width),
+        _ => (width, height),
+    }
+}
+
+fn load(path: &Path, min_size: Option<u32>) -> Result<DynamicImage> {
+    match MediaFormat::from_path(path) {
+        Some(MediaFormat::Heif) => heif::decode(path, min_size),
+        Some(MediaFormat::Video) => video::decode_frame(path),
+        Some(MediaFormat::Raw) => {
+            let preview = raw::read_preview(path)?;
+            let image = jpeg::decode(&preview, min_size).context("Failed to decode RAW preview")?;
+            Ok(apply_orientation(image, read_exif_orientation(path)))
+        }
+        Some(MediaFormat::Image) | None => {
+            let data = fs::read(path).context("Failed to open image file")?;
+
+            if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+                let image = jpeg::decode(&data, min_size)?;
+                return Ok(apply_orientation(image, read_exif_orientation(path)));
+            }
+
+            let mut decoder = ImageReader::new(Cursor::new(data))
+                .with_guessed_format()
+                .context("Failed to read image file")?
+                .into_decoder()
+                .context("Failed to decode image")?;
+            let orientation = decoder.orientation().ok();
+            let image = DynamicImage::from_decoder(decoder).context("Failed to decode image")?;
+
+            Ok(apply_orientation(image, orientation))
+        }
+    }
+}
+
+fn apply_orientation(mut image: DynamicImage, orientation: Option<Orientation>) -> DynamicImage {
+    if let Some(orientation) = orientation {
+        image.apply_orientation(orientation);
+    }
+    image
+}
+
+/// Reads the EXIF orientation of a file, for decoders that ignore it.
+fn read_exif_orientation(path: &Path) -> Option<Orientation> {
+    let orientation =
+        utils::exif_value_as_i32(utils::extract_exif(path)?.get(ExifTag::Orientation)?)?;
+    Orientation::from_exif(u8::try_from(orientation).ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageFormat, RgbImage};
+    use tempfile::TempDir;
+
+    fn encode(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, y| {
+            image::Rgb([(x % 256) as u8, (y % 256) as u8, 128])
+        }))
+        .write_to(&mut Cursor::new(&mut buffer), format)
+        .unwrap();
+        buffer
+    }
+
+    /// Inserts an EXIF segment recording an orientation right after the JPEG start marker.
+    fn with_exif_orientation(jpeg: &[u8], orientation: u16) -> Vec<u8> {
+        let mut exif = b"Exif\0\0II*\0".to_vec();
+        exif.extend_from_slice(&8u32.to_le_bytes());
+        exif.extend_from_slice(&1u16.to_le_bytes());
+        exif.extend_from_slice(&0x0112u16.to_le_bytes());
+        exif.extend_from_slice(&3u16.to_le_bytes());
+        exif.extend_from_slice(&1u32.to_le_bytes());
+        exif.extend_from_slice(&orientation.to_le_bytes());
+        exif.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+
+        let mut data = jpeg[..2].to_vec();
+        data.extend_from_slice(&[0xFF, 0xE1]);
+        data.extend_from_slice(&(exif.len() as u16 + 2).to_be_bytes());
+        data.extend_from_slice(&exif);
+        data.extend_from_slice(&jpeg[2..]);
+        data
+    }
+
+    #[test]
+    fn test_should_load_image_in_displayed_orientation() {
+        let temp_dir = TempDir::new().unwrap();
+        let rotated_path = temp_dir.path().join("rotated.jpg");
+        let upright_path = temp_dir.path().join("upright.jpg");
+        let jpeg = encode(64, 48, ImageFormat::Jpeg);
+        fs::write(&rotated_path, with_exif_orientation(&jpeg, 6)).unwrap();
+        fs::write(&upright_path, &jpeg).unwrap();
+
+        let rotated = load_image(&rotated_path).unwrap();
+        let upright = load_image(&upright_path).unwrap();
+
+        assert_eq!((rotated.width(), rotated.height()), (48, 64));
+        assert_eq!((upright.width(), upright.height()), (64, 48));
+    }
+
+    #[test]
+    fn test_should_scale_jpeg_while_decoding() {
+        let temp_dir = TempDir::new().unwrap();
+        let photo_path = temp_dir.path().join("photo.jpg");
+        fs::write(
+            &photo_path,
+            with_exif_orientation(&encode(1600, 1200, ImageFormat::Jpeg), 8),
+        )
+        .unwrap();
+
+        let image = load_image_scaled(&photo_path, 224).unwrap();
+
+        assert_eq!((image.width(), image.height()), (300, 400));
+    }
+
+    #[test]
+    fn test_should_not_scale_formats_without_fast_downscaling() {
+        let temp_dir = TempDir::new().unwrap();
+        let photo_path = temp_dir.path().join("photo.png");
+        fs::write(&photo_path, encode(640, 480, ImageFormat::Png)).unwrap();
+
+        let image = load_image_scaled(&photo_path, 224).unwrap();
+
+        assert_eq!((image.width(), image.height()), (640, 480));
+    }
+
+    #[test]
+    fn test_should_swap_dimensions_of_rotated_images() {
+        assert_eq!(displayed_dimensions(4000, 3000, Some(6)), (3000, 4000));
+        assert_eq!(displayed_dimensions(4000, 3000, Some(8)), (3000, 4000));
+        assert_eq!(displayed_dimensions(4000, 3000, Some(3)), (4000, 3000));
+        assert_eq!(displayed_dimensions(4000, 3000, None), (4000, 3000));
+    }
+}
@@ -0,0 +1,110 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+
+/// Reads the largest JPEG preview embedded in a RAW file.
+pub(super) fn read_preview(path: &Path) -> Result<Vec<u8>> {
+    let data = fs::read(path).context("Failed to read RAW file")?;
+    let preview = find_largest_jpeg(&data)
+        .ok_or_else(|| anyhow::anyhow!("No embedded preview found in RAW file"))?;
+
+    Ok(preview.to_vec())
+}
+
+/// Finds the largest complete JPEG stream in a buffer.
+fn find_largest_jpeg(data: &[u8]) -> Option<&[u8]> {
+    let mut largest: Option<&[u8]> = None;
+    let mut position = 0;
+
+    while let Some(offset) = data[position..]
+        .windows(3)
+        .position(|window| window == [0xFF, 0xD8, 0xFF])
+    {
+        let start = position + offset;
+
+        match jpeg_length(&data[start..]) {
+            Some(length) => {
+                let jpeg = &data[start..start + length];
+                if largest.is_none_or(|largest| jpeg.len() > largest.len()) {
+                    largest = Some(jpeg);
+                }
+                position = start + length;
+            }
+            None => position = start + 1,
+        }
+    }
+
+    largest
+}
+
+/// Returns the length of the JPEG stream starting at the beginning of a buffer.
+///
+/// Segments are skipped using their length, so that thumbnails embedded in the
+/// metadata of the stream do not end it early.
+fn jpeg_length(data: &[u8]) -> Option<usize> {
+    let mut position = 2;
+
+    loop {
+        if *data.get(position)? != 0xFF {
+            return None;
+        }
+
+        let marker = *data.get(position + 1)?;
+        match marker {
+            0xFF => position += 1,
+            0xD9 => return Some(position + 2),
+            0x01 | 0xD0..=0xD7 => position += 2,
+            _ => {
+                let length =
+                    u16::from_be_bytes([*data.get(position + 2)?, *data.get(position + 3)?]);
+                position += 2 + length as usize;
+
+                if marker == 0xDA {
+                    // Entropy-coded data ends at the first marker that is neither
+                    // a stuffed byte nor a restart marker.
+                    loop {
+                        position += data.get(position..)?.iter().position(|&b| b == 0xFF)?;
+                        match *data.get(position + 1)? {
+                            0x00 | 0xD0..=0xD7 => position += 2,
+                            _ => break,
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, ImageFormat, RgbImage};
+    use std::io::Cursor;
+
+    fn encode_jpeg(width: u32, height: u32) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        DynamicImage::ImageRgb8(RgbImage::new(width, height))
+            .write_to(&mut Cursor::new(&mut buffer), ImageFormat::Jpeg)
+            .unwrap();
+        buffer
+    }
+
+    #[test]
+    fn test_should_find_largest_embedded_jpeg() {
+        let thumbnail = encode_jpeg(8, 8);
+        let preview = encode_jpeg(64, 48);
+
+        let mut raw = b"II*\0 raw sensor data".to_vec();
+        raw.extend_from_slice(&thumbnail);
+        raw.extend_from_slice(&[0xFF, 0xD8, 0xFF, 0x00, 0x12]);
+        raw.extend_from_slice(&preview);
+        raw.extend_from_slice(b"trailing data");
+
+        assert_eq!(find_largest_jpeg(&raw), Some(preview.as_slice()));
+    }
+
+    #[test]
+    fn test_should_not_find_jpeg_in_data_without_preview() {
+        assert_eq!(find_largest_jpeg(b"II*\0 raw sensor data"), None);
+    }
+}
@@ -0,0 +1,29 @@
+use std::{path::Path, process::Command};
+
+use anyhow::{Context, Result, bail};
+use image::DynamicImage;
+
+/// Decodes a representative frame of a video, chosen by ffmpeg's thumbnail filter.
+///
+/// ffmpeg rotates the frame as recorded in the video metadata.
+pub(super) fn decode_frame(path: &Path) -> Result<DynamicImage> {
+    let output = Command::new("ffmpeg")
+        .arg("-v")
+        .arg("error")
+        .arg("-i")
+        .arg(path)
+        .args(["-vf", "thumbnail", "-frames:v", "1"])
+        .args(["-f", "image2pipe", "-c:v", "png", "-"])
+        .output()
+        .context("Failed to run ffmpeg")?;
+
+    if !output.status.success() || output.stdout.is_empty() {
+        bail!(
+            "Failed to extract video frame: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    image::load_from_memory_with_format(&output.stdout, image::ImageFormat::Png)
+        .context("Failed to decode video frame")
+}
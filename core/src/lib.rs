@@ -1,5 +1,6 @@
 pub mod config;
 pub mod database;
+pub mod imaging;
 pub mod models;
 pub mod repositories;
 pub mod services;
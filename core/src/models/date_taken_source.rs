@@ -0,0 +1,65 @@
+use std::{fmt, io::Write, str::FromStr};
+
+use anyhow::bail;
+use diesel::{
+    AsExpression, FromSqlRow,
+    deserialize::{self, FromSql},
+    pg::{Pg, PgValue},
+    serialize::{self, IsNull, Output, ToSql},
+    sql_types::Text,
+};
+
+/// Where the timezone used to convert the capture time of a photo to UTC comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Text)]
+pub enum DateTakenSource {
+    /// Offset recorded in the metadata, such as `OffsetTimeOriginal`.
+    Offset,
+    /// Timezone of the city matched from the GPS location.
+    GpsTimezone,
+    /// Timezone configured as default for photos without offset nor location.
+    DefaultTimezone,
+}
+
+impl DateTakenSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DateTakenSource::Offset => "offset",
+            DateTakenSource::GpsTimezone => "gps_timezone",
+            DateTakenSource::DefaultTimezone => "default_timezone",
+        }
+    }
+}
+
+impl fmt::Display for DateTakenSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DateTakenSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "offset" => Ok(DateTakenSource::Offset),
+            "gps_timezone" => Ok(DateTakenSource::GpsTimezone),
+            "default_timezone" => Ok(DateTakenSource::DefaultTimezone),
+            _ => bail!("Unknown date taken source: {}", s),
+        }
+    }
+}
+
+impl ToSql<Text, Pg> for DateTakenSource {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Text, Pg> for DateTakenSource {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let value = std::str::from_utf8(bytes.as_bytes())?;
+        value.parse().map_err(|err: anyhow::Error| err.into())
+    }
+}
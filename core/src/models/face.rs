@@ -1,6 +1,7 @@
 use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use pgvector::Vector;
+use serde::{Deserialize, Serialize};
 
 use crate::{database::schema::faces, models::PaginatedResult};
 
@@ -20,6 +21,7 @@ pub struct Face {
     pub person_id: Option<i32>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub source: String,
 }
 
 #[derive(Insertable, Default)]
@@ -49,4 +51,17 @@ pub struct FaceWithPhoto {
     pub photo_path: String,
 }
 
+/// Named face region read from photo metadata, in pixels.
+///
+/// Regions are stored on photos, from which the database creates faces linked to the
+/// people of the same name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FaceRegion {
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
 pub type PaginatedFaces = PaginatedResult<Face>;
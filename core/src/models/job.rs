@@ -0,0 +1,209 @@
+use std::{fmt, io::Write, str::FromStr};
+
+use anyhow::bail;
+use chrono::{DateTime, Utc};
+use diesel::{
+    AsExpression, FromSqlRow, Queryable, Selectable,
+    deserialize::{self, FromSql},
+    pg::{Pg, PgValue},
+    serialize::{self, IsNull, Output, ToSql},
+    sql_types::Text,
+};
+
+use crate::database::schema::jobs;
+
+/// Stage of the indexing pipeline run by a job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Text)]
+pub enum JobStage {
+    /// Walks a library and indexes new and changed files.
+    Scan,
+    /// Computes the file hash and perceptual hash of a photo.
+    Hash,
+    /// Extracts the EXIF data and XMP/IPTC metadata of a photo.
+    Exif,
+    /// Computes the image embedding of a photo.
+    Embed,
+    /// Detects the faces of a photo.
+    DetectFaces,
+    /// Clusters detected faces and assigns them to people.
+    Recognize,
+}
+
+impl JobStage {
+    /// Stages in pipeline order.
+    pub const ALL: [JobStage; 6] = [
+        JobStage::Scan,
+        JobStage::Hash,
+        JobStage::Exif,
+        JobStage::Embed,
+        JobStage::DetectFaces,
+        JobStage::Recognize,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStage::Scan => "scan",
+            JobStage::Hash => "hash",
+            JobStage::Exif => "exif",
+            JobStage::Embed => "embed",
+            JobStage::DetectFaces => "detect_faces",
+            JobStage::Recognize => "recognize",
+        }
+    }
+}
+
+impl fmt::Display for JobStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for JobStage {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "scan" => Ok(JobStage::Scan),
+            "hash" => Ok(JobStage::Hash),
+            "exif" => Ok(JobStage::Exif),
+            "embed" => Ok(JobStage::Embed),
+            "detect_faces" => Ok(JobStage::DetectFaces),
+            "recognize" => Ok(JobStage::Recognize),
+            _ => bail!("Unknown job stage: {}", s),
+        }
+    }
+}
+
+impl ToSql<Text, Pg> for JobStage {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Text, Pg> for JobStage {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let value = std::str::from_utf8(bytes.as_bytes())?;
+        value.parse().map_err(|err: anyhow::Error| err.into())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Text)]
+pub enum JobStatus {
+    /// Waiting to be claimed by a worker, possibly after a failed attempt.
+    Pending,
+    Running,
+    Done,
+    /// Failed after the maximum number of attempts.
+    Failed,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Pending => "pending",
+            JobStatus::Running => "running",
+            JobStatus::Done => "done",
+            JobStatus::Failed => "failed",
+        }
+    }
+}
+
+impl fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for JobStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pending" => Ok(JobStatus::Pending),
+            "running" => Ok(JobStatus::Running),
+            "done" => Ok(JobStatus::Done),
+            "failed" => Ok(JobStatus::Failed),
+            _ => bail!("Unknown job status: {}", s),
+        }
+    }
+}
+
+impl ToSql<Text, Pg> for JobStatus {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Text, Pg> for JobStatus {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let value = std::str::from_utf8(bytes.as_bytes())?;
+        value.parse().map_err(|err: anyhow::Error| err.into())
+    }
+}
+
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = jobs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Job {
+    pub id: i32,
+    pub stage: JobStage,
+    pub library_id: Option<i32>,
+    pub photo_id: Option<i32>,
+    pub status: JobStatus,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub run_after: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Job to enqueue, identified by its stage and target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewJob {
+    pub stage: JobStage,
+    pub library_id: Option<i32>,
+    pub photo_id: Option<i32>,
+}
+
+impl NewJob {
+    /// Creates a job running a stage over a whole library.
+    pub fn library(stage: JobStage, library_id: i32) -> Self {
+        Self {
+            stage,
+            library_id: Some(library_id),
+            photo_id: None,
+        }
+    }
+
+    /// Creates a job running a stage on a single photo.
+    pub fn photo(stage: JobStage, photo_id: i32) -> Self {
+        Self {
+            stage,
+            library_id: None,
+            photo_id: Some(photo_id),
+        }
+    }
+
+    /// Creates a job running a stage over the whole index.
+    pub fn global(stage: JobStage) -> Self {
+        Self {
+            stage,
+            library_id: None,
+            photo_id: None,
+        }
+    }
+}
+
+/// Number of jobs of a stage by status.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobStageSummary {
+    pub stage: JobStage,
+    pub pending: i64,
+    pub running: i64,
+    pub done: i64,
+    pub failed: i64,
+}
@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+
+use crate::database::schema::libraries;
+
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = libraries)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Library {
+    pub id: i32,
+    pub name: String,
+    pub root_path: String,
+    pub enabled: bool,
+    pub with_exif: bool,
+    pub with_hash: bool,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub write_xmp: bool,
+    /// Remove photos whose file no longer exists when scanning the library.
+    pub prune: bool,
+}
+
+#[derive(Insertable, Debug, Default, Clone)]
+#[diesel(table_name = libraries)]
+pub struct NewLibrary {
+    pub name: String,
+    pub root_path: String,
+    pub enabled: bool,
+    pub with_exif: bool,
+    pub with_hash: bool,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub write_xmp: bool,
+    pub prune: bool,
+}
+
+#[derive(AsChangeset, Debug, Default)]
+#[diesel(table_name = libraries)]
+pub struct UpdatedLibrary {
+    pub root_path: Option<String>,
+    pub enabled: Option<bool>,
+    pub write_xmp: Option<bool>,
+}
@@ -0,0 +1,60 @@
+use std::{fmt, io::Write, str::FromStr};
+
+use anyhow::bail;
+use diesel::{
+    AsExpression, FromSqlRow,
+    deserialize::{self, FromSql},
+    pg::{Pg, PgValue},
+    serialize::{self, IsNull, Output, ToSql},
+    sql_types::Text,
+};
+
+/// Kind of media stored in the `photos` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Text)]
+pub enum MediaType {
+    #[default]
+    Photo,
+    Video,
+}
+
+impl MediaType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MediaType::Photo => "photo",
+            MediaType::Video => "video",
+        }
+    }
+}
+
+impl fmt::Display for MediaType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for MediaType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "photo" => Ok(MediaType::Photo),
+            "video" => Ok(MediaType::Video),
+            _ => bail!("Unknown media type: {}", s),
+        }
+    }
+}
+
+impl ToSql<Text, Pg> for MediaType {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Text, Pg> for MediaType {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let value = std::str::from_utf8(bytes.as_bytes())?;
+        value.parse().map_err(|err: anyhow::Error| err.into())
+    }
+}
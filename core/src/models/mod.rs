@@ -1,18 +1,34 @@
 pub mod city;
 pub mod country;
+pub mod date_taken_source;
 pub mod face;
 pub mod face_cluster;
+pub mod job;
+pub mod library;
+pub mod media_type;
 pub mod new_photo;
 pub mod pagination;
 pub mod person;
 pub mod photo;
+pub mod photo_embedding;
+pub mod photo_error;
+pub mod sidecar_metadata;
+pub mod similarity_candidate;
 
 pub use city::{City, CityName};
 pub use country::{Country, CountryName};
+pub use date_taken_source::DateTakenSource;
+pub use job::{Job, JobStage, JobStageSummary, JobStatus, NewJob};
+pub use library::{Library, NewLibrary, UpdatedLibrary};
+pub use media_type::MediaType;
 pub use new_photo::NewPhoto;
 pub use pagination::{PaginatedResult, PaginationFilter};
-pub use photo::{PaginatedPhotoPaths, PaginatedPhotos, Photo, PhotoPath, UpdatedPhoto};
+pub use photo::{PaginatedPhotoPaths, PaginatedPhotos, Photo, PhotoFile, PhotoPath, UpdatedPhoto};
+pub use photo_embedding::NewPhotoEmbedding;
+pub use photo_error::{PhotoError, PhotoErrorWithPath};
+pub use sidecar_metadata::{SidecarFace, SidecarMetadata};
+pub use similarity_candidate::{EmbeddingSimilarity, SimilarityCandidate};
 
-pub use face::{Face, FaceWithPhoto, NewFace, PaginatedFaces, UpdatedFace};
+pub use face::{Face, FaceRegion, FaceWithPhoto, NewFace, PaginatedFaces, UpdatedFace};
 pub use face_cluster::FaceCluster;
 pub use person::{NewPerson, Person};
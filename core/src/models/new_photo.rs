@@ -1,9 +1,16 @@
-use crate::{database::schema::photos, utils::convert_exif_gps_info_to_postgis_point};
+use crate::{
+    database::schema::photos,
+    imaging,
+    models::{DateTakenSource, FaceRegion, MediaType},
+    utils::{
+        convert_exif_gps_info_to_postgis_point, convert_gps_altitude, exif_value_as_f32,
+        exif_value_as_i32, media::MediaFormat, metadata::PhotoMetadata,
+    },
+};
 use anyhow::{Context, Result};
 use chrono::{DateTime, NaiveDateTime, Utc};
 use diesel::Insertable;
-use nom_exif::{Exif, ExifTag};
-use pgvector::Vector;
+use nom_exif::{EntryValue, Exif, ExifTag, TrackInfo, TrackInfoTag};
 use postgis_diesel::types::Point;
 use std::path::Path;
 
@@ -27,7 +34,26 @@ pub struct NewPhoto {
     pub gps_location: Option<Point>,
     pub image_width: Option<i32>,
     pub image_height: Option<i32>,
-    pub embedding: Option<Vector>,
+    pub media_type: MediaType,
+    pub library_id: Option<i32>,
+    pub relative_path: Option<String>,
+    pub aperture: Option<f32>,
+    pub exposure_time: Option<f32>,
+    pub iso: Option<i32>,
+    pub focal_length: Option<f32>,
+    pub focal_length_35mm: Option<i32>,
+    pub flash: Option<bool>,
+    pub altitude: Option<f32>,
+    pub gps_heading: Option<f32>,
+    pub offset_time: Option<String>,
+    pub date_taken_timezone: Option<String>,
+    pub date_taken_source: Option<DateTakenSource>,
+    pub keywords: Vec<String>,
+    pub rating: Option<i32>,
+    pub title: Option<String>,
+    pub caption: Option<String>,
+    pub face_regions: Option<serde_json::Value>,
+    pub perceptual_hash: Option<i64>,
 }
 
 impl NewPhoto {
@@ -40,8 +66,7 @@ impl NewPhoto {
             file_name: path.file_name().unwrap().to_string_lossy().into_owned(),
             file_size: metadata.len() as i64,
             created_at: metadata.created().map(DateTime::<Utc>::from)?,
-            modified_at: metadata.created().map(DateTime::<Utc>::from)?,
-            embedding: None,
+            modified_at: metadata.modified().map(DateTime::<Utc>::from)?,
             indexed_at: now,
             hash: None,
             camera_make: None,
@@ -53,14 +78,101 @@ impl NewPhoto {
             image_width: None,
             image_height: None,
             gps_location: None,
+            media_type: MediaFormat::from_path(path)
+                .map(|format| format.media_type())
+                .unwrap_or_default(),
+            library_id: None,
+            relative_path: None,
+            aperture: None,
+            exposure_time: None,
+            iso: None,
+            focal_length: None,
+            focal_length_35mm: None,
+            flash: None,
+            altitude: None,
+            gps_heading: None,
+            offset_time: None,
+            date_taken_timezone: None,
+            date_taken_source: None,
+            keywords: Vec::new(),
+            rating: None,
+            title: None,
+            caption: None,
+            face_regions: None,
+            perceptual_hash: None,
         })
     }
 
+    /// Sets the timezone of capture times recorded without offset.
+    ///
+    /// The database replaces it with the timezone of the city matched from the GPS
+    /// location, if any, and converts the capture time to UTC.
+    pub fn with_default_timezone(mut self, timezone: &str) -> Self {
+        if self.date_taken_local.is_some() && self.date_taken_source.is_none() {
+            self.date_taken_timezone = Some(timezone.to_string());
+            self.date_taken_source = Some(DateTakenSource::DefaultTimezone);
+        }
+        self
+    }
+
+    /// Attaches the photo to a library, storing its path relative to the library root.
+    pub fn with_library(mut self, library_id: i32, root_path: &Path) -> Self {
+        if let Ok(relative_path) = Path::new(&self.path).strip_prefix(root_path) {
+            self.library_id = Some(library_id);
+            self.relative_path = Some(relative_path.to_string_lossy().into_owned());
+        }
+        self
+    }
+
+    /// Sets the keywords, rating, title and caption, and the face regions scaled to the
+    /// displayed image dimensions, or to the dimensions recorded with the regions.
+    pub fn with_metadata(mut self, metadata: PhotoMetadata) -> Self {
+        let image_dimensions = self
+            .image_width
+            .zip(self.image_height)
+            .map(|(width, height)| imaging::displayed_dimensions(width, height, self.orientation));
+
+        let face_regions: Vec<FaceRegion> = metadata
+            .regions
+            .into_iter()
+            .filter_map(|region| {
+                let (width, height) = image_dimensions
+                    .map(|(width, height)| (width as f64, height as f64))
+                    .or(region
+                        .applied_to
+                        .map(|(width, height)| (width as f64, height as f64)))
+                    .filter(|(width, height)| *width > 0.0 && *height > 0.0)?;
+
+                Some(FaceRegion {
+                    name: region.name,
+                    x: (region.left * width).round() as i32,
+                    y: (region.top * height).round() as i32,
+                    width: (region.width * width).round() as i32,
+                    height: (region.height * height).round() as i32,
+                })
+            })
+            .collect();
+
+        self.keywords = metadata.keywords;
+        self.rating = metadata.rating;
+        self.title = metadata.title;
+        self.caption = metadata.caption;
+        self.face_regions = (!face_regions.is_empty())
+            .then(|| serde_json::to_value(face_regions).ok())
+            .flatten();
+        self
+    }
+
     pub fn with_hash(mut self, hash: String) -> Self {
         self.hash = Some(hash);
         self
     }
 
+    pub fn with_perceptual_hash(mut self, perceptual_hash: i64) -> Self {
+        self.perceptual_hash = Some(perceptual_hash);
+        self
+    }
+
     pub fn with_exif(mut self, exif: Exif) -> Self {
         if let Some(make) = exif.get(ExifTag::Make) {
             self.camera_make = make.as_str().map(|s| s.to_string());
@@ -75,18 +187,51 @@ impl NewPhoto {
         }
 
         if let Some(orientation) = exif.get(ExifTag::Orientation) {
-            self.orientation = orientation.as_i32();
+            self.orientation = exif_value_as_i32(orientation);
         }
 
         if let Some(date_time_original) = exif.get(ExifTag::DateTimeOriginal) {
-            self.date_taken_utc = date_time_original.as_time().map(|t| t.to_utc());
-            self.date_taken_local = date_time_original.as_time().map(|t| t.naive_local());
+            self.set_date_taken(date_time_original);
+        }
+
+        if let Some(offset_time) = exif.get(ExifTag::OffsetTimeOriginal) {
+            self.offset_time = offset_time.as_str().map(|s| s.trim().to_string());
         }
 
         if let Ok(Some(gps_info)) = exif.get_gps_info() {
+            self.altitude = convert_gps_altitude(&gps_info);
             self.gps_location = convert_exif_gps_info_to_postgis_point(gps_info);
         }
 
+        if let Some(heading) = exif.get(ExifTag::GPSImgDirection) {
+            self.gps_heading = exif_value_as_f32(heading);
+        }
+
+        if let Some(aperture) = exif.get(ExifTag::FNumber) {
+            self.aperture = exif_value_as_f32(aperture);
+        }
+
+        if let Some(exposure_time) = exif.get(ExifTag::ExposureTime) {
+            self.exposure_time = exif_value_as_f32(exposure_time);
+        }
+
+        if let Some(iso) = exif.get(ExifTag::ISOSpeedRatings) {
+            self.iso = exif_value_as_i32(iso);
+        }
+
+        if let Some(focal_length) = exif.get(ExifTag::FocalLength) {
+            self.focal_length = exif_value_as_f32(focal_length);
+        }
+
+        if let Some(focal_length_35mm) = exif.get(ExifTag::FocalLengthIn35mmFilm) {
+            self.focal_length_35mm = exif_value_as_i32(focal_length_35mm).filter(|f| *f > 0);
+        }
+
+        // The lowest bit of the Flash tag tells whether the flash fired
+        if let Some(flash) = exif.get(ExifTag::Flash) {
+            self.flash = exif_value_as_i32(flash).map(|f| f & 1 == 1);
+        }
+
         if let Some(width) = exif.get(ExifTag::ExifImageWidth) {
             self.image_width = width.as_u32().map(|w| w as i32);
         }
@@ -97,4 +242,52 @@ impl NewPhoto {
 
         self
     }
+
+    pub fn with_track_info(mut self, track_info: TrackInfo) -> Self {
+        if let Some(make) = track_info.get(TrackInfoTag::Make) {
+            self.camera_make = make.as_str().map(|s| s.to_string());
+        }
+
+        if let Some(model) = track_info.get(TrackInfoTag::Model) {
+            self.camera_model = model.as_str().map(|s| s.to_string());
+        }
+
+        if let Some(create_date) = track_info.get(TrackInfoTag::CreateDate) {
+            self.set_date_taken(create_date);
+        }
+
+        if let Some(gps_info) = track_info.get_gps_info() {
+            self.altitude = convert_gps_altitude(gps_info);
+            self.gps_location = convert_exif_gps_info_to_postgis_point(gps_info.clone());
+        }
+
+        if let Some(width) = track_info.get(TrackInfoTag::ImageWidth) {
+            self.image_width = width.as_u32().map(|w| w as i32);
+        }
+
+        if let Some(height) = track_info.get(TrackInfoTag::ImageHeight) {
+            self.image_height = height.as_u32().map(|h| h as i32);
+        }
+
+        self
+    }
+
+    /// Sets the local capture time, and its UTC time when the metadata records an offset.
+    fn set_date_taken(&mut self, value: &EntryValue) {
+        match value {
+            EntryValue::Time(time) => {
+                self.date_taken_local = Some(time.naive_local());
+                self.date_taken_utc = Some(time.to_utc());
+                self.date_taken_timezone = Some(time.offset().to_string());
+                self.date_taken_source = Some(DateTakenSource::Offset);
+            }
+            EntryValue::NaiveDateTime(time) => {
+                self.date_taken_local = Some(*time);
+                self.date_taken_utc = None;
+                self.date_taken_timezone = None;
+                self.date_taken_source = None;
+            }
+            _ => {}
+        }
+    }
 }
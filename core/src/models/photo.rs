@@ -1,7 +1,9 @@
-use crate::{database::schema::photos, models::pagination::PaginatedResult};
+use crate::{
+    database::schema::photos,
+    models::{DateTakenSource, MediaType, pagination::PaginatedResult},
+};
 use chrono::{DateTime, NaiveDateTime, Utc};
 use diesel::{AsChangeset, Insertable, Queryable, QueryableByName, Selectable};
-use pgvector::Vector;
 use postgis_diesel::types::Point;
 
 #[derive(Debug, Queryable, Selectable, Insertable, Default, Clone, QueryableByName)]
@@ -24,17 +26,39 @@ pub struct Photo {
     pub gps_location: Option<Point>,
     pub image_width: Option<i32>,
     pub image_height: Option<i32>,
-    pub embedding: Option<Vector>,
     pub face_detection_completed: bool,
     pub country_id: Option<i32>,
     pub city_id: Option<i32>,
     pub indexed_at: DateTime<Utc>,
+    pub media_type: MediaType,
+    pub library_id: Option<i32>,
+    pub relative_path: Option<String>,
+    pub aperture: Option<f32>,
+    pub exposure_time: Option<f32>,
+    pub iso: Option<i32>,
+    pub focal_length: Option<f32>,
+    pub focal_length_35mm: Option<i32>,
+    pub flash: Option<bool>,
+    pub altitude: Option<f32>,
+    pub gps_heading: Option<f32>,
+    pub offset_time: Option<String>,
+    pub date_taken_timezone: Option<String>,
+    pub date_taken_source: Option<DateTakenSource>,
+    pub keywords: Vec<String>,
+    pub rating: Option<i32>,
+    pub title: Option<String>,
+    pub caption: Option<String>,
+    pub face_regions: Option<serde_json::Value>,
+    pub perceptual_hash: Option<i64>,
 }
 
 #[derive(AsChangeset, Debug, Default)]
 #[diesel(table_name = photos)]
 pub struct UpdatedPhoto {
-    pub embedding: Option<Option<Vector>>,
+    pub path: Option<String>,
+    pub file_name: Option<String>,
+    pub hash: Option<Option<String>>,
+    pub perceptual_hash: Option<Option<i64>>,
     pub face_detection_completed: Option<bool>,
 }
 
@@ -45,6 +69,18 @@ pub struct PhotoPath {
     pub path: String,
 }
 
+#[derive(Debug, Queryable, Selectable, Clone)]
+#[diesel(table_name = photos)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PhotoFile {
+    pub id: i32,
+    pub path: String,
+    pub file_name: String,
+    pub file_size: i64,
+    pub modified_at: DateTime<Utc>,
+    pub hash: Option<String>,
+}
+
 pub type PaginatedPhotos = PaginatedResult<Photo>;
 
 pub type PaginatedPhotoPaths = PaginatedResult<PhotoPath>;
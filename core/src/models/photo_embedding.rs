@@ -0,0 +1,15 @@
+use diesel::Insertable;
+use pgvector::Vector;
+
+use crate::database::schema::photo_embeddings;
+
+/// Embedding of a photo computed by a model of the registry.
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = photo_embeddings)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewPhotoEmbedding {
+    pub photo_id: i32,
+    /// Id of the model in the registry.
+    pub model: String,
+    pub embedding: Vector,
+}
@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use diesel::{Queryable, Selectable};
+
+use crate::{database::schema::photo_errors, models::JobStage};
+
+/// Failure of a pipeline stage on a photo, recorded so that the photo is skipped once it
+/// failed too many times.
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = photo_errors)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PhotoError {
+    pub id: i32,
+    pub photo_id: i32,
+    pub stage: JobStage,
+    /// Error of the last failed attempt.
+    pub error: String,
+    pub attempts: i32,
+    pub last_attempt_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct PhotoErrorWithPath {
+    pub photo_error: PhotoError,
+    pub photo_path: String,
+}
@@ -0,0 +1,23 @@
+/// Metadata curated in picasa-rs, written back to the XMP sidecar of a photo.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SidecarMetadata {
+    pub photo_id: i32,
+    pub path: String,
+    pub image_width: Option<i32>,
+    pub image_height: Option<i32>,
+    pub orientation: Option<i32>,
+    pub keywords: Vec<String>,
+    pub city: Option<String>,
+    pub country: Option<String>,
+    pub faces: Vec<SidecarFace>,
+}
+
+/// Face of a known person, in pixels.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SidecarFace {
+    pub person_name: String,
+    pub bbox_x: i32,
+    pub bbox_y: i32,
+    pub bbox_width: i32,
+    pub bbox_height: i32,
+}
@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use diesel::{
+    ExpressionMethods, NullableExpressionMethods, Queryable, QueryableByName, Selectable,
+    sql_types::{Float4, Integer},
+};
+
+use crate::database::schema::{photo_embeddings, photos};
+
+/// Photo compared with others to find near-duplicates and bursts, and whether it has an
+/// embedding for one model.
+#[derive(Debug, Queryable, Selectable, Default, Clone)]
+#[diesel(table_name = photos)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct SimilarityCandidate {
+    pub id: i32,
+    pub path: String,
+    pub file_size: i64,
+    pub image_width: Option<i32>,
+    pub image_height: Option<i32>,
+    pub date_taken_utc: Option<DateTime<Utc>>,
+    pub rating: Option<i32>,
+    pub perceptual_hash: Option<i64>,
+    #[diesel(select_expression = photo_embeddings::photo_id.nullable().is_not_null())]
+    #[diesel(select_expression_type = diesel::dsl::IsNotNull<diesel::dsl::Nullable<photo_embeddings::photo_id>>)]
+    pub has_embedding: bool,
+}
+
+/// Cosine similarity between the embeddings of two photos for one model.
+#[derive(Debug, QueryableByName, Clone, PartialEq)]
+pub struct EmbeddingSimilarity {
+    #[diesel(sql_type = Integer)]
+    pub photo_id: i32,
+    #[diesel(sql_type = Integer)]
+    pub other_photo_id: i32,
+    #[diesel(sql_type = Float4)]
+    pub similarity: f32,
+}
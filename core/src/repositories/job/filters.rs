@@ -0,0 +1,8 @@
+use crate::models::{JobStage, JobStatus};
+
+#[derive(Debug, Default, Clone)]
+pub struct JobFindFilters {
+    pub stage: Option<JobStage>,
+    pub status: Option<JobStatus>,
+    pub photo_id: Option<i32>,
+}
@@ -0,0 +1,280 @@
+use std::time::Duration;
+
+use anyhow::{Context, Error, Result};
+use diesel::{
+    Connection, ExpressionMethods, IntoSql, QueryDsl, RunQueryDsl, SelectableHelper,
+    dsl::{IntervalDsl, now},
+    sql_query,
+    sql_types::{Array, Integer, Nullable, Text, Timestamptz},
+};
+
+use crate::{
+    database::{DbConnection, DbPool, schema},
+    models::{Job, JobStage, JobStatus, NewJob},
+    repositories::job::filters::JobFindFilters,
+};
+
+#[cfg_attr(test, mockall::automock)]
+pub trait JobRepository {
+    /// Enqueues jobs, resetting the finished jobs of the same stage and target, and returns
+    /// the number of enqueued jobs. Running jobs are left untouched.
+    fn enqueue(&mut self, new_jobs: Vec<NewJob>) -> Result<usize>;
+
+    /// Claims up to `limit` due pending jobs of a stage, marking them as running.
+    fn claim(&mut self, stage: JobStage, limit: i64) -> Result<Vec<Job>>;
+
+    /// Marks a running job as done.
+    fn complete(&mut self, id: i32) -> Result<()>;
+
+    /// Records the error of a running job, which is retried after `retry_after`, or marked
+    /// as failed when there is no retry left.
+    fn fail(&mut self, id: i32, error: String, retry_after: Option<Duration>) -> Result<()>;
+
+    /// Refreshes the update time of running jobs, so that they are not taken for jobs of
+    /// an interrupted worker while they run.
+    fn heartbeat(&mut self, ids: Vec<i32>) -> Result<()>;
+
+    /// Makes jobs that have not been updated for `stale_after`, left running by an
+    /// interrupted worker, pending again and returns their number.
+    fn requeue_stale(&mut self, stale_after: Duration) -> Result<usize>;
+
+    /// Counts the pending and running jobs of the given stages.
+    fn count_active(&mut self, stages: Vec<JobStage>) -> Result<i64>;
+
+    /// Counts jobs by stage and status.
+    fn count_by_status(&mut self) -> Result<Vec<(JobStage, JobStatus, i64)>>;
+
+    /// Finds jobs matching the filters, most recently updated first.
+    fn find_many(&mut self, filters: JobFindFilters, limit: i64) -> Result<Vec<Job>>;
+
+    /// Makes failed jobs, of a stage or all of them, pending again and returns their number.
+    fn retry_failed(&mut self, stage: Option<JobStage>) -> Result<usize>;
+}
+
+pub struct PgJobRepository {
+    pool: DbPool,
+}
+
+impl PgJobRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    fn get_connection(&self) -> Result<DbConnection, Error> {
+        self.pool
+            .get()
+            .map_err(Error::from)
+            .context("Failed to get database connection")
+    }
+}
+
+impl JobRepository for PgJobRepository {
+    fn enqueue(&mut self, mut new_jobs: Vec<NewJob>) -> Result<usize> {
+        let mut conn = self.get_connection()?;
+
+        // A statement cannot update the same row twice
+        new_jobs.sort_by_key(|job| (job.stage.as_str(), job.library_id, job.photo_id));
+        new_jobs.dedup();
+
+        let (stages, (library_ids, photo_ids)): (Vec<JobStage>, (Vec<_>, Vec<_>)) = new_jobs
+            .into_iter()
+            .map(|job| (job.stage, (job.library_id, job.photo_id)))
+            .unzip();
+
+        let count = sql_query(
+            "INSERT INTO jobs (stage, library_id, photo_id)
+            SELECT * FROM UNNEST($1::TEXT[], $2::INTEGER[], $3::INTEGER[])
+            ON CONFLICT (stage, (COALESCE(library_id, 0)), (COALESCE(photo_id, 0))) DO UPDATE
+            SET status = 'pending', attempts = 0, last_error = NULL, run_after = NOW()
+            WHERE jobs.status <> 'running'",
+        )
+        .bind::<Array<Text>, _>(stages)
+        .bind::<Array<Nullable<Integer>>, _>(library_ids)
+        .bind::<Array<Nullable<Integer>>, _>(photo_ids)
+        .execute(&mut conn)?;
+
+        Ok(count)
+    }
+
+    fn claim(&mut self, stage: JobStage, limit: i64) -> Result<Vec<Job>> {
+        let mut conn = self.get_connection()?;
+
+        let mut jobs = conn.transaction(|conn| {
+            // Skipping locked rows lets several workers claim jobs at the same time
+            let ids: Vec<i32> = schema::jobs::table
+                .select(schema::jobs::id)
+                .filter(schema::jobs::stage.eq(stage))
+                .filter(schema::jobs::status.eq(JobStatus::Pending))
+                .filter(schema::jobs::run_after.le(now))
+                .order(schema::jobs::id.asc())
+                .limit(limit)
+                .for_update()
+                .skip_locked()
+                .load(conn)?;
+
+            diesel::update(schema::jobs::table.filter(schema::jobs::id.eq_any(ids)))
+                .set((
+                    schema::jobs::status.eq(JobStatus::Running),
+                    schema::jobs::attempts.eq(schema::jobs::attempts + 1),
+                ))
+                .returning(Job::as_returning())
+                .get_results::<Job>(conn)
+        })?;
+        jobs.sort_by_key(|job| job.id);
+
+        Ok(jobs)
+    }
+
+    fn complete(&mut self, id: i32) -> Result<()> {
+        let mut conn = self.get_connection()?;
+
+        diesel::update(
+            schema::jobs::table
+                .find(id)
+                .filter(schema::jobs::status.eq(JobStatus::Running)),
+        )
+        .set((
+            schema::jobs::status.eq(JobStatus::Done),
+            schema::jobs::last_error.eq(None::<String>),
+        ))
+        .execute(&mut conn)?;
+
+        Ok(())
+    }
+
+    fn fail(&mut self, id: i32, error: String, retry_after: Option<Duration>) -> Result<()> {
+        let mut conn = self.get_connection()?;
+        let target = schema::jobs::table
+            .find(id)
+            .filter(schema::jobs::status.eq(JobStatus::Running));
+
+        // Times are computed by the database, whose clock may differ from ours
+        match retry_after {
+            Some(retry_after) => diesel::update(target)
+                .set((
+                    schema::jobs::status.eq(JobStatus::Pending),
+                    schema::jobs::last_error.eq(error),
+                    schema::jobs::run_after
+                        .eq(now.into_sql::<Timestamptz>()
+                            + (retry_after.as_secs() as i64).seconds()),
+                ))
+                .execute(&mut conn)?,
+            None => diesel::update(target)
+                .set((
+                    schema::jobs::status.eq(JobStatus::Failed),
+                    schema::jobs::last_error.eq(error),
+                ))
+                .execute(&mut conn)?,
+        };
+
+        Ok(())
+    }
+
+    fn heartbeat(&mut self, ids: Vec<i32>) -> Result<()> {
+        let mut conn = self.get_connection()?;
+
+        diesel::update(
+            schema::jobs::table
+                .filter(schema::jobs::id.eq_any(ids))
+                .filter(schema::jobs::status.eq(JobStatus::Running)),
+        )
+        .set(schema::jobs::updated_at.eq(now))
+        .execute(&mut conn)?;
+
+        Ok(())
+    }
+
+    fn requeue_stale(&mut self, stale_after: Duration) -> Result<usize> {
+        let mut conn = self.get_connection()?;
+
+        let count = diesel::update(
+            schema::jobs::table
+                .filter(schema::jobs::status.eq(JobStatus::Running))
+                .filter(
+                    schema::jobs::updated_at
+                        .lt(now.into_sql::<Timestamptz>()
+                            - (stale_after.as_secs() as i64).seconds()),
+                ),
+        )
+        .set(schema::jobs::status.eq(JobStatus::Pending))
+        .execute(&mut conn)?;
+
+        Ok(count)
+    }
+
+    fn count_active(&mut self, stages: Vec<JobStage>) -> Result<i64> {
+        let mut conn = self.get_connection()?;
+
+        let count = schema::jobs::table
+            .filter(schema::jobs::stage.eq_any(stages))
+            .filter(schema::jobs::status.eq_any([JobStatus::Pending, JobStatus::Running]))
+            .count()
+            .get_result(&mut conn)?;
+
+        Ok(count)
+    }
+
+    fn count_by_status(&mut self) -> Result<Vec<(JobStage, JobStatus, i64)>> {
+        let mut conn = self.get_connection()?;
+
+        let counts = schema::jobs::table
+            .group_by((schema::jobs::stage, schema::jobs::status))
+            .select((
+                schema::jobs::stage,
+                schema::jobs::status,
+                diesel::dsl::count_star(),
+            ))
+            .load(&mut conn)?;
+
+        Ok(counts)
+    }
+
+    fn find_many(&mut self, filters: JobFindFilters, limit: i64) -> Result<Vec<Job>> {
+        let mut conn = self.get_connection()?;
+
+        let mut query = schema::jobs::table.select(Job::as_select()).into_boxed();
+
+        if let Some(stage) = filters.stage {
+            query = query.filter(schema::jobs::stage.eq(stage));
+        }
+
+        if let Some(status) = filters.status {
+            query = query.filter(schema::jobs::status.eq(status));
+        }
+
+        if let Some(photo_id) = filters.photo_id {
+            query = query.filter(schema::jobs::photo_id.eq(photo_id));
+        }
+
+        let jobs = query
+            .order((schema::jobs::updated_at.desc(), schema::jobs::id.desc()))
+            .limit(limit)
+            .load(&mut conn)?;
+
+        Ok(jobs)
+    }
+
+    fn retry_failed(&mut self, stage: Option<JobStage>) -> Result<usize> {
+        let mut conn = self.get_connection()?;
+
+        let mut target = schema::jobs::table
+            .filter(schema::jobs::status.eq(JobStatus::Failed))
+            .into_boxed();
+
+        if let Some(stage) = stage {
+            target = target.filter(schema::jobs::stage.eq(stage));
+        }
+
+        let ids: Vec<i32> = target.select(schema::jobs::id).load(&mut conn)?;
+
+        let count = diesel::update(schema::jobs::table.filter(schema::jobs::id.eq_any(ids)))
+            .set((
+                schema::jobs::status.eq(JobStatus::Pending),
+                schema::jobs::attempts.eq(0),
+                schema::jobs::run_after.eq(now),
+            ))
+            .execute(&mut conn)?;
+
+        Ok(count)
+    }
+}
@@ -0,0 +1,112 @@
+use anyhow::{Context, Error, Result};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl, SelectableHelper};
+
+use crate::{
+    database::{DbConnection, DbPool, schema},
+    models::{Library, NewLibrary, UpdatedLibrary},
+};
+
+#[cfg_attr(test, mockall::automock)]
+pub trait LibraryRepository {
+    /// Inserts a library and returns the created library.
+    fn insert_one(&mut self, new_library: NewLibrary) -> Result<Library>;
+
+    /// Retrieves all libraries ordered by name.
+    fn find_many(&mut self) -> Result<Vec<Library>>;
+
+    /// Finds a library by name.
+    fn find_by_name(&mut self, name: &str) -> Result<Option<Library>>;
+
+    /// Finds a library by ID.
+    fn find_by_id(&mut self, id: i32) -> Result<Option<Library>>;
+
+    /// Updates a library and returns the updated library.
+    fn update_one(&mut self, id: i32, updated_library: UpdatedLibrary) -> Result<Library>;
+
+    /// Deletes a library along with its photos and returns the number of deleted rows.
+    fn delete_one(&mut self, id: i32) -> Result<usize>;
+}
+
+pub struct PgLibraryRepository {
+    pool: DbPool,
+}
+
+impl PgLibraryRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    fn get_connection(&self) -> Result<DbConnection, Error> {
+        self.pool
+            .get()
+            .map_err(Error::from)
+            .context("Failed to get database connection")
+    }
+}
+
+impl LibraryRepository for PgLibraryRepository {
+    fn insert_one(&mut self, new_library: NewLibrary) -> Result<Library> {
+        let mut conn = self.get_connection()?;
+
+        let library = diesel::insert_into(schema::libraries::table)
+            .values(&new_library)
+            .returning(Library::as_returning())
+            .get_result(&mut conn)?;
+
+        Ok(library)
+    }
+
+    fn find_many(&mut self) -> Result<Vec<Library>> {
+        let mut conn = self.get_connection()?;
+
+        let libraries = schema::libraries::table
+            .select(Library::as_select())
+            .order(schema::libraries::name.asc())
+            .load(&mut conn)?;
+
+        Ok(libraries)
+    }
+
+    fn find_by_name(&mut self, name: &str) -> Result<Option<Library>> {
+        let mut conn = self.get_connection()?;
+
+        let library = schema::libraries::table
+            .select(Library::as_select())
+            .filter(schema::libraries::name.eq(name))
+            .first(&mut conn)
+            .optional()?;
+
+        Ok(library)
+    }
+
+    fn find_by_id(&mut self, id: i32) -> Result<Option<Library>> {
+        let mut conn = self.get_connection()?;
+
+        let library = schema::libraries::table
+            .select(Library::as_select())
+            .find(id)
+            .first(&mut conn)
+            .optional()?;
+
+        Ok(library)
+    }
+
+    fn update_one(&mut self, id: i32, updated_library: UpdatedLibrary) -> Result<Library> {
+        let mut conn = self.get_connection()?;
+
+        let library = diesel::update(schema::libraries::table.find(id))
+            .set(&updated_library)
+            .returning(Library::as_returning())
+            .get_result(&mut conn)?;
+
+        Ok(library)
+    }
+
+    fn delete_one(&mut self, id: i32) -> Result<usize> {
+        let mut conn = self.get_connection()?;
+
+        let count = diesel::delete(schema::libraries::table.find(id)).execute(&mut conn)?;
+
+        Ok(count)
+    }
+}
@@ -1,12 +1,22 @@
 pub mod face;
 pub mod geo;
+pub mod job;
+pub mod library;
 pub mod person;
 pub mod photo;
+pub mod photo_error;
 
 pub use face::filters::FaceFindFilters;
 pub use face::repository::{FaceRepository, PgFaceRepository};
 pub use geo::{GeoRepository, PgGeoRepository};
+pub use job::filters::JobFindFilters;
+pub use job::repository::{JobRepository, PgJobRepository};
+pub use library::repository::{LibraryRepository, PgLibraryRepository};
 pub use person::filters::FindPersonFilters;
 pub use person::repository::{PersonRepository, PgPersonRepository};
-pub use photo::filters::{PersonMatchMode, PhotoFindFilters, PhotoFindPathFilters};
+pub use photo::filters::{
+    PersonMatchMode, PhotoFindFilters, PhotoFindPathFilters, SkipFailedFilter, ValueRange,
+};
 pub use photo::repository::{PgPhotoRepository, PhotoRepository};
+pub use photo_error::filters::PhotoErrorFindFilters;
+pub use photo_error::repository::{PgPhotoErrorRepository, PhotoErrorRepository};
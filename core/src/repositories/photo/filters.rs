@@ -1,5 +1,7 @@
 use chrono::{DateTime, Utc};
 
+use crate::models::{JobStage, MediaType};
+
 #[derive(Debug, Clone)]
 pub enum PersonMatchMode {
     Any,
@@ -12,10 +14,29 @@ impl Default for PersonMatchMode {
     }
 }
 
+/// Inclusive range of values, unbounded on the sides without a limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ValueRange<T> {
+    pub min: Option<T>,
+    pub max: Option<T>,
+}
+
+impl<T> ValueRange<T> {
+    pub fn new(min: Option<T>, max: Option<T>) -> Self {
+        Self { min, max }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct PhotoFindFilters {
-    pub text_embedding: Option<Vec<f32>>,
+    /// Embedding of a text or an image, photos being ranked by the cosine similarity of
+    /// their embedding with it.
+    pub query_embedding: Option<Vec<f32>>,
+    /// Model whose photo embeddings are compared with the query embedding.
+    pub embedding_model: Option<String>,
     pub threshold: Option<f32>,
+    /// Excludes a photo, such as the photo whose similar photos are searched.
+    pub exclude_id: Option<i32>,
 
     pub country_id: Option<i32>,
     pub city_id: Option<i32>,
@@ -25,10 +46,35 @@ pub struct PhotoFindFilters {
 
     pub person_ids: Option<Vec<i32>>,
     pub person_match_mode: Option<PersonMatchMode>,
+
+    pub media_type: Option<MediaType>,
+
+    pub aperture: ValueRange<f32>,
+    pub exposure_time: ValueRange<f32>,
+    pub iso: ValueRange<i32>,
+    pub focal_length: ValueRange<f32>,
+    pub focal_length_35mm: ValueRange<i32>,
+    pub altitude: ValueRange<f32>,
+    pub flash: Option<bool>,
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct PhotoFindPathFilters {
     pub has_face_detection_completed: Option<bool>,
     pub has_embedding: Option<bool>,
+    /// Model of the embeddings checked by `has_embedding`, any model when not set.
+    pub embedding_model: Option<String>,
+    /// Counts the embeddings computed before this time as missing, to compute them again.
+    pub embedded_before: Option<DateTime<Utc>>,
+    pub media_type: Option<MediaType>,
+    pub skip_failed: Option<SkipFailedFilter>,
+}
+
+/// Skips the photos on which a stage failed too many times, or already failed during the
+/// current run, so that runs end when only failing photos are left.
+#[derive(Debug, Clone)]
+pub struct SkipFailedFilter {
+    pub stage: JobStage,
+    pub max_attempts: i32,
+    pub failed_since: DateTime<Utc>,
 }
@@ -1,15 +1,20 @@
-use anyhow::{Context, Error, Result};
+use std::collections::HashMap;
+
+use anyhow::{Context, Error, Result, anyhow};
+use chrono::{DateTime, Utc};
 use diesel::{
     dsl::sql,
     prelude::*,
-    sql_query,
-    sql_types::{Bool, Float},
+    sql_types::{Array, Bool, Float, Integer, Text, Timestamptz},
 };
+use pgvector::Vector;
 
 use crate::{
     database::{DbConnection, DbPool, schema},
     models::{
-        NewPhoto, PaginatedPhotoPaths, PaginatedPhotos, PaginationFilter, Photo, UpdatedPhoto,
+        EmbeddingSimilarity, MediaType, NewPhoto, NewPhotoEmbedding, PaginatedPhotoPaths,
+        PaginatedPhotos, PaginationFilter, Photo, PhotoFile, SidecarFace, SidecarMetadata,
+        SimilarityCandidate, UpdatedPhoto,
     },
     repositories::{PersonMatchMode, PhotoFindFilters, PhotoFindPathFilters},
     utils::serialize_float_array,
@@ -17,6 +22,10 @@ use crate::{
 
 #[cfg_attr(test, mockall::automock)]
 pub trait PhotoRepository {
+    /// Returns the current time of the database, which timestamps embeddings and errors,
+    /// so that it can be compared with them regardless of the client clock.
+    fn now(&mut self) -> Result<DateTime<Utc>>;
+
     /// Lists photo paths with pagination.
     fn find_path(
         &mut self,
@@ -24,12 +33,23 @@ pub trait PhotoRepository {
         filters: PhotoFindPathFilters,
     ) -> Result<PaginatedPhotoPaths>;
 
-    /// Inserts a batch of new photos.
+    /// Inserts or updates a batch of new photos in a single transaction.
     fn insert_batch(&mut self, new_photos: Vec<NewPhoto>) -> Result<usize>;
 
     /// Updates a photo and returns the updated photo.
     fn update_one(&mut self, id: i32, updated_photo: UpdatedPhoto) -> Result<Photo>;
 
+    /// Saves a batch of photo embeddings in a single statement, replacing the previous
+    /// embeddings of the photos for the same model.
+    fn save_embeddings(&mut self, new_embeddings: Vec<NewPhotoEmbedding>) -> Result<usize>;
+
+    /// Creates the partial HNSW index over the embeddings of a model, cast to their
+    /// dimensions, unless it already exists.
+    fn create_embedding_index(&mut self, model: String, dimensions: usize) -> Result<()>;
+
+    /// Returns the embedding of a photo for a model, if computed.
+    fn find_embedding(&mut self, photo_id: i32, model: String) -> Result<Option<Vec<f32>>>;
+
     /// Finds photos with filters, pagination, and sorting.
     fn find(
         &mut self,
@@ -48,8 +68,82 @@ pub trait PhotoRepository {
 
     /// Finds a single photo by its ID.
     fn find_by_id(&mut self, id: i32) -> Result<Option<Photo>>;
+
+    /// Lists the indexed files whose path starts with the given prefix.
+    fn find_files(&mut self, path_prefix: &str) -> Result<Vec<PhotoFile>>;
+
+    /// Finds the file of a single photo by its ID.
+    fn find_file(&mut self, id: i32) -> Result<Option<PhotoFile>>;
+
+    /// Lists the IDs of the photos whose path starts with the given prefix and that were
+    /// indexed since a time, ordered by ID.
+    fn find_ids_indexed_since(
+        &mut self,
+        path_prefix: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<i32>>;
+
+    /// Deletes photos by their IDs and returns the number of deleted rows.
+    fn delete_many(&mut self, ids: Vec<i32>) -> Result<usize>;
+
+    /// Lists the metadata of the photos of a library to write back to their sidecars.
+    fn find_sidecar_metadata(&mut self, library_id: i32) -> Result<Vec<SidecarMetadata>>;
+
+    /// Returns the given file hashes that are already indexed.
+    fn find_existing_hashes(&mut self, hashes: Vec<String>) -> Result<Vec<String>>;
+
+    /// Lists the photos whose hash is shared by other photos, ordered by hash.
+    fn find_duplicates(&mut self) -> Result<Vec<Photo>>;
+
+    /// Lists the photos with a perceptual hash or an embedding of the model, ordered by id,
+    /// from the photo after the given id.
+    fn find_similarity_candidates(
+        &mut self,
+        model: String,
+        after_id: Option<i32>,
+        limit: i64,
+    ) -> Result<Vec<SimilarityCandidate>>;
+
+    /// Returns the cosine similarity between the embeddings of the model of each pair of
+    /// photos, skipping the pairs where a photo has no embedding.
+    fn find_embedding_similarities(
+        &mut self,
+        model: String,
+        pairs: Vec<(i32, i32)>,
+    ) -> Result<Vec<EmbeddingSimilarity>>;
 }
 
+/// Filters both the count and select queries on the bounds of a value range.
+macro_rules! filter_range {
+    ($count_query:ident, $select_query:ident, $column:expr, $range:expr) => {
+        if let Some(min) = $range.min {
+            $count_query = $count_query.filter($column.ge(min));
+            $select_query = $select_query.filter($column.ge(min));
+        }
+
+        if let Some(max) = $range.max {
+            $count_query = $count_query.filter($column.le(max));
+            $select_query = $select_query.filter($column.le(max));
+        }
+    };
+}
+
+/// Maximum number of photos per INSERT statement, keeping the query under
+/// PostgreSQL's limit of 65535 bind parameters.
+const INSERT_CHUNK_SIZE: usize = 1000;
+
+/// Photo columns written to sidecars: id, path, dimensions, keywords, city and country.
+type SidecarPhotoRow = (
+    i32,
+    String,
+    Option<i32>,
+    Option<i32>,
+    Option<i32>,
+    Vec<String>,
+    Option<String>,
+    Option<String>,
+);
+
 pub struct PgPhotoRepository {
     pool: DbPool,
 }
@@ -68,23 +162,54 @@ impl PgPhotoRepository {
 }
 
 impl PgPhotoRepository {
-    fn build_semantic_filter_sql(text_embedding: &[f32], threshold: f32) -> String {
+    /// Builds the cosine distance between a query embedding and the joined embedding of the
+    /// photo, NULL when the photo has no embedding for the searched model.
+    ///
+    /// Embeddings are cast to the dimensions of the query, as the partial HNSW index of
+    /// each model is, so that the planner can pick the index of the searched model.
+    fn build_semantic_distance_sql(query_embedding: &[f32]) -> String {
         format!(
-            "(1 - (photos.embedding <=> '{}'::vector)) > {}",
-            serialize_float_array(text_embedding),
-            threshold
+            "(photo_embeddings.embedding::vector({dimensions}) <=> '{}'::vector({dimensions}))",
+            serialize_float_array(query_embedding),
+            dimensions = query_embedding.len()
         )
     }
 
-    fn build_semantic_order_sql(text_embedding: &[f32]) -> String {
+    fn build_semantic_filter_sql(query_embedding: &[f32], threshold: f32) -> String {
         format!(
-            "1 - (photos.embedding <=> '{}'::vector)",
-            serialize_float_array(text_embedding)
+            "{} < {}",
+            Self::build_semantic_distance_sql(query_embedding),
+            1.0 - threshold
         )
     }
+
+    /// Returns the name of the partial HNSW index over the embeddings of a model.
+    fn embedding_index_name(model: &str) -> String {
+        let model: String = model
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        format!("photo_embeddings_{}_idx", model)
+    }
+
+    fn build_prefix_pattern(path_prefix: &str) -> String {
+        let escaped = path_prefix
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        format!("{}%", escaped)
+    }
 }
 
 impl PhotoRepository for PgPhotoRepository {
+    fn now(&mut self) -> Result<DateTime<Utc>> {
+        let mut conn = self.get_connection()?;
+
+        let now = diesel::select(sql::<Timestamptz>("now()")).get_result(&mut conn)?;
+
+        Ok(now)
+    }
+
     fn find(
         &mut self,
         pagination: PaginationFilter,
@@ -93,23 +218,43 @@ impl PhotoRepository for PgPhotoRepository {
         let mut conn = self.get_connection()?;
         let offset = (pagination.page - 1) * pagination.per_page;
 
-        let mut count_query = schema::photos::table
+        let model = match filters.query_embedding {
+            Some(_) => filters
+                .embedding_model
+                .clone()
+                .ok_or_else(|| anyhow!("No embedding model to search"))?,
+            None => String::new(),
+        };
+        // Joins the embedding of the searched model, if any, so that searches order the
+        // embeddings by distance as their index does
+        let with_embedding = || {
+            schema::photos::table.left_join(
+                schema::photo_embeddings::table.on(schema::photo_embeddings::photo_id
+                    .eq(schema::photos::id)
+                    .and(schema::photo_embeddings::model.eq(model.clone()))),
+            )
+        };
+
+        let mut count_query = with_embedding()
             .select(diesel::dsl::count_star())
             .into_boxed();
 
-        let mut select_query = schema::photos::table
-            .select(Photo::as_select())
-            .into_boxed();
+        let mut select_query = with_embedding().select(Photo::as_select()).into_boxed();
 
-        if let Some(ref text_embedding) = filters.text_embedding {
+        if let Some(ref query_embedding) = filters.query_embedding {
             let threshold = filters.threshold.unwrap_or(0.0);
-            let semantic_filter_sql = Self::build_semantic_filter_sql(text_embedding, threshold);
+            let semantic_filter_sql = Self::build_semantic_filter_sql(query_embedding, threshold);
 
             count_query = count_query.filter(sql::<Bool>(&semantic_filter_sql));
             select_query = select_query.filter(sql::<Bool>(&semantic_filter_sql));
 
-            let order_sql = Self::build_semantic_order_sql(text_embedding);
-            select_query = select_query.order(sql::<Float>(&order_sql).desc());
+            let order_sql = Self::build_semantic_distance_sql(query_embedding);
+            select_query = select_query.order(sql::<Float>(&order_sql).asc());
+        }
+
+        if let Some(exclude_id) = filters.exclude_id {
+            count_query = count_query.filter(schema::photos::id.ne(exclude_id));
+            select_query = select_query.filter(schema::photos::id.ne(exclude_id));
         }
 
         if let Some(country_id) = filters.country_id {
@@ -132,6 +277,48 @@ impl PhotoRepository for PgPhotoRepository {
             select_query = select_query.filter(schema::photos::date_taken_utc.le(date_to));
         }
 
+        if let Some(media_type) = filters.media_type {
+            count_query = count_query.filter(schema::photos::media_type.eq(media_type));
+            select_query = select_query.filter(schema::photos::media_type.eq(media_type));
+        }
+
+        filter_range!(
+            count_query,
+            select_query,
+            schema::photos::aperture,
+            filters.aperture
+        );
+        filter_range!(
+            count_query,
+            select_query,
+            schema::photos::exposure_time,
+            filters.exposure_time
+        );
+        filter_range!(count_query, select_query, schema::photos::iso, filters.iso);
+        filter_range!(
+            count_query,
+            select_query,
+            schema::photos::focal_length,
+            filters.focal_length
+        );
+        filter_range!(
+            count_query,
+            select_query,
+            schema::photos::focal_length_35mm,
+            filters.focal_length_35mm
+        );
+        filter_range!(
+            count_query,
+            select_query,
+            schema::photos::altitude,
+            filters.altitude
+        );
+
+        if let Some(flash) = filters.flash {
+            count_query = count_query.filter(schema::photos::flash.eq(flash));
+            select_query = select_query.filter(schema::photos::flash.eq(flash));
+        }
+
         if let Some(person_ids) = filters.person_ids {
             if !person_ids.is_empty() {
                 let match_mode = filters.person_match_mode.unwrap_or_default();
@@ -167,10 +354,6 @@ impl PhotoRepository for PgPhotoRepository {
             }
         }
 
-        if filters.text_embedding.is_some() {
-            sql_query("SET hnsw.ef_search = 80").execute(&mut conn)?;
-        }
-
         let total: i64 = count_query.first(&mut conn)?;
         let photos = select_query
             .then_order_by(schema::photos::date_taken_utc.desc().nulls_last())
@@ -194,28 +377,60 @@ impl PhotoRepository for PgPhotoRepository {
         let mut conn = self.get_connection()?;
         use diesel::upsert::excluded;
 
-        diesel::insert_into(schema::photos::table)
-            .values(&new_photos)
-            .on_conflict(schema::photos::path)
-            .do_update()
-            .set((
-                schema::photos::file_size.eq(excluded(schema::photos::file_size)),
-                schema::photos::created_at.eq(excluded(schema::photos::created_at)),
-                schema::photos::modified_at.eq(excluded(schema::photos::modified_at)),
-                schema::photos::indexed_at.eq(excluded(schema::photos::indexed_at)),
-                schema::photos::hash.eq(excluded(schema::photos::hash)),
-                schema::photos::camera_make.eq(excluded(schema::photos::camera_make)),
-                schema::photos::camera_model.eq(excluded(schema::photos::camera_model)),
-                schema::photos::lens_model.eq(excluded(schema::photos::lens_model)),
-                schema::photos::orientation.eq(excluded(schema::photos::orientation)),
-                schema::photos::date_taken_local.eq(excluded(schema::photos::date_taken_local)),
-                schema::photos::date_taken_utc.eq(excluded(schema::photos::date_taken_utc)),
-                schema::photos::gps_location.eq(excluded(schema::photos::gps_location)),
-                schema::photos::image_width.eq(excluded(schema::photos::image_width)),
-                schema::photos::image_height.eq(excluded(schema::photos::image_height)),
-            ))
-            .execute(&mut conn)
-            .map_err(Error::from)
+        conn.transaction(|conn| {
+            let mut count = 0;
+
+            for chunk in new_photos.chunks(INSERT_CHUNK_SIZE) {
+                count += diesel::insert_into(schema::photos::table)
+                    .values(chunk)
+                    .on_conflict(schema::photos::path)
+                    .do_update()
+                    .set((
+                        schema::photos::file_size.eq(excluded(schema::photos::file_size)),
+                        schema::photos::created_at.eq(excluded(schema::photos::created_at)),
+                        schema::photos::modified_at.eq(excluded(schema::photos::modified_at)),
+                        schema::photos::indexed_at.eq(excluded(schema::photos::indexed_at)),
+                        schema::photos::hash.eq(excluded(schema::photos::hash)),
+                        schema::photos::camera_make.eq(excluded(schema::photos::camera_make)),
+                        schema::photos::camera_model.eq(excluded(schema::photos::camera_model)),
+                        schema::photos::lens_model.eq(excluded(schema::photos::lens_model)),
+                        schema::photos::orientation.eq(excluded(schema::photos::orientation)),
+                        schema::photos::date_taken_local
+                            .eq(excluded(schema::photos::date_taken_local)),
+                        schema::photos::date_taken_utc.eq(excluded(schema::photos::date_taken_utc)),
+                        schema::photos::gps_location.eq(excluded(schema::photos::gps_location)),
+                        schema::photos::image_width.eq(excluded(schema::photos::image_width)),
+                        schema::photos::image_height.eq(excluded(schema::photos::image_height)),
+                        schema::photos::media_type.eq(excluded(schema::photos::media_type)),
+                        schema::photos::library_id.eq(excluded(schema::photos::library_id)),
+                        schema::photos::relative_path.eq(excluded(schema::photos::relative_path)),
+                        schema::photos::aperture.eq(excluded(schema::photos::aperture)),
+                        schema::photos::exposure_time.eq(excluded(schema::photos::exposure_time)),
+                        schema::photos::iso.eq(excluded(schema::photos::iso)),
+                        schema::photos::focal_length.eq(excluded(schema::photos::focal_length)),
+                        schema::photos::focal_length_35mm
+                            .eq(excluded(schema::photos::focal_length_35mm)),
+                        schema::photos::flash.eq(excluded(schema::photos::flash)),
+                        schema::photos::altitude.eq(excluded(schema::photos::altitude)),
+                        schema::photos::gps_heading.eq(excluded(schema::photos::gps_heading)),
+                        schema::photos::offset_time.eq(excluded(schema::photos::offset_time)),
+                        schema::photos::date_taken_timezone
+                            .eq(excluded(schema::photos::date_taken_timezone)),
+                        schema::photos::date_taken_source
+                            .eq(excluded(schema::photos::date_taken_source)),
+                        schema::photos::keywords.eq(excluded(schema::photos::keywords)),
+                        schema::photos::rating.eq(excluded(schema::photos::rating)),
+                        schema::photos::title.eq(excluded(schema::photos::title)),
+                        schema::photos::caption.eq(excluded(schema::photos::caption)),
+                        schema::photos::face_regions.eq(excluded(schema::photos::face_regions)),
+                        schema::photos::perceptual_hash
+                            .eq(excluded(schema::photos::perceptual_hash)),
+                    ))
+                    .execute(conn)?;
+            }
+
+            Ok(count)
+        })
     }
 
     fn find_path(
@@ -242,15 +457,47 @@ impl PhotoRepository for PgPhotoRepository {
         }
 
         if let Some(has_embedding) = filters.has_embedding {
+            let embedded_photo_ids = || {
+                let mut query = schema::photo_embeddings::table
+                    .select(schema::photo_embeddings::photo_id)
+                    .into_boxed();
+                if let Some(ref model) = filters.embedding_model {
+                    query = query.filter(schema::photo_embeddings::model.eq(model.clone()));
+                }
+                if let Some(embedded_before) = filters.embedded_before {
+                    query = query.filter(schema::photo_embeddings::created_at.ge(embedded_before));
+                }
+                query
+            };
+
             if has_embedding {
-                count_query = count_query.filter(schema::photos::embedding.is_not_null());
-                select_query = select_query.filter(schema::photos::embedding.is_not_null());
+                count_query = count_query.filter(schema::photos::id.eq_any(embedded_photo_ids()));
+                select_query = select_query.filter(schema::photos::id.eq_any(embedded_photo_ids()));
             } else {
-                count_query = count_query.filter(schema::photos::embedding.is_null());
-                select_query = select_query.filter(schema::photos::embedding.is_null());
+                count_query = count_query.filter(schema::photos::id.ne_all(embedded_photo_ids()));
+                select_query = select_query.filter(schema::photos::id.ne_all(embedded_photo_ids()));
             }
         }
 
+        if let Some(media_type) = filters.media_type {
+            count_query = count_query.filter(schema::photos::media_type.eq(media_type));
+            select_query = select_query.filter(schema::photos::media_type.eq(media_type));
+        }
+
+        if let Some(skip_failed) = filters.skip_failed {
+            let failed_photo_ids = schema::photo_errors::table
+                .select(schema::photo_errors::photo_id)
+                .filter(schema::photo_errors::stage.eq(skip_failed.stage))
+                .filter(
+                    schema::photo_errors::attempts
+                        .ge(skip_failed.max_attempts)
+                        .or(schema::photo_errors::last_attempt_at.ge(skip_failed.failed_since)),
+                );
+
+            count_query = count_query.filter(schema::photos::id.ne_all(failed_photo_ids));
+            select_query = select_query.filter(schema::photos::id.ne_all(failed_photo_ids));
+        }
+
         let total: i64 = count_query.first(&mut conn)?;
         let photo_paths = select_query
             .limit(pagination.per_page)
@@ -278,6 +525,59 @@ impl PhotoRepository for PgPhotoRepository {
         Ok(photo)
     }
 
+    fn save_embeddings(&mut self, new_embeddings: Vec<NewPhotoEmbedding>) -> Result<usize> {
+        use diesel::upsert::excluded;
+
+        if new_embeddings.is_empty() {
+            return Ok(0);
+        }
+
+        let mut conn = self.get_connection()?;
+
+        let count = diesel::insert_into(schema::photo_embeddings::table)
+            .values(&new_embeddings)
+            .on_conflict((
+                schema::photo_embeddings::photo_id,
+                schema::photo_embeddings::model,
+            ))
+            .do_update()
+            .set((
+                schema::photo_embeddings::embedding
+                    .eq(excluded(schema::photo_embeddings::embedding)),
+                schema::photo_embeddings::created_at.eq(diesel::dsl::now),
+            ))
+            .execute(&mut conn)?;
+
+        Ok(count)
+    }
+
+    fn create_embedding_index(&mut self, model: String, dimensions: usize) -> Result<()> {
+        let mut conn = self.get_connection()?;
+
+        diesel::sql_query(format!(
+            "CREATE INDEX IF NOT EXISTS {} ON photo_embeddings \
+            USING hnsw ((embedding::vector({})) vector_cosine_ops) WHERE model = '{}'",
+            Self::embedding_index_name(&model),
+            dimensions,
+            model.replace('\'', "''")
+        ))
+        .execute(&mut conn)?;
+
+        Ok(())
+    }
+
+    fn find_embedding(&mut self, photo_id: i32, model: String) -> Result<Option<Vec<f32>>> {
+        let mut conn = self.get_connection()?;
+
+        let embedding: Option<Vector> = schema::photo_embeddings::table
+            .find((photo_id, model))
+            .select(schema::photo_embeddings::embedding)
+            .first(&mut conn)
+            .optional()?;
+
+        Ok(embedding.map(|embedding| embedding.to_vec()))
+    }
+
     fn find_country_ids(&mut self) -> Result<Vec<i32>> {
         let mut conn = self.get_connection()?;
 
@@ -321,4 +621,217 @@ impl PhotoRepository for PgPhotoRepository {
 
         Ok(photo)
     }
+
+    fn find_files(&mut self, path_prefix: &str) -> Result<Vec<PhotoFile>> {
+        let mut conn = self.get_connection()?;
+
+        let files = schema::photos::table
+            .select(PhotoFile::as_select())
+            .filter(schema::photos::path.like(Self::build_prefix_pattern(path_prefix)))
+            .load(&mut conn)?;
+
+        Ok(files)
+    }
+
+    fn find_file(&mut self, id: i32) -> Result<Option<PhotoFile>> {
+        let mut conn = self.get_connection()?;
+
+        let file = schema::photos::table
+            .select(PhotoFile::as_select())
+            .find(id)
+            .first(&mut conn)
+            .optional()?;
+
+        Ok(file)
+    }
+
+    fn find_ids_indexed_since(
+        &mut self,
+        path_prefix: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<i32>> {
+        let mut conn = self.get_connection()?;
+
+        let ids = schema::photos::table
+            .select(schema::photos::id)
+            .filter(schema::photos::path.like(Self::build_prefix_pattern(path_prefix)))
+            .filter(schema::photos::indexed_at.ge(since))
+            .order(schema::photos::id.asc())
+            .load(&mut conn)?;
+
+        Ok(ids)
+    }
+
+    fn delete_many(&mut self, ids: Vec<i32>) -> Result<usize> {
+        let mut conn = self.get_connection()?;
+
+        let count = diesel::delete(schema::photos::table.filter(schema::photos::id.eq_any(ids)))
+            .execute(&mut conn)?;
+
+        Ok(count)
+    }
+
+    fn find_sidecar_metadata(&mut self, library_id: i32) -> Result<Vec<SidecarMetadata>> {
+        let mut conn = self.get_connection()?;
+
+        let mut faces_by_photo: HashMap<i32, Vec<SidecarFace>> = HashMap::new();
+        let faces: Vec<(i32, String, i32, i32, i32, i32)> = schema::faces::table
+            .inner_join(schema::people::table)
+            .inner_join(schema::photos::table)
+            .filter(schema::photos::library_id.eq(library_id))
+            .select((
+                schema::faces::photo_id,
+                schema::people::name,
+                schema::faces::bbox_x,
+                schema::faces::bbox_y,
+                schema::faces::bbox_width,
+                schema::faces::bbox_height,
+            ))
+            .order(schema::faces::id.asc())
+            .load(&mut conn)?;
+
+        for (photo_id, person_name, bbox_x, bbox_y, bbox_width, bbox_height) in faces {
+            faces_by_photo
+                .entry(photo_id)
+                .or_default()
+                .push(SidecarFace {
+                    person_name,
+                    bbox_x,
+                    bbox_y,
+                    bbox_width,
+                    bbox_height,
+                });
+        }
+
+        let photos: Vec<SidecarPhotoRow> = schema::photos::table
+            .left_join(schema::cities::table)
+            .left_join(schema::countries::table)
+            .filter(schema::photos::library_id.eq(library_id))
+            .select((
+                schema::photos::id,
+                schema::photos::path,
+                schema::photos::image_width,
+                schema::photos::image_height,
+                schema::photos::orientation,
+                schema::photos::keywords,
+                schema::cities::name.nullable(),
+                schema::countries::name.nullable(),
+            ))
+            .order(schema::photos::path.asc())
+            .load(&mut conn)?;
+
+        Ok(photos
+            .into_iter()
+            .map(
+                |(
+                    photo_id,
+                    path,
+                    image_width,
+                    image_height,
+                    orientation,
+                    keywords,
+                    city,
+                    country,
+                )| {
+                    SidecarMetadata {
+                        photo_id,
+                        path,
+                        image_width,
+                        image_height,
+                        orientation,
+                        keywords,
+                        city,
+                        country,
+                        faces: faces_by_photo.remove(&photo_id).unwrap_or_default(),
+                    }
+                },
+            )
+            .collect())
+    }
+
+    fn find_existing_hashes(&mut self, hashes: Vec<String>) -> Result<Vec<String>> {
+        let mut conn = self.get_connection()?;
+
+        let existing_hashes: Vec<Option<String>> = schema::photos::table
+            .select(schema::photos::hash)
+            .filter(schema::photos::hash.eq_any(hashes))
+            .distinct()
+            .load(&mut conn)?;
+
+        Ok(existing_hashes.into_iter().flatten().collect())
+    }
+
+    fn find_duplicates(&mut self) -> Result<Vec<Photo>> {
+        let mut conn = self.get_connection()?;
+
+        let duplicated_hashes: Vec<Option<String>> = schema::photos::table
+            .select(schema::photos::hash)
+            .filter(schema::photos::hash.is_not_null())
+            .group_by(schema::photos::hash)
+            .having(diesel::dsl::count_star().gt(1))
+            .load(&mut conn)?;
+
+        let photos = schema::photos::table
+            .filter(schema::photos::hash.eq_any(duplicated_hashes.into_iter().flatten()))
+            .order((schema::photos::hash.asc(), schema::photos::id.asc()))
+            .load::<Photo>(&mut conn)?;
+
+        Ok(photos)
+    }
+
+    fn find_similarity_candidates(
+        &mut self,
+        model: String,
+        after_id: Option<i32>,
+        limit: i64,
+    ) -> Result<Vec<SimilarityCandidate>> {
+        let mut conn = self.get_connection()?;
+
+        let candidates = schema::photos::table
+            .left_join(
+                schema::photo_embeddings::table.on(schema::photo_embeddings::photo_id
+                    .eq(schema::photos::id)
+                    .and(schema::photo_embeddings::model.eq(model))),
+            )
+            .select(SimilarityCandidate::as_select())
+            .filter(schema::photos::media_type.eq(MediaType::Photo))
+            .filter(
+                schema::photos::perceptual_hash
+                    .is_not_null()
+                    .or(schema::photo_embeddings::photo_id.nullable().is_not_null()),
+            )
+            .filter(schema::photos::id.gt(after_id.unwrap_or(0)))
+            .order(schema::photos::id.asc())
+            .limit(limit)
+            .load(&mut conn)?;
+
+        Ok(candidates)
+    }
+
+    fn find_embedding_similarities(
+        &mut self,
+        model: String,
+        pairs: Vec<(i32, i32)>,
+    ) -> Result<Vec<EmbeddingSimilarity>> {
+        let mut conn = self.get_connection()?;
+
+        let (photo_ids, other_photo_ids): (Vec<i32>, Vec<i32>) = pairs.into_iter().unzip();
+
+        // Distances are computed by pgvector, so that embeddings never leave the database
+        let similarities = diesel::sql_query(
+            "SELECT pairs.photo_id, pairs.other_photo_id,
+                (1 - (embeddings.embedding <=> other_embeddings.embedding))::REAL AS similarity
+            FROM UNNEST($1::INTEGER[], $2::INTEGER[]) AS pairs (photo_id, other_photo_id)
+            JOIN photo_embeddings embeddings
+                ON embeddings.photo_id = pairs.photo_id AND embeddings.model = $3
+            JOIN photo_embeddings other_embeddings
+                ON other_embeddings.photo_id = pairs.other_photo_id AND other_embeddings.model = $3",
+        )
+        .bind::<Array<Integer>, _>(photo_ids)
+        .bind::<Array<Integer>, _>(other_photo_ids)
+        .bind::<Text, _>(model)
+        .load(&mut conn)?;
+
+        Ok(similarities)
+    }
 }
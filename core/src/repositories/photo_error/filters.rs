@@ -0,0 +1,9 @@
+use crate::models::JobStage;
+
+#[derive(Debug, Default, Clone)]
+pub struct PhotoErrorFindFilters {
+    pub stage: Option<JobStage>,
+    pub photo_id: Option<i32>,
+    /// Only errors of photos which failed at least this many times.
+    pub min_attempts: Option<i32>,
+}
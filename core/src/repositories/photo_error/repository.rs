@@ -0,0 +1,154 @@
+use anyhow::{Context, Error, Result};
+use diesel::{
+    ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper, dsl::now, upsert::excluded,
+};
+
+use crate::{
+    database::{DbConnection, DbPool, schema},
+    models::{JobStage, PhotoError, PhotoErrorWithPath},
+    repositories::photo_error::filters::PhotoErrorFindFilters,
+};
+
+#[cfg_attr(test, mockall::automock)]
+pub trait PhotoErrorRepository {
+    /// Records a failed attempt of a stage on a photo and returns the number of attempts.
+    fn record(&mut self, photo_id: i32, stage: JobStage, error: String) -> Result<i32>;
+
+    /// Removes the errors of a stage on photos which were processed successfully.
+    fn resolve(&mut self, stage: JobStage, photo_ids: Vec<i32>) -> Result<usize>;
+
+    /// Finds errors matching the filters, most recently attempted first.
+    fn find_many(
+        &mut self,
+        filters: PhotoErrorFindFilters,
+        limit: i64,
+    ) -> Result<Vec<PhotoErrorWithPath>>;
+
+    /// Deletes errors matching the filters, so that their photos are processed again, and
+    /// returns their number.
+    fn delete_many(&mut self, filters: PhotoErrorFindFilters) -> Result<usize>;
+}
+
+pub struct PgPhotoErrorRepository {
+    pool: DbPool,
+}
+
+impl PgPhotoErrorRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    fn get_connection(&self) -> Result<DbConnection, Error> {
+        self.pool
+            .get()
+            .map_err(Error::from)
+            .context("Failed to get database connection")
+    }
+}
+
+impl PhotoErrorRepository for PgPhotoErrorRepository {
+    fn record(&mut self, photo_id: i32, stage: JobStage, error: String) -> Result<i32> {
+        let mut conn = self.get_connection()?;
+
+        let attempts = diesel::insert_into(schema::photo_errors::table)
+            .values((
+                schema::photo_errors::photo_id.eq(photo_id),
+                schema::photo_errors::stage.eq(stage),
+                schema::photo_errors::error.eq(error),
+            ))
+            .on_conflict((schema::photo_errors::photo_id, schema::photo_errors::stage))
+            .do_update()
+            .set((
+                schema::photo_errors::error.eq(excluded(schema::photo_errors::error)),
+                schema::photo_errors::attempts.eq(schema::photo_errors::attempts + 1),
+                schema::photo_errors::last_attempt_at.eq(now),
+            ))
+            .returning(schema::photo_errors::attempts)
+            .get_result(&mut conn)?;
+
+        Ok(attempts)
+    }
+
+    fn resolve(&mut self, stage: JobStage, photo_ids: Vec<i32>) -> Result<usize> {
+        let mut conn = self.get_connection()?;
+
+        let count = diesel::delete(
+            schema::photo_errors::table
+                .filter(schema::photo_errors::stage.eq(stage))
+                .filter(schema::photo_errors::photo_id.eq_any(photo_ids)),
+        )
+        .execute(&mut conn)?;
+
+        Ok(count)
+    }
+
+    fn find_many(
+        &mut self,
+        filters: PhotoErrorFindFilters,
+        limit: i64,
+    ) -> Result<Vec<PhotoErrorWithPath>> {
+        let mut conn = self.get_connection()?;
+
+        let mut query = schema::photo_errors::table
+            .inner_join(schema::photos::table)
+            .select((PhotoError::as_select(), schema::photos::path))
+            .into_boxed();
+
+        if let Some(stage) = filters.stage {
+            query = query.filter(schema::photo_errors::stage.eq(stage));
+        }
+
+        if let Some(photo_id) = filters.photo_id {
+            query = query.filter(schema::photo_errors::photo_id.eq(photo_id));
+        }
+
+        if let Some(min_attempts) = filters.min_attempts {
+            query = query.filter(schema::photo_errors::attempts.ge(min_attempts));
+        }
+
+        let photo_errors = query
+            .order((
+                schema::photo_errors::last_attempt_at.desc(),
+                schema::photo_errors::id.desc(),
+            ))
+            .limit(limit)
+            .load::<(PhotoError, String)>(&mut conn)?
+            .into_iter()
+            .map(|(photo_error, photo_path)| PhotoErrorWithPath {
+                photo_error,
+                photo_path,
+            })
+            .collect();
+
+        Ok(photo_errors)
+    }
+
+    fn delete_many(&mut self, filters: PhotoErrorFindFilters) -> Result<usize> {
+        let mut conn = self.get_connection()?;
+
+        let mut target = schema::photo_errors::table
+            .select(schema::photo_errors::id)
+            .into_boxed();
+
+        if let Some(stage) = filters.stage {
+            target = target.filter(schema::photo_errors::stage.eq(stage));
+        }
+
+        if let Some(photo_id) = filters.photo_id {
+            target = target.filter(schema::photo_errors::photo_id.eq(photo_id));
+        }
+
+        if let Some(min_attempts) = filters.min_attempts {
+            target = target.filter(schema::photo_errors::attempts.ge(min_attempts));
+        }
+
+        let ids: Vec<i32> = target.load(&mut conn)?;
+
+        let count = diesel::delete(
+            schema::photo_errors::table.filter(schema::photo_errors::id.eq_any(ids)),
+        )
+        .execute(&mut conn)?;
+
+        Ok(count)
+    }
+}
@@ -0,0 +1,476 @@
+use std::{
+    cmp::Ordering,
+    fs,
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{Context, Result, bail};
+
+use crate::{
+    models::Photo,
+    repositories::PhotoRepository,
+    utils::{self, progress_reporter::ProgressReporter},
+};
+
+/// Rule used to suggest which photo of a duplicate group to keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeeperStrategy {
+    /// Keeps the largest file, then the one with the most pixels.
+    #[default]
+    Largest,
+    /// Keeps the file created first, then the one modified first.
+    Oldest,
+}
+
+impl FromStr for KeeperStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "largest" => Ok(KeeperStrategy::Largest),
+            "oldest" => Ok(KeeperStrategy::Oldest),
+            _ => bail!("Unknown keeper strategy: {}", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct KeeperPreference {
+    pub strategy: KeeperStrategy,
+    /// Library whose photos are kept over photos of other libraries.
+    pub preferred_library_id: Option<i32>,
+}
+
+/// Photos sharing the same content hash.
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    pub hash: String,
+    pub keeper: Photo,
+    pub duplicates: Vec<Photo>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DuplicateAction {
+    /// Only reports the groups.
+    Report,
+    /// Moves the duplicates to a quarantine directory, keeping their absolute path
+    /// below it.
+    Move(PathBuf),
+    /// Deletes the duplicates from disk.
+    Delete,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct DuplicateSummary {
+    pub groups: usize,
+    pub duplicates: usize,
+    /// Bytes used by the duplicates.
+    pub reclaimable_bytes: i64,
+    pub moved: usize,
+    pub deleted: usize,
+    /// Duplicates left untouched because their content or the keeper's changed since
+    /// they were indexed, or because they are the same file as the keeper.
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// Finds photos with identical content from their BLAKE3 hash, and cleans up the extra
+/// copies.
+pub struct DuplicateService<PR: PhotoRepository, P: ProgressReporter> {
+    photo_repository: PR,
+    progress_reporter: P,
+}
+
+impl<PR: PhotoRepository, P: ProgressReporter> DuplicateService<PR, P> {
+    pub fn new(photo_repository: PR, progress_reporter: P) -> Self {
+        Self {
+            photo_repository,
+            progress_reporter,
+        }
+    }
+
+    /// Groups indexed photos by hash and suggests a keeper for each group.
+    pub fn find_groups(&mut self, preference: &KeeperPreference) -> Result<Vec<DuplicateGroup>> {
+        let photos = self
+            .photo_repository
+            .find_duplicates()
+            .context("Failed to retrieve duplicate photos")?;
+
+        let mut groups: Vec<(String, Vec<Photo>)> = Vec::new();
+        for photo in photos {
+            let Some(hash) = photo.hash.clone() else {
+                continue;
+            };
+            match groups.last_mut() {
+                Some((group_hash, photos)) if *group_hash == hash => photos.push(photo),
+                _ => groups.push((hash, vec![photo])),
+            }
+        }
+
+        Ok(groups
+            .into_iter()
+            .filter(|(_, photos)| photos.len() > 1)
+            .map(|(hash, mut photos)| {
+                photos.sort_by(|a, b| compare_keepers(a, b, preference));
+                let keeper = photos.remove(0);
+                DuplicateGroup {
+                    hash,
+                    keeper,
+                    duplicates: photos,
+                }
+            })
+            .collect())
+    }
+
+    /// Applies an action to the duplicates of each group, and removes the moved or
+    /// deleted photos from the index.
+    ///
+    /// Files are hashed again beforehand, so that nothing is removed unless the keeper
+    /// still holds the same content. Duplicates that resolve to the keeper's file, through
+    /// a symbolic link, a hard link or overlapping mounts, are skipped.
+    pub fn resolve(
+        &mut self,
+        groups: &[DuplicateGroup],
+        action: &DuplicateAction,
+    ) -> Result<DuplicateSummary> {
+        let mut summary = DuplicateSummary {
+            groups: groups.len(),
+            ..Default::default()
+        };
+        let mut removed_ids = Vec::new();
+
+        for group in groups {
+            summary.duplicates += group.duplicates.len();
+            summary.reclaimable_bytes += group
+                .duplicates
+                .iter()
+                .map(|photo| photo.file_size)
+                .sum::<i64>();
+
+            if *action == DuplicateAction::Report {
+                continue;
+            }
+
+            if !has_hash(&group.keeper.path, &group.hash) {
+                summary.skipped += group.duplicates.len();
+                continue;
+            }
+
+            for duplicate in &group.duplicates {
+                if !has_hash(&duplicate.path, &group.hash)
+                    || is_same_file(&group.keeper.path, &duplicate.path)
+                {
+                    summary.skipped += 1;
+                    continue;
+                }
+
+                let result = match action {
+                    DuplicateAction::Move(quarantine_dir) => {
+                        move_to_quarantine(Path::new(&duplicate.path), quarantine_dir)
+                    }
+                    DuplicateAction::Delete => {
+                        fs::remove_file(&duplicate.path).context("Failed to delete file")
+                    }
+                    DuplicateAction::Report => unreachable!(),
+                };
+
+                match result {
+                    Ok(()) => {
+                        if let DuplicateAction::Move(_) = action {
+                            summary.moved += 1;
+                        } else {
+                            summary.deleted += 1;
+                        }
+                        removed_ids.push(duplicate.id);
+                    }
+                    Err(err) => {
+                        self.progress_reporter.report_error(format!(
+                            "Failed to remove duplicate {}: {:#}",
+                            duplicate.path, err
+                        ));
+                        summary.failed += 1;
+                    }
+                }
+            }
+        }
+
+        if !removed_ids.is_empty() {
+            self.photo_repository
+                .delete_many(removed_ids)
+                .context("Failed to remove duplicates from the index")?;
+        }
+
+        Ok(summary)
+    }
+}
+
+/// Orders photos from the best keeper to the worst.
+fn compare_keepers(a: &Photo, b: &Photo, preference: &KeeperPreference) -> Ordering {
+    let is_preferred = |photo: &Photo| {
+        preference.preferred_library_id.is_some()
+            && photo.library_id == preference.preferred_library_id
+    };
+    let pixels = |photo: &Photo| {
+        photo.image_width.unwrap_or(0) as i64 * photo.image_height.unwrap_or(0) as i64
+    };
+
+    let by_strategy = match preference.strategy {
+        KeeperStrategy::Largest => b
+            .file_size
+            .cmp(&a.file_size)
+            .then_with(|| pixels(b).cmp(&pixels(a))),
+        KeeperStrategy::Oldest => a
+            .created_at
+            .cmp(&b.created_at)
+            .then_with(|| a.modified_at.cmp(&b.modified_at)),
+    };
+
+    is_preferred(b)
+        .cmp(&is_preferred(a))
+        .then(by_strategy)
+        .then_with(|| a.id.cmp(&b.id))
+}
+
+fn has_hash(path: &str, hash: &str) -> bool {
+    utils::compute_file_hash(path).is_ok_and(|file_hash| file_hash == hash)
+}
+
+/// Checks whether two paths resolve to the same physical file. Paths that cannot be
+/// resolved are considered the same, so that nothing is removed.
+fn is_same_file(path: &str, other_path: &str) -> bool {
+    let (Ok(path), Ok(other_path)) = (
+        Path::new(path).canonicalize(),
+        Path::new(other_path).canonicalize(),
+    ) else {
+        return true;
+    };
+    if path == other_path {
+        return true;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        match (fs::metadata(&path), fs::metadata(&other_path)) {
+            (Ok(metadata), Ok(other_metadata)) => {
+                metadata.dev() == other_metadata.dev() && metadata.ino() == other_metadata.ino()
+            }
+            _ => true,
+        }
+    }
+
+    #[cfg(not(unix))]
+    false
+}
+
+/// Moves a file below the quarantine directory, at its own absolute path, so that
+/// files with the same name never collide and can be restored.
+fn move_to_quarantine(path: &Path, quarantine_dir: &Path) -> Result<()> {
+    let relative_path: PathBuf = path
+        .components()
+        .filter(|component| matches!(component, Component::Normal(_)))
+        .collect();
+    let target = quarantine_dir.join(relative_path);
+
+    if target.exists() {
+        bail!("File already in quarantine: {}", target.display());
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).context("Failed to create quarantine directory")?;
+    }
+
+    // Renaming fails across file systems, where the file is copied instead
+    if fs::rename(path, &target).is_err() {
+        fs::copy(path, &target).context("Failed to copy file to quarantine")?;
+        fs::remove_file(path).context("Failed to remove file after copy")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        repositories::photo::repository::MockPhotoRepository,
+        utils::progress_reporter::NoOpProgressReporter,
+    };
+    use chrono::{TimeZone, Utc};
+    use mockall::predicate::eq;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn photo(id: i32, path: &str, hash: &str, file_size: i64, created_year: i32) -> Photo {
+        Photo {
+            id,
+            path: path.to_string(),
+            hash: Some(hash.to_string()),
+            file_size,
+            created_at: Utc.with_ymd_and_hms(created_year, 1, 1, 0, 0, 0).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    fn repository(photos: Vec<Photo>) -> MockPhotoRepository {
+        let mut repository = MockPhotoRepository::new();
+        repository
+            .expect_find_duplicates()
+            .returning(move || Ok(photos.clone()));
+        repository
+    }
+
+    #[test]
+    fn test_should_group_photos_by_hash_and_suggest_keeper() {
+        let photos = vec![
+            photo(1, "/a/1.jpg", "aaa", 100, 2020),
+            photo(2, "/b/1.jpg", "aaa", 200, 2021),
+            photo(3, "/a/2.jpg", "bbb", 100, 2022),
+            photo(4, "/b/2.jpg", "bbb", 100, 2019),
+        ];
+        let mut service = DuplicateService::new(repository(photos), NoOpProgressReporter);
+
+        let groups = service.find_groups(&KeeperPreference::default()).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].keeper.id, 2);
+        assert_eq!(groups[0].duplicates[0].id, 1);
+        assert_eq!(groups[1].keeper.id, 3);
+
+        let groups = service
+            .find_groups(&KeeperPreference {
+                strategy: KeeperStrategy::Oldest,
+                preferred_library_id: None,
+            })
+            .unwrap();
+        assert_eq!(groups[0].keeper.id, 1);
+        assert_eq!(groups[1].keeper.id, 4);
+    }
+
+    #[test]
+    fn test_should_keep_photo_of_preferred_library() {
+        let mut preferred = photo(1, "/a/1.jpg", "aaa", 100, 2021);
+        preferred.library_id = Some(7);
+        let photos = vec![preferred, photo(2, "/b/1.jpg", "aaa", 200, 2020)];
+        let mut service = DuplicateService::new(repository(photos), NoOpProgressReporter);
+
+        let groups = service
+            .find_groups(&KeeperPreference {
+                strategy: KeeperStrategy::Largest,
+                preferred_library_id: Some(7),
+            })
+            .unwrap();
+
+        assert_eq!(groups[0].keeper.id, 1);
+    }
+
+    #[test]
+    fn test_should_move_duplicates_to_quarantine() {
+        let temp_dir = TempDir::new().unwrap();
+        let keeper_path = temp_dir.path().join("keeper.jpg");
+        let duplicate_path = temp_dir.path().join("copy.jpg");
+        let changed_path = temp_dir.path().join("changed.jpg");
+        fs::write(&keeper_path, b"image bytes").unwrap();
+        fs::write(&duplicate_path, b"image bytes").unwrap();
+        fs::write(&changed_path, b"edited since indexing").unwrap();
+        let hash = utils::compute_file_hash(&keeper_path).unwrap();
+        let quarantine_dir = temp_dir.path().join("quarantine");
+
+        let mut repository = MockPhotoRepository::new();
+        repository
+            .expect_delete_many()
+            .with(eq(vec![2]))
+            .times(1)
+            .returning(|ids| Ok(ids.len()));
+        let mut service = DuplicateService::new(repository, NoOpProgressReporter);
+
+        let groups = vec![DuplicateGroup {
+            hash: hash.clone(),
+            keeper: photo(1, keeper_path.to_str().unwrap(), &hash, 11, 2020),
+            duplicates: vec![
+                photo(2, duplicate_path.to_str().unwrap(), &hash, 11, 2021),
+                photo(3, changed_path.to_str().unwrap(), &hash, 11, 2022),
+            ],
+        }];
+
+        let summary = service
+            .resolve(&groups, &DuplicateAction::Move(quarantine_dir.clone()))
+            .unwrap();
+
+        assert_eq!(summary.moved, 1);
+        assert_eq!(summary.skipped, 1);
+        assert_eq!(summary.reclaimable_bytes, 22);
+        assert!(keeper_path.exists());
+        assert!(!duplicate_path.exists());
+        assert!(changed_path.exists());
+        assert!(
+            quarantine_dir
+                .join(duplicate_path.strip_prefix("/").unwrap())
+                .exists()
+        );
+    }
+
+    #[test]
+    fn test_should_not_delete_duplicates_when_keeper_is_missing() {
+        let temp_dir = TempDir::new().unwrap();
+        let duplicate_path = temp_dir.path().join("copy.jpg");
+        fs::write(&duplicate_path, b"image bytes").unwrap();
+        let hash = utils::compute_file_hash(&duplicate_path).unwrap();
+
+        let mut repository = MockPhotoRepository::new();
+        repository.expect_delete_many().times(0);
+        let mut service = DuplicateService::new(repository, NoOpProgressReporter);
+
+        let groups = vec![DuplicateGroup {
+            hash: hash.clone(),
+            keeper: photo(1, "/missing/keeper.jpg", &hash, 11, 2020),
+            duplicates: vec![photo(2, duplicate_path.to_str().unwrap(), &hash, 11, 2021)],
+        }];
+
+        let summary = service.resolve(&groups, &DuplicateAction::Delete).unwrap();
+
+        assert_eq!(summary.deleted, 0);
+        assert_eq!(summary.skipped, 1);
+        assert!(duplicate_path.exists());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_should_skip_duplicate_that_is_the_keeper_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let keeper_path = temp_dir.path().join("keeper.jpg");
+        let linked_path = temp_dir.path().join("linked.jpg");
+        let hard_linked_path = temp_dir.path().join("hard_linked.jpg");
+        fs::write(&keeper_path, b"image bytes").unwrap();
+        std::os::unix::fs::symlink(&keeper_path, &linked_path).unwrap();
+        fs::hard_link(&keeper_path, &hard_linked_path).unwrap();
+        let hash = utils::compute_file_hash(&keeper_path).unwrap();
+
+        let mut repository = MockPhotoRepository::new();
+        repository.expect_delete_many().times(0);
+        let mut service = DuplicateService::new(repository, NoOpProgressReporter);
+
+        let groups = vec![DuplicateGroup {
+            hash: hash.clone(),
+            keeper: photo(1, keeper_path.to_str().unwrap(), &hash, 11, 2020),
+            duplicates: vec![
+                photo(2, linked_path.to_str().unwrap(), &hash, 11, 2021),
+                photo(3, hard_linked_path.to_str().unwrap(), &hash, 11, 2021),
+                photo(
+                    4,
+                    &format!("{}/./keeper.jpg", temp_dir.path().display()),
+                    &hash,
+                    11,
+                    2021,
+                ),
+            ],
+        }];
+
+        let summary = service.resolve(&groups, &DuplicateAction::Delete).unwrap();
+
+        assert_eq!(summary.deleted, 0);
+        assert_eq!(summary.skipped, 3);
+        assert!(keeper_path.exists());
+        assert!(hard_linked_path.exists());
+    }
+}
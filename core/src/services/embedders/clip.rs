@@ -0,0 +1,99 @@
+use crate::config::{ClipArchitecture, EmbeddingsConfig, ModelDType};
+use anyhow::{Context, Error, Result, anyhow};
+use candle_core::{DType, Device};
+use candle_nn::VarBuilder;
+use candle_transformers::models::clip::{
+    self,
+    text_model::{Activation, ClipTextConfig},
+    vision_model::ClipVisionConfig,
+};
+
+/// Returns the configuration of a CLIP architecture.
+pub fn clip_config(architecture: ClipArchitecture) -> clip::ClipConfig {
+    match architecture {
+        ClipArchitecture::VitBasePatch32 => clip::ClipConfig::vit_base_patch32(),
+        ClipArchitecture::VitBasePatch16 => clip::ClipConfig {
+            vision_config: ClipVisionConfig {
+                patch_size: 16,
+                ..ClipVisionConfig::vit_base_patch32()
+            },
+            ..clip::ClipConfig::vit_base_patch32()
+        },
+        ClipArchitecture::VitLargePatch14 => clip::ClipConfig {
+            text_config: large_text_config(),
+            vision_config: ClipVisionConfig {
+                image_size: 224,
+                ..ClipVisionConfig::clip_vit_large_patch14_336()
+            },
+            logit_scale_init_value: 2.6592,
+            image_size: 224,
+        },
+        ClipArchitecture::VitLargePatch14_336 => clip::ClipConfig {
+            text_config: large_text_config(),
+            vision_config: ClipVisionConfig::clip_vit_large_patch14_336(),
+            logit_scale_init_value: 2.6592,
+            image_size: 336,
+        },
+    }
+}
+
+// The config details can be found in the "text_config" section of this json file:
+// https://huggingface.co/openai/clip-vit-large-patch14/blob/main/config.json
+fn large_text_config() -> ClipTextConfig {
+    ClipTextConfig {
+        vocab_size: 49408,
+        embed_dim: 768,
+        activation: Activation::QuickGelu,
+        intermediate_size: 3072,
+        max_position_embeddings: 77,
+        pad_with: None,
+        num_hidden_layers: 12,
+        num_attention_heads: 12,
+        projection_dim: 768,
+    }
+}
+
+pub(crate) fn device(name: &str) -> Result<Device> {
+    Ok(match name {
+        "cpu" => Device::Cpu,
+        "cuda" => Device::new_cuda(0)
+            .map_err(Error::from)
+            .context("Failed to open CUDA device, which needs the cuda feature")?,
+        _ => return Err(anyhow!("Unknown device: {}", name)),
+    })
+}
+
+pub(crate) fn dtype(dtype: ModelDType) -> DType {
+    match dtype {
+        ModelDType::F32 => DType::F32,
+        ModelDType::F16 => DType::F16,
+        ModelDType::Bf16 => DType::BF16,
+    }
+}
+
+/// Loads a CLIP model of the registry on the configured device, with weights of the
+/// configured precision.
+pub(crate) fn load_model(
+    embeddings_config: &EmbeddingsConfig,
+    model_id: &str,
+) -> Result<(clip::ClipConfig, clip::ClipModel, Device)> {
+    let model_config = embeddings_config.get(model_id)?;
+    let device = device(&embeddings_config.device)?;
+    let config = clip_config(model_config.architecture);
+    let model_path = embeddings_config.path(&model_config.safetensors_file);
+
+    if !model_path.exists() {
+        return Err(anyhow!("Model file not found: {}", model_path.display()));
+    }
+
+    let vb = unsafe {
+        VarBuilder::from_mmaped_safetensors(&[&model_path], dtype(embeddings_config.dtype), &device)
+            .map_err(Error::from)
+            .context("Failed to load model safetensors")?
+    };
+    let model = clip::ClipModel::new(vb, &config)
+        .map_err(Error::from)
+        .context("Failed to create CLIP model")?;
+
+    Ok((config, model, device))
+}
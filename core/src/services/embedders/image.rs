@@ -1,119 +1,130 @@
-use crate::config::ClipModelConfig;
+use crate::{
+    config::EmbeddingsConfig,
+    imaging,
+    services::embedders::clip::{dtype, load_model},
+};
 use anyhow::{Context, Error, Result};
 use candle_core::{DType, Device, Tensor};
-use candle_nn::VarBuilder;
 use candle_transformers::models::clip;
+use image::{DynamicImage, imageops::FilterType};
 use rayon::prelude::*;
-use std::path::Path;
+
+/// Mean and standard deviation of the RGB channels of the CLIP training images.
+const CLIP_MEAN: [f32; 3] = [0.481_454_66, 0.457_827_5, 0.408_210_73];
+const CLIP_STD: [f32; 3] = [0.268_629_54, 0.261_302_6, 0.275_777_1];
 
 #[cfg_attr(test, mockall::automock)]
 pub trait ImageEmbedder {
+    /// Returns the id of the model computing the embeddings, in the registry.
+    fn model_id(&self) -> &str;
+
     /// Computes embeddings for a batch of images.
     fn embed(&self, image_paths: &Vec<String>) -> Result<Vec<Vec<f32>>>;
 }
 
 pub struct ClipImageEmbedder {
+    model_id: String,
     config: clip::ClipConfig,
     model: clip::ClipModel,
     device: Device,
+    dtype: DType,
+    /// Threads decoding images and running the model on the CPU.
+    thread_pool: rayon::ThreadPool,
 }
 
 impl ClipImageEmbedder {
-    pub fn new(clip_config: &ClipModelConfig) -> Result<Self> {
-        let device = match clip_config.device.as_str() {
-            "cpu" => Device::Cpu,
-            "cuda" => Device::new_cuda(0)?,
-            _ => Device::Cpu,
-        };
-        let config = clip::ClipConfig::vit_base_patch32();
-        let model_path = Path::new(&clip_config.dir).join(&clip_config.safetensors_file);
-
-        if !model_path.exists() {
-            return Err(anyhow::anyhow!(
-                "Model file not found: {}",
-                model_path.display()
-            ));
-        }
-
-        let vb = unsafe {
-            VarBuilder::from_mmaped_safetensors(&[&model_path], DType::F32, &device)
-                .map_err(Error::from)
-                .context("Failed to load model safetensors")?
-        };
-        let model = clip::ClipModel::new(vb, &config)
-            .map_err(Error::from)
-            .context("Failed to create CLIP model")?;
+    /// Loads a model of the registry.
+    pub fn new(embeddings_config: &EmbeddingsConfig, model_id: &str) -> Result<Self> {
+        let (config, model, device) = load_model(embeddings_config, model_id)?;
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(embeddings_config.threads)
+            .build()
+            .context("Failed to create embedding thread pool")?;
 
         Ok(Self {
+            model_id: model_id.to_string(),
             config,
             model,
             device,
+            dtype: dtype(embeddings_config.dtype),
+            thread_pool,
         })
     }
 }
 
 impl ImageEmbedder for ClipImageEmbedder {
-    /// Computes embeddings for a batch of images.
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    /// Computes L2-normalized embeddings for a batch of images.
     fn embed(&self, image_paths: &Vec<String>) -> Result<Vec<Vec<f32>>> {
-        let images = load_images(image_paths, self.config.image_size, &self.device)
-            .context("Failed to load images")?;
-        let tensors = self
-            .model
-            .get_image_features(&images)
-            .map_err(Error::from)
-            .context("Failed to compute image features")?;
-        let embeddings = tensors
-            .to_vec2::<f32>()
-            .map_err(Error::from)
-            .context("Failed to convert tensors to embeddings")?;
-        Ok(embeddings)
+        // Both image decoding and CPU matrix products run on the threads of the pool
+        self.thread_pool.install(|| {
+            let images = load_images(image_paths, self.config.image_size, &self.device)
+                .and_then(|images| Ok(images.to_dtype(self.dtype)?))
+                .context("Failed to load images")?;
+            let tensors = self
+                .model
+                .get_image_features(&images)
+                .and_then(|features| clip::div_l2_norm(&features))
+                .map_err(Error::from)
+                .context("Failed to compute image features")?;
+            let embeddings = tensors
+                .to_dtype(DType::F32)
+                .and_then(|tensors| tensors.to_vec2::<f32>())
+                .map_err(Error::from)
+                .context("Failed to convert tensors to embeddings")?;
+            Ok(embeddings)
+        })
     }
 }
 
-fn load_heic_image_data<T: AsRef<std::path::Path>>(path: T, image_size: usize) -> Result<Vec<u8>> {
-    use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
-
-    let lib_heif = LibHeif::new();
-    let ctx = HeifContext::read_from_file(
-        path.as_ref()
-            .to_str()
-            .ok_or_else(|| anyhow::anyhow!("Invalid path"))?,
-    )
-    .context("Failed to read HEIC file")?;
-    let handle = ctx
-        .primary_image_handle()
-        .context("Failed to get primary image handle")?;
-
-    let image = lib_heif
-        .decode(&handle, ColorSpace::Rgb(RgbChroma::Rgb), None)
-        .context("Failed to decode HEIC image")?;
-
-    let resized_image = image
-        .scale(image_size as u32, image_size as u32, None)
-        .context("Failed to resize HEIC image")?;
-
-    let planes = resized_image.planes();
-    let interleaved_plane = planes
-        .interleaved
-        .ok_or_else(|| anyhow::anyhow!("No interleaved plane available"))?;
-    let img_data = interleaved_plane.data.to_vec();
-
-    Ok(img_data)
+impl<E: ImageEmbedder> ImageEmbedder for &E {
+    fn model_id(&self) -> &str {
+        (**self).model_id()
+    }
+
+    fn embed(&self, image_paths: &Vec<String>) -> Result<Vec<Vec<f32>>> {
+        (**self).embed(image_paths)
+    }
 }
 
-fn load_image_data<T: AsRef<std::path::Path>>(path: T, image_size: usize) -> Result<Vec<u8>> {
-    let img = image::ImageReader::open(&path)
-        .context("Failed to open image file")?
-        .decode()
-        .context("Failed to decode image")?;
-    let img = img.resize_to_fill(
-        image_size as u32,
-        image_size as u32,
-        image::imageops::FilterType::Triangle,
-    );
-    let img = img.to_rgb8();
-    let img_data = img.into_raw();
-    Ok(img_data)
+/// Resizes an image as the reference CLIP preprocessing does: its shorter side is resized to
+/// `image_size` with a bicubic filter, then its centre square is cropped.
+fn resize_image_data(img: DynamicImage, image_size: usize) -> Vec<u8> {
+    let size = image_size as u32;
+    let (resized_width, resized_height) = resized_dimensions(img.width(), img.height(), size);
+
+    let img = img.resize_exact(resized_width, resized_height, FilterType::CatmullRom);
+    let left = ((resized_width - size) as f64 / 2.).round_ties_even() as u32;
+    let top = ((resized_height - size) as f64 / 2.).round_ties_even() as u32;
+    img.crop_imm(left, top, size, size).to_rgb8().into_raw()
+}
+
+/// Returns the dimensions of an image whose shorter side is resized to `size`, truncating the
+/// longer side like the reference preprocessing.
+fn resized_dimensions(width: u32, height: u32, size: u32) -> (u32, u32) {
+    let (width, height, size) = (width as u64, height as u64, size as u64);
+    if width <= height {
+        (size as u32, (size * height / width) as u32)
+    } else {
+        ((size * width / height) as u32, size as u32)
+    }
+}
+
+/// Converts RGB pixels to a channels-first tensor normalized with the CLIP mean and
+/// standard deviation.
+fn normalize_image_data(data: Vec<u8>, image_size: usize, device: &Device) -> Result<Tensor> {
+    let mean = Tensor::new(&CLIP_MEAN, device)?.reshape((3, 1, 1))?;
+    let std = Tensor::new(&CLIP_STD, device)?.reshape((3, 1, 1))?;
+
+    Ok(Tensor::from_vec(data, (image_size, image_size, 3), device)?
+        .permute((2, 0, 1))?
+        .to_dtype(DType::F32)?
+        .affine(1. / 255., 0.)?
+        .broadcast_sub(&mean)?
+        .broadcast_div(&std)?)
 }
 
 fn load_images<T: AsRef<std::path::Path> + Sync>(
@@ -124,17 +135,9 @@ fn load_images<T: AsRef<std::path::Path> + Sync>(
     let image_data: Vec<Result<Vec<u8>, anyhow::Error>> = paths
         .par_iter()
         .map(|path| -> Result<Vec<u8>, anyhow::Error> {
-            if path
-                .as_ref()
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .map(|ext| ext.to_lowercase())
-                == Some("heic".to_string())
-            {
-                load_heic_image_data(path, image_size)
-            } else {
-                load_image_data(path, image_size)
-            }
+            // Images are decoded at full resolution, as scaling while decoding would not
+            // match the reference preprocessing
+            Ok(resize_image_data(imaging::load_image(path)?, image_size))
         })
         .collect();
 
@@ -148,14 +151,8 @@ fn load_images<T: AsRef<std::path::Path> + Sync>(
 
     let mut images = Vec::new();
     for data in processed_data {
-        let img = (|| {
-            Tensor::from_vec(data, (image_size, image_size, 3), &device)?
-                .permute((2, 0, 1))?
-                .to_dtype(DType::F32)?
-                .affine(2. / 255., -1.)
-                .map_err(Error::from)
-        })()
-        .context("Failed to create tensor from image data")?;
+        let img = normalize_image_data(data, image_size, device)
+            .context("Failed to create tensor from image data")?;
         images.push(img);
     }
 
@@ -164,3 +161,65 @@ fn load_images<T: AsRef<std::path::Path> + Sync>(
         .context("Failed to stack image tensors")?;
     Ok(stacked_images)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    #[test]
+    fn test_should_resize_shorter_side_and_crop_centre() {
+        // Only the centre square of the wide image is white
+        let wide = DynamicImage::ImageRgb8(RgbImage::from_fn(448, 224, |x, _| {
+            if (112..336).contains(&x) {
+                Rgb([255, 255, 255])
+            } else {
+                Rgb([0, 0, 0])
+            }
+        }));
+
+        let data = resize_image_data(wide, 224);
+
+        assert_eq!(data.len(), 224 * 224 * 3);
+        assert!(data.iter().all(|&value| value == 255));
+    }
+
+    #[test]
+    fn test_should_resize_shorter_side_keeping_aspect_ratio() {
+        assert_eq!(resized_dimensions(640, 480, 224), (298, 224));
+        assert_eq!(resized_dimensions(3024, 4032, 224), (224, 298));
+        assert_eq!(resized_dimensions(500, 500, 336), (336, 336));
+    }
+
+    #[test]
+    fn test_should_crop_centre_of_tall_images() {
+        let tall = DynamicImage::ImageRgb8(RgbImage::from_fn(224, 300, |_, y| {
+            if (38..262).contains(&y) {
+                Rgb([255, 255, 255])
+            } else {
+                Rgb([0, 0, 0])
+            }
+        }));
+
+        let data = resize_image_data(tall, 224);
+
+        assert_eq!(data.len(), 224 * 224 * 3);
+        assert!(data.iter().all(|&value| value == 255));
+    }
+
+    #[test]
+    fn test_should_normalize_pixels_with_clip_mean_and_std() {
+        let data = [255, 0, 128].repeat(4);
+
+        let tensor = normalize_image_data(data, 2, &Device::Cpu).unwrap();
+
+        // Values of the reference CLIP preprocessing
+        let expected = [1.930_336_3, -1.752_097_1, 0.339_948_6];
+        assert_eq!(tensor.dims(), [3, 2, 2]);
+        for (channel, expected) in tensor.to_vec3::<f32>().unwrap().iter().zip(expected) {
+            for value in channel.iter().flatten() {
+                assert!((value - expected).abs() < 1e-5, "{} != {}", value, expected);
+            }
+        }
+    }
+}
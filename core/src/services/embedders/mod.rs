@@ -1,5 +1,8 @@
+pub mod clip;
 pub mod image;
+pub mod multilingual;
 pub mod text;
 
 pub use image::ClipImageEmbedder;
-pub use text::ClipTextEmbedder;
+pub use multilingual::MultilingualTextEmbedder;
+pub use text::{ClipTextEmbedder, load_text_embedder};
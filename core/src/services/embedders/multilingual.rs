@@ -0,0 +1,156 @@
+use crate::{
+    config::{EmbeddingsConfig, TextEncoderConfig},
+    services::embedders::{
+        clip::{clip_config, device, dtype},
+        text::TextEmbedder,
+    },
+};
+use anyhow::{Context, Error, Result, anyhow};
+use candle_core::{DType, Device, Module, Tensor};
+use candle_nn::{Linear, VarBuilder, linear};
+use candle_transformers::models::{
+    clip::div_l2_norm,
+    xlm_roberta::{Config as XLMRobertaConfig, XLMRobertaModel},
+};
+use std::fs;
+use tokenizers::{Encoding, Tokenizer, TruncationParams};
+
+/// Embeds queries written in any language with a multilingual text encoder aligned with the
+/// image embeddings of a CLIP model, such as M-CLIP.
+pub struct MultilingualTextEmbedder {
+    model_id: String,
+    transformer: XLMRobertaModel,
+    projection: Linear,
+    tokenizer: Tokenizer,
+    device: Device,
+}
+
+impl MultilingualTextEmbedder {
+    /// Loads a text encoder of the registry, which must be aligned with a registered model.
+    pub fn new(embeddings_config: &EmbeddingsConfig, text_encoder_id: &str) -> Result<Self> {
+        let encoder_config = embeddings_config.get_text_encoder(text_encoder_id)?;
+        let model_config = embeddings_config.get(&encoder_config.model)?;
+        let device = device(&embeddings_config.device)?;
+        let model_path = embeddings_config.path(&encoder_config.safetensors_file);
+        let tokenizer_path = embeddings_config.path(&encoder_config.tokenizer_file);
+        let config_path = embeddings_config.path(&encoder_config.config_file);
+
+        for path in [&model_path, &tokenizer_path, &config_path] {
+            if !path.exists() {
+                return Err(anyhow!("Text encoder file not found: {}", path.display()));
+            }
+        }
+
+        let config = read_config(embeddings_config, encoder_config)?;
+        // Embeddings have the dimensions of the image embeddings they are compared with
+        let embedding_dim = clip_config(model_config.architecture)
+            .vision_config
+            .projection_dim;
+
+        let vb = unsafe {
+            VarBuilder::from_mmaped_safetensors(
+                &[&model_path],
+                dtype(embeddings_config.dtype),
+                &device,
+            )
+            .map_err(Error::from)
+            .context("Failed to load model safetensors")?
+        };
+        let transformer = XLMRobertaModel::new(&config, vb.pp("transformer"))
+            .map_err(Error::from)
+            .context("Failed to create text encoder")?;
+        let projection = linear(
+            config.hidden_size,
+            embedding_dim,
+            vb.pp("LinearTransformation"),
+        )
+        .map_err(Error::from)
+        .context("Failed to create text encoder projection")?;
+
+        let tokenizer = load_tokenizer(embeddings_config, text_encoder_id)?;
+
+        Ok(Self {
+            model_id: encoder_config.model.clone(),
+            transformer,
+            projection,
+            tokenizer,
+            device,
+        })
+    }
+}
+
+impl TextEmbedder for MultilingualTextEmbedder {
+    /// Returns the id of the model whose image embeddings the encoder is aligned with.
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    /// Computes the L2-normalized embedding of a given text.
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let encoding = encode(&self.tokenizer, text)?;
+        let token_ids = Tensor::new(encoding.get_ids(), &self.device)?.unsqueeze(0)?;
+        let attention_mask =
+            Tensor::new(encoding.get_attention_mask(), &self.device)?.unsqueeze(0)?;
+        let token_type_ids = token_ids.zeros_like()?;
+
+        let hidden_states = self.transformer.forward(
+            &token_ids,
+            &attention_mask,
+            &token_type_ids,
+            None,
+            None,
+            None,
+        )?;
+        // Mean pooling over the tokens, which are all attended to in a single query
+        let text_features = div_l2_norm(&self.projection.forward(&hidden_states.mean(1)?)?)?;
+        let embedding_vec = text_features
+            .squeeze(0)?
+            .to_dtype(DType::F32)?
+            .to_vec1::<f32>()?;
+        Ok(embedding_vec)
+    }
+}
+
+/// Loads the tokenizer of a text encoder of the registry, truncating encodings to the number
+/// of positions of its transformer, special tokens included.
+pub fn load_tokenizer(
+    embeddings_config: &EmbeddingsConfig,
+    text_encoder_id: &str,
+) -> Result<Tokenizer> {
+    let encoder_config = embeddings_config.get_text_encoder(text_encoder_id)?;
+    let config = read_config(embeddings_config, encoder_config)?;
+
+    let mut tokenizer =
+        Tokenizer::from_file(embeddings_config.path(&encoder_config.tokenizer_file))
+            .map_err(Error::from_boxed)
+            .context("Failed to open tokenizer")?;
+    tokenizer
+        .with_truncation(Some(TruncationParams {
+            // Positions are offset by the padding token, which leaves two of them unused
+            max_length: config.max_position_embeddings - 2,
+            ..Default::default()
+        }))
+        .map_err(Error::from_boxed)
+        .context("Failed to configure tokenizer truncation")?;
+    Ok(tokenizer)
+}
+
+/// Encodes a text wrapped in the special tokens of the tokenizer, along with the attention
+/// mask of its tokens.
+pub fn encode(tokenizer: &Tokenizer, text: &str) -> Result<Encoding> {
+    tokenizer
+        .encode(text, true)
+        .map_err(Error::from_boxed)
+        .context("Failed to tokenize text")
+}
+
+fn read_config(
+    embeddings_config: &EmbeddingsConfig,
+    encoder_config: &TextEncoderConfig,
+) -> Result<XLMRobertaConfig> {
+    let config_path = embeddings_config.path(&encoder_config.config_file);
+    serde_json::from_str(
+        &fs::read_to_string(&config_path).context("Failed to read text encoder config")?,
+    )
+    .context("Failed to parse text encoder config")
+}
@@ -1,40 +1,35 @@
-use crate::config::ClipModelConfig;
-use anyhow::{Context, Error, Result};
+use crate::{
+    config::EmbeddingsConfig,
+    services::embedders::{clip::load_model, multilingual::MultilingualTextEmbedder},
+};
+use anyhow::{Context, Error, Result, bail};
 use candle_core::{DType, Device, Tensor};
-use candle_nn::VarBuilder;
 use candle_transformers::models::clip;
-use std::path::Path;
 use tokenizers::Tokenizer;
 
 #[cfg_attr(test, mockall::automock)]
 pub trait TextEmbedder {
+    /// Returns the id of the model computing the embeddings, in the registry.
+    fn model_id(&self) -> &str;
+
     /// Computes embeddings for a given text.
     fn embed(&self, text: &str) -> Result<Vec<f32>>;
 }
 
 pub struct ClipTextEmbedder {
+    model_id: String,
+    /// Number of tokens of the text encoder context.
+    context_length: usize,
     model: clip::ClipModel,
     tokenizer: Tokenizer,
     device: Device,
 }
 
 impl ClipTextEmbedder {
-    pub fn new(clip_config: &ClipModelConfig) -> Result<Self> {
-        let config = clip::ClipConfig::vit_base_patch32();
-        let device = match clip_config.device.as_str() {
-            "cpu" => Device::Cpu,
-            "cuda" => Device::new_cuda(0)?,
-            _ => Device::Cpu,
-        };
-        let model_path = Path::new(&clip_config.dir).join(&clip_config.safetensors_file);
-        let tokenizer_path = Path::new(&clip_config.dir).join(&clip_config.tokenizer_file);
-
-        if !model_path.exists() {
-            return Err(anyhow::anyhow!(
-                "Model file not found: {}",
-                model_path.display()
-            ));
-        }
+    /// Loads a model of the registry along with its tokenizer.
+    pub fn new(embeddings_config: &EmbeddingsConfig, model_id: &str) -> Result<Self> {
+        let tokenizer_path =
+            embeddings_config.path(&embeddings_config.get(model_id)?.tokenizer_file);
 
         if !tokenizer_path.exists() {
             return Err(anyhow::anyhow!(
@@ -43,16 +38,14 @@ impl ClipTextEmbedder {
             ));
         }
 
-        let vb = unsafe {
-            VarBuilder::from_mmaped_safetensors(&[&model_path], DType::F32, &device)
-                .context("Failed to load model safetensors")?
-        };
+        let (config, model, device) = load_model(embeddings_config, model_id)?;
         let tokenizer = Tokenizer::from_file(&tokenizer_path)
             .map_err(Error::from_boxed)
             .context("Failed to open tokenizer")?;
-        let model = clip::ClipModel::new(vb, &config).context("Failed to load model")?;
 
         Ok(Self {
+            model_id: model_id.to_string(),
+            context_length: config.text_config.max_position_embeddings,
             model,
             tokenizer,
             device,
@@ -61,16 +54,143 @@ impl ClipTextEmbedder {
 }
 
 impl TextEmbedder for ClipTextEmbedder {
-    /// Computes embeddings for a given text.
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    /// Computes the L2-normalized embedding of a given text.
     fn embed(&self, text: &str) -> Result<Vec<f32>> {
-        let encoding = self
-            .tokenizer
-            .encode(text, true)
-            .map_err(Error::from_boxed)?;
-        let tokens = encoding.get_ids();
-        let token_ids = Tensor::new(tokens, &self.device)?.unsqueeze(0)?;
-        let text_features = self.model.get_text_features(&token_ids)?;
-        let embedding_vec = text_features.squeeze(0)?.to_vec1::<f32>()?;
+        let tokens = tokenize(&self.tokenizer, text, self.context_length)?;
+        let token_ids = Tensor::new(tokens.as_slice(), &self.device)?.unsqueeze(0)?;
+        let text_features = clip::div_l2_norm(&self.model.get_text_features(&token_ids)?)?;
+        let embedding_vec = text_features
+            .squeeze(0)?
+            .to_dtype(DType::F32)?
+            .to_vec1::<f32>()?;
         Ok(embedding_vec)
     }
 }
+
+impl<E: TextEmbedder + ?Sized> TextEmbedder for Box<E> {
+    fn model_id(&self) -> &str {
+        (**self).model_id()
+    }
+
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        (**self).embed(text)
+    }
+}
+
+/// Loads the text embedder of searches: the multilingual text encoder when one is set, which
+/// must be aligned with the active model, or else the text encoder of the active model.
+pub fn load_text_embedder(
+    embeddings_config: &EmbeddingsConfig,
+) -> Result<Box<dyn TextEmbedder + Send + Sync>> {
+    let Some(text_encoder_id) = &embeddings_config.text_encoder else {
+        return Ok(Box::new(ClipTextEmbedder::new(
+            embeddings_config,
+            &embeddings_config.model,
+        )?));
+    };
+
+    let aligned_model = &embeddings_config.get_text_encoder(text_encoder_id)?.model;
+    if *aligned_model != embeddings_config.model {
+        bail!(
+            "Text encoder {} is aligned with {}, not with the active model {}",
+            text_encoder_id,
+            aligned_model,
+            embeddings_config.model
+        );
+    }
+
+    Ok(Box::new(MultilingualTextEmbedder::new(
+        embeddings_config,
+        text_encoder_id,
+    )?))
+}
+
+/// Returns the token ids of a text as the reference CLIP tokenization does: truncated to the
+/// context length, keeping the end of text token last, and padded with zeros.
+///
+/// The text features are read at the end of text token, which has the highest id.
+fn tokenize(tokenizer: &Tokenizer, text: &str, context_length: usize) -> Result<Vec<u32>> {
+    let encoding = tokenizer
+        .encode(text, true)
+        .map_err(Error::from_boxed)
+        .context("Failed to tokenize text")?;
+    let mut tokens = encoding.get_ids().to_vec();
+
+    if tokens.len() > context_length {
+        let end_of_text = tokens[tokens.len() - 1];
+        tokens.truncate(context_length);
+        tokens[context_length - 1] = end_of_text;
+    }
+    tokens.resize(context_length, 0);
+
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokenizers::{
+        models::wordlevel::WordLevel, pre_tokenizers::whitespace::Whitespace,
+        processors::template::TemplateProcessing,
+    };
+
+    /// Builds a tokenizer wrapping texts in the CLIP start and end of text tokens.
+    fn tokenizer() -> Tokenizer {
+        let vocab = [
+            ("!", 0),
+            ("a", 1),
+            ("white", 2),
+            ("building", 3),
+            ("<|unk|>", 4),
+            ("<|startoftext|>", 49406),
+            ("<|endoftext|>", 49407),
+        ]
+        .into_iter()
+        .map(|(token, id)| (token.to_string(), id))
+        .collect();
+
+        let mut tokenizer = Tokenizer::new(
+            WordLevel::builder()
+                .vocab(vocab)
+                .unk_token("<|unk|>".to_string())
+                .build()
+                .unwrap(),
+        );
+        tokenizer.with_pre_tokenizer(Some(Whitespace {}));
+        tokenizer.with_post_processor(Some(
+            TemplateProcessing::builder()
+                .try_single("<|startoftext|> $A <|endoftext|>")
+                .unwrap()
+                .special_tokens(vec![("<|startoftext|>", 49406), ("<|endoftext|>", 49407)])
+                .build()
+                .unwrap(),
+        ));
+        tokenizer
+    }
+
+    #[test]
+    fn test_should_pad_tokens_to_context_length() {
+        let tokens = tokenize(&tokenizer(), "a white building", 77).unwrap();
+
+        assert_eq!(tokens.len(), 77);
+        assert_eq!(tokens[..5], [49406, 1, 2, 3, 49407]);
+        assert!(tokens[5..].iter().all(|&token| token == 0));
+    }
+
+    #[test]
+    fn test_should_truncate_long_texts_keeping_end_of_text_token() {
+        let text = "a white building ".repeat(40);
+
+        let tokens = tokenize(&tokenizer(), &text, 77).unwrap();
+
+        assert_eq!(tokens.len(), 77);
+        assert_eq!(tokens[..4], [49406, 1, 2, 3]);
+        assert_eq!(tokens[76], 49407);
+        // The end of text token is the single highest id, where features are read
+        assert_eq!(tokens.iter().filter(|&&token| token == 49407).count(), 1);
+    }
+}
@@ -1,21 +1,29 @@
-use std::time::Instant;
+use std::{io::Cursor, time::Instant};
 
 use anyhow::{Context, Result};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use image::codecs::jpeg::JpegEncoder;
 use pgvector::Vector;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     config::FaceDetectionServerConfig,
-    models::{NewFace, PaginationFilter, UpdatedPhoto},
+    imaging,
+    models::{JobStage, MediaType, NewFace, PaginationFilter, UpdatedPhoto},
     repositories::{
-        PhotoFindPathFilters, face::repository::FaceRepository, photo::repository::PhotoRepository,
+        PhotoErrorRepository, PhotoFindPathFilters, SkipFailedFilter,
+        face::repository::FaceRepository, photo::repository::PhotoRepository,
     },
     utils::progress_reporter::ProgressReporter,
 };
 
+/// Quality of the JPEG images sent to the face detection server.
+const JPEG_QUALITY: u8 = 95;
+
 #[derive(Serialize)]
 struct DetectFacesRequest {
-    image_path: String,
+    /// JPEG image in its displayed orientation, encoded in base64.
+    image_data: String,
 }
 
 #[derive(Deserialize)]
@@ -39,20 +47,31 @@ struct BoundingBox {
     height: i32,
 }
 
-pub struct FaceDetectionService<PR: PhotoRepository, FR: FaceRepository, P: ProgressReporter> {
+pub struct FaceDetectionService<
+    PR: PhotoRepository,
+    FR: FaceRepository,
+    ER: PhotoErrorRepository,
+    P: ProgressReporter,
+> {
     photo_repository: PR,
     face_repository: FR,
+    photo_error_repository: ER,
     progress_reporter: P,
     http_client: reqwest::blocking::Client,
     face_detection_url: String,
+    max_attempts: i32,
 }
 
-impl<PR: PhotoRepository, FR: FaceRepository, P: ProgressReporter> FaceDetectionService<PR, FR, P> {
+impl<PR: PhotoRepository, FR: FaceRepository, ER: PhotoErrorRepository, P: ProgressReporter>
+    FaceDetectionService<PR, FR, ER, P>
+{
     pub fn new(
         photo_repository: PR,
         face_repository: FR,
+        photo_error_repository: ER,
         progress_reporter: P,
         face_detection_config: &FaceDetectionServerConfig,
+        max_attempts: i32,
     ) -> Self {
         let face_detection_url = format!(
             "http://{}:{}/detect-faces",
@@ -61,16 +80,27 @@ impl<PR: PhotoRepository, FR: FaceRepository, P: ProgressReporter> FaceDetection
         Self {
             photo_repository,
             face_repository,
+            photo_error_repository,
             progress_reporter,
             http_client: reqwest::blocking::Client::new(),
             face_detection_url,
+            max_attempts,
         }
     }
 
     /// Detects faces in photos that haven't been processed yet.
+    ///
+    /// Photos in which faces cannot be detected are recorded as failed and skipped until the
+    /// next run, or for good once they failed `max_attempts` times.
     pub fn detect_faces(&mut self) -> Result<usize> {
         let start = Instant::now();
+        // Errors are timestamped by the database, so its clock sets the start of the run
+        let started_at = self
+            .photo_repository
+            .now()
+            .context("Failed to fetch database time")?;
         let mut total_processed = 0usize;
+        let mut total_failed = 0usize;
 
         loop {
             let paginated_paths = self
@@ -82,6 +112,12 @@ impl<PR: PhotoRepository, FR: FaceRepository, P: ProgressReporter> FaceDetection
                     },
                     PhotoFindPathFilters {
                         has_face_detection_completed: Some(false),
+                        media_type: Some(MediaType::Photo),
+                        skip_failed: Some(SkipFailedFilter {
+                            stage: JobStage::DetectFaces,
+                            max_attempts: self.max_attempts,
+                            failed_since: started_at,
+                        }),
                         ..Default::default()
                     },
                 )
@@ -96,50 +132,95 @@ impl<PR: PhotoRepository, FR: FaceRepository, P: ProgressReporter> FaceDetection
                 total_processed, paginated_paths.total
             ));
 
+            let mut detected_ids = Vec::new();
             for photo_path in &paginated_paths.items {
-                let photo_id = photo_path.id;
-                let path = photo_path.path.clone();
-                match self.detect_faces_for_photo(&path) {
-                    Ok(detected_faces) => {
-                        for detected_face in detected_faces {
-                            let new_face =
-                                convert_detected_face_to_new_face(detected_face, photo_id);
-                            self.face_repository
-                                .insert_one(new_face)
-                                .context(format!("Failed to insert face for photo: {}", path))?;
-                        }
-
-                        self.photo_repository
-                            .update_one(
-                                photo_id,
-                                UpdatedPhoto {
-                                    face_detection_completed: Some(true),
-                                    ..Default::default()
-                                },
-                            )
-                            .context("Failed to update face detection completed status")?;
-                    }
-                    Err(_) => {
-                        // TODO: Fix infinite loop if only errors
+                let detected_faces = match self.detect_faces_for_photo(&photo_path.path) {
+                    Ok(detected_faces) => detected_faces,
+                    Err(error) => {
+                        self.photo_error_repository
+                            .record(photo_path.id, JobStage::DetectFaces, format!("{:#}", error))
+                            .context("Failed to record face detection error")?;
+                        total_failed += 1;
+                        continue;
                     }
-                }
+                };
+
+                self.save_detected_faces(photo_path.id, &photo_path.path, detected_faces)?;
+                detected_ids.push(photo_path.id);
             }
 
-            total_processed += paginated_paths.items.len();
+            total_processed += detected_ids.len();
+
+            if !detected_ids.is_empty() {
+                self.photo_error_repository
+                    .resolve(JobStage::DetectFaces, detected_ids)
+                    .context("Failed to clear face detection errors")?;
+            }
         }
 
         let duration = start.elapsed();
         self.progress_reporter.finish_with_message(format!(
-            "✓ Processed face detection for {} photos in {:.2?}",
-            total_processed, duration
+            "✓ Processed face detection for {} photos in {:.2?}, {} failed",
+            total_processed, duration, total_failed
         ));
 
         Ok(total_processed)
     }
 
+    /// Detects the faces of a single photo, marks its face detection as completed and
+    /// returns the number of detected faces.
+    pub fn detect_faces_in_photo(&mut self, photo_id: i32, path: &str) -> Result<usize> {
+        let detected_faces = self
+            .detect_faces_for_photo(path)
+            .context(format!("Failed to detect faces in photo: {}", path))?;
+
+        self.save_detected_faces(photo_id, path, detected_faces)
+    }
+
+    fn save_detected_faces(
+        &mut self,
+        photo_id: i32,
+        path: &str,
+        detected_faces: Vec<DetectedFace>,
+    ) -> Result<usize> {
+        let face_count = detected_faces.len();
+
+        for detected_face in detected_faces {
+            let new_face = convert_detected_face_to_new_face(detected_face, photo_id);
+            self.face_repository
+                .insert_one(new_face)
+                .context(format!("Failed to insert face for photo: {}", path))?;
+        }
+
+        self.photo_repository
+            .update_one(
+                photo_id,
+                UpdatedPhoto {
+                    face_detection_completed: Some(true),
+                    ..Default::default()
+                },
+            )
+            .context("Failed to update face detection completed status")?;
+
+        Ok(face_count)
+    }
+
+    /// Detects faces in a photo decoded in its displayed orientation, so that bounding
+    /// boxes match the photo as it is displayed.
     fn detect_faces_for_photo(&self, image_path: &str) -> Result<Vec<DetectedFace>> {
+        let image = imaging::load_image(image_path).context("Failed to load image")?;
+
+        let mut image_data = Vec::new();
+        image
+            .to_rgb8()
+            .write_with_encoder(JpegEncoder::new_with_quality(
+                &mut Cursor::new(&mut image_data),
+                JPEG_QUALITY,
+            ))
+            .context("Failed to encode image")?;
+
         let request = DetectFacesRequest {
-            image_path: image_path.to_string(),
+            image_data: BASE64_STANDARD.encode(image_data),
         };
 
         let response = self
@@ -187,17 +268,20 @@ fn convert_detected_face_to_new_face(detected_face: DetectedFace, photo_id: i32)
 mod tests {
     use super::*;
     use crate::{
-        models::PaginatedPhotoPaths,
+        models::{PaginatedPhotoPaths, PhotoPath},
         repositories::{
             face::repository::MockFaceRepository, photo::repository::MockPhotoRepository,
+            photo_error::repository::MockPhotoErrorRepository,
         },
         utils::progress_reporter::NoOpProgressReporter,
     };
     use anyhow::anyhow;
+    use chrono::Utc;
 
     #[test]
     fn test_detect_faces_no_photos() {
         let mut photo_repository = MockPhotoRepository::new();
+        photo_repository.expect_now().returning(|| Ok(Utc::now()));
         photo_repository
             .expect_find_path()
             .withf(|p: &PaginationFilter, f: &PhotoFindPathFilters| {
@@ -205,6 +289,7 @@ mod tests {
                     && p.per_page == 20
                     && f.has_embedding == None
                     && f.has_face_detection_completed == Some(false)
+                    && f.media_type == Some(MediaType::Photo)
             })
             .times(1)
             .returning(|_, __| {
@@ -227,8 +312,10 @@ mod tests {
         let mut face_detection_service = FaceDetectionService::new(
             photo_repository,
             face_repository,
+            MockPhotoErrorRepository::new(),
             NoOpProgressReporter,
             &face_detection_config,
+            3,
         );
         let result = face_detection_service.detect_faces();
 
@@ -239,6 +326,7 @@ mod tests {
     #[test]
     fn test_should_return_error_when_photo_repository_fails() {
         let mut photo_repository = MockPhotoRepository::new();
+        photo_repository.expect_now().returning(|| Ok(Utc::now()));
 
         photo_repository
             .expect_find_path()
@@ -247,6 +335,7 @@ mod tests {
                     && p.per_page == 20
                     && f.has_embedding == None
                     && f.has_face_detection_completed == Some(false)
+                    && f.media_type == Some(MediaType::Photo)
             })
             .times(1)
             .returning(|_, __| Err(anyhow!("Repository error")));
@@ -261,8 +350,10 @@ mod tests {
         let mut face_detection_service = FaceDetectionService::new(
             photo_repository,
             face_repository,
+            MockPhotoErrorRepository::new(),
             NoOpProgressReporter,
             &face_detection_config,
+            3,
         );
         let result = face_detection_service.detect_faces();
 
@@ -272,4 +363,76 @@ mod tests {
             "Failed to fetch photos without face detection"
         );
     }
+
+    #[test]
+    fn test_should_record_error_when_face_detection_fails() {
+        let mut photo_repository = MockPhotoRepository::new();
+        // The database clock is ahead of the client one
+        let database_now = Utc::now() + chrono::Duration::minutes(5);
+        photo_repository
+            .expect_now()
+            .returning(move || Ok(database_now));
+        let mut find_count = 0;
+
+        photo_repository
+            .expect_find_path()
+            .withf(move |_, f: &PhotoFindPathFilters| {
+                f.skip_failed.as_ref().is_some_and(|s| {
+                    s.stage == JobStage::DetectFaces
+                        && s.max_attempts == 3
+                        && s.failed_since == database_now
+                })
+            })
+            .times(2)
+            .returning(move |_, __| {
+                find_count += 1;
+                let items = if find_count == 1 {
+                    vec![PhotoPath {
+                        id: 1,
+                        path: "missing.jpg".to_string(),
+                    }]
+                } else {
+                    vec![]
+                };
+                Ok(PaginatedPhotoPaths {
+                    total: items.len() as i64,
+                    items,
+                    page: 1,
+                    per_page: 20,
+                    total_pages: 1,
+                })
+            });
+        photo_repository.expect_update_one().times(0);
+
+        let mut face_repository = MockFaceRepository::new();
+        face_repository.expect_insert_one().times(0);
+
+        let mut photo_error_repository = MockPhotoErrorRepository::new();
+        photo_error_repository
+            .expect_record()
+            .withf(|id: &i32, stage: &JobStage, error: &String| {
+                *id == 1
+                    && *stage == JobStage::DetectFaces
+                    && error.starts_with("Failed to load image")
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(1));
+        photo_error_repository.expect_resolve().times(0);
+
+        let face_detection_config = FaceDetectionServerConfig {
+            host: "127.0.0.1".to_string(),
+            port: 8080,
+        };
+        let mut face_detection_service = FaceDetectionService::new(
+            photo_repository,
+            face_repository,
+            photo_error_repository,
+            NoOpProgressReporter,
+            &face_detection_config,
+            3,
+        );
+        let result = face_detection_service.detect_faces();
+
+        assert_eq!(result.unwrap(), 0);
+    }
 }
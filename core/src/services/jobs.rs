@@ -0,0 +1,482 @@
+use std::{
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use anyhow::{Context, Result, bail};
+use rayon::prelude::*;
+
+use crate::{
+    config::JobsConfig,
+    models::{Job, JobStage, JobStageSummary, JobStatus, NewJob},
+    repositories::{JobFindFilters, JobRepository},
+    utils::progress_reporter::ProgressReporter,
+};
+
+/// Number of jobs claimed at once per concurrent job of a stage.
+const JOBS_PER_WORKER: usize = 4;
+
+/// Number of heartbeats of running jobs within the delay after which they are stale.
+const HEARTBEATS_PER_STALE_DELAY: u32 = 4;
+
+/// Runs the jobs of the pipeline stages.
+#[cfg_attr(test, mockall::automock)]
+pub trait JobHandler: Sync {
+    /// Runs a job and returns the jobs of the next stages to enqueue once it succeeded.
+    fn run(&self, job: &Job) -> Result<Vec<NewJob>>;
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct JobRunSummary {
+    pub completed: usize,
+    /// Failed jobs that will be attempted again.
+    pub retried: usize,
+    /// Failed jobs without attempt left.
+    pub failed: usize,
+}
+
+impl JobRunSummary {
+    fn total(&self) -> usize {
+        self.completed + self.retried + self.failed
+    }
+
+    fn add(&mut self, other: JobRunSummary) {
+        self.completed += other.completed;
+        self.retried += other.retried;
+        self.failed += other.failed;
+    }
+}
+
+/// Persistent queue of the indexing pipeline: scan → hash → EXIF → embed → detect faces →
+/// recognize.
+///
+/// Jobs are claimed stage by stage, in pipeline order, and the jobs of a stage run in
+/// parallel up to its configured concurrency. A successful job enqueues the jobs of the
+/// next stages, while a failing job is retried after an increasing delay until it runs
+/// out of attempts. Running jobs are refreshed by a heartbeat, so that only the jobs of
+/// an interrupted worker become stale and are run again.
+pub struct JobService<JR: JobRepository, P: ProgressReporter> {
+    job_repository: JR,
+    progress_reporter: P,
+    config: JobsConfig,
+}
+
+impl<JR: JobRepository, P: ProgressReporter> JobService<JR, P> {
+    pub fn new(job_repository: JR, progress_reporter: P, config: &JobsConfig) -> Self {
+        Self {
+            job_repository,
+            progress_reporter,
+            config: config.clone(),
+        }
+    }
+
+    /// Enqueues jobs and returns the number of enqueued jobs.
+    pub fn enqueue(&mut self, new_jobs: Vec<NewJob>) -> Result<usize> {
+        if new_jobs.is_empty() {
+            return Ok(0);
+        }
+
+        self.job_repository
+            .enqueue(new_jobs)
+            .context("Failed to enqueue jobs")
+    }
+
+    /// Counts the jobs of every stage by status, in pipeline order.
+    pub fn summarize(&mut self) -> Result<Vec<JobStageSummary>> {
+        let counts = self
+            .job_repository
+            .count_by_status()
+            .context("Failed to count jobs")?;
+
+        Ok(JobStage::ALL
+            .iter()
+            .map(|&stage| {
+                let count = |status: JobStatus| {
+                    counts
+                        .iter()
+                        .filter(|(s, st, _)| *s == stage && *st == status)
+                        .map(|(_, _, count)| count)
+                        .sum()
+                };
+
+                JobStageSummary {
+                    stage,
+                    pending: count(JobStatus::Pending),
+                    running: count(JobStatus::Running),
+                    done: count(JobStatus::Done),
+                    failed: count(JobStatus::Failed),
+                }
+            })
+            .collect())
+    }
+
+    /// Lists jobs matching the filters, most recently updated first.
+    pub fn list(&mut self, filters: JobFindFilters, limit: i64) -> Result<Vec<Job>> {
+        self.job_repository
+            .find_many(filters, limit)
+            .context("Failed to list jobs")
+    }
+
+    /// Makes failed jobs pending again, with all their attempts, and returns their number.
+    pub fn retry_failed(&mut self, stage: Option<JobStage>) -> Result<usize> {
+        self.job_repository
+            .retry_failed(stage)
+            .context("Failed to retry failed jobs")
+    }
+
+    /// Runs jobs until none is due when `until_idle` is set, or forever, waiting for new
+    /// jobs between runs.
+    pub fn run<H: JobHandler>(&mut self, handler: &H, until_idle: bool) -> Result<JobRunSummary> {
+        let requeued = self
+            .job_repository
+            .requeue_stale(Duration::from_secs(self.config.stale_after))
+            .context("Failed to requeue stale jobs")?;
+        if requeued > 0 {
+            self.progress_reporter
+                .set_message(format!("Requeued {} interrupted jobs", requeued));
+        }
+
+        let mut summary = JobRunSummary::default();
+
+        loop {
+            let run_summary = self.run_due(handler)?;
+            let is_idle = run_summary.total() == 0;
+            summary.add(run_summary);
+
+            if is_idle {
+                if until_idle {
+                    break;
+                }
+                self.progress_reporter.set_message(format!(
+                    "Waiting for jobs: {} completed, {} retried, {} failed",
+                    summary.completed, summary.retried, summary.failed
+                ));
+                thread::sleep(Duration::from_secs(self.config.poll_interval));
+            }
+        }
+
+        self.progress_reporter.finish_with_message(format!(
+            "✓ Ran {} jobs: {} completed, {} retried, {} failed",
+            summary.total(),
+            summary.completed,
+            summary.retried,
+            summary.failed
+        ));
+
+        Ok(summary)
+    }
+
+    /// Claims and runs the due jobs of every stage once, in pipeline order.
+    pub fn run_due<H: JobHandler>(&mut self, handler: &H) -> Result<JobRunSummary> {
+        let mut summary = JobRunSummary::default();
+
+        for (index, &stage) in JobStage::ALL.iter().enumerate() {
+            // Recognition clusters all faces, so it waits for every photo to go through
+            // the earlier stages instead of running after each detection
+            if stage == JobStage::Recognize
+                && self
+                    .job_repository
+                    .count_active(JobStage::ALL[..index].to_vec())
+                    .context("Failed to count active jobs")?
+                    > 0
+            {
+                continue;
+            }
+
+            summary.add(self.run_stage(stage, handler)?);
+        }
+
+        Ok(summary)
+    }
+
+    fn run_stage<H: JobHandler>(&mut self, stage: JobStage, handler: &H) -> Result<JobRunSummary> {
+        let concurrency = self.config.concurrency.get(stage);
+        let jobs = self
+            .job_repository
+            .claim(stage, (concurrency * JOBS_PER_WORKER) as i64)
+            .context(format!("Failed to claim {} jobs", stage))?;
+
+        let mut summary = JobRunSummary::default();
+        if jobs.is_empty() {
+            return Ok(summary);
+        }
+
+        self.progress_reporter
+            .set_message(format!("Running {} {} jobs", jobs.len(), stage));
+
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(concurrency)
+            .build()
+            .context("Failed to create job thread pool")?;
+        let results = self.run_with_heartbeat(&jobs, || {
+            thread_pool.install(|| jobs.par_iter().map(|job| handler.run(job)).collect())
+        })?;
+
+        for (job, result) in jobs.iter().zip(results) {
+            match result {
+                Ok(next_jobs) => {
+                    self.enqueue(next_jobs)?;
+                    self.job_repository
+                        .complete(job.id)
+                        .context("Failed to complete job")?;
+                    summary.completed += 1;
+                }
+                Err(err) => {
+                    let error = format!("{:#}", err);
+                    self.progress_reporter
+                        .report_error(format!("{} job {} failed: {}", stage, job.id, error));
+
+                    let retry_after = (job.attempts < self.config.max_attempts).then(|| {
+                        Duration::from_secs(
+                            self.config.retry_delay << (job.attempts - 1).clamp(0, 10),
+                        )
+                    });
+                    if retry_after.is_some() {
+                        summary.retried += 1;
+                    } else {
+                        summary.failed += 1;
+                    }
+
+                    self.job_repository
+                        .fail(job.id, error, retry_after)
+                        .context("Failed to record job error")?;
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Runs claimed jobs on another thread, refreshing them until they are all done.
+    fn run_with_heartbeat<F>(&mut self, jobs: &[Job], run: F) -> Result<Vec<Result<Vec<NewJob>>>>
+    where
+        F: FnOnce() -> Vec<Result<Vec<NewJob>>> + Send,
+    {
+        let ids: Vec<i32> = jobs.iter().map(|job| job.id).collect();
+        let interval = Duration::from_secs(self.config.stale_after) / HEARTBEATS_PER_STALE_DELAY;
+
+        thread::scope(|scope| {
+            let (sender, receiver) = mpsc::channel();
+            scope.spawn(move || sender.send(run()));
+
+            loop {
+                match receiver.recv_timeout(interval) {
+                    Ok(results) => return Ok(results),
+                    Err(RecvTimeoutError::Timeout) => self
+                        .job_repository
+                        .heartbeat(ids.clone())
+                        .context("Failed to refresh running jobs")?,
+                    Err(RecvTimeoutError::Disconnected) => bail!("Jobs stopped without a result"),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::JobConcurrencyConfig, repositories::job::repository::MockJobRepository,
+        utils::progress_reporter::NoOpProgressReporter,
+    };
+    use anyhow::anyhow;
+    use chrono::Utc;
+    use mockall::predicate::eq;
+
+    use super::*;
+
+    fn config() -> JobsConfig {
+        JobsConfig {
+            max_attempts: 2,
+            retry_delay: 30,
+            poll_interval: 0,
+            stale_after: 3600,
+            concurrency: JobConcurrencyConfig {
+                scan: 1,
+                hash: 2,
+                exif: 2,
+                embed: 1,
+                detect_faces: 1,
+                recognize: 1,
+            },
+        }
+    }
+
+    fn job(id: i32, stage: JobStage, attempts: i32) -> Job {
+        Job {
+            id,
+            stage,
+            library_id: None,
+            photo_id: Some(id),
+            status: JobStatus::Running,
+            attempts,
+            last_error: None,
+            run_after: Utc::now(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_should_run_jobs_and_enqueue_next_stages() {
+        let mut repository = MockJobRepository::new();
+        repository
+            .expect_claim()
+            .returning(|stage, limit| match stage {
+                JobStage::Hash => {
+                    assert_eq!(limit, 8);
+                    Ok(vec![job(1, JobStage::Hash, 1), job(2, JobStage::Hash, 1)])
+                }
+                _ => Ok(vec![]),
+            });
+        repository
+            .expect_enqueue()
+            .with(eq(vec![NewJob::photo(JobStage::Exif, 1)]))
+            .times(1)
+            .returning(|jobs| Ok(jobs.len()));
+        repository
+            .expect_complete()
+            .with(eq(1))
+            .times(1)
+            .returning(|_| Ok(()));
+        repository
+            .expect_fail()
+            .withf(|id, error, retry_after| {
+                *id == 2
+                    && error == "unreadable file"
+                    && *retry_after == Some(Duration::from_secs(30))
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        repository.expect_count_active().returning(|_| Ok(1));
+
+        let mut handler = MockJobHandler::new();
+        handler.expect_run().returning(|job| match job.id {
+            1 => Ok(vec![NewJob::photo(JobStage::Exif, 1)]),
+            _ => Err(anyhow!("unreadable file")),
+        });
+
+        let mut service = JobService::new(repository, NoOpProgressReporter, &config());
+        let summary = service.run_due(&handler).unwrap();
+
+        assert_eq!(
+            summary,
+            JobRunSummary {
+                completed: 1,
+                retried: 1,
+                failed: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_should_fail_jobs_without_attempt_left() {
+        let mut repository = MockJobRepository::new();
+        repository.expect_claim().returning(|stage, _| match stage {
+            JobStage::Embed => Ok(vec![job(1, JobStage::Embed, 2)]),
+            _ => Ok(vec![]),
+        });
+        repository
+            .expect_fail()
+            .withf(|id, _, retry_after| *id == 1 && retry_after.is_none())
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        repository.expect_count_active().returning(|_| Ok(0));
+
+        let mut handler = MockJobHandler::new();
+        handler
+            .expect_run()
+            .returning(|_| Err(anyhow!("decoding failed")));
+
+        let mut service = JobService::new(repository, NoOpProgressReporter, &config());
+        let summary = service.run_due(&handler).unwrap();
+
+        assert_eq!(summary.failed, 1);
+    }
+
+    #[test]
+    fn test_should_refresh_jobs_while_they_run() {
+        let mut repository = MockJobRepository::new();
+        repository.expect_claim().returning(|stage, _| match stage {
+            JobStage::Embed => Ok(vec![job(1, JobStage::Embed, 1)]),
+            _ => Ok(vec![]),
+        });
+        repository
+            .expect_heartbeat()
+            .with(eq(vec![1]))
+            .times(2..)
+            .returning(|_| Ok(()));
+        repository
+            .expect_complete()
+            .with(eq(1))
+            .times(1)
+            .returning(|_| Ok(()));
+        repository.expect_count_active().returning(|_| Ok(0));
+
+        let mut handler = MockJobHandler::new();
+        handler.expect_run().returning(|_| {
+            thread::sleep(Duration::from_millis(700));
+            Ok(vec![])
+        });
+
+        // Jobs become stale after a second, so the heartbeat runs every 250ms
+        let config = JobsConfig {
+            stale_after: 1,
+            ..config()
+        };
+        let mut service = JobService::new(repository, NoOpProgressReporter, &config);
+        let summary = service.run_due(&handler).unwrap();
+
+        assert_eq!(summary.completed, 1);
+    }
+
+    #[test]
+    fn test_should_wait_for_earlier_stages_before_recognizing_faces() {
+        let mut repository = MockJobRepository::new();
+        repository
+            .expect_claim()
+            .withf(|stage, _| *stage != JobStage::Recognize)
+            .returning(|_, _| Ok(vec![]));
+        repository
+            .expect_count_active()
+            .withf(|stages| stages.len() == 5 && !stages.contains(&JobStage::Recognize))
+            .times(1)
+            .returning(|_| Ok(3));
+
+        let handler = MockJobHandler::new();
+
+        let mut service = JobService::new(repository, NoOpProgressReporter, &config());
+        let summary = service.run_due(&handler).unwrap();
+
+        assert_eq!(summary, JobRunSummary::default());
+    }
+
+    #[test]
+    fn test_should_summarize_jobs_of_every_stage() {
+        let mut repository = MockJobRepository::new();
+        repository.expect_count_by_status().returning(|| {
+            Ok(vec![
+                (JobStage::Embed, JobStatus::Pending, 3),
+                (JobStage::Embed, JobStatus::Done, 5),
+                (JobStage::Scan, JobStatus::Failed, 1),
+            ])
+        });
+
+        let mut service = JobService::new(repository, NoOpProgressReporter, &config());
+        let summaries = service.summarize().unwrap();
+
+        assert_eq!(summaries.len(), JobStage::ALL.len());
+        assert_eq!(summaries[0].failed, 1);
+        assert_eq!(
+            summaries[3],
+            JobStageSummary {
+                stage: JobStage::Embed,
+                pending: 3,
+                running: 0,
+                done: 5,
+                failed: 0,
+            }
+        );
+    }
+}
@@ -0,0 +1,333 @@
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow, bail};
+
+use crate::{
+    models::{Library, NewLibrary, UpdatedLibrary},
+    repositories::LibraryRepository,
+};
+
+/// Manages the library roots photos are indexed from.
+///
+/// Photos of a library store their path relative to the library root, so a library
+/// can be relocated to another mount point without being scanned again.
+pub struct LibraryService<LR: LibraryRepository> {
+    library_repository: LR,
+}
+
+impl<LR: LibraryRepository> LibraryService<LR> {
+    pub fn new(library_repository: LR) -> Self {
+        Self { library_repository }
+    }
+
+    /// Adds a library, rejecting roots that overlap the root of another library.
+    pub fn add(&mut self, mut new_library: NewLibrary) -> Result<Library> {
+        new_library.root_path = normalize_root(&new_library.root_path)?;
+
+        self.check_overlap(&new_library.root_path, None)?;
+
+        self.library_repository
+            .insert_one(new_library)
+            .context("Failed to create library")
+    }
+
+    pub fn list(&mut self) -> Result<Vec<Library>> {
+        self.library_repository
+            .find_many()
+            .context("Failed to retrieve libraries")
+    }
+
+    pub fn get(&mut self, name: &str) -> Result<Library> {
+        self.library_repository
+            .find_by_name(name)
+            .context("Failed to retrieve library")?
+            .ok_or_else(|| anyhow!("Library not found: {}", name))
+    }
+
+    /// Finds the library whose root contains the given path.
+    pub fn find_for_path(&mut self, path: &str) -> Result<Option<Library>> {
+        let path = Path::new(path)
+            .canonicalize()
+            .context(format!("Failed to resolve path: {}", path))?;
+
+        Ok(self
+            .list()?
+            .into_iter()
+            .find(|library| path.starts_with(&library.root_path)))
+    }
+
+    /// Moves a library to a new root, rewriting the paths of its photos without scanning them.
+    ///
+    /// As when adding a library, the new root cannot overlap the root of another library.
+    pub fn relocate(&mut self, name: &str, root_path: &str) -> Result<Library> {
+        let library = self.get(name)?;
+        let root_path = normalize_root(root_path)?;
+        self.check_overlap(&root_path, Some(library.id))?;
+
+        self.library_repository
+            .update_one(
+                library.id,
+                UpdatedLibrary {
+                    root_path: Some(root_path),
+                    ..Default::default()
+                },
+            )
+            .context("Failed to relocate library")
+    }
+
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> Result<Library> {
+        let library = self.get(name)?;
+
+        self.library_repository
+            .update_one(
+                library.id,
+                UpdatedLibrary {
+                    enabled: Some(enabled),
+                    ..Default::default()
+                },
+            )
+            .context("Failed to update library")
+    }
+
+    /// Opts a library in or out of writing metadata back to XMP sidecars.
+    pub fn set_write_xmp(&mut self, name: &str, write_xmp: bool) -> Result<Library> {
+        let library = self.get(name)?;
+
+        self.library_repository
+            .update_one(
+                library.id,
+                UpdatedLibrary {
+                    write_xmp: Some(write_xmp),
+                    ..Default::default()
+                },
+            )
+            .context("Failed to update library")
+    }
+
+    /// Removes a library and its photos from the index. Files are left untouched.
+    pub fn remove(&mut self, name: &str) -> Result<Library> {
+        let library = self.get(name)?;
+
+        self.library_repository
+            .delete_one(library.id)
+            .context("Failed to remove library")?;
+
+        Ok(library)
+    }
+
+    /// Fails when a root contains or is contained by the root of another library than the
+    /// excluded one.
+    fn check_overlap(&mut self, root_path: &str, excluded_id: Option<i32>) -> Result<()> {
+        let new_root = Path::new(root_path);
+
+        if let Some(library) = self.list()?.iter().find(|library| {
+            let root = Path::new(&library.root_path);
+            Some(library.id) != excluded_id
+                && (root.starts_with(new_root) || new_root.starts_with(root))
+        }) {
+            bail!(
+                "Library root {} overlaps library {} at {}",
+                root_path,
+                library.name,
+                library.root_path
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Resolves a library root to an absolute path without trailing separator.
+fn normalize_root(root_path: &str) -> Result<String> {
+    let path = Path::new(root_path);
+    if !path.is_dir() {
+        bail!("Library root is not a directory: {}", root_path);
+    }
+
+    let path = path
+        .canonicalize()
+        .context(format!("Failed to resolve library root: {}", root_path))?;
+
+    Ok(path.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::repositories::library::repository::MockLibraryRepository;
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn library(id: i32, name: &str, root_path: &str) -> Library {
+        Library {
+            id,
+            name: name.to_string(),
+            root_path: root_path.to_string(),
+            enabled: true,
+            with_exif: false,
+            with_hash: false,
+            include: vec![],
+            exclude: vec![],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            write_xmp: false,
+            prune: false,
+        }
+    }
+
+    #[test]
+    fn test_should_add_library_with_absolute_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let root_path = temp_dir.path().canonicalize().unwrap();
+        let expected_root = root_path.to_string_lossy().into_owned();
+
+        let mut repository = MockLibraryRepository::new();
+        repository.expect_find_many().returning(|| Ok(vec![]));
+        repository
+            .expect_insert_one()
+            .withf(move |new_library| new_library.root_path == expected_root)
+            .times(1)
+            .returning(|new_library| Ok(library(1, &new_library.name, &new_library.root_path)));
+
+        let mut service = LibraryService::new(repository);
+        let result = service.add(NewLibrary {
+            name: "photos".to_string(),
+            root_path: format!("{}/", root_path.join(".").display()),
+            ..Default::default()
+        });
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_should_not_add_library_inside_another_library() {
+        let temp_dir = TempDir::new().unwrap();
+        let root_path = temp_dir.path().canonicalize().unwrap();
+        std::fs::create_dir(root_path.join("sub")).unwrap();
+        let existing_root = root_path.to_string_lossy().into_owned();
+
+        let mut repository = MockLibraryRepository::new();
+        repository
+            .expect_find_many()
+            .returning(move || Ok(vec![library(1, "photos", &existing_root)]));
+        repository.expect_insert_one().times(0);
+
+        let mut service = LibraryService::new(repository);
+        let result = service.add(NewLibrary {
+            name: "sub".to_string(),
+            root_path: root_path.join("sub").to_string_lossy().into_owned(),
+            ..Default::default()
+        });
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_should_relocate_library() {
+        let temp_dir = TempDir::new().unwrap();
+        let new_root = temp_dir.path().canonicalize().unwrap();
+        let expected_root = new_root.to_string_lossy().into_owned();
+
+        let mut repository = MockLibraryRepository::new();
+        repository
+            .expect_find_by_name()
+            .returning(|name| Ok(Some(library(1, name, "/mnt/old"))));
+        repository
+            .expect_find_many()
+            .returning(|| Ok(vec![library(1, "photos", "/mnt/old")]));
+        repository
+            .expect_update_one()
+            .withf(move |id, updated_library| {
+                *id == 1
+                    && updated_library.root_path.as_deref() == Some(expected_root.as_str())
+                    && updated_library.enabled.is_none()
+            })
+            .times(1)
+            .returning(|_, updated_library| {
+                Ok(library(1, "photos", &updated_library.root_path.unwrap()))
+            });
+
+        let mut service = LibraryService::new(repository);
+        let library = service
+            .relocate("photos", new_root.to_str().unwrap())
+            .unwrap();
+
+        assert_eq!(library.root_path, new_root.to_string_lossy());
+    }
+
+    #[test]
+    fn test_should_not_relocate_library_into_another_library() {
+        let temp_dir = TempDir::new().unwrap();
+        let root_path = temp_dir.path().canonicalize().unwrap();
+        std::fs::create_dir(root_path.join("sub")).unwrap();
+        let other_root = root_path.to_string_lossy().into_owned();
+
+        let mut repository = MockLibraryRepository::new();
+        repository
+            .expect_find_by_name()
+            .returning(|name| Ok(Some(library(1, name, "/mnt/old"))));
+        repository.expect_find_many().returning(move || {
+            Ok(vec![
+                library(1, "photos", "/mnt/old"),
+                library(2, "other", &other_root),
+            ])
+        });
+        repository.expect_update_one().times(0);
+
+        let mut service = LibraryService::new(repository);
+
+        assert!(
+            service
+                .relocate("photos", root_path.join("sub").to_str().unwrap())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_should_not_relocate_library_to_missing_root() {
+        let mut repository = MockLibraryRepository::new();
+        repository
+            .expect_find_by_name()
+            .returning(|name| Ok(Some(library(1, name, "/mnt/old"))));
+        repository.expect_update_one().times(0);
+
+        let mut service = LibraryService::new(repository);
+
+        assert!(service.relocate("photos", "/does/not/exist").is_err());
+    }
+
+    #[test]
+    fn test_should_find_library_containing_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let root_path = temp_dir.path().canonicalize().unwrap();
+        std::fs::create_dir(root_path.join("2024")).unwrap();
+        let library_root = root_path.to_string_lossy().into_owned();
+
+        let mut repository = MockLibraryRepository::new();
+        repository.expect_find_many().returning(move || {
+            Ok(vec![
+                library(1, "other", "/mnt/other"),
+                library(2, "photos", &library_root),
+            ])
+        });
+
+        let mut service = LibraryService::new(repository);
+        let library = service
+            .find_for_path(root_path.join("2024").to_str().unwrap())
+            .unwrap();
+
+        assert_eq!(library.map(|library| library.id), Some(2));
+    }
+
+    #[test]
+    fn test_should_return_error_when_library_does_not_exist() {
+        let mut repository = MockLibraryRepository::new();
+        repository.expect_find_by_name().returning(|_| Ok(None));
+        repository.expect_delete_one().times(0);
+
+        let mut service = LibraryService::new(repository);
+
+        assert!(service.remove("photos").is_err());
+    }
+}
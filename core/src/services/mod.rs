@@ -1,15 +1,34 @@
+pub mod duplicates;
 pub mod embedders;
 pub mod face;
 pub mod face_detection;
 pub mod face_recognition;
+pub mod jobs;
+pub mod library;
+pub mod near_duplicates;
 pub mod person;
 pub mod photo_embedder;
+pub mod photo_errors;
+pub mod photo_import;
 pub mod photo_scanner;
 pub mod photo_search;
+pub mod photo_watcher;
+pub mod pipeline;
+pub mod thumbnails;
+pub mod xmp_export;
 
+pub use duplicates::DuplicateService;
 pub use face::FaceService;
 pub use face_detection::FaceDetectionService;
 pub use face_recognition::FaceRecognitionService;
+pub use jobs::JobService;
+pub use library::LibraryService;
+pub use near_duplicates::NearDuplicateService;
 pub use person::PersonService;
 pub use photo_embedder::PhotoEmbedderService;
-pub use photo_search::{PhotoSearchParams, PhotoSearchService};
+pub use photo_errors::PhotoErrorService;
+pub use photo_search::{PhotoSearchParams, PhotoSearchService, SimilarTo};
+pub use photo_watcher::PhotoWatcher;
+pub use pipeline::PipelineJobHandler;
+pub use thumbnails::ThumbnailService;
+pub use xmp_export::XmpExportService;
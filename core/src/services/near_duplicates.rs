@@ -0,0 +1,474 @@
+use std::{cmp::Ordering, collections::HashMap, fmt};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    models::SimilarityCandidate, repositories::PhotoRepository,
+    utils::perceptual_hash::hamming_distance,
+};
+
+/// Number of photos loaded, or pairs of photos compared, per query.
+const BATCH_SIZE: usize = 10_000;
+
+#[derive(Debug, Clone)]
+pub struct NearDuplicateConfig {
+    /// Maximum number of differing bits between the perceptual hashes of two copies.
+    pub max_hash_distance: u32,
+    /// Minimum cosine similarity between the embeddings of two copies, when both have one.
+    pub min_similarity: f32,
+    /// Maximum delay between two consecutive shots of a burst, in seconds.
+    pub burst_interval: i64,
+    /// Minimum cosine similarity between the embeddings of two consecutive shots of a burst.
+    pub min_burst_similarity: f32,
+}
+
+impl Default for NearDuplicateConfig {
+    fn default() -> Self {
+        Self {
+            max_hash_distance: 6,
+            min_similarity: 0.92,
+            burst_interval: 2,
+            min_burst_similarity: 0.85,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClusterKind {
+    /// Resized, re-encoded or slightly edited copies of the same photo.
+    NearDuplicate,
+    /// Similar shots taken in quick succession.
+    Burst,
+}
+
+impl fmt::Display for ClusterKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ClusterKind::NearDuplicate => "near-duplicate",
+            ClusterKind::Burst => "burst",
+        })
+    }
+}
+
+/// Visually similar photos, with the shot suggested to keep.
+#[derive(Debug, Clone)]
+pub struct NearDuplicateCluster {
+    pub kind: ClusterKind,
+    pub best_shot: SimilarityCandidate,
+    pub others: Vec<SimilarityCandidate>,
+}
+
+/// Finds near-duplicates and bursts, which do not share a file hash, from perceptual
+/// hashes, the embeddings of a model and capture times.
+pub struct NearDuplicateService<PR: PhotoRepository> {
+    photo_repository: PR,
+    embedding_model: String,
+    config: NearDuplicateConfig,
+}
+
+impl<PR: PhotoRepository> NearDuplicateService<PR> {
+    pub fn new(
+        photo_repository: PR,
+        embedding_model: &str,
+        config: Option<NearDuplicateConfig>,
+    ) -> Self {
+        Self {
+            photo_repository,
+            embedding_model: embedding_model.to_string(),
+            config: config.unwrap_or_default(),
+        }
+    }
+
+    /// Clusters similar photos, ordered by the capture time of their first shot.
+    pub fn find_clusters(&mut self) -> Result<Vec<NearDuplicateCluster>> {
+        let candidates = self.find_candidates()?;
+
+        let hash_distance = |a: usize, b: usize| -> Option<u32> {
+            Some(hamming_distance(
+                candidates[a].perceptual_hash?,
+                candidates[b].perceptual_hash?,
+            ))
+        };
+
+        let hash_pairs: Vec<(usize, usize)> = self
+            .find_hash_neighbors(&candidates)
+            .into_iter()
+            .filter(|&(a, b)| {
+                hash_distance(a, b)
+                    .is_some_and(|distance| distance <= self.config.max_hash_distance)
+            })
+            .collect();
+
+        let mut by_capture_time: Vec<usize> = (0..candidates.len())
+            .filter(|&index| candidates[index].date_taken_utc.is_some())
+            .collect();
+        by_capture_time.sort_by_key(|&index| candidates[index].date_taken_utc);
+
+        let burst_pairs: Vec<(usize, usize)> = by_capture_time
+            .windows(2)
+            .map(|pair| (pair[0], pair[1]))
+            .filter(|&(a, b)| {
+                let interval =
+                    candidates[b].date_taken_utc.unwrap() - candidates[a].date_taken_utc.unwrap();
+                interval.num_seconds() <= self.config.burst_interval
+            })
+            .collect();
+
+        let similarities =
+            self.find_similarities(&candidates, hash_pairs.iter().chain(&burst_pairs))?;
+        let similarity = |a: usize, b: usize| similarities.get(&(a.min(b), a.max(b))).copied();
+
+        let mut clusters = UnionFind::new(candidates.len());
+        let mut is_burst = vec![false; candidates.len()];
+
+        for (a, b) in hash_pairs {
+            if similarity(a, b).is_none_or(|similarity| similarity >= self.config.min_similarity) {
+                clusters.union(a, b);
+            }
+        }
+
+        for (a, b) in burst_pairs {
+            let is_similar = match similarity(a, b) {
+                Some(similarity) => similarity >= self.config.min_burst_similarity,
+                None => hash_distance(a, b)
+                    .is_some_and(|distance| distance <= self.config.max_hash_distance),
+            };
+            if is_similar {
+                clusters.union(a, b);
+                is_burst[a] = true;
+                is_burst[b] = true;
+            }
+        }
+
+        let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+        for index in 0..candidates.len() {
+            members.entry(clusters.find(index)).or_default().push(index);
+        }
+
+        let mut groups: Vec<Vec<usize>> = members
+            .into_values()
+            .filter(|indices| indices.len() > 1)
+            .collect();
+        groups.sort_by_key(|indices| indices[0]);
+
+        Ok(groups
+            .into_iter()
+            .map(|indices| {
+                let kind = if indices.iter().any(|&index| is_burst[index]) {
+                    ClusterKind::Burst
+                } else {
+                    ClusterKind::NearDuplicate
+                };
+
+                let mut photos: Vec<SimilarityCandidate> = indices
+                    .into_iter()
+                    .map(|index| candidates[index].clone())
+                    .collect();
+                let best_index = (0..photos.len())
+                    .min_by(|&a, &b| compare_shots(&photos[a], &photos[b]))
+                    .unwrap();
+                let best_shot = photos.remove(best_index);
+
+                NearDuplicateCluster {
+                    kind,
+                    best_shot,
+                    others: photos,
+                }
+            })
+            .collect())
+    }
+
+    /// Loads the photos to compare in batches, ordered by capture time.
+    fn find_candidates(&mut self) -> Result<Vec<SimilarityCandidate>> {
+        let mut candidates: Vec<SimilarityCandidate> = Vec::new();
+
+        loop {
+            let batch = self
+                .photo_repository
+                .find_similarity_candidates(
+                    self.embedding_model.clone(),
+                    candidates.last().map(|candidate| candidate.id),
+                    BATCH_SIZE as i64,
+                )
+                .context("Failed to retrieve photos to compare")?;
+            let is_last = batch.len() < BATCH_SIZE;
+            candidates.extend(batch);
+            if is_last {
+                break;
+            }
+        }
+
+        candidates.sort_by_key(|candidate| {
+            (
+                candidate.date_taken_utc.is_none(),
+                candidate.date_taken_utc,
+                candidate.id,
+            )
+        });
+        Ok(candidates)
+    }
+
+    /// Returns the similarity between the embeddings of the given pairs of photos, keyed by
+    /// their ordered indices, when both have one.
+    ///
+    /// Similarities are computed by the database in batches of pairs, so that embeddings
+    /// are never loaded.
+    fn find_similarities<'a>(
+        &mut self,
+        candidates: &[SimilarityCandidate],
+        pairs: impl Iterator<Item = &'a (usize, usize)>,
+    ) -> Result<HashMap<(usize, usize), f32>> {
+        let mut pairs: Vec<(usize, usize)> = pairs
+            .filter(|&&(a, b)| candidates[a].has_embedding && candidates[b].has_embedding)
+            .map(|&(a, b)| (a.min(b), a.max(b)))
+            .collect();
+        pairs.sort_unstable();
+        pairs.dedup();
+
+        let indices: HashMap<i32, usize> = candidates
+            .iter()
+            .enumerate()
+            .map(|(index, candidate)| (candidate.id, index))
+            .collect();
+
+        let mut similarities = HashMap::new();
+        for batch in pairs.chunks(BATCH_SIZE) {
+            let ids = batch
+                .iter()
+                .map(|&(a, b)| (candidates[a].id, candidates[b].id))
+                .collect();
+            let batch_similarities = self
+                .photo_repository
+                .find_embedding_similarities(self.embedding_model.clone(), ids)
+                .context("Failed to compare photo embeddings")?;
+
+            // The similarity with an empty embedding is undefined
+            for similarity in batch_similarities
+                .into_iter()
+                .filter(|similarity| !similarity.similarity.is_nan())
+            {
+                similarities.insert(
+                    (
+                        indices[&similarity.photo_id],
+                        indices[&similarity.other_photo_id],
+                    ),
+                    similarity.similarity,
+                );
+            }
+        }
+
+        Ok(similarities)
+    }
+
+    /// Returns the pairs of photos that may be within the maximum hash distance.
+    ///
+    /// Hashes are split into one more chunk than the maximum distance, so that close
+    /// hashes share at least one identical chunk and only photos sharing a chunk are
+    /// compared.
+    fn find_hash_neighbors(&self, candidates: &[SimilarityCandidate]) -> Vec<(usize, usize)> {
+        let chunk_count = (self.config.max_hash_distance + 1).min(64);
+        let mut buckets: HashMap<(u32, u64), Vec<usize>> = HashMap::new();
+
+        for (index, candidate) in candidates.iter().enumerate() {
+            let Some(hash) = candidate.perceptual_hash else {
+                continue;
+            };
+            for chunk in 0..chunk_count {
+                let start = chunk * 64 / chunk_count;
+                let end = (chunk + 1) * 64 / chunk_count;
+                let mask = u64::MAX >> (64 - (end - start));
+                let value = ((hash as u64) >> start) & mask;
+                buckets.entry((chunk, value)).or_default().push(index);
+            }
+        }
+
+        let mut pairs = Vec::new();
+        for indices in buckets.values() {
+            for (position, &a) in indices.iter().enumerate() {
+                for &b in &indices[position + 1..] {
+                    pairs.push((a, b));
+                }
+            }
+        }
+        pairs.sort_unstable();
+        pairs.dedup();
+        pairs
+    }
+}
+
+/// Orders shots from the best to the worst: rated higher, then with more pixels, then
+/// larger, as blurry shots compress better at the same resolution.
+fn compare_shots(a: &SimilarityCandidate, b: &SimilarityCandidate) -> Ordering {
+    let pixels = |photo: &SimilarityCandidate| {
+        photo.image_width.unwrap_or(0) as i64 * photo.image_height.unwrap_or(0) as i64
+    };
+
+    b.rating
+        .unwrap_or(0)
+        .cmp(&a.rating.unwrap_or(0))
+        .then_with(|| pixels(b).cmp(&pixels(a)))
+        .then_with(|| b.file_size.cmp(&a.file_size))
+        .then_with(|| a.id.cmp(&b.id))
+}
+
+struct UnionFind {
+    parents: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(size: usize) -> Self {
+        Self {
+            parents: (0..size).collect(),
+        }
+    }
+
+    fn find(&mut self, index: usize) -> usize {
+        let parent = self.parents[index];
+        if parent == index {
+            return index;
+        }
+        let root = self.find(parent);
+        self.parents[index] = root;
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        if root_a != root_b {
+            self.parents[root_a.max(root_b)] = root_a.min(root_b);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::EmbeddingSimilarity, repositories::photo::repository::MockPhotoRepository,
+    };
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    fn candidate(
+        id: i32,
+        perceptual_hash: Option<i64>,
+        embedding: Option<Vec<f32>>,
+        second: Option<u32>,
+    ) -> (SimilarityCandidate, Option<Vec<f32>>) {
+        let candidate = SimilarityCandidate {
+            id,
+            path: format!("photo_{}.jpg", id),
+            file_size: 1000,
+            perceptual_hash,
+            has_embedding: embedding.is_some(),
+            date_taken_utc: second
+                .map(|second| Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, second).unwrap()),
+            ..Default::default()
+        };
+        (candidate, embedding)
+    }
+
+    fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+        let dot = |a: &[f32], b: &[f32]| -> f32 { a.iter().zip(b).map(|(a, b)| a * b).sum() };
+        dot(a, b) / (dot(a, a).sqrt() * dot(b, b).sqrt())
+    }
+
+    fn service(
+        photos: Vec<(SimilarityCandidate, Option<Vec<f32>>)>,
+    ) -> NearDuplicateService<MockPhotoRepository> {
+        let (candidates, embeddings): (Vec<SimilarityCandidate>, Vec<Option<Vec<f32>>>) =
+            photos.into_iter().unzip();
+        let embeddings: HashMap<i32, Vec<f32>> = candidates
+            .iter()
+            .zip(embeddings)
+            .filter_map(|(candidate, embedding)| Some((candidate.id, embedding?)))
+            .collect();
+
+        let mut repository = MockPhotoRepository::new();
+        repository
+            .expect_find_similarity_candidates()
+            .withf(|model: &String, _, _| model == "clip-vit-base-patch32")
+            .returning(move |_, after_id, limit| {
+                Ok(candidates
+                    .iter()
+                    .filter(|candidate| candidate.id > after_id.unwrap_or(0))
+                    .take(limit as usize)
+                    .cloned()
+                    .collect())
+            });
+        repository
+            .expect_find_embedding_similarities()
+            .withf(|model: &String, _| model == "clip-vit-base-patch32")
+            .returning(move |_, pairs| {
+                Ok(pairs
+                    .into_iter()
+                    .filter_map(|(photo_id, other_photo_id)| {
+                        Some(EmbeddingSimilarity {
+                            photo_id,
+                            other_photo_id,
+                            similarity: cosine_similarity(
+                                embeddings.get(&photo_id)?,
+                                embeddings.get(&other_photo_id)?,
+                            ),
+                        })
+                    })
+                    .collect())
+            });
+        NearDuplicateService::new(repository, "clip-vit-base-patch32", None)
+    }
+
+    #[test]
+    fn test_should_cluster_near_duplicates_by_perceptual_hash() {
+        let mut resized = candidate(2, Some(0b1011), None, None);
+        resized.0.image_width = Some(4000);
+        resized.0.image_height = Some(3000);
+
+        let clusters = service(vec![
+            candidate(1, Some(0b1111), None, None),
+            resized,
+            candidate(3, Some(!0b1111), None, None),
+        ])
+        .find_clusters()
+        .unwrap();
+
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].kind, ClusterKind::NearDuplicate);
+        assert_eq!(clusters[0].best_shot.id, 2);
+        assert_eq!(clusters[0].others.len(), 1);
+        assert_eq!(clusters[0].others[0].id, 1);
+    }
+
+    #[test]
+    fn test_should_not_cluster_close_hashes_with_dissimilar_embeddings() {
+        let clusters = service(vec![
+            candidate(1, Some(0), Some(vec![1.0, 0.0]), None),
+            candidate(2, Some(1), Some(vec![0.0, 1.0]), None),
+        ])
+        .find_clusters()
+        .unwrap();
+
+        assert!(clusters.is_empty());
+    }
+
+    #[test]
+    fn test_should_cluster_bursts_by_capture_time_and_embedding() {
+        let mut rated = candidate(2, Some(0xFF00), Some(vec![0.95, 0.3]), Some(1));
+        rated.0.rating = Some(4);
+
+        let clusters = service(vec![
+            candidate(1, Some(0x00FF), Some(vec![1.0, 0.2]), Some(0)),
+            rated,
+            candidate(3, Some(0x0FF0), Some(vec![0.9, 0.35]), Some(3)),
+            candidate(4, Some(0xF00F), Some(vec![0.0, 1.0]), Some(4)),
+        ])
+        .find_clusters()
+        .unwrap();
+
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].kind, ClusterKind::Burst);
+        assert_eq!(clusters[0].best_shot.id, 2);
+        let mut other_ids: Vec<i32> = clusters[0].others.iter().map(|p| p.id).collect();
+        other_ids.sort();
+        assert_eq!(other_ids, vec![1, 3]);
+    }
+}
@@ -1,34 +1,81 @@
 use std::time::Instant;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow};
 use pgvector::Vector;
 
 use crate::{
-    models::{PaginationFilter, UpdatedPhoto},
-    repositories::{PhotoFindPathFilters, PhotoRepository},
+    models::{JobStage, NewPhotoEmbedding, PaginationFilter},
+    repositories::{PhotoErrorRepository, PhotoFindPathFilters, PhotoRepository, SkipFailedFilter},
     services::embedders::image::ImageEmbedder,
-    utils::progress_reporter::ProgressReporter,
+    utils::progress_reporter::{ProgressReporter, Throughput},
 };
 
-pub struct PhotoEmbedderService<R: PhotoRepository, E: ImageEmbedder, P: ProgressReporter> {
+pub struct PhotoEmbedderService<
+    R: PhotoRepository,
+    ER: PhotoErrorRepository,
+    E: ImageEmbedder,
+    P: ProgressReporter,
+> {
     photo_repository: R,
+    photo_error_repository: ER,
     image_embedder: E,
     progress_reporter: P,
+    max_attempts: i32,
+    batch_size: usize,
 }
 
-impl<R: PhotoRepository, E: ImageEmbedder, P: ProgressReporter> PhotoEmbedderService<R, E, P> {
-    pub fn new(photo_repository: R, image_embedder: E, progress_reporter: P) -> Self {
+impl<R: PhotoRepository, ER: PhotoErrorRepository, E: ImageEmbedder, P: ProgressReporter>
+    PhotoEmbedderService<R, ER, E, P>
+{
+    pub fn new(
+        photo_repository: R,
+        photo_error_repository: ER,
+        image_embedder: E,
+        progress_reporter: P,
+        max_attempts: i32,
+        batch_size: usize,
+    ) -> Self {
         Self {
             photo_repository,
+            photo_error_repository,
             image_embedder,
             progress_reporter,
+            max_attempts,
+            batch_size,
         }
     }
 
-    /// Computes embeddings for photos that don't have them yet.
+    /// Computes embeddings for photos that don't have them yet for the model of the embedder,
+    /// `batch_size` photos at a time.
+    ///
+    /// Photos which cannot be embedded are recorded as failed and skipped until the next
+    /// run, or for good once they failed `max_attempts` times.
     pub fn embed(&mut self) -> Result<usize> {
+        self.embed_photos(false)
+    }
+
+    /// Computes embeddings for every photo again, replacing the embeddings of the model, such
+    /// as after a change of preprocessing.
+    ///
+    /// Photos keep their previous embedding until the new one is computed, so that searches
+    /// are still served while it runs.
+    pub fn reembed(&mut self) -> Result<usize> {
+        self.embed_photos(true)
+    }
+
+    fn embed_photos(&mut self, reembed: bool) -> Result<usize> {
         let start = Instant::now();
+        let throughput = Throughput::start();
+        // Embeddings and errors are timestamped by the database, so its clock sets the start
+        // of the run: photos embedded during the run are never picked again
+        let started_at = self
+            .photo_repository
+            .now()
+            .context("Failed to fetch database time")?;
         let mut total_processed = 0usize;
+        let mut total_failed = 0usize;
+        let mut dimensions = None;
+        let model = self.image_embedder.model_id().to_string();
 
         loop {
             // Get the next batch of photos without embeddings
@@ -37,10 +84,17 @@ impl<R: PhotoRepository, E: ImageEmbedder, P: ProgressReporter> PhotoEmbedderSer
                 .find_path(
                     PaginationFilter {
                         page: 1,
-                        per_page: 20,
+                        per_page: self.batch_size as i64,
                     },
                     PhotoFindPathFilters {
                         has_embedding: Some(false),
+                        embedding_model: Some(model.clone()),
+                        embedded_before: reembed.then_some(started_at),
+                        skip_failed: Some(SkipFailedFilter {
+                            stage: JobStage::Embed,
+                            max_attempts: self.max_attempts,
+                            failed_since: started_at,
+                        }),
                         ..Default::default()
                     },
                 )
@@ -52,45 +106,80 @@ impl<R: PhotoRepository, E: ImageEmbedder, P: ProgressReporter> PhotoEmbedderSer
             }
 
             self.progress_reporter.set_message(format!(
-                "Processing photos: {} total, {} remaining",
-                total_processed, paginated_paths.total
+                "Embedding photos: {}",
+                throughput.message("images", total_processed, paginated_paths.total as usize)
             ));
 
-            // Compute embeddings for this batch
-            let embeddings_data = self
-                .image_embedder
-                .embed(
-                    &paginated_paths
-                        .items
-                        .iter()
-                        .map(|f| f.path.clone())
-                        .collect(),
-                )
-                .context("Failed to compute embeddings")?;
-
-            for (photo, embedding) in paginated_paths.items.iter().zip(embeddings_data.iter()) {
-                self.photo_repository
-                    .update_one(
-                        photo.id,
-                        UpdatedPhoto {
-                            embedding: Some(Some(Vector::from(embedding.clone()))),
-                            ..Default::default()
-                        },
-                    )
+            let paths: Vec<String> = paginated_paths
+                .items
+                .iter()
+                .map(|f| f.path.clone())
+                .collect();
+
+            // Compute embeddings for this batch, or for each photo when a photo of the batch
+            // cannot be embedded
+            let embeddings_data: Vec<Result<Vec<f32>>> = match self.image_embedder.embed(&paths) {
+                Ok(embeddings) => embeddings.into_iter().map(Ok).collect(),
+                Err(_) => paths.iter().map(|path| self.embed_one(path)).collect(),
+            };
+
+            let mut new_embeddings = Vec::new();
+            for (photo, embedding) in paginated_paths.items.iter().zip(embeddings_data) {
+                let embedding = match embedding {
+                    Ok(embedding) => embedding,
+                    Err(error) => {
+                        self.photo_error_repository
+                            .record(photo.id, JobStage::Embed, format!("{:#}", error))
+                            .context("Failed to record embedding error")?;
+                        total_failed += 1;
+                        continue;
+                    }
+                };
+
+                new_embeddings.push(NewPhotoEmbedding {
+                    photo_id: photo.id,
+                    model: model.clone(),
+                    embedding: Vector::from(embedding),
+                });
+            }
+
+            if let Some(new_embedding) = new_embeddings.first() {
+                dimensions = Some(new_embedding.embedding.as_slice().len());
+                let embedded_ids = new_embeddings.iter().map(|e| e.photo_id).collect();
+                // Embeddings of the whole batch are saved at once
+                total_processed += self
+                    .photo_repository
+                    .save_embeddings(new_embeddings)
                     .context("Failed to update embeddings in database")?;
+                self.photo_error_repository
+                    .resolve(JobStage::Embed, embedded_ids)
+                    .context("Failed to clear embedding errors")?;
             }
+        }
 
-            total_processed += paginated_paths.items.len();
+        // The index is built once the embeddings are saved, which is faster than updating it
+        // along the way
+        if let Some(dimensions) = dimensions {
+            self.photo_repository
+                .create_embedding_index(model, dimensions)
+                .context("Failed to create embedding index")?;
         }
 
         let duration = start.elapsed();
         self.progress_reporter.finish_with_message(format!(
-            "✓ Processed embeddings for {} photos in {:.2?}",
-            total_processed, duration
+            "✓ Processed embeddings for {} photos in {:.2?}, {} failed",
+            total_processed, duration, total_failed
         ));
 
         Ok(total_processed)
     }
+
+    fn embed_one(&self, path: &str) -> Result<Vec<f32>> {
+        self.image_embedder
+            .embed(&vec![path.to_string()])?
+            .pop()
+            .ok_or_else(|| anyhow!("No embedding computed for photo: {}", path))
+    }
 }
 
 #[cfg(test)]
@@ -98,21 +187,32 @@ mod tests {
     use super::*;
     use crate::{
         models::{PaginatedPhotoPaths, PhotoPath},
-        repositories::photo::repository::MockPhotoRepository,
+        repositories::{
+            photo::repository::MockPhotoRepository,
+            photo_error::repository::MockPhotoErrorRepository,
+        },
         services::embedders::image::MockImageEmbedder,
         utils::progress_reporter::NoOpProgressReporter,
     };
     use anyhow::anyhow;
+    use chrono::Utc;
+    use mockall::predicate::eq;
+    use std::{
+        collections::BTreeMap,
+        sync::{Arc, Mutex},
+    };
 
     #[test]
     fn test_process_embeddings_no_photos() {
         let mut photo_repository = MockPhotoRepository::new();
+        photo_repository.expect_now().returning(|| Ok(Utc::now()));
         photo_repository
             .expect_find_path()
             .withf(|p: &PaginationFilter, f: &PhotoFindPathFilters| {
                 p.page == 1
                     && p.per_page == 20
                     && f.has_embedding == Some(false)
+                    && f.embedding_model.as_deref() == Some("clip-vit-base-patch32")
                     && f.has_face_detection_completed == None
             })
             .times(1)
@@ -125,16 +225,25 @@ mod tests {
                     total_pages: 1,
                 })
             });
-        photo_repository.expect_update_one().times(0);
+        photo_repository.expect_save_embeddings().times(0);
 
         let mut image_embedder = MockImageEmbedder::new();
+        image_embedder
+            .expect_model_id()
+            .return_const("clip-vit-base-patch32".to_string());
         image_embedder
             .expect_embed()
             .times(0)
             .returning(|_| Ok(vec![]));
 
-        let mut photo_embedder_service =
-            PhotoEmbedderService::new(photo_repository, image_embedder, NoOpProgressReporter);
+        let mut photo_embedder_service = PhotoEmbedderService::new(
+            photo_repository,
+            MockPhotoErrorRepository::new(),
+            image_embedder,
+            NoOpProgressReporter,
+            3,
+            20,
+        );
         let result = photo_embedder_service.embed();
 
         assert!(result.is_ok());
@@ -143,6 +252,7 @@ mod tests {
     #[test]
     fn test_should_return_error_when_repository_fails() {
         let mut photo_repository = MockPhotoRepository::new();
+        photo_repository.expect_now().returning(|| Ok(Utc::now()));
 
         photo_repository
             .expect_find_path()
@@ -150,20 +260,30 @@ mod tests {
                 p.page == 1
                     && p.per_page == 20
                     && f.has_embedding == Some(false)
+                    && f.embedding_model.as_deref() == Some("clip-vit-base-patch32")
                     && f.has_face_detection_completed == None
             })
             .times(1)
             .returning(|_, __| Err(anyhow!("Repository error")));
 
         let mut image_embedder = MockImageEmbedder::new();
+        image_embedder
+            .expect_model_id()
+            .return_const("clip-vit-base-patch32".to_string());
 
         image_embedder
             .expect_embed()
             .times(0)
             .returning(|_| Ok(vec![]));
 
-        let mut photo_embedder_service =
-            PhotoEmbedderService::new(photo_repository, image_embedder, NoOpProgressReporter);
+        let mut photo_embedder_service = PhotoEmbedderService::new(
+            photo_repository,
+            MockPhotoErrorRepository::new(),
+            image_embedder,
+            NoOpProgressReporter,
+            3,
+            20,
+        );
         let result = photo_embedder_service.embed();
 
         assert_eq!(
@@ -175,6 +295,7 @@ mod tests {
     #[test]
     fn test_should_return_error_when_update_embeddings_fails() {
         let mut photo_repository = MockPhotoRepository::new();
+        photo_repository.expect_now().returning(|| Ok(Utc::now()));
 
         photo_repository
             .expect_find_path()
@@ -193,20 +314,31 @@ mod tests {
             });
 
         photo_repository
-            .expect_update_one()
-            .withf(|id: &i32, _| *id == 1)
+            .expect_save_embeddings()
+            .withf(|e: &Vec<NewPhotoEmbedding>| {
+                e.len() == 1 && e[0].photo_id == 1 && e[0].model == "clip-vit-base-patch32"
+            })
             .times(1)
-            .returning(|_, __| Err(anyhow!("Failed to update embeddings in database")));
+            .returning(|_| Err(anyhow!("Failed to update embeddings in database")));
 
         let mut image_embedder = MockImageEmbedder::new();
+        image_embedder
+            .expect_model_id()
+            .return_const("clip-vit-base-patch32".to_string());
 
         image_embedder
             .expect_embed()
             .times(1)
             .returning(|_| Ok(vec![vec![0.]]));
 
-        let mut photo_embedder_service =
-            PhotoEmbedderService::new(photo_repository, image_embedder, NoOpProgressReporter);
+        let mut photo_embedder_service = PhotoEmbedderService::new(
+            photo_repository,
+            MockPhotoErrorRepository::new(),
+            image_embedder,
+            NoOpProgressReporter,
+            3,
+            20,
+        );
         let result = photo_embedder_service.embed();
 
         assert_eq!(
@@ -214,4 +346,290 @@ mod tests {
             "Failed to update embeddings in database"
         );
     }
+
+    #[test]
+    fn test_should_record_error_and_embed_other_photos_when_one_fails() {
+        let mut photo_repository = MockPhotoRepository::new();
+        photo_repository.expect_now().returning(|| Ok(Utc::now()));
+        photo_repository
+            .expect_create_embedding_index()
+            .with(eq("clip-vit-base-patch32".to_string()), eq(1))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        let mut find_count = 0;
+
+        photo_repository
+            .expect_find_path()
+            .withf(|_, f: &PhotoFindPathFilters| {
+                f.skip_failed
+                    .as_ref()
+                    .is_some_and(|s| s.stage == JobStage::Embed && s.max_attempts == 3)
+            })
+            .times(2)
+            .returning(move |_, __| {
+                find_count += 1;
+                let items = if find_count == 1 {
+                    vec![
+                        PhotoPath {
+                            id: 1,
+                            path: "corrupt.jpg".to_string(),
+                        },
+                        PhotoPath {
+                            id: 2,
+                            path: "test.jpg".to_string(),
+                        },
+                    ]
+                } else {
+                    vec![]
+                };
+                Ok(PaginatedPhotoPaths {
+                    total: items.len() as i64,
+                    items,
+                    page: 1,
+                    per_page: 20,
+                    total_pages: 1,
+                })
+            });
+
+        photo_repository
+            .expect_save_embeddings()
+            .withf(|e: &Vec<NewPhotoEmbedding>| e.len() == 1 && e[0].photo_id == 2)
+            .times(1)
+            .returning(|e| Ok(e.len()));
+
+        let mut image_embedder = MockImageEmbedder::new();
+        image_embedder
+            .expect_model_id()
+            .return_const("clip-vit-base-patch32".to_string());
+
+        image_embedder
+            .expect_embed()
+            .times(3)
+            .returning(|paths: &Vec<String>| {
+                if paths.contains(&"corrupt.jpg".to_string()) {
+                    Err(anyhow!("Failed to load images"))
+                } else {
+                    Ok(vec![vec![0.]])
+                }
+            });
+
+        let mut photo_error_repository = MockPhotoErrorRepository::new();
+
+        photo_error_repository
+            .expect_record()
+            .withf(|id: &i32, stage: &JobStage, error: &String| {
+                *id == 1 && *stage == JobStage::Embed && error == "Failed to load images"
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(1));
+        photo_error_repository
+            .expect_resolve()
+            .withf(|stage: &JobStage, ids: &Vec<i32>| *stage == JobStage::Embed && *ids == vec![2])
+            .times(1)
+            .returning(|_, _| Ok(0));
+
+        let mut photo_embedder_service = PhotoEmbedderService::new(
+            photo_repository,
+            photo_error_repository,
+            image_embedder,
+            NoOpProgressReporter,
+            3,
+            20,
+        );
+        let result = photo_embedder_service.embed();
+
+        assert_eq!(result.unwrap(), 1);
+    }
+
+    #[test]
+    fn test_should_save_embeddings_of_each_batch_at_once() {
+        let mut photo_repository = MockPhotoRepository::new();
+        photo_repository.expect_now().returning(|| Ok(Utc::now()));
+        photo_repository
+            .expect_create_embedding_index()
+            .with(eq("clip-vit-base-patch32".to_string()), eq(1))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        let mut find_count = 0;
+
+        photo_repository
+            .expect_find_path()
+            .withf(|p: &PaginationFilter, _| p.page == 1 && p.per_page == 2)
+            .times(3)
+            .returning(move |_, __| {
+                find_count += 1;
+                let items: Vec<PhotoPath> = match find_count {
+                    1 => vec![1, 2],
+                    2 => vec![3],
+                    _ => vec![],
+                }
+                .into_iter()
+                .map(|id| PhotoPath {
+                    id,
+                    path: format!("{}.jpg", id),
+                })
+                .collect();
+                Ok(PaginatedPhotoPaths {
+                    total: items.len() as i64,
+                    items,
+                    page: 1,
+                    per_page: 2,
+                    total_pages: 1,
+                })
+            });
+
+        let mut saved_ids = vec![vec![1, 2], vec![3]].into_iter();
+        photo_repository
+            .expect_save_embeddings()
+            .times(2)
+            .returning(move |e: Vec<NewPhotoEmbedding>| {
+                let ids: Vec<i32> = e.iter().map(|e| e.photo_id).collect();
+                assert_eq!(ids, saved_ids.next().unwrap());
+                Ok(e.len())
+            });
+
+        let mut image_embedder = MockImageEmbedder::new();
+        image_embedder
+            .expect_model_id()
+            .return_const("clip-vit-base-patch32".to_string());
+        image_embedder
+            .expect_embed()
+            .times(2)
+            .returning(|paths: &Vec<String>| Ok(vec![vec![0.]; paths.len()]));
+
+        let mut photo_error_repository = MockPhotoErrorRepository::new();
+        photo_error_repository
+            .expect_resolve()
+            .times(2)
+            .returning(|_, ids| Ok(ids.len()));
+
+        let mut photo_embedder_service = PhotoEmbedderService::new(
+            photo_repository,
+            photo_error_repository,
+            image_embedder,
+            NoOpProgressReporter,
+            3,
+            2,
+        );
+
+        assert_eq!(photo_embedder_service.embed().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_should_embed_again_photos_embedded_before_reembedding() {
+        let mut photo_repository = MockPhotoRepository::new();
+        photo_repository.expect_now().returning(|| Ok(Utc::now()));
+        photo_repository
+            .expect_find_path()
+            .withf(|_, f: &PhotoFindPathFilters| {
+                f.has_embedding == Some(false)
+                    && f.embedding_model.as_deref() == Some("clip-vit-base-patch32")
+                    && f.embedded_before.is_some()
+            })
+            .times(1)
+            .returning(|_, __| {
+                Ok(PaginatedPhotoPaths {
+                    items: vec![],
+                    total: 0,
+                    page: 1,
+                    per_page: 20,
+                    total_pages: 1,
+                })
+            });
+
+        let mut image_embedder = MockImageEmbedder::new();
+        image_embedder
+            .expect_model_id()
+            .return_const("clip-vit-base-patch32".to_string());
+
+        let mut photo_embedder_service = PhotoEmbedderService::new(
+            photo_repository,
+            MockPhotoErrorRepository::new(),
+            image_embedder,
+            NoOpProgressReporter,
+            3,
+            20,
+        );
+
+        assert_eq!(photo_embedder_service.reembed().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_should_not_reembed_photos_embedded_during_the_run() {
+        // The database clock is behind the client one, so embeddings saved during the run
+        // are timestamped before the client start time
+        let database_now = Utc::now() - chrono::Duration::minutes(5);
+        let embedded_at = Arc::new(Mutex::new(BTreeMap::from([
+            (1, database_now - chrono::Duration::days(1)),
+            (2, database_now - chrono::Duration::days(1)),
+        ])));
+
+        let mut photo_repository = MockPhotoRepository::new();
+        photo_repository
+            .expect_now()
+            .returning(move || Ok(database_now));
+        photo_repository
+            .expect_create_embedding_index()
+            .returning(|_, _| Ok(()));
+        let find_embedded_at = embedded_at.clone();
+        photo_repository.expect_find_path().times(2).returning(
+            move |_, f: PhotoFindPathFilters| {
+                let embedded_before = f.embedded_before.unwrap();
+                let items: Vec<PhotoPath> = find_embedded_at
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .filter(|(_, created_at)| **created_at < embedded_before)
+                    .map(|(&id, _)| PhotoPath {
+                        id,
+                        path: format!("{}.jpg", id),
+                    })
+                    .collect();
+                Ok(PaginatedPhotoPaths {
+                    total: items.len() as i64,
+                    items,
+                    page: 1,
+                    per_page: 20,
+                    total_pages: 1,
+                })
+            },
+        );
+        photo_repository
+            .expect_save_embeddings()
+            .returning(move |e: Vec<NewPhotoEmbedding>| {
+                let mut embedded_at = embedded_at.lock().unwrap();
+                for embedding in &e {
+                    embedded_at.insert(
+                        embedding.photo_id,
+                        database_now + chrono::Duration::seconds(1),
+                    );
+                }
+                Ok(e.len())
+            });
+
+        let mut image_embedder = MockImageEmbedder::new();
+        image_embedder
+            .expect_model_id()
+            .return_const("clip-vit-base-patch32".to_string());
+        image_embedder
+            .expect_embed()
+            .times(1)
+            .returning(|paths: &Vec<String>| Ok(vec![vec![0.]; paths.len()]));
+
+        let mut photo_error_repository = MockPhotoErrorRepository::new();
+        photo_error_repository
+            .expect_resolve()
+            .returning(|_, ids| Ok(ids.len()));
+
+        let mut photo_embedder_service = PhotoEmbedderService::new(
+            photo_repository,
+            photo_error_repository,
+            image_embedder,
+            NoOpProgressReporter,
+            3,
+            20,
+        );
+
+        assert_eq!(photo_embedder_service.reembed().unwrap(), 2);
+    }
 }
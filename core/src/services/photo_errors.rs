@@ -0,0 +1,41 @@
+use anyhow::{Context, Result};
+
+use crate::{
+    models::PhotoErrorWithPath,
+    repositories::{PhotoErrorFindFilters, PhotoErrorRepository},
+};
+
+/// Lists the failures of pipeline stages on photos and retries them.
+///
+/// Photos are skipped by a stage once it failed `max_attempts` times on them, until their
+/// errors are retried.
+pub struct PhotoErrorService<ER: PhotoErrorRepository> {
+    photo_error_repository: ER,
+}
+
+impl<ER: PhotoErrorRepository> PhotoErrorService<ER> {
+    pub fn new(photo_error_repository: ER) -> Self {
+        Self {
+            photo_error_repository,
+        }
+    }
+
+    /// Lists errors matching the filters, most recently attempted first.
+    pub fn list(
+        &mut self,
+        filters: PhotoErrorFindFilters,
+        limit: i64,
+    ) -> Result<Vec<PhotoErrorWithPath>> {
+        self.photo_error_repository
+            .find_many(filters, limit)
+            .context("Failed to list photo errors")
+    }
+
+    /// Clears errors matching the filters, so that the next runs of their stages process
+    /// their photos again, and returns their number.
+    pub fn retry(&mut self, filters: PhotoErrorFindFilters) -> Result<usize> {
+        self.photo_error_repository
+            .delete_many(filters)
+            .context("Failed to retry photo errors")
+    }
+}
@@ -0,0 +1,497 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    fs::{self, File},
+    path::{Component, Path, PathBuf},
+    time::Instant,
+};
+
+use anyhow::{Context, Result, bail};
+use chrono::Local;
+
+use crate::{
+    models::{Library, MediaType, NewPhoto},
+    repositories::PhotoRepository,
+    services::photo_scanner::{self, ScanOptions, ScanSummary},
+    utils::{self, progress_reporter::ProgressReporter},
+};
+
+/// Placeholders of import templates, replaced by values of each imported file.
+const PLACEHOLDERS: &[&str] = &["year", "month", "day", "date", "camera", "make", "model"];
+
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// Layout of imported files below the library root, such as `{year}/{month}/{date}_{camera}`.
+    pub template: String,
+    /// List the files that would be copied without copying them.
+    pub dry_run: bool,
+    /// Options of the scan registering the copied files.
+    pub scan: ScanOptions,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportSummary {
+    pub imported: usize,
+    /// Files whose content is already indexed, or was found earlier in the source.
+    pub skipped: usize,
+    pub failed: usize,
+    /// Files copied into the library, or that would be in a dry run.
+    pub imported_paths: Vec<String>,
+    /// Summary of the scan registering the copied files.
+    pub scan: ScanSummary,
+}
+
+/// Media file found in the import source.
+struct SourceFile {
+    path: PathBuf,
+    hash: String,
+}
+
+/// Copies media files from a source such as a memory card into a library, laid out by a
+/// template, then scans the copies.
+///
+/// Files are identified by their BLAKE3 hash, so that content already indexed is never
+/// copied twice, and copies are hashed again before being moved into place.
+pub fn import(
+    source: &str,
+    library: &Library,
+    photo_repository: &mut dyn PhotoRepository,
+    options: &ImportOptions,
+    progress: &dyn ProgressReporter,
+) -> Result<ImportSummary> {
+    let start = Instant::now();
+
+    if !Path::new(source).exists() {
+        bail!("Import source does not exist: {}", source);
+    }
+    validate_template(&options.template)?;
+
+    let mut summary = ImportSummary::default();
+    let mut files = Vec::new();
+
+    let paths = ignore::WalkBuilder::new(source)
+        .git_ignore(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| photo_scanner::is_media_file(entry.path()))
+        .map(|entry| entry.into_path());
+
+    for path in paths {
+        progress.set_message(format!("Hashing {}", path.display()));
+
+        match utils::compute_file_hash(&path) {
+            Ok(hash) => files.push(SourceFile { path, hash }),
+            Err(err) => {
+                progress.report_error(format!("Failed to hash {}: {}", path.display(), err));
+                summary.failed += 1;
+            }
+        }
+    }
+
+    let mut known_hashes: HashSet<String> = photo_repository
+        .find_existing_hashes(files.iter().map(|file| file.hash.clone()).collect())
+        .context("Failed to fetch indexed hashes")?
+        .into_iter()
+        .collect();
+
+    let mut target_dirs = BTreeSet::new();
+
+    for file in files {
+        if !known_hashes.insert(file.hash.clone()) {
+            summary.skipped += 1;
+            continue;
+        }
+
+        match import_file(&file, library, options) {
+            Ok(target) => {
+                progress.set_message(target.to_string_lossy().into_owned());
+                if let Some(target_dir) = target.parent() {
+                    target_dirs.insert(target_dir.to_path_buf());
+                }
+                summary.imported += 1;
+                summary
+                    .imported_paths
+                    .push(target.to_string_lossy().into_owned());
+            }
+            Err(err) => {
+                progress.report_error(format!(
+                    "Failed to import {}: {:#}",
+                    file.path.display(),
+                    err
+                ));
+                summary.failed += 1;
+            }
+        }
+    }
+
+    if !options.dry_run {
+        // Hashes are always computed, so that later imports skip these files
+        let scan_options = ScanOptions {
+            with_hash: true,
+            prune: false,
+            library: Some(library.clone()),
+            ..options.scan.clone()
+        };
+
+        for target_dir in target_dirs {
+            let scan_summary = photo_scanner::scan(
+                &target_dir.to_string_lossy(),
+                photo_repository,
+                &scan_options,
+                progress,
+            )?;
+            add_scan_summary(&mut summary.scan, scan_summary);
+        }
+    }
+
+    let duration = start.elapsed();
+    progress.finish_with_message(format!(
+        "✓ Imported {} files in {:.2?}",
+        summary.imported, duration
+    ));
+
+    Ok(summary)
+}
+
+/// Copies a file to its place in the library and returns its path there.
+fn import_file(file: &SourceFile, library: &Library, options: &ImportOptions) -> Result<PathBuf> {
+    let mut photo = NewPhoto::new(&file.path)?;
+    match photo.media_type {
+        MediaType::Photo => {
+            if let Some(exif) = utils::extract_exif(&file.path) {
+                photo = photo.with_exif(exif);
+            }
+        }
+        MediaType::Video => {
+            if let Some(track_info) = utils::extract_track_info(&file.path) {
+                photo = photo.with_track_info(track_info);
+            }
+        }
+    }
+
+    let target_dir = Path::new(&library.root_path).join(render_template(&options.template, &photo));
+    let target = find_target_path(&target_dir, &file.path, &file.hash)?;
+
+    if !options.dry_run && !target.exists() {
+        copy_verified(&file.path, &target, &file.hash)?;
+    }
+
+    Ok(target)
+}
+
+/// Checks that a template only uses known placeholders and stays below the library root.
+fn validate_template(template: &str) -> Result<()> {
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .map(|end| start + end)
+            .ok_or_else(|| anyhow::anyhow!("Unclosed placeholder in template: {}", template))?;
+
+        let placeholder = &rest[start + 1..end];
+        if !PLACEHOLDERS.contains(&placeholder) {
+            bail!("Unknown placeholder in template: {{{}}}", placeholder);
+        }
+        rest = &rest[end + 1..];
+    }
+
+    if !Path::new(template)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        bail!(
+            "Template must be a relative path below the library root: {}",
+            template
+        );
+    }
+
+    Ok(())
+}
+
+/// Renders the directory of a file from a template, using its capture time, or its
+/// modification time when it was not recorded.
+fn render_template(template: &str, photo: &NewPhoto) -> PathBuf {
+    let date = photo
+        .date_taken_local
+        .unwrap_or_else(|| photo.modified_at.with_timezone(&Local).naive_local());
+    let make = photo.camera_make.as_deref();
+    let model = photo.camera_model.as_deref();
+
+    let mut rendered = template.to_string();
+    for placeholder in PLACEHOLDERS {
+        let value = match *placeholder {
+            "year" => date.format("%Y").to_string(),
+            "month" => date.format("%m").to_string(),
+            "day" => date.format("%d").to_string(),
+            "date" => date.format("%Y-%m-%d").to_string(),
+            "camera" => sanitize(model.or(make)),
+            "make" => sanitize(make),
+            "model" => sanitize(model),
+            _ => unreachable!(),
+        };
+        rendered = rendered.replace(&format!("{{{}}}", placeholder), &value);
+    }
+
+    PathBuf::from(rendered)
+}
+
+/// Makes a metadata value safe to use in a file name.
+fn sanitize(value: Option<&str>) -> String {
+    let value: String = value
+        .unwrap_or_default()
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    if value.is_empty() || value.chars().all(|c| c == '.') {
+        "Unknown".to_string()
+    } else {
+        value
+    }
+}
+
+/// Returns the path of a file in the target directory, numbering its name when another
+/// file already uses it.
+fn find_target_path(target_dir: &Path, source: &Path, hash: &str) -> Result<PathBuf> {
+    let file_name = source
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("Invalid file name: {}", source.display()))?;
+    let stem = source.file_stem().unwrap_or(file_name).to_string_lossy();
+    let extension = source
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+
+    let mut target = target_dir.join(file_name);
+    let mut index = 0;
+
+    // A file with the same content was copied by an earlier, interrupted import
+    while target.exists() && utils::compute_file_hash(&target).ok().as_deref() != Some(hash) {
+        index += 1;
+        target = target_dir.join(format!("{}_{}{}", stem, index, extension));
+    }
+
+    Ok(target)
+}
+
+/// Copies a file next to its target under a hidden name, then moves it into place once
+/// its hash matches the original.
+fn copy_verified(source: &Path, target: &Path, hash: &str) -> Result<()> {
+    let target_dir = target
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("Invalid target path: {}", target.display()))?;
+    fs::create_dir_all(target_dir).context("Failed to create target directory")?;
+
+    let temp_path = target_dir.join(format!(
+        ".{}.part",
+        target.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    fs::copy(source, &temp_path).context("Failed to copy file")?;
+
+    if utils::compute_file_hash(&temp_path).ok().as_deref() != Some(hash) {
+        let _ = fs::remove_file(&temp_path);
+        bail!("Copy does not match the original");
+    }
+
+    // Keeps the modification time, used as capture time of files without metadata
+    if let Ok(modified) = fs::metadata(source).and_then(|metadata| metadata.modified()) {
+        let _ = File::options()
+            .write(true)
+            .open(&temp_path)
+            .and_then(|file| file.set_modified(modified));
+    }
+
+    fs::rename(&temp_path, target).context("Failed to move copy into place")?;
+
+    Ok(())
+}
+
+fn add_scan_summary(total: &mut ScanSummary, summary: ScanSummary) {
+    total.added += summary.added;
+    total.updated += summary.updated;
+    total.unchanged += summary.unchanged;
+    total.moved += summary.moved;
+    total.missing += summary.missing;
+    total.removed += summary.removed;
+    total.skipped += summary.skipped;
+    total.failed += summary.failed;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        repositories::photo::repository::MockPhotoRepository,
+        utils::progress_reporter::NoOpProgressReporter,
+    };
+    use chrono::{TimeZone, Utc};
+    use std::time::SystemTime;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn library(root_path: &Path) -> Library {
+        Library {
+            id: 1,
+            name: "photos".to_string(),
+            root_path: root_path.to_string_lossy().into_owned(),
+            enabled: true,
+            with_exif: false,
+            with_hash: false,
+            include: vec![],
+            exclude: vec![],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            write_xmp: false,
+            prune: false,
+        }
+    }
+
+    fn options(dry_run: bool) -> ImportOptions {
+        ImportOptions {
+            template: "{year}/{month}/{date}_{camera}".to_string(),
+            dry_run,
+            scan: ScanOptions::default(),
+        }
+    }
+
+    /// Writes a source file modified on 5 March 2024 at noon, local time.
+    fn write_source_file(path: &Path, content: &[u8]) {
+        fs::write(path, content).unwrap();
+        let modified: SystemTime = Local.with_ymd_and_hms(2024, 3, 5, 12, 0, 0).unwrap().into();
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_should_copy_new_files_into_template_layout() {
+        let source_dir = TempDir::new().unwrap();
+        let library_dir = TempDir::new().unwrap();
+        write_source_file(&source_dir.path().join("a.jpg"), b"photo a");
+        write_source_file(&source_dir.path().join("b.jpg"), b"photo b");
+        write_source_file(&source_dir.path().join("c.jpg"), b"photo a");
+        let indexed_hash = utils::compute_file_hash(source_dir.path().join("b.jpg")).unwrap();
+
+        let mut repository = MockPhotoRepository::new();
+        repository
+            .expect_find_existing_hashes()
+            .times(1)
+            .returning(move |_| Ok(vec![indexed_hash.clone()]));
+        repository.expect_find_files().returning(|_| Ok(vec![]));
+        repository
+            .expect_insert_batch()
+            .withf(|new_photos| {
+                new_photos.len() == 1
+                    && new_photos[0].library_id == Some(1)
+                    && new_photos[0].hash.is_some()
+            })
+            .times(1)
+            .returning(|new_photos| Ok(new_photos.len()));
+
+        let summary = import(
+            source_dir.path().to_str().unwrap(),
+            &library(library_dir.path()),
+            &mut repository,
+            &options(false),
+            &NoOpProgressReporter,
+        )
+        .unwrap();
+
+        let target = library_dir.path().join("2024/03/2024-03-05_Unknown/a.jpg");
+        assert_eq!(summary.imported, 1);
+        assert_eq!(summary.skipped, 2);
+        assert_eq!(summary.scan.added, 1);
+        assert_eq!(fs::read(&target).unwrap(), b"photo a");
+        assert!(source_dir.path().join("a.jpg").exists());
+    }
+
+    #[test]
+    fn test_should_not_copy_files_in_dry_run() {
+        let source_dir = TempDir::new().unwrap();
+        let library_dir = TempDir::new().unwrap();
+        write_source_file(&source_dir.path().join("a.jpg"), b"photo a");
+
+        let mut repository = MockPhotoRepository::new();
+        repository
+            .expect_find_existing_hashes()
+            .returning(|_| Ok(vec![]));
+        repository.expect_insert_batch().times(0);
+
+        let summary = import(
+            source_dir.path().to_str().unwrap(),
+            &library(library_dir.path()),
+            &mut repository,
+            &options(true),
+            &NoOpProgressReporter,
+        )
+        .unwrap();
+
+        let target = library_dir.path().join("2024/03/2024-03-05_Unknown/a.jpg");
+        assert_eq!(
+            summary.imported_paths,
+            vec![target.to_string_lossy().into_owned()]
+        );
+        assert!(!target.exists());
+    }
+
+    #[test]
+    fn test_should_render_template_from_capture_time_and_camera() {
+        let photo = NewPhoto {
+            date_taken_local: Some(
+                chrono::NaiveDate::from_ymd_opt(2023, 12, 31)
+                    .unwrap()
+                    .and_hms_opt(23, 59, 0)
+                    .unwrap(),
+            ),
+            camera_make: Some("Canon".to_string()),
+            camera_model: Some("EOS R5/II".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            render_template("{year}/{month}/{day}/{date}_{camera} ({make})", &photo),
+            PathBuf::from("2023/12/31/2023-12-31_EOS R5_II (Canon)")
+        );
+    }
+
+    #[test]
+    fn test_should_reject_invalid_templates() {
+        assert!(validate_template("{year}/{date}_{camera}").is_ok());
+        assert!(validate_template("{year}/{lens}").is_err());
+        assert!(validate_template("{year}/{month").is_err());
+        assert!(validate_template("../{year}").is_err());
+        assert!(validate_template("/{year}").is_err());
+    }
+
+    #[test]
+    fn test_should_number_target_when_name_is_taken() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("IMG_0001.JPG");
+        fs::write(&source, b"new photo").unwrap();
+        let hash = utils::compute_file_hash(&source).unwrap();
+
+        let target_dir = temp_dir.path().join("library");
+        fs::create_dir(&target_dir).unwrap();
+        fs::write(target_dir.join("IMG_0001.JPG"), b"other photo").unwrap();
+
+        assert_eq!(
+            find_target_path(&target_dir, &source, &hash).unwrap(),
+            target_dir.join("IMG_0001_1.JPG")
+        );
+
+        fs::write(target_dir.join("IMG_0001.JPG"), b"new photo").unwrap();
+
+        assert_eq!(
+            find_target_path(&target_dir, &source, &hash).unwrap(),
+            target_dir.join("IMG_0001.JPG")
+        );
+    }
+}
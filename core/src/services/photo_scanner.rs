@@ -1,80 +1,681 @@
-use std::time::Instant;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::{MAIN_SEPARATOR, Path, PathBuf},
+    time::Instant,
+};
 
-use anyhow::{Context, Result};
-use ignore;
+use anyhow::{Context, Result, bail};
+use ignore::overrides::{Override, OverrideBuilder};
+use rayon::prelude::*;
 
-use crate::models::NewPhoto;
+use crate::config::ScannerConfig;
+use crate::models::{Library, MediaType, NewPhoto, PhotoFile, UpdatedPhoto};
 use crate::repositories::PhotoRepository;
-use crate::utils::{self, progress_reporter::ProgressReporter};
+use crate::utils::{self, media::MediaFormat, progress_reporter::ProgressReporter};
 
-/// Scans photos in the given path and inserts them into the repository.
+/// Name of the gitignore-style files listing paths to leave out of scans.
+pub const IGNORE_FILE_NAME: &str = ".picasaignore";
+
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    /// Extract EXIF data, XMP sidecars and embedded XMP/IPTC metadata from photos.
+    pub with_exif: bool,
+    /// Compute file hashes.
+    pub with_hash: bool,
+    /// Delete indexed photos whose file no longer exists under the scanned root.
+    pub prune: bool,
+    /// Re-extract EXIF data and recompute hashes of files that did not change.
+    pub force: bool,
+    /// Number of photos committed to the repository at once.
+    pub batch_size: usize,
+    /// Number of threads reading files, or 0 to use one per CPU.
+    pub workers: usize,
+    /// Glob patterns of files to scan, relative to the scanned root. Everything is scanned when empty.
+    pub include: Vec<String>,
+    /// Glob patterns of files and directories to skip, relative to the scanned root.
+    pub exclude: Vec<String>,
+    /// Minimum size of scanned files, in bytes.
+    pub min_file_size: u64,
+    /// Minimum width of scanned photos, in pixels.
+    pub min_width: u32,
+    /// Minimum height of scanned photos, in pixels.
+    pub min_height: u32,
+    /// Library the scanned photos belong to, storing their path relative to its root.
+    pub library: Option<Library>,
+    /// IANA timezone of capture times recorded without offset nor GPS location.
+    pub default_timezone: String,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            with_exif: false,
+            with_hash: false,
+            prune: false,
+            force: false,
+            batch_size: 500,
+            workers: 0,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            min_file_size: 0,
+            min_width: 0,
+            min_height: 0,
+            library: None,
+            default_timezone: "UTC".to_string(),
+        }
+    }
+}
+
+impl ScanOptions {
+    /// Builds the options scanning a library, combining its rules with the scanner settings.
+    pub fn for_library(library: Library, config: &ScannerConfig) -> Self {
+        Self {
+            with_exif: library.with_exif,
+            with_hash: library.with_hash,
+            prune: library.prune,
+            workers: config.workers,
+            include: [config.include.clone(), library.include.clone()].concat(),
+            exclude: [config.exclude.clone(), library.exclude.clone()].concat(),
+            min_file_size: config.min_file_size,
+            min_width: config.min_width,
+            min_height: config.min_height,
+            default_timezone: config.default_timezone.clone(),
+            library: Some(library),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ScanSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub moved: usize,
+    pub missing: usize,
+    pub removed: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// Result of reading a single file during a scan.
+enum ScannedFile {
+    /// The file is new or changed since it was indexed.
+    Changed(Box<NewPhoto>),
+    /// The file has the size and modification time stored in the index.
+    Unchanged,
+    /// The file is smaller than the minimum size or resolution.
+    Skipped,
+}
+
+/// Photos waiting to be committed, with the counts they add to the summary once committed.
+#[derive(Default)]
+struct Batch {
+    photos: Vec<NewPhoto>,
+    added: usize,
+    updated: usize,
+    moved: usize,
+}
+
+/// Indexed photos under the scanned root that have not been seen yet during the walk.
+struct PhotoIndex {
+    files: HashMap<String, PhotoFile>,
+    paths_by_size: HashMap<i64, Vec<String>>,
+}
+
+impl PhotoIndex {
+    fn new(files: Vec<PhotoFile>) -> Self {
+        let mut paths_by_size: HashMap<i64, Vec<String>> = HashMap::new();
+        for file in &files {
+            paths_by_size
+                .entry(file.file_size)
+                .or_default()
+                .push(file.path.clone());
+        }
+
+        Self {
+            files: files
+                .into_iter()
+                .map(|file| (file.path.clone(), file))
+                .collect(),
+            paths_by_size,
+        }
+    }
+
+    /// Checks whether a file still has the size and modification time stored in the index.
+    ///
+    /// Modification times are compared at microsecond precision, as stored by PostgreSQL.
+    fn is_unchanged(&self, new_photo: &NewPhoto) -> bool {
+        self.files.get(&new_photo.path).is_some_and(|file| {
+            file.file_size == new_photo.file_size
+                && file.modified_at.timestamp_micros() == new_photo.modified_at.timestamp_micros()
+        })
+    }
+
+    /// Marks an indexed path as seen, returning whether it was indexed.
+    fn take(&mut self, path: &str) -> bool {
+        self.files.remove(path).is_some()
+    }
+
+    /// Finds an indexed photo whose file disappeared and that matches the new photo.
+    ///
+    /// Photos indexed with a hash must have the same size and hash, the others can only
+    /// be matched on size and file name.
+    fn take_moved(&mut self, new_photo: &NewPhoto) -> Option<PhotoFile> {
+        let candidate_paths = self.paths_by_size.get(&new_photo.file_size)?;
+        let mut hash = new_photo.hash.clone();
+
+        for candidate_path in candidate_paths {
+            let Some(candidate) = self.files.get(candidate_path) else {
+                continue;
+            };
+
+            if Path::new(&candidate.path).exists() {
+                continue;
+            }
+
+            let is_same_file = match &candidate.hash {
+                Some(candidate_hash) => {
+                    if hash.is_none() {
+                        hash = utils::compute_file_hash(&new_photo.path).ok();
+                    }
+                    hash.as_ref() == Some(candidate_hash)
+                }
+                None => candidate.file_name == new_photo.file_name,
+            };
+
+            if is_same_file {
+                return self.files.remove(candidate_path);
+            }
+        }
+
+        None
+    }
+
+    /// Returns the indexed photos that were not seen during the walk.
+    fn into_missing(self) -> Vec<PhotoFile> {
+        self.files.into_values().collect()
+    }
+}
+
+/// Scans photos in the given path and reconciles them with the repository.
+///
+/// A file that is not indexed yet but matches, by size and hash, an indexed photo
+/// whose file disappeared is treated as a move: the existing row is renamed in
+/// place so that its embedding and faces are kept.
+///
+/// Files whose size and modification time match the indexed photo are skipped
+/// unless `force` is set. Photos are committed in batches while walking, so an
+/// interrupted scan resumes where it stopped: committed files are skipped as
+/// unchanged on the next run. A failing batch is reported and the scan goes on.
+///
+/// Hashes and EXIF data of each batch are read in parallel, while the walk order,
+/// reconciliation and commits stay sequential so that results are deterministic.
+/// Missing photos are not pruned when the root is empty.
+///
+/// Files matching the exclude patterns or a `.picasaignore` file, and files below the
+/// minimum size or resolution, are left out as if they did not exist, so photos that
+/// were indexed before the rules changed are reported as missing.
 pub fn scan(
     path: &str,
     photo_repository: &mut dyn PhotoRepository,
-    with_exif: bool,
-    with_hash: bool,
+    options: &ScanOptions,
     progress: &dyn ProgressReporter,
-) -> Result<usize> {
+) -> Result<ScanSummary> {
     let start = Instant::now();
-    let photos: Vec<NewPhoto> = ignore::WalkBuilder::new(path)
+
+    if !Path::new(path).exists() {
+        bail!("Scan root does not exist: {}", path);
+    }
+
+    let mut index = PhotoIndex::new(
+        photo_repository
+            .find_files(&root_prefix(path))
+            .context("Failed to fetch indexed photos")?,
+    );
+
+    let mut summary = ScanSummary::default();
+
+    let entries = walk(Path::new(path), &build_overrides(path, options)?, None);
+    index_files(
+        entries,
+        &mut index,
+        photo_repository,
+        options,
+        progress,
+        &mut summary,
+    )?;
+
+    remove_missing(
+        path,
+        index.into_missing(),
+        photo_repository,
+        options,
+        &mut summary,
+    )?;
+
+    let duration = start.elapsed();
+    progress.finish_with_message(format!(
+        "✓ Scanned {} photos in {:.2?}",
+        summary.added + summary.updated + summary.moved,
+        duration
+    ));
+
+    Ok(summary)
+}
+
+/// Scans the files and directories reported as changed under the given root, as a watcher
+/// does, and reconciles them with the repository.
+///
+/// Changed files are indexed and changed directories are walked as by [`scan`], while
+/// the rest of the root is left untouched. Only photos whose reported path, or the
+/// reported directory containing them, no longer exists are pruned; photos missing
+/// from a directory that still exists are reported as missing.
+pub fn scan_changes(
+    path: &str,
+    changed_paths: &[PathBuf],
+    photo_repository: &mut dyn PhotoRepository,
+    options: &ScanOptions,
+    progress: &dyn ProgressReporter,
+) -> Result<ScanSummary> {
+    let start = Instant::now();
+
+    if !Path::new(path).exists() {
+        bail!("Scan root does not exist: {}", path);
+    }
+
+    let under_root: Vec<&PathBuf> = changed_paths
+        .iter()
+        .filter(|changed_path| changed_path.starts_with(path))
+        .collect();
+    // Paths under another changed directory are covered by its walk
+    let changed_paths: Vec<&PathBuf> = under_root
+        .iter()
+        .filter(|changed_path| {
+            !under_root
+                .iter()
+                .any(|other| other != *changed_path && changed_path.starts_with(other))
+        })
+        .copied()
+        .collect();
+
+    let mut indexed_files = Vec::new();
+    for changed_path in &changed_paths {
+        let changed_path = changed_path.to_string_lossy();
+        let dir_prefix = root_prefix(&changed_path);
+
+        indexed_files.extend(
+            photo_repository
+                .find_files(&changed_path)
+                .context("Failed to fetch indexed photos")?
+                .into_iter()
+                .filter(|file| file.path == changed_path || file.path.starts_with(&dir_prefix)),
+        );
+    }
+    let mut index = PhotoIndex::new(indexed_files);
+
+    let overrides = build_overrides(path, options)?;
+    let mut changed_files: BTreeMap<&Path, HashSet<&Path>> = BTreeMap::new();
+    let mut changed_dirs = Vec::new();
+    for changed_path in &changed_paths {
+        if changed_path.is_dir() {
+            changed_dirs.push(changed_path.as_path());
+        } else if let Some(parent) = changed_path.parent().filter(|_| changed_path.is_file()) {
+            changed_files
+                .entry(parent)
+                .or_default()
+                .insert(changed_path.as_path());
+        }
+    }
+
+    // Files are walked from their directory so that exclude patterns and ignore files apply
+    let entries = changed_dirs
+        .into_iter()
+        .flat_map(|dir| walk(dir, &overrides, None))
+        .chain(changed_files.into_iter().flat_map(|(dir, files)| {
+            walk(dir, &overrides, Some(1)).filter(move |entry| files.contains(entry.as_path()))
+        }));
+
+    let mut summary = ScanSummary::default();
+    index_files(
+        entries,
+        &mut index,
+        photo_repository,
+        options,
+        progress,
+        &mut summary,
+    )?;
+
+    let deleted_prefixes: Vec<(String, String)> = changed_paths
+        .iter()
+        .filter(|changed_path| !changed_path.exists())
+        .map(|changed_path| {
+            let changed_path = changed_path.to_string_lossy().into_owned();
+            let dir_prefix = root_prefix(&changed_path);
+            (changed_path, dir_prefix)
+        })
+        .collect();
+    let (deleted, missing): (Vec<PhotoFile>, Vec<PhotoFile>) =
+        index.into_missing().into_iter().partition(|file| {
+            deleted_prefixes.iter().any(|(deleted_path, dir_prefix)| {
+                file.path == *deleted_path || file.path.starts_with(dir_prefix)
+            })
+        });
+
+    summary.missing = missing.len();
+    remove_missing(path, deleted, photo_repository, options, &mut summary)?;
+
+    let duration = start.elapsed();
+    progress.finish_with_message(format!(
+        "✓ Scanned {} changed photos in {:.2?}",
+        summary.added + summary.updated + summary.moved,
+        duration
+    ));
+
+    Ok(summary)
+}
+
+/// Walks the media files under a path, in file name order, leaving out ignored files.
+fn walk(
+    path: &Path,
+    overrides: &Override,
+    max_depth: Option<usize>,
+) -> impl Iterator<Item = PathBuf> + use<> {
+    ignore::WalkBuilder::new(path)
         .git_ignore(false)
+        .add_custom_ignore_filename(IGNORE_FILE_NAME)
+        .overrides(overrides.clone())
+        .max_depth(max_depth)
+        .sort_by_file_name(|a, b| a.cmp(b))
         .build()
         .filter_map(Result::ok)
-        .filter(|entry| {
-            entry
-                .path()
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .map(|ext| matches!(ext.to_lowercase().as_str(), "jpg" | "jpeg" | "png" | "heic"))
-                .unwrap_or(false)
-        })
-        .filter_map(|entry| match NewPhoto::new(entry.path()) {
-            Ok(mut new_photo) => {
-                if with_hash {
-                    if let Ok(hash) = utils::compute_file_hash(&new_photo.path) {
-                        new_photo = new_photo.with_hash(hash);
-                    }
+        .filter(|entry| is_media_file(entry.path()))
+        .map(|entry| entry.into_path())
+}
+
+/// Reads and commits files in batches, taking the files that were already indexed out
+/// of the index.
+fn index_files(
+    mut entries: impl Iterator<Item = PathBuf>,
+    index: &mut PhotoIndex,
+    photo_repository: &mut dyn PhotoRepository,
+    options: &ScanOptions,
+    progress: &dyn ProgressReporter,
+    summary: &mut ScanSummary,
+) -> Result<()> {
+    let thread_pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(options.workers)
+        .build()
+        .context("Failed to create scan thread pool")?;
+
+    let mut batch_number = 0;
+
+    loop {
+        let paths: Vec<PathBuf> = entries.by_ref().take(options.batch_size.max(1)).collect();
+        if paths.is_empty() {
+            break;
+        }
+
+        let scanned_files: Vec<Result<ScannedFile>> = thread_pool.install(|| {
+            paths
+                .par_iter()
+                .map(|path| scan_file(path, index, options))
+                .collect()
+        });
+
+        let mut batch = Batch::default();
+
+        for (path, scanned_file) in paths.iter().zip(scanned_files) {
+            let mut new_photo = match scanned_file {
+                Ok(ScannedFile::Changed(new_photo)) => *new_photo,
+                Ok(ScannedFile::Unchanged) => {
+                    index.take(&path.to_string_lossy());
+                    summary.unchanged += 1;
+                    continue;
+                }
+                Ok(ScannedFile::Skipped) => {
+                    summary.skipped += 1;
+                    continue;
+                }
+                Err(err) => {
+                    progress.report_error(format!(
+                        "Error processing {}: {:#}",
+                        path.display(),
+                        err
+                    ));
+                    summary.failed += 1;
+                    continue;
                 }
+            };
 
-                if with_exif {
-                    if let Some(exif) = utils::extract_exif(&new_photo.path) {
-                        new_photo = new_photo.with_exif(exif);
-                    }
+            if index.take(&new_photo.path) {
+                batch.updated += 1;
+            } else if let Some(moved_photo) = index.take_moved(&new_photo) {
+                let moved = photo_repository.update_one(
+                    moved_photo.id,
+                    UpdatedPhoto {
+                        path: Some(new_photo.path.clone()),
+                        file_name: Some(new_photo.file_name.clone()),
+                        ..Default::default()
+                    },
+                );
+
+                if let Err(err) = moved {
+                    progress.report_error(format!(
+                        "Failed to move {} to {}: {:#}",
+                        moved_photo.path, new_photo.path, err
+                    ));
+                    summary.failed += 1;
+                    continue;
+                }
+
+                if new_photo.hash.is_none() {
+                    new_photo.hash = moved_photo.hash;
                 }
+                batch.moved += 1;
+            } else {
+                batch.added += 1;
+            }
+
+            progress.set_message(new_photo.path.to_string());
+
+            batch.photos.push(new_photo);
+        }
+
+        if !batch.photos.is_empty() {
+            batch_number += 1;
+            commit_batch(photo_repository, batch, batch_number, summary, progress);
+        }
+    }
+
+    Ok(())
+}
 
-                progress.set_message(format!("{}", new_photo.path));
+/// Deletes missing photos when pruning, or counts them as missing.
+///
+/// Nothing is deleted when the scan root is empty, as an unmounted drive or a network
+/// share that is not connected yet would otherwise wipe the index.
+fn remove_missing(
+    path: &str,
+    missing: Vec<PhotoFile>,
+    photo_repository: &mut dyn PhotoRepository,
+    options: &ScanOptions,
+    summary: &mut ScanSummary,
+) -> Result<()> {
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    if options.prune && !is_empty_dir(Path::new(path)) {
+        summary.removed = photo_repository
+            .delete_many(missing.iter().map(|file| file.id).collect())
+            .context("Failed to remove missing photos from repository")?;
+    } else {
+        summary.missing += missing.len();
+    }
+
+    Ok(())
+}
+
+/// Builds the walker overrides matching the include and exclude patterns.
+fn build_overrides(path: &str, options: &ScanOptions) -> Result<Override> {
+    let mut builder = OverrideBuilder::new(path);
+
+    for pattern in &options.include {
+        builder
+            .add(pattern)
+            .context(format!("Invalid include pattern: {}", pattern))?;
+    }
+
+    for pattern in &options.exclude {
+        builder
+            .add(&format!("!{}", pattern))
+            .context(format!("Invalid exclude pattern: {}", pattern))?;
+    }
+
+    builder.build().context("Failed to build scan patterns")
+}
 
-                Some(new_photo)
+/// Reads the metadata, hash and EXIF data of a file, unless it did not change or is skipped.
+fn scan_file(path: &Path, index: &PhotoIndex, options: &ScanOptions) -> Result<ScannedFile> {
+    let mut new_photo = NewPhoto::new(path)?;
+
+    if let Some(library) = &options.library {
+        new_photo = new_photo.with_library(library.id, Path::new(&library.root_path));
+    }
+
+    if (new_photo.file_size as u64) < options.min_file_size
+        || is_below_min_resolution(path, options)
+    {
+        return Ok(ScannedFile::Skipped);
+    }
+
+    if !options.force && index.is_unchanged(&new_photo) {
+        return Ok(ScannedFile::Unchanged);
+    }
+
+    if options.with_hash {
+        if let Ok(hash) = utils::compute_file_hash(&new_photo.path) {
+            new_photo = new_photo.with_hash(hash);
+        }
+
+        if let Ok(perceptual_hash) =
+            utils::perceptual_hash::compute_perceptual_hash(&new_photo.path)
+        {
+            new_photo = new_photo.with_perceptual_hash(perceptual_hash);
+        }
+    }
+
+    if options.with_exif {
+        new_photo = read_metadata(new_photo, &options.default_timezone);
+    }
+
+    Ok(ScannedFile::Changed(Box::new(new_photo)))
+}
+
+/// Extracts the EXIF data or video track info of a photo, then its XMP/IPTC metadata.
+pub(crate) fn read_metadata(mut new_photo: NewPhoto, default_timezone: &str) -> NewPhoto {
+    match new_photo.media_type {
+        MediaType::Photo => {
+            if let Some(exif) = utils::extract_exif(&new_photo.path) {
+                new_photo = new_photo
+                    .with_exif(exif)
+                    .with_default_timezone(default_timezone);
             }
-            Err(err) => {
-                progress.set_message(format!(
-                    "Error processing {}: {}",
-                    entry.path().display(),
-                    err
-                ));
-                None
+        }
+        MediaType::Video => {
+            if let Some(track_info) = utils::extract_track_info(&new_photo.path) {
+                new_photo = new_photo
+                    .with_track_info(track_info)
+                    .with_default_timezone(default_timezone);
             }
-        })
-        .collect();
+        }
+    }
 
-    let count = photo_repository
-        .insert_batch(photos)
-        .context("Failed to insert photos into repository")?;
+    if let Some(metadata) = utils::metadata::extract_metadata(&new_photo.path) {
+        new_photo = new_photo.with_metadata(metadata);
+    }
 
-    let duration = start.elapsed();
-    progress.finish_with_message(format!("✓ Scanned {} photos in {:.2?}", count, duration));
+    new_photo
+}
+
+/// Checks whether a photo is smaller than the minimum resolution.
+///
+/// Dimensions are read from the image header, so only formats decoded by the `image`
+/// crate are checked. Other files and unreadable headers are kept.
+fn is_below_min_resolution(path: &Path, options: &ScanOptions) -> bool {
+    if options.min_width == 0 && options.min_height == 0 {
+        return false;
+    }
+
+    if MediaFormat::from_path(path) != Some(MediaFormat::Image) {
+        return false;
+    }
+
+    image::image_dimensions(path)
+        .is_ok_and(|(width, height)| width < options.min_width || height < options.min_height)
+}
+
+/// Commits a batch of photos, reporting the batch as failed instead of aborting the scan.
+fn commit_batch(
+    photo_repository: &mut dyn PhotoRepository,
+    batch: Batch,
+    batch_number: usize,
+    summary: &mut ScanSummary,
+    progress: &dyn ProgressReporter,
+) {
+    let batch_size = batch.photos.len();
 
-    Ok(count)
+    match photo_repository.insert_batch(batch.photos) {
+        Ok(_) => {
+            summary.added += batch.added;
+            summary.updated += batch.updated;
+            summary.moved += batch.moved;
+            progress.set_message(format!(
+                "Committed batch {} ({} photos)",
+                batch_number, batch_size
+            ));
+        }
+        Err(err) => {
+            summary.failed += batch_size;
+            progress.report_error(format!(
+                "Failed to insert batch {} ({} photos): {:#}",
+                batch_number, batch_size, err
+            ));
+        }
+    }
+}
+
+/// Checks whether a directory has no entry, or cannot be read.
+pub(crate) fn is_empty_dir(path: &Path) -> bool {
+    fs::read_dir(path).map_or(true, |mut entries| entries.next().is_none())
+}
+
+/// Checks whether a path has the extension of a supported photo or video format.
+pub(crate) fn is_media_file(path: &Path) -> bool {
+    MediaFormat::from_path(path).is_some()
+}
+
+/// Returns the path prefix shared by every file under the scanned root.
+pub fn root_prefix(path: &str) -> String {
+    format!(
+        "{}{}",
+        path.trim_end_matches(MAIN_SEPARATOR),
+        MAIN_SEPARATOR
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        repositories::photo::repository::MockPhotoRepository,
+        models::Photo, repositories::photo::repository::MockPhotoRepository,
         utils::progress_reporter::NoOpProgressReporter,
     };
 
     use super::*;
+    use chrono::DateTime;
+    use mockall::predicate::eq;
     use std::fs::File;
     use tempfile::TempDir;
 
@@ -94,18 +695,749 @@ mod tests {
         File::create(temp_path.join("subdir/not_a_photo2.txt")).unwrap();
 
         let mut mock = MockPhotoRepository::new();
+        mock.expect_find_files().returning(|_| Ok(vec![]));
         mock.expect_insert_batch()
             .returning(|new_photos| Ok(new_photos.len()));
 
-        let photo_count = scan(
+        let summary = scan(
             temp_path.to_str().unwrap(),
             &mut mock,
-            false,
-            false,
+            &ScanOptions::default(),
             &NoOpProgressReporter,
         )
         .unwrap();
-        assert_eq!(photo_count, 5, "Expected 5 photos to be scanned");
+        assert_eq!(summary.added, 5, "Expected 5 photos to be scanned");
+    }
+
+    #[test]
+    fn test_should_scan_raw_and_video_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        File::create(temp_path.join("photo1.dng")).unwrap();
+        File::create(temp_path.join("photo2.webp")).unwrap();
+        File::create(temp_path.join("photo3.tiff")).unwrap();
+        File::create(temp_path.join("photo4.avif")).unwrap();
+        File::create(temp_path.join("video1.mp4")).unwrap();
+        File::create(temp_path.join("video2.MOV")).unwrap();
+
+        let mut mock = MockPhotoRepository::new();
+        mock.expect_find_files().returning(|_| Ok(vec![]));
+        mock.expect_insert_batch()
+            .withf(|new_photos| {
+                new_photos
+                    .iter()
+                    .filter(|photo| photo.media_type == MediaType::Video)
+                    .map(|photo| photo.file_name.as_str())
+                    .eq(["video1.mp4", "video2.MOV"])
+            })
+            .times(1)
+            .returning(|new_photos| Ok(new_photos.len()));
+
+        let summary = scan(
+            temp_path.to_str().unwrap(),
+            &mut mock,
+            &ScanOptions {
+                with_exif: true,
+                ..Default::default()
+            },
+            &NoOpProgressReporter,
+        )
+        .unwrap();
+
+        assert_eq!(summary.added, 6);
+        assert_eq!(summary.failed, 0);
+    }
+
+    #[test]
+    fn test_should_apply_include_and_exclude_patterns() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        File::create(temp_path.join("photo1.jpg")).unwrap();
+        File::create(temp_path.join("photo2.png")).unwrap();
+        std::fs::create_dir(temp_path.join("@eaDir")).unwrap();
+        File::create(temp_path.join("@eaDir/photo1.jpg")).unwrap();
+        std::fs::create_dir(temp_path.join("Private")).unwrap();
+        File::create(temp_path.join("Private/photo3.jpg")).unwrap();
+
+        let mut mock = MockPhotoRepository::new();
+        mock.expect_find_files().returning(|_| Ok(vec![]));
+        mock.expect_insert_batch()
+            .withf(|new_photos| {
+                new_photos
+                    .iter()
+                    .map(|photo| photo.file_name.as_str())
+                    .eq(["photo1.jpg"])
+            })
+            .times(1)
+            .returning(|new_photos| Ok(new_photos.len()));
+
+        let summary = scan(
+            temp_path.to_str().unwrap(),
+            &mut mock,
+            &ScanOptions {
+                include: vec!["*.jpg".to_string()],
+                exclude: vec!["@eaDir".to_string(), "/Private/".to_string()],
+                ..Default::default()
+            },
+            &NoOpProgressReporter,
+        )
+        .unwrap();
+
+        assert_eq!(summary.added, 1);
+    }
+
+    #[test]
+    fn test_should_fail_when_pattern_is_invalid() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let mut mock = MockPhotoRepository::new();
+        mock.expect_find_files().returning(|_| Ok(vec![]));
+
+        let result = scan(
+            temp_dir.path().to_str().unwrap(),
+            &mut mock,
+            &ScanOptions {
+                exclude: vec!["photos/[".to_string()],
+                ..Default::default()
+            },
+            &NoOpProgressReporter,
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_should_respect_ignore_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        std::fs::write(
+            temp_path.join(IGNORE_FILE_NAME),
+            "Lightroom Previews/\n*.png\n",
+        )
+        .unwrap();
+        File::create(temp_path.join("photo1.jpg")).unwrap();
+        File::create(temp_path.join("photo2.png")).unwrap();
+        std::fs::create_dir(temp_path.join("Lightroom Previews")).unwrap();
+        File::create(temp_path.join("Lightroom Previews/preview.jpg")).unwrap();
+
+        let mut mock = MockPhotoRepository::new();
+        mock.expect_find_files().returning(|_| Ok(vec![]));
+        mock.expect_insert_batch()
+            .withf(|new_photos| {
+                new_photos
+                    .iter()
+                    .map(|photo| photo.file_name.as_str())
+                    .eq(["photo1.jpg"])
+            })
+            .times(1)
+            .returning(|new_photos| Ok(new_photos.len()));
+
+        let summary = scan(
+            temp_path.to_str().unwrap(),
+            &mut mock,
+            &ScanOptions::default(),
+            &NoOpProgressReporter,
+        )
+        .unwrap();
+
+        assert_eq!(summary.added, 1);
+    }
+
+    #[test]
+    fn test_should_skip_files_below_minimum_size_and_resolution() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        image::RgbImage::new(64, 64)
+            .save(temp_path.join("photo1.png"))
+            .unwrap();
+        image::RgbImage::new(16, 16)
+            .save(temp_path.join("thumbnail.png"))
+            .unwrap();
+        File::create(temp_path.join("empty.jpg")).unwrap();
+
+        let mut mock = MockPhotoRepository::new();
+        mock.expect_find_files().returning(|_| Ok(vec![]));
+        mock.expect_insert_batch()
+            .withf(|new_photos| {
+                new_photos
+                    .iter()
+                    .map(|photo| photo.file_name.as_str())
+                    .eq(["photo1.png"])
+            })
+            .times(1)
+            .returning(|new_photos| Ok(new_photos.len()));
+
+        let summary = scan(
+            temp_path.to_str().unwrap(),
+            &mut mock,
+            &ScanOptions {
+                min_file_size: 1,
+                min_width: 32,
+                min_height: 32,
+                ..Default::default()
+            },
+            &NoOpProgressReporter,
+        )
+        .unwrap();
+
+        assert_eq!(summary.added, 1);
+        assert_eq!(summary.skipped, 2);
+    }
+
+    #[test]
+    fn test_should_attach_photos_to_library() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        std::fs::create_dir(temp_path.join("2024")).unwrap();
+        File::create(temp_path.join("2024/photo1.jpg")).unwrap();
+
+        let mut mock = MockPhotoRepository::new();
+        mock.expect_find_files().returning(|_| Ok(vec![]));
+        mock.expect_insert_batch()
+            .withf(|new_photos| {
+                new_photos[0].library_id == Some(1)
+                    && new_photos[0].relative_path.as_deref() == Some("2024/photo1.jpg")
+            })
+            .times(1)
+            .returning(|new_photos| Ok(new_photos.len()));
+
+        let summary = scan(
+            temp_path.join("2024").to_str().unwrap(),
+            &mut mock,
+            &ScanOptions {
+                library: Some(Library {
+                    id: 1,
+                    name: "photos".to_string(),
+                    root_path: temp_path.to_string_lossy().into_owned(),
+                    enabled: true,
+                    with_exif: false,
+                    with_hash: false,
+                    include: vec![],
+                    exclude: vec![],
+                    created_at: DateTime::default(),
+                    updated_at: DateTime::default(),
+                    write_xmp: false,
+                    prune: false,
+                }),
+                ..Default::default()
+            },
+            &NoOpProgressReporter,
+        )
+        .unwrap();
+
+        assert_eq!(summary.added, 1);
+    }
+
+    #[test]
+    fn test_should_move_photo_when_file_was_renamed() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+        let old_path = temp_path.join("old_name.jpg");
+        let new_path = temp_path.join("new_name.jpg");
+
+        std::fs::write(&new_path, b"Hello, World!").unwrap();
+
+        let mut mock = MockPhotoRepository::new();
+        let indexed_path = old_path.to_string_lossy().into_owned();
+        mock.expect_find_files().returning(move |_| {
+            Ok(vec![PhotoFile {
+                id: 1,
+                path: indexed_path.clone(),
+                file_name: "old_name.jpg".to_string(),
+                file_size: 13,
+                modified_at: DateTime::default(),
+                hash: Some(
+                    "288a86a79f20a3d6dccdca7713beaed178798296bdfa7913fa2a62d9727bf8f8".to_string(),
+                ),
+            }])
+        });
+        let expected_path = new_path.to_string_lossy().into_owned();
+        mock.expect_update_one()
+            .withf(move |id, updated_photo| {
+                *id == 1
+                    && updated_photo.path.as_deref() == Some(expected_path.as_str())
+                    && updated_photo.file_name.as_deref() == Some("new_name.jpg")
+            })
+            .times(1)
+            .returning(|_, _| Ok(Photo::default()));
+        mock.expect_insert_batch()
+            .withf(|new_photos| new_photos[0].hash.is_some())
+            .returning(|new_photos| Ok(new_photos.len()));
+        mock.expect_delete_many().times(0);
+
+        let summary = scan(
+            temp_path.to_str().unwrap(),
+            &mut mock,
+            &ScanOptions {
+                prune: true,
+                ..Default::default()
+            },
+            &NoOpProgressReporter,
+        )
+        .unwrap();
+
+        assert_eq!(
+            summary,
+            ScanSummary {
+                moved: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_should_not_count_moves_when_batch_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+        let old_path = temp_path.join("old_name.jpg");
+
+        std::fs::write(temp_path.join("new_name.jpg"), b"Hello, World!").unwrap();
+
+        let mut mock = MockPhotoRepository::new();
+        let indexed_path = old_path.to_string_lossy().into_owned();
+        mock.expect_find_files().returning(move |_| {
+            Ok(vec![PhotoFile {
+                id: 1,
+                path: indexed_path.clone(),
+                file_name: "new_name.jpg".to_string(),
+                file_size: 13,
+                modified_at: DateTime::default(),
+                hash: None,
+            }])
+        });
+        mock.expect_update_one()
+            .times(1)
+            .returning(|_, _| Ok(Photo::default()));
+        mock.expect_insert_batch()
+            .returning(|_| Err(anyhow::anyhow!("Connection lost")));
+
+        let summary = scan(
+            temp_path.to_str().unwrap(),
+            &mut mock,
+            &ScanOptions::default(),
+            &NoOpProgressReporter,
+        )
+        .unwrap();
+
+        assert_eq!(
+            summary,
+            ScanSummary {
+                failed: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_should_not_move_photo_when_original_still_exists() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+        let original_path = temp_path.join("original.jpg");
+
+        std::fs::write(&original_path, b"Hello, World!").unwrap();
+        std::fs::write(temp_path.join("copy.jpg"), b"Hello, World!").unwrap();
+
+        let mut mock = MockPhotoRepository::new();
+        let indexed_path = original_path.to_string_lossy().into_owned();
+        mock.expect_find_files().returning(move |_| {
+            Ok(vec![PhotoFile {
+                id: 1,
+                path: indexed_path.clone(),
+                file_name: "original.jpg".to_string(),
+                file_size: 13,
+                modified_at: DateTime::default(),
+                hash: None,
+            }])
+        });
+        mock.expect_update_one().times(0);
+        mock.expect_insert_batch()
+            .returning(|new_photos| Ok(new_photos.len()));
+
+        let summary = scan(
+            temp_path.to_str().unwrap(),
+            &mut mock,
+            &ScanOptions::default(),
+            &NoOpProgressReporter,
+        )
+        .unwrap();
+
+        assert_eq!(summary.added, 1);
+        assert_eq!(summary.updated, 1);
+        assert_eq!(summary.moved, 0);
+    }
+
+    #[test]
+    fn test_should_skip_unchanged_photos() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+        let photo_path = temp_path.join("photo.jpg");
+
+        std::fs::write(&photo_path, b"Hello, World!").unwrap();
+        let indexed_photo = NewPhoto::new(&photo_path).unwrap();
+
+        let mut mock = MockPhotoRepository::new();
+        mock.expect_find_files().returning(move |_| {
+            Ok(vec![PhotoFile {
+                id: 1,
+                path: indexed_photo.path.clone(),
+                file_name: indexed_photo.file_name.clone(),
+                file_size: indexed_photo.file_size,
+                modified_at: indexed_photo.modified_at,
+                hash: None,
+            }])
+        });
+        mock.expect_insert_batch().times(0);
+
+        let summary = scan(
+            temp_path.to_str().unwrap(),
+            &mut mock,
+            &ScanOptions {
+                with_hash: true,
+                prune: true,
+                ..Default::default()
+            },
+            &NoOpProgressReporter,
+        )
+        .unwrap();
+
+        assert_eq!(
+            summary,
+            ScanSummary {
+                unchanged: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_should_rescan_unchanged_photos_when_forced() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+        let photo_path = temp_path.join("photo.jpg");
+
+        std::fs::write(&photo_path, b"Hello, World!").unwrap();
+        let indexed_photo = NewPhoto::new(&photo_path).unwrap();
+
+        let mut mock = MockPhotoRepository::new();
+        mock.expect_find_files().returning(move |_| {
+            Ok(vec![PhotoFile {
+                id: 1,
+                path: indexed_photo.path.clone(),
+                file_name: indexed_photo.file_name.clone(),
+                file_size: indexed_photo.file_size,
+                modified_at: indexed_photo.modified_at,
+                hash: None,
+            }])
+        });
+        mock.expect_insert_batch()
+            .withf(|new_photos| new_photos.len() == 1 && new_photos[0].hash.is_some())
+            .returning(|new_photos| Ok(new_photos.len()));
+
+        let summary = scan(
+            temp_path.to_str().unwrap(),
+            &mut mock,
+            &ScanOptions {
+                with_hash: true,
+                force: true,
+                ..Default::default()
+            },
+            &NoOpProgressReporter,
+        )
+        .unwrap();
+
+        assert_eq!(summary.updated, 1);
+        assert_eq!(summary.unchanged, 0);
+    }
+
+    #[test]
+    fn test_should_prune_missing_photos() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+        let deleted_path = temp_path.join("deleted.jpg");
+        File::create(temp_path.join("kept.jpg")).unwrap();
+
+        let mut mock = MockPhotoRepository::new();
+        let indexed_path = deleted_path.to_string_lossy().into_owned();
+        mock.expect_find_files().returning(move |_| {
+            Ok(vec![PhotoFile {
+                id: 7,
+                path: indexed_path.clone(),
+                file_name: "deleted.jpg".to_string(),
+                file_size: 13,
+                modified_at: DateTime::default(),
+                hash: None,
+            }])
+        });
+        mock.expect_insert_batch()
+            .returning(|new_photos| Ok(new_photos.len()));
+        mock.expect_delete_many()
+            .with(eq(vec![7]))
+            .times(1)
+            .returning(|ids| Ok(ids.len()));
+
+        let summary = scan(
+            temp_path.to_str().unwrap(),
+            &mut mock,
+            &ScanOptions {
+                prune: true,
+                ..Default::default()
+            },
+            &NoOpProgressReporter,
+        )
+        .unwrap();
+
+        assert_eq!(summary.removed, 1);
+        assert_eq!(summary.missing, 0);
+    }
+
+    #[test]
+    fn test_should_not_prune_photos_when_root_is_empty() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        let mut mock = MockPhotoRepository::new();
+        let indexed_path = temp_path.join("deleted.jpg").to_string_lossy().into_owned();
+        mock.expect_find_files().returning(move |_| {
+            Ok(vec![PhotoFile {
+                id: 7,
+                path: indexed_path.clone(),
+                file_name: "deleted.jpg".to_string(),
+                file_size: 13,
+                modified_at: DateTime::default(),
+                hash: None,
+            }])
+        });
+        mock.expect_delete_many().times(0);
+
+        let summary = scan(
+            temp_path.to_str().unwrap(),
+            &mut mock,
+            &ScanOptions {
+                prune: true,
+                ..Default::default()
+            },
+            &NoOpProgressReporter,
+        )
+        .unwrap();
+
+        assert_eq!(summary.removed, 0);
+        assert_eq!(summary.missing, 1);
+    }
+
+    #[test]
+    fn test_should_scan_only_changed_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        File::create(temp_path.join("changed.jpg")).unwrap();
+        File::create(temp_path.join("untouched.jpg")).unwrap();
+        std::fs::create_dir(temp_path.join("album")).unwrap();
+        File::create(temp_path.join("album/photo.jpg")).unwrap();
+
+        let indexed_file = |id: i32, file_name: &str| PhotoFile {
+            id,
+            path: temp_path.join(file_name).to_string_lossy().into_owned(),
+            file_name: file_name.to_string(),
+            file_size: 13,
+            modified_at: DateTime::default(),
+            hash: None,
+        };
+        // Only the reported deleted file is pruned, the other missing file was not reported
+        let indexed_files = [
+            indexed_file(7, "deleted.jpg"),
+            indexed_file(8, "deleted.jpg.bak.jpg"),
+            indexed_file(9, "album/missing.jpg"),
+        ];
+
+        let mut mock = MockPhotoRepository::new();
+        mock.expect_find_files().returning(move |prefix| {
+            Ok(indexed_files
+                .iter()
+                .filter(|file| file.path.starts_with(prefix))
+                .cloned()
+                .collect())
+        });
+        let album_path = temp_path.join("album/photo.jpg");
+        let changed_path = temp_path.join("changed.jpg");
+        mock.expect_insert_batch()
+            .withf(move |new_photos| {
+                new_photos
+                    .iter()
+                    .map(|new_photo| Path::new(&new_photo.path))
+                    .eq([album_path.as_path(), changed_path.as_path()])
+            })
+            .times(1)
+            .returning(|new_photos| Ok(new_photos.len()));
+        mock.expect_delete_many()
+            .with(eq(vec![7]))
+            .times(1)
+            .returning(|ids| Ok(ids.len()));
+
+        let summary = scan_changes(
+            temp_path.to_str().unwrap(),
+            &[
+                temp_path.join("changed.jpg"),
+                temp_path.join("album"),
+                temp_path.join("deleted.jpg"),
+                PathBuf::from("/elsewhere/photo.jpg"),
+            ],
+            &mut mock,
+            &ScanOptions {
+                prune: true,
+                ..Default::default()
+            },
+            &NoOpProgressReporter,
+        )
+        .unwrap();
+
+        assert_eq!(
+            summary,
+            ScanSummary {
+                added: 2,
+                removed: 1,
+                missing: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_should_commit_photos_in_batches() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        for i in 0..5 {
+            File::create(temp_path.join(format!("photo{}.jpg", i))).unwrap();
+        }
+
+        let mut mock = MockPhotoRepository::new();
+        mock.expect_find_files().returning(|_| Ok(vec![]));
+        mock.expect_insert_batch()
+            .withf(|new_photos| new_photos.len() <= 2)
+            .times(3)
+            .returning(|new_photos| Ok(new_photos.len()));
+
+        let summary = scan(
+            temp_path.to_str().unwrap(),
+            &mut mock,
+            &ScanOptions {
+                batch_size: 2,
+                ..Default::default()
+            },
+            &NoOpProgressReporter,
+        )
+        .unwrap();
+
+        assert_eq!(summary.added, 5);
+    }
+
+    #[test]
+    fn test_should_keep_walk_order_when_scanning_in_parallel() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        for i in 0..20 {
+            File::create(temp_path.join(format!("photo{:02}.jpg", i))).unwrap();
+        }
+
+        let mut mock = MockPhotoRepository::new();
+        mock.expect_find_files().returning(|_| Ok(vec![]));
+        mock.expect_insert_batch()
+            .withf(|new_photos| new_photos.is_sorted_by_key(|photo| &photo.path))
+            .times(1)
+            .returning(|new_photos| Ok(new_photos.len()));
+
+        let summary = scan(
+            temp_path.to_str().unwrap(),
+            &mut mock,
+            &ScanOptions {
+                workers: 4,
+                ..Default::default()
+            },
+            &NoOpProgressReporter,
+        )
+        .unwrap();
+
+        assert_eq!(summary.added, 20);
+    }
+
+    #[test]
+    fn test_should_continue_when_a_batch_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        for i in 0..5 {
+            File::create(temp_path.join(format!("photo{}.jpg", i))).unwrap();
+        }
+
+        let mut mock = MockPhotoRepository::new();
+        mock.expect_find_files().returning(|_| Ok(vec![]));
+        let mut calls = 0;
+        mock.expect_insert_batch()
+            .times(3)
+            .returning(move |new_photos| {
+                calls += 1;
+                if calls == 1 {
+                    Err(anyhow::anyhow!("Too many bind parameters"))
+                } else {
+                    Ok(new_photos.len())
+                }
+            });
+
+        let summary = scan(
+            temp_path.to_str().unwrap(),
+            &mut mock,
+            &ScanOptions {
+                batch_size: 2,
+                ..Default::default()
+            },
+            &NoOpProgressReporter,
+        )
+        .unwrap();
+
+        assert_eq!(summary.added, 3);
+        assert_eq!(summary.failed, 2);
+    }
+
+    #[test]
+    fn test_should_compute_perceptual_hash_with_file_hash() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        image::RgbImage::from_fn(64, 48, |x, _| image::Rgb([(x * 4) as u8, 0, 0]))
+            .save(temp_path.join("photo.png"))
+            .unwrap();
+        File::create(temp_path.join("clip.mp4")).unwrap();
+
+        let mut mock = MockPhotoRepository::new();
+        mock.expect_find_files().returning(|_| Ok(vec![]));
+        mock.expect_insert_batch()
+            .withf(|new_photos| {
+                new_photos.iter().all(|photo| {
+                    photo.hash.is_some()
+                        && photo.perceptual_hash.is_some() == (photo.file_name == "photo.png")
+                })
+            })
+            .times(1)
+            .returning(|new_photos| Ok(new_photos.len()));
+
+        let summary = scan(
+            temp_path.to_str().unwrap(),
+            &mut mock,
+            &ScanOptions {
+                with_hash: true,
+                ..Default::default()
+            },
+            &NoOpProgressReporter,
+        )
+        .unwrap();
+
+        assert_eq!(summary.added, 2);
     }
 
     #[test]
@@ -1,13 +1,15 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow, bail};
 use chrono::{DateTime, Utc};
 
 use crate::{
-    models::{CityName, CountryName, Face, PaginatedPhotos, PaginationFilter, Person, Photo},
+    models::{
+        CityName, CountryName, Face, MediaType, PaginatedPhotos, PaginationFilter, Person, Photo,
+    },
     repositories::{
         FaceRepository, FindPersonFilters, GeoRepository, PersonMatchMode, PersonRepository,
-        PhotoFindFilters, PhotoRepository, face::filters::FaceFindFilters,
+        PhotoFindFilters, PhotoRepository, ValueRange, face::filters::FaceFindFilters,
     },
-    services::embedders::text::TextEmbedder,
+    services::embedders::{image::ImageEmbedder, text::TextEmbedder},
 };
 
 #[derive(Debug)]
@@ -29,9 +31,20 @@ pub struct PhotoSearchOptions {
     pub persons: Vec<Person>,
 }
 
+/// Photo whose similar photos are searched.
+#[derive(Debug, Clone)]
+pub enum SimilarTo {
+    /// An indexed photo, compared by its computed embedding.
+    Photo(i32),
+    /// An image file, embedded when searching.
+    Image(String),
+}
+
 #[derive(Default)]
 pub struct PhotoSearchParams {
     pub text: Option<String>,
+    /// Ranks photos by their similarity with a photo instead of a text.
+    pub similar_to: Option<SimilarTo>,
     pub threshold: Option<f32>,
 
     pub country: Option<String>,
@@ -45,6 +58,16 @@ pub struct PhotoSearchParams {
     pub person_ids: Option<Vec<i32>>,
     pub person_match_mode: Option<PersonMatchMode>,
 
+    pub media_type: Option<MediaType>,
+
+    pub aperture: ValueRange<f32>,
+    pub exposure_time: ValueRange<f32>,
+    pub iso: ValueRange<i32>,
+    pub focal_length: ValueRange<f32>,
+    pub focal_length_35mm: ValueRange<i32>,
+    pub altitude: ValueRange<f32>,
+    pub flash: Option<bool>,
+
     pub page: u32,
     pub per_page: u32,
 }
@@ -54,39 +77,56 @@ pub struct PhotoSearchService<
     GR: GeoRepository,
     PR2: PersonRepository,
     FR: FaceRepository,
-    E: TextEmbedder,
 > {
     photo_repository: PR,
     geo_repository: GR,
     person_repository: PR2,
     face_repository: FR,
-    text_embedder: E,
+    /// Id of the model whose embeddings are searched.
+    embedding_model: String,
+    text_embedder: Option<Box<dyn TextEmbedder + Send + Sync>>,
+    image_embedder: Option<Box<dyn ImageEmbedder + Send + Sync>>,
 }
 
-impl<
-    PR: PhotoRepository,
-    GR: GeoRepository,
-    PR2: PersonRepository,
-    FR: FaceRepository,
-    E: TextEmbedder,
-> PhotoSearchService<PR, GR, PR2, FR, E>
+impl<PR: PhotoRepository, GR: GeoRepository, PR2: PersonRepository, FR: FaceRepository>
+    PhotoSearchService<PR, GR, PR2, FR>
 {
     pub fn new(
         photo_repository: PR,
         geo_repository: GR,
         person_repository: PR2,
         face_repository: FR,
-        text_embedder: E,
+        embedding_model: &str,
     ) -> Self {
         Self {
             photo_repository,
             geo_repository,
             person_repository,
             face_repository,
-            text_embedder,
+            embedding_model: embedding_model.to_string(),
+            text_embedder: None,
+            image_embedder: None,
         }
     }
 
+    /// Sets the embedder of the texts whose matching photos are searched.
+    pub fn with_text_embedder(
+        mut self,
+        text_embedder: Box<dyn TextEmbedder + Send + Sync>,
+    ) -> Self {
+        self.text_embedder = Some(text_embedder);
+        self
+    }
+
+    /// Sets the embedder of the image files whose similar photos are searched.
+    pub fn with_image_embedder(
+        mut self,
+        image_embedder: Box<dyn ImageEmbedder + Send + Sync>,
+    ) -> Self {
+        self.image_embedder = Some(image_embedder);
+        self
+    }
+
     /// Returns available search options (cities, countries, persons) based on existing photos.
     pub fn get_search_options(&mut self) -> Result<PhotoSearchOptions> {
         let country_ids = self.photo_repository.find_country_ids()?;
@@ -189,13 +229,30 @@ impl<
     pub fn search(&mut self, search_params: PhotoSearchParams) -> Result<PaginatedPhotos> {
         let mut find_filters = PhotoFindFilters::default();
 
-        if let Some(text) = search_params.text {
-            let text_embedding = self
-                .text_embedder
-                .embed(&text)
-                .context("Failed to create text embedding")?;
-
-            find_filters.text_embedding = Some(text_embedding);
+        match (search_params.text, search_params.similar_to) {
+            (Some(_), Some(_)) => bail!("Cannot search by text and by similar photo at once"),
+            (Some(text), None) => {
+                let text_embedder = self
+                    .text_embedder
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("No text embedder to search photos by text"))?;
+                let text_embedding = text_embedder
+                    .embed(&text)
+                    .context("Failed to create text embedding")?;
+
+                find_filters.query_embedding = Some(text_embedding);
+                find_filters.embedding_model = Some(text_embedder.model_id().to_string());
+            }
+            (None, Some(similar_to)) => {
+                let (embedding, model) = self.embed_similar_to(&similar_to)?;
+
+                find_filters.query_embedding = Some(embedding);
+                find_filters.embedding_model = Some(model);
+                if let SimilarTo::Photo(id) = similar_to {
+                    find_filters.exclude_id = Some(id);
+                }
+            }
+            (None, None) => {}
         }
 
         find_filters.threshold = search_params.threshold;
@@ -230,6 +287,14 @@ impl<
 
         find_filters.person_ids = search_params.person_ids;
         find_filters.person_match_mode = search_params.person_match_mode;
+        find_filters.media_type = search_params.media_type;
+        find_filters.aperture = search_params.aperture;
+        find_filters.exposure_time = search_params.exposure_time;
+        find_filters.iso = search_params.iso;
+        find_filters.focal_length = search_params.focal_length;
+        find_filters.focal_length_35mm = search_params.focal_length_35mm;
+        find_filters.altitude = search_params.altitude;
+        find_filters.flash = search_params.flash;
 
         let pagination_filter = PaginationFilter {
             page: search_params.page as i64,
@@ -240,6 +305,37 @@ impl<
             .find(pagination_filter, find_filters)
             .context("Failed to find photos")
     }
+
+    /// Returns the embedding of the photo whose similar photos are searched, along with the
+    /// model which computed it.
+    fn embed_similar_to(&mut self, similar_to: &SimilarTo) -> Result<(Vec<f32>, String)> {
+        match similar_to {
+            SimilarTo::Photo(id) => {
+                // Indexed photos are compared with the embeddings of the searched model
+                let model = self.embedding_model.clone();
+                let embedding = self
+                    .photo_repository
+                    .find_embedding(*id, model.clone())
+                    .context("Failed to find photo embedding")?
+                    .ok_or_else(|| anyhow!("No {} embedding for photo {}", model, id))?;
+
+                Ok((embedding, model))
+            }
+            SimilarTo::Image(path) => {
+                let image_embedder = self
+                    .image_embedder
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("No image embedder to search similar photos"))?;
+                let embedding = image_embedder
+                    .embed(&vec![path.clone()])
+                    .context("Failed to create image embedding")?
+                    .pop()
+                    .ok_or_else(|| anyhow!("No embedding computed for image: {}", path))?;
+
+                Ok((embedding, image_embedder.model_id().to_string()))
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -249,7 +345,7 @@ mod tests {
             face::repository::MockFaceRepository, geo::MockGeoRepository,
             person::repository::MockPersonRepository, photo::repository::MockPhotoRepository,
         },
-        services::embedders::text::MockTextEmbedder,
+        services::embedders::{image::MockImageEmbedder, text::MockTextEmbedder},
     };
 
     use super::*;
@@ -273,8 +369,9 @@ mod tests {
             geo_repository,
             person_repository,
             face_repository,
-            text_embedder,
-        );
+            "clip-vit-base-patch32",
+        )
+        .with_text_embedder(Box::new(text_embedder));
         let result = service.search(PhotoSearchParams {
             text: Some("test".to_string()),
             ..PhotoSearchParams::default()
@@ -288,7 +385,6 @@ mod tests {
 
     #[test]
     fn test_should_return_error_when_repository_fails() {
-        let text_embedder = MockTextEmbedder::new();
         let geo_repository = MockGeoRepository::new();
         let mut repo = MockPhotoRepository::new();
         repo.expect_find()
@@ -301,10 +397,180 @@ mod tests {
             geo_repository,
             person_repository,
             face_repository,
-            text_embedder,
+            "clip-vit-base-patch32",
         );
         let result = service.search(PhotoSearchParams::default());
 
         assert_eq!(result.unwrap_err().to_string(), "Failed to find photos");
     }
+
+    #[test]
+    fn test_should_search_embeddings_of_text_embedder_model() {
+        let mut text_embedder = MockTextEmbedder::new();
+        text_embedder
+            .expect_embed()
+            .with(eq("beach"))
+            .returning(|_| Ok(vec![0.5, 0.5]));
+        text_embedder
+            .expect_model_id()
+            .return_const("clip-vit-large-patch14".to_string());
+
+        let mut repo = MockPhotoRepository::new();
+        repo.expect_find()
+            .withf(|_, f: &PhotoFindFilters| {
+                f.query_embedding == Some(vec![0.5, 0.5])
+                    && f.embedding_model.as_deref() == Some("clip-vit-large-patch14")
+            })
+            .times(1)
+            .returning(|_, __| {
+                Ok(PaginatedPhotos {
+                    items: vec![],
+                    total: 0,
+                    page: 1,
+                    per_page: 20,
+                    total_pages: 0,
+                })
+            });
+
+        let mut service = PhotoSearchService::new(
+            repo,
+            MockGeoRepository::new(),
+            MockPersonRepository::new(),
+            MockFaceRepository::new(),
+            "clip-vit-base-patch32",
+        )
+        .with_text_embedder(Box::new(text_embedder));
+        let result = service.search(PhotoSearchParams {
+            text: Some("beach".to_string()),
+            ..PhotoSearchParams::default()
+        });
+
+        assert!(result.is_ok());
+    }
+
+    fn empty_page() -> PaginatedPhotos {
+        PaginatedPhotos {
+            items: vec![],
+            total: 0,
+            page: 1,
+            per_page: 20,
+            total_pages: 0,
+        }
+    }
+
+    #[test]
+    fn test_should_search_photos_similar_to_photo_except_itself_without_text_embedder() {
+        let mut repo = MockPhotoRepository::new();
+        repo.expect_find_embedding()
+            .with(eq(7), eq("clip-vit-base-patch32".to_string()))
+            .times(1)
+            .returning(|_, __| Ok(Some(vec![0.6, 0.8])));
+        repo.expect_find()
+            .withf(|_, f: &PhotoFindFilters| {
+                f.query_embedding == Some(vec![0.6, 0.8])
+                    && f.embedding_model.as_deref() == Some("clip-vit-base-patch32")
+                    && f.exclude_id == Some(7)
+                    && f.country_id == Some(3)
+            })
+            .times(1)
+            .returning(|_, __| Ok(empty_page()));
+
+        let mut service = PhotoSearchService::new(
+            repo,
+            MockGeoRepository::new(),
+            MockPersonRepository::new(),
+            MockFaceRepository::new(),
+            "clip-vit-base-patch32",
+        );
+        let result = service.search(PhotoSearchParams {
+            similar_to: Some(SimilarTo::Photo(7)),
+            country_id: Some(3),
+            ..PhotoSearchParams::default()
+        });
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_should_return_error_when_similar_photo_has_no_embedding() {
+        let mut repo = MockPhotoRepository::new();
+        repo.expect_find_embedding().returning(|_, __| Ok(None));
+        repo.expect_find().times(0);
+
+        let mut service = PhotoSearchService::new(
+            repo,
+            MockGeoRepository::new(),
+            MockPersonRepository::new(),
+            MockFaceRepository::new(),
+            "clip-vit-base-patch32",
+        );
+        let result = service.search(PhotoSearchParams {
+            similar_to: Some(SimilarTo::Photo(7)),
+            ..PhotoSearchParams::default()
+        });
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "No clip-vit-base-patch32 embedding for photo 7"
+        );
+    }
+
+    #[test]
+    fn test_should_search_photos_similar_to_image_file() {
+        let mut image_embedder = MockImageEmbedder::new();
+        image_embedder
+            .expect_embed()
+            .withf(|paths: &Vec<String>| *paths == vec!["/tmp/beach.jpg".to_string()])
+            .times(1)
+            .returning(|_| Ok(vec![vec![0.6, 0.8]]));
+        image_embedder
+            .expect_model_id()
+            .return_const("clip-vit-base-patch32".to_string());
+
+        let mut repo = MockPhotoRepository::new();
+        repo.expect_find()
+            .withf(|_, f: &PhotoFindFilters| {
+                f.query_embedding == Some(vec![0.6, 0.8])
+                    && f.embedding_model.as_deref() == Some("clip-vit-base-patch32")
+                    && f.exclude_id.is_none()
+            })
+            .times(1)
+            .returning(|_, __| Ok(empty_page()));
+
+        let mut service = PhotoSearchService::new(
+            repo,
+            MockGeoRepository::new(),
+            MockPersonRepository::new(),
+            MockFaceRepository::new(),
+            "clip-vit-base-patch32",
+        )
+        .with_image_embedder(Box::new(image_embedder));
+        let result = service.search(PhotoSearchParams {
+            similar_to: Some(SimilarTo::Image("/tmp/beach.jpg".to_string())),
+            ..PhotoSearchParams::default()
+        });
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_should_return_error_when_searching_by_text_and_similar_photo() {
+        let mut service = PhotoSearchService::new(
+            MockPhotoRepository::new(),
+            MockGeoRepository::new(),
+            MockPersonRepository::new(),
+            MockFaceRepository::new(),
+            "clip-vit-base-patch32",
+        );
+        let result = service.search(PhotoSearchParams {
+            text: Some("beach".to_string()),
+            similar_to: Some(SimilarTo::Photo(7)),
+            ..PhotoSearchParams::default()
+        });
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Cannot search by text and by similar photo at once"
+        );
+    }
 }
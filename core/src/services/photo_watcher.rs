@@ -0,0 +1,101 @@
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::mpsc::{Receiver, channel},
+    time::Duration,
+};
+
+use anyhow::{Context, Result, bail};
+use notify_debouncer_mini::{
+    DebounceEventResult, Debouncer, new_debouncer,
+    notify::{RecommendedWatcher, RecursiveMode},
+};
+
+use crate::services::photo_scanner::is_media_file;
+
+/// Watches a directory tree for created, modified, renamed and deleted photos and videos.
+///
+/// Events are debounced so that a burst of changes, such as a sync client copying
+/// a whole album, is reported as a single set of changed paths.
+pub struct PhotoWatcher {
+    _debouncer: Debouncer<RecommendedWatcher>,
+    receiver: Receiver<DebounceEventResult>,
+}
+
+impl PhotoWatcher {
+    pub fn new(path: &str, debounce: Duration) -> Result<Self> {
+        if !Path::new(path).exists() {
+            bail!("Watch root does not exist: {}", path);
+        }
+
+        let (sender, receiver) = channel();
+        let mut debouncer =
+            new_debouncer(debounce, sender).context("Failed to create file watcher")?;
+
+        debouncer
+            .watcher()
+            .watch(Path::new(path), RecursiveMode::Recursive)
+            .context(format!("Failed to watch directory: {}", path))?;
+
+        Ok(Self {
+            _debouncer: debouncer,
+            receiver,
+        })
+    }
+
+    /// Blocks until photos change under the watched root and returns the changed paths.
+    ///
+    /// Directories and paths that no longer exist are included as well, since renaming
+    /// or deleting a directory only reports the directory itself.
+    pub fn next_changes(&self) -> Result<Vec<PathBuf>> {
+        loop {
+            let events = self
+                .receiver
+                .recv()
+                .context("File watcher stopped")?
+                .context("Failed to watch for changes")?;
+
+            let changed_paths: BTreeSet<PathBuf> = events
+                .into_iter()
+                .map(|event| event.path)
+                .filter(|path| is_relevant_change(path))
+                .collect();
+
+            if !changed_paths.is_empty() {
+                return Ok(changed_paths.into_iter().collect());
+            }
+        }
+    }
+}
+
+fn is_relevant_change(path: &Path) -> bool {
+    is_media_file(path) || path.is_dir() || !path.exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_should_report_changed_photos() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        let watcher =
+            PhotoWatcher::new(temp_path.to_str().unwrap(), Duration::from_millis(100)).unwrap();
+
+        File::create(temp_path.join("not_a_photo.txt")).unwrap();
+        File::create(temp_path.join("photo1.jpg")).unwrap();
+
+        let changed_paths = watcher.next_changes().unwrap();
+
+        assert_eq!(changed_paths, vec![temp_path.join("photo1.jpg")]);
+    }
+
+    #[test]
+    fn test_should_fail_when_root_does_not_exist() {
+        assert!(PhotoWatcher::new("/does/not/exist", Duration::from_millis(100)).is_err());
+    }
+}
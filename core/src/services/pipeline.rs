@@ -0,0 +1,316 @@
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::Utc;
+use pgvector::Vector;
+
+use crate::{
+    config::{ErrorsConfig, FaceDetectionServerConfig, ScannerConfig},
+    database::DbPool,
+    models::{
+        Job, JobStage, Library, MediaType, NewJob, NewPhoto, NewPhotoEmbedding, Photo, UpdatedPhoto,
+    },
+    repositories::{
+        LibraryRepository, PgFaceRepository, PgLibraryRepository, PgPersonRepository,
+        PgPhotoErrorRepository, PgPhotoRepository, PhotoErrorRepository, PhotoRepository,
+    },
+    services::{
+        FaceDetectionService, FaceRecognitionService,
+        embedders::image::ImageEmbedder,
+        jobs::JobHandler,
+        photo_scanner::{self, ScanOptions},
+    },
+    utils::{self, progress_reporter::NoOpProgressReporter},
+};
+
+/// Stages run on each photo, in pipeline order.
+const PHOTO_STAGES: [JobStage; 4] = [
+    JobStage::Hash,
+    JobStage::Exif,
+    JobStage::Embed,
+    JobStage::DetectFaces,
+];
+
+/// Runs the jobs of the indexing pipeline against the database.
+///
+/// Scans index files without reading them, so that hashes and EXIF data are read by the
+/// per-photo stages, as enabled by the library of each photo.
+pub struct PipelineJobHandler<E: ImageEmbedder> {
+    pool: DbPool,
+    scanner_config: ScannerConfig,
+    face_detection_config: FaceDetectionServerConfig,
+    max_attempts: i32,
+    image_embedder: E,
+}
+
+impl<E: ImageEmbedder + Sync> PipelineJobHandler<E> {
+    pub fn new(
+        pool: DbPool,
+        scanner_config: &ScannerConfig,
+        face_detection_config: &FaceDetectionServerConfig,
+        errors_config: &ErrorsConfig,
+        image_embedder: E,
+    ) -> Self {
+        Self {
+            pool,
+            scanner_config: scanner_config.clone(),
+            face_detection_config: face_detection_config.clone(),
+            max_attempts: errors_config.max_attempts,
+            image_embedder,
+        }
+    }
+
+    fn scan(&self, library_id: i32) -> Result<Vec<NewJob>> {
+        let library = PgLibraryRepository::new(self.pool.clone())
+            .find_by_id(library_id)?
+            .ok_or_else(|| anyhow!("Library not found: {}", library_id))?;
+        if !library.enabled {
+            return Ok(Vec::new());
+        }
+
+        let mut photo_repository = PgPhotoRepository::new(self.pool.clone());
+        let root_directory = library.root_path.clone();
+        let start = Utc::now();
+
+        // An unmounted drive leaves the root missing or empty, so the scan is retried later
+        if photo_scanner::is_empty_dir(Path::new(&root_directory)) {
+            bail!("Library root is missing or empty: {}", root_directory);
+        }
+
+        photo_scanner::scan(
+            &root_directory,
+            &mut photo_repository,
+            &ScanOptions {
+                with_exif: false,
+                with_hash: false,
+                ..ScanOptions::for_library(library.clone(), &self.scanner_config)
+            },
+            &NoOpProgressReporter,
+        )?;
+
+        let changed_ids = photo_repository
+            .find_ids_indexed_since(&photo_scanner::root_prefix(&root_directory), start)
+            .context("Failed to fetch scanned photos")?;
+
+        Ok(changed_ids
+            .into_iter()
+            .flat_map(|photo_id| next_jobs(JobStage::Scan, photo_id, Some(&library)))
+            .collect())
+    }
+
+    fn hash(&self, photo: &Photo) -> Result<()> {
+        let hash = utils::compute_file_hash(&photo.path).context("Failed to hash file")?;
+        let perceptual_hash = utils::perceptual_hash::compute_perceptual_hash(&photo.path).ok();
+
+        PgPhotoRepository::new(self.pool.clone()).update_one(
+            photo.id,
+            UpdatedPhoto {
+                hash: Some(Some(hash)),
+                perceptual_hash: Some(perceptual_hash),
+                ..Default::default()
+            },
+        )?;
+
+        Ok(())
+    }
+
+    fn extract_metadata(&self, photo: &Photo, library: Option<&Library>) -> Result<()> {
+        let mut new_photo = NewPhoto::new(Path::new(&photo.path))?;
+        if let Some(library) = library {
+            new_photo = new_photo.with_library(library.id, Path::new(&library.root_path));
+        }
+
+        let mut new_photo =
+            photo_scanner::read_metadata(new_photo, &self.scanner_config.default_timezone);
+        // Hashes are read by the previous stage
+        new_photo.hash = photo.hash.clone();
+        new_photo.perceptual_hash = photo.perceptual_hash;
+
+        PgPhotoRepository::new(self.pool.clone()).insert_batch(vec![new_photo])?;
+
+        Ok(())
+    }
+
+    fn embed(&self, photo: &Photo) -> Result<()> {
+        let embedding = self
+            .image_embedder
+            .embed(&vec![photo.path.clone()])?
+            .pop()
+            .ok_or_else(|| anyhow!("No embedding computed for photo: {}", photo.path))?;
+
+        PgPhotoRepository::new(self.pool.clone()).save_embeddings(vec![NewPhotoEmbedding {
+            photo_id: photo.id,
+            model: self.image_embedder.model_id().to_string(),
+            embedding: Vector::from(embedding),
+        }])?;
+
+        Ok(())
+    }
+
+    fn detect_faces(&self, photo: &Photo) -> Result<()> {
+        // Faces are only detected once, as detecting them again would duplicate them
+        if photo.media_type == MediaType::Video || photo.face_detection_completed {
+            return Ok(());
+        }
+
+        FaceDetectionService::new(
+            PgPhotoRepository::new(self.pool.clone()),
+            PgFaceRepository::new(self.pool.clone()),
+            PgPhotoErrorRepository::new(self.pool.clone()),
+            NoOpProgressReporter,
+            &self.face_detection_config,
+            self.max_attempts,
+        )
+        .detect_faces_in_photo(photo.id, &photo.path)?;
+
+        Ok(())
+    }
+
+    fn recognize(&self) -> Result<()> {
+        FaceRecognitionService::new(
+            PgFaceRepository::new(self.pool.clone()),
+            PgPersonRepository::new(self.pool.clone()),
+            NoOpProgressReporter,
+            None,
+        )
+        .recognize_faces(false)?;
+
+        Ok(())
+    }
+
+    /// Loads the photo of a job along with its library, if any.
+    fn load_photo(&self, job: &Job) -> Result<(Photo, Option<Library>)> {
+        let photo_id = job
+            .photo_id
+            .ok_or_else(|| anyhow!("No photo for {} job {}", job.stage, job.id))?;
+        let photo = PgPhotoRepository::new(self.pool.clone())
+            .find_by_id(photo_id)?
+            .ok_or_else(|| anyhow!("Photo not found: {}", photo_id))?;
+
+        let library = match photo.library_id {
+            Some(library_id) => {
+                PgLibraryRepository::new(self.pool.clone()).find_by_id(library_id)?
+            }
+            None => None,
+        };
+
+        Ok((photo, library))
+    }
+}
+
+impl<E: ImageEmbedder + Sync> JobHandler for PipelineJobHandler<E> {
+    fn run(&self, job: &Job) -> Result<Vec<NewJob>> {
+        match job.stage {
+            JobStage::Scan => {
+                let library_id = job
+                    .library_id
+                    .ok_or_else(|| anyhow!("No library for scan job {}", job.id))?;
+                self.scan(library_id)
+            }
+            JobStage::Recognize => {
+                self.recognize()?;
+                Ok(Vec::new())
+            }
+            stage => {
+                let (photo, library) = self.load_photo(job)?;
+
+                let result = match stage {
+                    JobStage::Hash => self.hash(&photo),
+                    JobStage::Exif => self.extract_metadata(&photo, library.as_ref()),
+                    JobStage::Embed => self.embed(&photo),
+                    _ => self.detect_faces(&photo),
+                };
+
+                // Failures are also recorded on the photo, to be listed with other photo errors
+                let mut photo_error_repository = PgPhotoErrorRepository::new(self.pool.clone());
+                match result {
+                    Ok(()) => photo_error_repository.resolve(stage, vec![photo.id])?,
+                    Err(error) => {
+                        photo_error_repository.record(photo.id, stage, format!("{:#}", error))?;
+                        return Err(error);
+                    }
+                };
+
+                Ok(next_jobs(stage, photo.id, library.as_ref()))
+            }
+        }
+    }
+}
+
+/// Returns the jobs following a stage for a photo: the next per-photo stage enabled by
+/// its library, or face recognition once every per-photo stage ran.
+///
+/// Hashes and EXIF data are read for photos outside of any library.
+pub fn next_jobs(stage: JobStage, photo_id: i32, library: Option<&Library>) -> Vec<NewJob> {
+    let is_enabled = |stage: &JobStage| match stage {
+        JobStage::Hash => library.is_none_or(|library| library.with_hash),
+        JobStage::Exif => library.is_none_or(|library| library.with_exif),
+        _ => true,
+    };
+
+    // Scans are followed by the first per-photo stage
+    let start = PHOTO_STAGES
+        .iter()
+        .position(|&photo_stage| photo_stage == stage)
+        .map_or(0, |index| index + 1);
+    let next_stage = PHOTO_STAGES[start..].iter().find(|stage| is_enabled(stage));
+
+    match (stage, next_stage) {
+        (JobStage::Recognize, _) => Vec::new(),
+        (_, Some(&next_stage)) => vec![NewJob::photo(next_stage, photo_id)],
+        (_, None) => vec![NewJob::global(JobStage::Recognize)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library(with_hash: bool, with_exif: bool) -> Library {
+        Library {
+            id: 1,
+            name: "family".to_string(),
+            root_path: "/photos".to_string(),
+            enabled: true,
+            with_exif,
+            with_hash,
+            include: vec![],
+            exclude: vec![],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            write_xmp: false,
+            prune: false,
+        }
+    }
+
+    #[test]
+    fn test_should_chain_stages_enabled_by_library() {
+        let library = library(false, true);
+
+        assert_eq!(
+            next_jobs(JobStage::Scan, 7, Some(&library)),
+            vec![NewJob::photo(JobStage::Exif, 7)]
+        );
+        assert_eq!(
+            next_jobs(JobStage::Exif, 7, Some(&library)),
+            vec![NewJob::photo(JobStage::Embed, 7)]
+        );
+        assert_eq!(
+            next_jobs(JobStage::Scan, 7, None),
+            vec![NewJob::photo(JobStage::Hash, 7)]
+        );
+        assert_eq!(
+            next_jobs(JobStage::Hash, 7, None),
+            vec![NewJob::photo(JobStage::Exif, 7)]
+        );
+    }
+
+    #[test]
+    fn test_should_recognize_faces_after_detection() {
+        assert_eq!(
+            next_jobs(JobStage::DetectFaces, 7, None),
+            vec![NewJob::global(JobStage::Recognize)]
+        );
+        assert!(next_jobs(JobStage::Recognize, 7, None).is_empty());
+    }
+}
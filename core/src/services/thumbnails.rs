@@ -0,0 +1,460 @@
+use std::{
+    collections::HashSet,
+    fs,
+    io::{Cursor, ErrorKind},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use anyhow::{Context, Result, bail};
+use image::{DynamicImage, ImageFormat};
+use rayon::prelude::*;
+
+use crate::{
+    config::ThumbnailsConfig, imaging, models::PhotoFile, repositories::PhotoRepository,
+    utils::progress_reporter::ProgressReporter,
+};
+
+/// Number of photos whose thumbnails are generated in parallel between progress updates.
+const BATCH_SIZE: usize = 64;
+
+/// Thumbnails stored on disk as WebP files, named after the content hash of their photo so
+/// that they are generated again once the photo changes.
+#[derive(Debug, Clone)]
+pub struct ThumbnailCache {
+    cache_dir: PathBuf,
+    sizes: Vec<u32>,
+}
+
+impl ThumbnailCache {
+    pub fn new(config: &ThumbnailsConfig) -> Self {
+        let mut sizes = config.sizes.clone();
+        sizes.sort_unstable();
+        sizes.dedup();
+
+        Self {
+            cache_dir: config.cache_path(),
+            sizes,
+        }
+    }
+
+    /// Lengths of the shorter side of thumbnails, from the smallest.
+    pub fn sizes(&self) -> &[u32] {
+        &self.sizes
+    }
+
+    /// Returns the key of the thumbnails of a photo: its hash, or a hash of its path, size
+    /// and modification time when its content was not hashed.
+    pub fn key(photo: &PhotoFile) -> String {
+        match &photo.hash {
+            Some(hash) => hash.clone(),
+            None => blake3::hash(
+                format!(
+                    "{}:{}:{}",
+                    photo.path,
+                    photo.file_size,
+                    photo.modified_at.timestamp_micros()
+                )
+                .as_bytes(),
+            )
+            .to_hex()
+            .to_string(),
+        }
+    }
+
+    /// Returns the path of a thumbnail, spread over subdirectories named after the first
+    /// characters of its key.
+    pub fn path(&self, key: &str, size: u32) -> PathBuf {
+        self.cache_dir
+            .join(size.to_string())
+            .join(key.get(..2).unwrap_or(key))
+            .join(format!("{}.webp", key))
+    }
+
+    /// Returns the WebP thumbnail of a photo, generating its thumbnails when missing.
+    pub fn get(&self, photo: &PhotoFile, size: u32) -> Result<Vec<u8>> {
+        if !self.sizes.contains(&size) {
+            bail!("Thumbnail size is not configured: {}", size);
+        }
+
+        let path = self.path(&Self::key(photo), size);
+        if !path.exists() {
+            self.generate(photo)?;
+        }
+
+        fs::read(&path).context("Failed to read thumbnail")
+    }
+
+    /// Generates the missing thumbnails of a photo, decoding it once for all sizes, and
+    /// returns whether any was generated.
+    pub fn generate(&self, photo: &PhotoFile) -> Result<bool> {
+        let key = Self::key(photo);
+        let missing_sizes: Vec<u32> = self
+            .sizes
+            .iter()
+            .copied()
+            .filter(|&size| !self.path(&key, size).exists())
+            .collect();
+
+        let Some(&largest_size) = missing_sizes.last() else {
+            return Ok(false);
+        };
+
+        let image = imaging::load_image_scaled(&photo.path, largest_size)?;
+        for size in missing_sizes {
+            write_webp(&resize(&image, size), &self.path(&key, size))?;
+        }
+
+        Ok(true)
+    }
+
+    /// Removes the thumbnails whose key is not in `keys`, or whose size is no longer
+    /// configured, and returns the number of removed files.
+    ///
+    /// Only the files laid out as thumbnails are removed, leaving any other file in the
+    /// cache directory untouched.
+    pub fn retain(&self, keys: &HashSet<String>) -> Result<usize> {
+        let mut removed = 0;
+
+        for size_dir in read_dir(&self.cache_dir)? {
+            // Only directories named after a size are managed by the cache
+            let Some(size) = file_name(&size_dir)
+                .and_then(|name| name.parse::<u32>().ok())
+                .filter(|size| self.cache_dir.join(size.to_string()) == size_dir)
+            else {
+                continue;
+            };
+            let is_configured = self.sizes.contains(&size);
+
+            for prefix_dir in read_dir(&size_dir)? {
+                for path in read_dir(&prefix_dir)? {
+                    let Some(key) = file_name(&path)
+                        .and_then(|name| name.strip_suffix(".webp"))
+                        .filter(|key| is_key(key) && self.path(key, size) == path)
+                    else {
+                        continue;
+                    };
+                    if !path.is_file() {
+                        continue;
+                    }
+
+                    if !is_configured || !keys.contains(key) {
+                        fs::remove_file(&path).context("Failed to remove thumbnail")?;
+                        removed += 1;
+                    }
+                }
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+/// Returns whether a name is a thumbnail key: the hexadecimal BLAKE3 hash of a photo or of
+/// its path.
+fn is_key(name: &str) -> bool {
+    name.len() == 64
+        && name
+            .bytes()
+            .all(|byte| byte.is_ascii_digit() || (b'a'..=b'f').contains(&byte))
+}
+
+fn file_name(path: &Path) -> Option<&str> {
+    path.file_name().and_then(|name| name.to_str())
+}
+
+/// Lists the entries of a directory, which may not exist yet.
+fn read_dir(path: &Path) -> Result<Vec<PathBuf>> {
+    match fs::read_dir(path) {
+        Ok(entries) => Ok(entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .collect()),
+        Err(err) if matches!(err.kind(), ErrorKind::NotFound | ErrorKind::NotADirectory) => {
+            Ok(Vec::new())
+        }
+        Err(err) => Err(err).context("Failed to list thumbnails"),
+    }
+}
+
+/// Scales an image down so that its shorter side is `size` pixels long.
+fn resize(image: &DynamicImage, size: u32) -> DynamicImage {
+    let (width, height) = (image.width(), image.height());
+    let shorter_side = width.min(height);
+    if shorter_side <= size {
+        return image.clone();
+    }
+
+    let scale = size as f64 / shorter_side as f64;
+    image.thumbnail(
+        ((width as f64 * scale).round() as u32).max(1),
+        ((height as f64 * scale).round() as u32).max(1),
+    )
+}
+
+/// Writes a thumbnail next to its path under a temporary name, then moves it into place,
+/// so that readers never see partial files.
+fn write_webp(image: &DynamicImage, path: &Path) -> Result<()> {
+    let mut data = Vec::new();
+    DynamicImage::ImageRgb8(image.to_rgb8())
+        .write_to(&mut Cursor::new(&mut data), ImageFormat::WebP)
+        .context("Failed to encode thumbnail")?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).context("Failed to create thumbnail directory")?;
+    }
+    let temp_path = path.with_extension("webp.part");
+    fs::write(&temp_path, data).context("Failed to write thumbnail")?;
+    fs::rename(&temp_path, path).context("Failed to move thumbnail into place")?;
+
+    Ok(())
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ThumbnailSummary {
+    pub generated: usize,
+    /// Photos whose thumbnails were all already generated.
+    pub existing: usize,
+    pub failed: usize,
+    /// Thumbnails of photos no longer indexed, or of sizes no longer configured.
+    pub removed: usize,
+}
+
+/// Pre-generates the thumbnails of indexed photos and keeps the cache clean.
+pub struct ThumbnailService<PR: PhotoRepository, P: ProgressReporter> {
+    photo_repository: PR,
+    cache: ThumbnailCache,
+    progress_reporter: P,
+}
+
+impl<PR: PhotoRepository, P: ProgressReporter> ThumbnailService<PR, P> {
+    pub fn new(photo_repository: PR, cache: ThumbnailCache, progress_reporter: P) -> Self {
+        Self {
+            photo_repository,
+            cache,
+            progress_reporter,
+        }
+    }
+
+    /// Generates the missing thumbnails of all indexed photos on `workers` threads, or one
+    /// per CPU when 0, then removes the thumbnails of photos no longer indexed.
+    pub fn generate_all(&mut self, workers: usize) -> Result<ThumbnailSummary> {
+        let start = Instant::now();
+
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(workers)
+            .build()
+            .context("Failed to create thumbnail thread pool")?;
+
+        let photos = self
+            .photo_repository
+            .find_files("")
+            .context("Failed to fetch indexed photos")?;
+
+        let mut summary = ThumbnailSummary::default();
+        let cache = &self.cache;
+
+        for (batch_index, batch) in photos.chunks(BATCH_SIZE).enumerate() {
+            let results: Vec<Result<bool>> = thread_pool.install(|| {
+                batch
+                    .par_iter()
+                    .map(|photo| cache.generate(photo))
+                    .collect()
+            });
+
+            for (photo, result) in batch.iter().zip(results) {
+                match result {
+                    Ok(true) => summary.generated += 1,
+                    Ok(false) => summary.existing += 1,
+                    Err(err) => {
+                        self.progress_reporter.report_error(format!(
+                            "Failed to generate thumbnails of {}: {:#}",
+                            photo.path, err
+                        ));
+                        summary.failed += 1;
+                    }
+                }
+            }
+
+            self.progress_reporter.set_message(format!(
+                "Generating thumbnails: {} of {} photos",
+                (batch_index * BATCH_SIZE + batch.len()),
+                photos.len()
+            ));
+        }
+
+        let keys: HashSet<String> = photos.iter().map(ThumbnailCache::key).collect();
+        summary.removed = self
+            .cache
+            .retain(&keys)
+            .context("Failed to remove stale thumbnails")?;
+
+        let duration = start.elapsed();
+        self.progress_reporter.finish_with_message(format!(
+            "✓ Generated thumbnails of {} photos in {:.2?}",
+            summary.generated, duration
+        ));
+
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        repositories::photo::repository::MockPhotoRepository,
+        utils::progress_reporter::NoOpProgressReporter,
+    };
+    use chrono::Utc;
+    use image::RgbImage;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn cache(cache_dir: &Path) -> ThumbnailCache {
+        ThumbnailCache::new(&ThumbnailsConfig {
+            cache_dir: cache_dir.to_string_lossy().into_owned(),
+            sizes: vec![64, 16],
+        })
+    }
+
+    fn photo(id: i32, path: &Path, hash: Option<&str>) -> PhotoFile {
+        PhotoFile {
+            id,
+            path: path.to_string_lossy().into_owned(),
+            file_name: "photo.png".to_string(),
+            file_size: 100,
+            modified_at: Utc::now(),
+            hash: hash.map(str::to_string),
+        }
+    }
+
+    fn write_photo(path: &Path) {
+        DynamicImage::ImageRgb8(RgbImage::new(400, 300))
+            .save(path)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_should_generate_thumbnails_of_all_sizes_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let photo_path = temp_dir.path().join("photo.png");
+        write_photo(&photo_path);
+        let cache = cache(&temp_dir.path().join("cache"));
+        let photo = photo(1, &photo_path, Some("abcdef"));
+
+        assert!(cache.generate(&photo).unwrap());
+        assert!(!cache.generate(&photo).unwrap());
+
+        let thumbnail = image::load_from_memory(&cache.get(&photo, 16).unwrap()).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (21, 16));
+        assert!(temp_dir.path().join("cache/64/ab/abcdef.webp").exists());
+        assert!(cache.get(&photo, 32).is_err());
+    }
+
+    #[test]
+    fn test_should_key_thumbnails_by_hash() {
+        let temp_dir = TempDir::new().unwrap();
+        let photo_path = temp_dir.path().join("photo.png");
+
+        let hashed = photo(1, &photo_path, Some("abcdef"));
+        let not_hashed = photo(1, &photo_path, None);
+        let mut modified = not_hashed.clone();
+        modified.file_size += 1;
+
+        assert_eq!(ThumbnailCache::key(&hashed), "abcdef");
+        assert_eq!(ThumbnailCache::key(&not_hashed).len(), 64);
+        assert_ne!(
+            ThumbnailCache::key(&not_hashed),
+            ThumbnailCache::key(&modified)
+        );
+    }
+
+    fn key(name: &str) -> String {
+        blake3::hash(name.as_bytes()).to_hex().to_string()
+    }
+
+    #[test]
+    fn test_should_generate_missing_thumbnails_and_remove_stale_ones() {
+        let temp_dir = TempDir::new().unwrap();
+        let photo_path = temp_dir.path().join("photo.png");
+        write_photo(&photo_path);
+        let cache_dir = temp_dir.path().join("cache");
+
+        let stale_path = cache(&cache_dir).path(&key("deleted"), 16);
+        fs::create_dir_all(stale_path.parent().unwrap()).unwrap();
+        fs::write(&stale_path, b"stale").unwrap();
+
+        let photos = vec![
+            photo(1, &photo_path, Some(&key("photo"))),
+            photo(
+                2,
+                &temp_dir.path().join("missing.png"),
+                Some(&key("missing")),
+            ),
+        ];
+        let mut repository = MockPhotoRepository::new();
+        repository
+            .expect_find_files()
+            .withf(|path_prefix| path_prefix.is_empty())
+            .returning(move |_| Ok(photos.clone()));
+
+        let mut service =
+            ThumbnailService::new(repository, cache(&cache_dir), NoOpProgressReporter);
+        let summary = service.generate_all(2).unwrap();
+
+        assert_eq!(
+            summary,
+            ThumbnailSummary {
+                generated: 1,
+                existing: 0,
+                failed: 1,
+                removed: 1,
+            }
+        );
+        assert!(!stale_path.exists());
+        assert!(cache(&cache_dir).path(&key("photo"), 64).exists());
+    }
+
+    #[test]
+    fn test_should_only_remove_files_laid_out_as_thumbnails() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache_dir = temp_dir.path().join("cache");
+        let cache = cache(&cache_dir);
+        let stale_key = key("deleted");
+
+        let stale_path = cache.path(&stale_key, 16);
+        let unrelated_paths = [
+            cache_dir
+                .join("16")
+                .join(&stale_key[..2])
+                .join("notes.webp"),
+            cache_dir
+                .join("16")
+                .join(&stale_key[..2])
+                .join(format!("{}.webp.part", stale_key)),
+            cache_dir
+                .join("16")
+                .join("zz")
+                .join(format!("{}.webp", stale_key)),
+            cache_dir
+                .join("016")
+                .join(&stale_key[..2])
+                .join(format!("{}.webp", stale_key)),
+            cache_dir
+                .join("backup")
+                .join(&stale_key[..2])
+                .join(format!("{}.webp", stale_key)),
+        ];
+        for path in unrelated_paths.iter().chain([&stale_path]) {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, b"data").unwrap();
+        }
+
+        let removed = cache.retain(&HashSet::new()).unwrap();
+
+        assert_eq!(removed, 1);
+        assert!(!stale_path.exists());
+        assert!(unrelated_paths.iter().all(|path| path.exists()));
+    }
+}
@@ -0,0 +1,299 @@
+use std::{fs, io::ErrorKind, path::Path};
+
+use anyhow::{Context, Result, bail};
+
+use crate::{
+    imaging,
+    models::{Library, SidecarMetadata},
+    repositories::PhotoRepository,
+    utils::{
+        metadata::{MetadataRegion, PhotoMetadata},
+        progress_reporter::ProgressReporter,
+        xmp,
+    },
+};
+
+#[derive(Debug, Default, PartialEq)]
+pub struct XmpExportSummary {
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub failed: usize,
+    /// Sidecars created or updated, or that would be in a dry run.
+    pub changed_paths: Vec<String>,
+}
+
+/// Change made to a sidecar by an export.
+#[derive(Debug, PartialEq)]
+enum SidecarChange {
+    Created,
+    Updated,
+    Unchanged,
+}
+
+/// Writes metadata curated in picasa-rs back to XMP sidecars, so that other tools see
+/// the people, keywords and places of photos.
+///
+/// Only sidecars are written: the photos themselves are never modified.
+pub struct XmpExportService<PR: PhotoRepository, P: ProgressReporter> {
+    photo_repository: PR,
+    progress_reporter: P,
+}
+
+impl<PR: PhotoRepository, P: ProgressReporter> XmpExportService<PR, P> {
+    pub fn new(photo_repository: PR, progress_reporter: P) -> Self {
+        Self {
+            photo_repository,
+            progress_reporter,
+        }
+    }
+
+    /// Writes or merges the sidecars of the photos of a library that opted in.
+    ///
+    /// In a dry run, sidecars are compared with what would be written without being
+    /// modified.
+    pub fn export(&mut self, library: &Library, dry_run: bool) -> Result<XmpExportSummary> {
+        if !library.write_xmp {
+            bail!(
+                "XMP write-back is not enabled for library: {}",
+                library.name
+            );
+        }
+
+        let photos = self
+            .photo_repository
+            .find_sidecar_metadata(library.id)
+            .context("Failed to retrieve photo metadata")?;
+
+        let mut summary = XmpExportSummary::default();
+
+        for photo in photos {
+            let metadata = convert_sidecar_metadata(&photo);
+            if metadata.is_empty() {
+                summary.unchanged += 1;
+                continue;
+            }
+
+            let sidecar_path = xmp::sidecar_path(Path::new(&photo.path));
+            match export_sidecar(&sidecar_path, &metadata, dry_run) {
+                Ok(SidecarChange::Unchanged) => summary.unchanged += 1,
+                Ok(change) => {
+                    if change == SidecarChange::Created {
+                        summary.created += 1;
+                    } else {
+                        summary.updated += 1;
+                    }
+                    summary
+                        .changed_paths
+                        .push(sidecar_path.to_string_lossy().into_owned());
+                }
+                Err(err) => {
+                    self.progress_reporter.report_error(format!(
+                        "Failed to export sidecar {}: {:#}",
+                        sidecar_path.display(),
+                        err
+                    ));
+                    summary.failed += 1;
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+}
+
+/// Converts face boxes to regions normalized to the displayed image dimensions. Faces of
+/// photos without known dimensions are left out.
+fn convert_sidecar_metadata(photo: &SidecarMetadata) -> PhotoMetadata {
+    let dimensions = photo
+        .image_width
+        .zip(photo.image_height)
+        .filter(|(width, height)| *width > 0 && *height > 0)
+        .map(|(width, height)| imaging::displayed_dimensions(width, height, photo.orientation));
+
+    let regions = match dimensions {
+        Some((width, height)) => photo
+            .faces
+            .iter()
+            .map(|face| MetadataRegion {
+                name: face.person_name.clone(),
+                left: face.bbox_x as f64 / width as f64,
+                top: face.bbox_y as f64 / height as f64,
+                width: face.bbox_width as f64 / width as f64,
+                height: face.bbox_height as f64 / height as f64,
+                applied_to: Some((width as u32, height as u32)),
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+
+    PhotoMetadata {
+        keywords: photo.keywords.clone(),
+        city: photo.city.clone(),
+        country: photo.country.clone(),
+        regions,
+        ..Default::default()
+    }
+}
+
+fn export_sidecar(
+    sidecar_path: &Path,
+    metadata: &PhotoMetadata,
+    dry_run: bool,
+) -> Result<SidecarChange> {
+    let existing = match fs::read_to_string(sidecar_path) {
+        Ok(existing) => Some(existing),
+        Err(error) if error.kind() == ErrorKind::NotFound => None,
+        Err(error) => return Err(error).context("Failed to read sidecar"),
+    };
+
+    let packet =
+        xmp::write(existing.as_deref(), metadata).context("Failed to merge sidecar metadata")?;
+
+    let change = match &existing {
+        None => SidecarChange::Created,
+        Some(existing) if *existing == packet => return Ok(SidecarChange::Unchanged),
+        Some(_) => SidecarChange::Updated,
+    };
+
+    if !dry_run {
+        // Written next to the sidecar then renamed, so that an interrupted export
+        // never leaves a truncated sidecar
+        let temp_path = sidecar_path.with_extension("xmp.tmp");
+        fs::write(&temp_path, packet).context("Failed to write sidecar")?;
+        fs::rename(&temp_path, sidecar_path).context("Failed to write sidecar")?;
+    }
+
+    Ok(change)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::SidecarFace,
+        repositories::photo::repository::MockPhotoRepository,
+        utils::progress_reporter::{MockProgressReporter, NoOpProgressReporter},
+    };
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn library(write_xmp: bool) -> Library {
+        Library {
+            id: 1,
+            name: "photos".to_string(),
+            root_path: "/mnt/photos".to_string(),
+            enabled: true,
+            with_exif: false,
+            with_hash: false,
+            include: vec![],
+            exclude: vec![],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            write_xmp,
+            prune: false,
+        }
+    }
+
+    fn repository(photo_path: &Path) -> MockPhotoRepository {
+        let photo_path = photo_path.to_string_lossy().into_owned();
+        let mut repository = MockPhotoRepository::new();
+        repository
+            .expect_find_sidecar_metadata()
+            .returning(move |_| {
+                Ok(vec![SidecarMetadata {
+                    photo_id: 1,
+                    path: photo_path.clone(),
+                    image_width: Some(400),
+                    image_height: Some(300),
+                    orientation: None,
+                    keywords: vec!["Beach".to_string()],
+                    city: Some("Nice".to_string()),
+                    country: Some("France".to_string()),
+                    faces: vec![SidecarFace {
+                        person_name: "Alice".to_string(),
+                        bbox_x: 100,
+                        bbox_y: 60,
+                        bbox_width: 40,
+                        bbox_height: 30,
+                    }],
+                }])
+            });
+        repository
+    }
+
+    #[test]
+    fn test_should_write_sidecar_without_touching_photo() {
+        let temp_dir = TempDir::new().unwrap();
+        let photo_path = temp_dir.path().join("photo.jpg");
+        fs::write(&photo_path, b"image bytes").unwrap();
+
+        let mut service = XmpExportService::new(repository(&photo_path), NoOpProgressReporter);
+        let summary = service.export(&library(true), false).unwrap();
+
+        assert_eq!(summary.created, 1);
+        assert_eq!(fs::read(&photo_path).unwrap(), b"image bytes");
+
+        let sidecar = xmp::read_sidecar(&photo_path).unwrap();
+        assert_eq!(sidecar.keywords, vec!["Beach"]);
+        assert_eq!(sidecar.city.as_deref(), Some("Nice"));
+        assert_eq!(sidecar.regions[0].name, "Alice");
+        assert!((sidecar.regions[0].left - 0.25).abs() < 1e-6);
+        assert!((sidecar.regions[0].top - 0.2).abs() < 1e-6);
+
+        let summary = service.export(&library(true), false).unwrap();
+        assert_eq!(summary.unchanged, 1);
+    }
+
+    #[test]
+    fn test_should_not_write_sidecar_in_dry_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let photo_path = temp_dir.path().join("photo.jpg");
+        fs::write(&photo_path, b"image bytes").unwrap();
+
+        let mut service = XmpExportService::new(repository(&photo_path), NoOpProgressReporter);
+        let summary = service.export(&library(true), true).unwrap();
+
+        let sidecar_path = temp_dir.path().join("photo.jpg.xmp");
+        assert_eq!(summary.created, 1);
+        assert_eq!(
+            summary.changed_paths,
+            vec![sidecar_path.to_string_lossy().into_owned()]
+        );
+        assert!(!sidecar_path.exists());
+    }
+
+    #[test]
+    fn test_should_not_overwrite_malformed_sidecar() {
+        let temp_dir = TempDir::new().unwrap();
+        let photo_path = temp_dir.path().join("photo.jpg");
+        let sidecar_path = temp_dir.path().join("photo.xmp");
+        fs::write(&photo_path, b"image bytes").unwrap();
+        fs::write(&sidecar_path, "<x:xmpmeta>").unwrap();
+
+        let mut progress_reporter = MockProgressReporter::new();
+        let expected_path = sidecar_path.display().to_string();
+        progress_reporter
+            .expect_report_error()
+            .withf(move |message| message.contains(&expected_path))
+            .times(1)
+            .return_const(());
+
+        let mut service = XmpExportService::new(repository(&photo_path), progress_reporter);
+        let summary = service.export(&library(true), false).unwrap();
+
+        assert_eq!(summary.failed, 1);
+        assert_eq!(fs::read_to_string(&sidecar_path).unwrap(), "<x:xmpmeta>");
+    }
+
+    #[test]
+    fn test_should_return_error_when_library_did_not_opt_in() {
+        let mut repository = MockPhotoRepository::new();
+        repository.expect_find_sidecar_metadata().times(0);
+
+        let mut service = XmpExportService::new(repository, NoOpProgressReporter);
+
+        assert!(service.export(&library(false), false).is_err());
+    }
+}
@@ -0,0 +1,196 @@
+use std::{fs::File, io::Read, path::Path};
+
+use crate::utils::metadata::PhotoMetadata;
+
+/// Number of bytes read to find the IPTC segment, which precedes the image data.
+const MAX_HEADER_SIZE: u64 = 1024 * 1024;
+
+const PHOTOSHOP_SIGNATURE: &[u8] = b"Photoshop 3.0\0";
+const IPTC_RESOURCE_ID: u16 = 0x0404;
+
+const RECORD_APPLICATION: u8 = 2;
+const DATASET_OBJECT_NAME: u8 = 5;
+const DATASET_KEYWORDS: u8 = 25;
+const DATASET_CAPTION: u8 = 120;
+
+/// Reads the IPTC-IIM records embedded in the APP13 segment of a JPEG file.
+pub fn read_embedded(path: &Path) -> Option<PhotoMetadata> {
+    let mut data = Vec::new();
+    File::open(path)
+        .ok()?
+        .take(MAX_HEADER_SIZE)
+        .read_to_end(&mut data)
+        .ok()?;
+
+    let resources = find_photoshop_resources(&data)?;
+    let records = find_iptc_records(resources)?;
+
+    Some(parse_records(records))
+}
+
+/// Returns the Photoshop image resources stored in the APP13 segment of a JPEG stream.
+fn find_photoshop_resources(data: &[u8]) -> Option<&[u8]> {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+
+    let mut position = 2;
+
+    loop {
+        if *data.get(position)? != 0xFF {
+            return None;
+        }
+
+        let marker = *data.get(position + 1)?;
+        if marker == 0xDA || marker == 0xD9 {
+            return None;
+        }
+
+        let length = u16::from_be_bytes([*data.get(position + 2)?, *data.get(position + 3)?]);
+        let segment = data.get(position + 4..position + 2 + length as usize)?;
+
+        if marker == 0xED
+            && let Some(resources) = segment.strip_prefix(PHOTOSHOP_SIGNATURE)
+        {
+            return Some(resources);
+        }
+
+        position += 2 + length as usize;
+    }
+}
+
+/// Returns the IPTC-IIM resource among Photoshop image resources.
+fn find_iptc_records(resources: &[u8]) -> Option<&[u8]> {
+    let mut position = 0;
+
+    while resources.get(position..position + 4)? == b"8BIM" {
+        let id = u16::from_be_bytes([*resources.get(position + 4)?, *resources.get(position + 5)?]);
+
+        // The resource name is a Pascal string padded to an even length
+        let name_length = *resources.get(position + 6)? as usize;
+        let size_position = position + 6 + (name_length + 2) / 2 * 2;
+        let size = u32::from_be_bytes(
+            resources
+                .get(size_position..size_position + 4)?
+                .try_into()
+                .ok()?,
+        ) as usize;
+
+        let data_position = size_position + 4;
+        let data = resources.get(data_position..data_position + size)?;
+        if id == IPTC_RESOURCE_ID {
+            return Some(data);
+        }
+
+        position = data_position + size + size % 2;
+    }
+
+    None
+}
+
+fn parse_records(records: &[u8]) -> PhotoMetadata {
+    let mut metadata = PhotoMetadata::default();
+    let mut position = 0;
+
+    while records.get(position) == Some(&0x1C) {
+        let Some(header) = records.get(position + 1..position + 5) else {
+            break;
+        };
+        let (record, dataset) = (header[0], header[1]);
+        let length = u16::from_be_bytes([header[2], header[3]]);
+
+        // Extended datasets are only used for binary data
+        if length & 0x8000 != 0 {
+            break;
+        }
+
+        let Some(value) = records.get(position + 5..position + 5 + length as usize) else {
+            break;
+        };
+        position += 5 + length as usize;
+
+        if record != RECORD_APPLICATION {
+            continue;
+        }
+
+        let value = decode_text(value);
+        if value.is_empty() {
+            continue;
+        }
+
+        match dataset {
+            DATASET_KEYWORDS if !metadata.keywords.contains(&value) => {
+                metadata.keywords.push(value)
+            }
+            DATASET_OBJECT_NAME => metadata.title = Some(value),
+            DATASET_CAPTION => metadata.caption = Some(value),
+            _ => {}
+        }
+    }
+
+    metadata
+}
+
+/// Decodes IPTC text, which older writers store as Latin-1 rather than UTF-8.
+fn decode_text(value: &[u8]) -> String {
+    let text = match std::str::from_utf8(value) {
+        Ok(text) => text.to_string(),
+        Err(_) => value.iter().map(|&byte| byte as char).collect(),
+    };
+    text.trim_matches(|c: char| c.is_whitespace() || c == '\0')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(dataset: u8, value: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0x1C, RECORD_APPLICATION, dataset];
+        bytes.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(value);
+        bytes
+    }
+
+    fn jpeg_with_iptc(records: &[u8]) -> Vec<u8> {
+        let mut resources = PHOTOSHOP_SIGNATURE.to_vec();
+        resources.extend_from_slice(b"8BIM\x03\xED\0\0\0\0\0\x02\0\0");
+        resources.extend_from_slice(b"8BIM\x04\x04\0\0");
+        resources.extend_from_slice(&(records.len() as u32).to_be_bytes());
+        resources.extend_from_slice(records);
+        if records.len() % 2 == 1 {
+            resources.push(0);
+        }
+
+        let mut data = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, 0xFF, 0xED];
+        data.extend_from_slice(&(resources.len() as u16 + 2).to_be_bytes());
+        data.extend_from_slice(&resources);
+        data.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02, 0xFF, 0xD9]);
+        data
+    }
+
+    #[test]
+    fn test_should_parse_iptc_records_from_jpeg() {
+        let mut records = record(DATASET_KEYWORDS, b"Beach");
+        records.extend(record(DATASET_KEYWORDS, b"Caf\xE9"));
+        records.extend(record(DATASET_OBJECT_NAME, b"Sunset"));
+        records.extend(record(DATASET_CAPTION, "Sunset over the bay ☀".as_bytes()));
+        let data = jpeg_with_iptc(&records);
+
+        let metadata = find_photoshop_resources(&data)
+            .and_then(find_iptc_records)
+            .map(parse_records)
+            .unwrap();
+
+        assert_eq!(metadata.keywords, vec!["Beach", "Café"]);
+        assert_eq!(metadata.title.as_deref(), Some("Sunset"));
+        assert_eq!(metadata.caption.as_deref(), Some("Sunset over the bay ☀"));
+    }
+
+    #[test]
+    fn test_should_not_find_iptc_in_jpeg_without_app13() {
+        let data = [0xFF, 0xD8, 0xFF, 0xDA, 0x00, 0x02, 0xFF, 0xD9];
+
+        assert_eq!(find_photoshop_resources(&data), None);
+    }
+}
@@ -0,0 +1,81 @@
+use std::path::Path;
+
+use crate::models::MediaType;
+
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "tif", "tiff"];
+const HEIF_EXTENSIONS: &[&str] = &["heic", "heif", "avif"];
+const RAW_EXTENSIONS: &[&str] = &[
+    "dng", "cr2", "cr3", "nef", "nrw", "arw", "orf", "rw2", "raf", "pef", "srw",
+];
+const VIDEO_EXTENSIONS: &[&str] = &["mp4", "mov", "m4v", "3gp", "mkv", "webm", "avi"];
+
+/// Supported media formats, grouped by how they are decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaFormat {
+    /// Decoded by the `image` crate.
+    Image,
+    /// HEIF containers (HEIC, AVIF), decoded by libheif.
+    Heif,
+    /// Camera RAW files, decoded from their embedded JPEG preview.
+    Raw,
+    /// Videos, decoded from a representative frame extracted by ffmpeg.
+    Video,
+}
+
+impl MediaFormat {
+    /// Returns the format matching the extension of a path, if it is supported.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        let extension = extension.as_str();
+
+        if IMAGE_EXTENSIONS.contains(&extension) {
+            Some(MediaFormat::Image)
+        } else if HEIF_EXTENSIONS.contains(&extension) {
+            Some(MediaFormat::Heif)
+        } else if RAW_EXTENSIONS.contains(&extension) {
+            Some(MediaFormat::Raw)
+        } else if VIDEO_EXTENSIONS.contains(&extension) {
+            Some(MediaFormat::Video)
+        } else {
+            None
+        }
+    }
+
+    pub fn media_type(&self) -> MediaType {
+        match self {
+            MediaFormat::Video => MediaType::Video,
+            _ => MediaType::Photo,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_detect_media_format_from_extension() {
+        assert_eq!(
+            MediaFormat::from_path(Path::new("photo.JPG")),
+            Some(MediaFormat::Image)
+        );
+        assert_eq!(
+            MediaFormat::from_path(Path::new("photo.webp")),
+            Some(MediaFormat::Image)
+        );
+        assert_eq!(
+            MediaFormat::from_path(Path::new("photo.avif")),
+            Some(MediaFormat::Heif)
+        );
+        assert_eq!(
+            MediaFormat::from_path(Path::new("photo.CR3")),
+            Some(MediaFormat::Raw)
+        );
+        assert_eq!(
+            MediaFormat::from_path(Path::new("clip.mov")),
+            Some(MediaFormat::Video)
+        );
+        assert_eq!(MediaFormat::from_path(Path::new("notes.txt")), None);
+        assert_eq!(MediaFormat::from_path(Path::new("no_extension")), None);
+    }
+}
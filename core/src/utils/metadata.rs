@@ -0,0 +1,141 @@
+use std::path::Path;
+
+use crate::utils::{iptc, xmp};
+
+/// Descriptive metadata written by cataloging tools such as Lightroom or digiKam.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PhotoMetadata {
+    pub keywords: Vec<String>,
+    pub rating: Option<i32>,
+    pub title: Option<String>,
+    pub caption: Option<String>,
+    pub city: Option<String>,
+    pub country: Option<String>,
+    pub regions: Vec<MetadataRegion>,
+}
+
+/// Named face region, with coordinates normalized to the image dimensions.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MetadataRegion {
+    pub name: String,
+    pub left: f64,
+    pub top: f64,
+    pub width: f64,
+    pub height: f64,
+    /// Dimensions of the image the region was drawn on, if recorded.
+    pub applied_to: Option<(u32, u32)>,
+}
+
+impl PhotoMetadata {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Fills missing values from another source, with lower priority.
+    ///
+    /// Keywords are merged, while regions are only taken as a whole from the first
+    /// source that has any.
+    pub fn merge(mut self, other: PhotoMetadata) -> Self {
+        for keyword in other.keywords {
+            if !self
+                .keywords
+                .iter()
+                .any(|existing| existing.eq_ignore_ascii_case(&keyword))
+            {
+                self.keywords.push(keyword);
+            }
+        }
+
+        self.rating = self.rating.or(other.rating);
+        self.title = self.title.or(other.title);
+        self.caption = self.caption.or(other.caption);
+        self.city = self.city.or(other.city);
+        self.country = self.country.or(other.country);
+
+        if self.regions.is_empty() {
+            self.regions = other.regions;
+        }
+
+        self
+    }
+}
+
+/// Extracts descriptive metadata from the XMP sidecar of a file, then from the XMP
+/// packet and IPTC records embedded in the file itself.
+pub fn extract_metadata<P: AsRef<Path>>(path: P) -> Option<PhotoMetadata> {
+    let path = path.as_ref();
+
+    let metadata = [
+        xmp::read_sidecar(path),
+        xmp::read_embedded(path),
+        iptc::read_embedded(path),
+    ]
+    .into_iter()
+    .flatten()
+    .fold(PhotoMetadata::default(), PhotoMetadata::merge);
+
+    (!metadata.is_empty()).then_some(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_should_merge_metadata_by_priority() {
+        let sidecar = PhotoMetadata {
+            keywords: vec!["Beach".to_string()],
+            rating: Some(4),
+            ..Default::default()
+        };
+        let embedded = PhotoMetadata {
+            keywords: vec!["beach".to_string(), "Summer".to_string()],
+            rating: Some(2),
+            caption: Some("Sunset".to_string()),
+            ..Default::default()
+        };
+
+        let merged = sidecar.merge(embedded);
+
+        assert_eq!(merged.keywords, vec!["Beach", "Summer"]);
+        assert_eq!(merged.rating, Some(4));
+        assert_eq!(merged.caption.as_deref(), Some("Sunset"));
+    }
+
+    #[test]
+    fn test_should_prefer_sidecar_over_embedded_xmp() {
+        let temp_dir = TempDir::new().unwrap();
+        let photo_path = temp_dir.path().join("photo.jpg");
+        fs::write(
+            &photo_path,
+            br#"not a real jpeg <x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF
+                xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+                <rdf:Description xmlns:xmp="http://ns.adobe.com/xap/1.0/" xmp:Rating="1"/>
+            </rdf:RDF></x:xmpmeta>"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("photo.xmp"),
+            r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF
+                xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+                <rdf:Description xmlns:xmp="http://ns.adobe.com/xap/1.0/" xmp:Rating="5"/>
+            </rdf:RDF></x:xmpmeta>"#,
+        )
+        .unwrap();
+
+        let metadata = extract_metadata(&photo_path).unwrap();
+
+        assert_eq!(metadata.rating, Some(5));
+    }
+
+    #[test]
+    fn test_should_return_none_without_metadata() {
+        let temp_dir = TempDir::new().unwrap();
+        let photo_path = temp_dir.path().join("photo.jpg");
+        fs::write(&photo_path, b"no metadata").unwrap();
+
+        assert_eq!(extract_metadata(&photo_path), None);
+    }
+}
@@ -4,10 +4,16 @@ use std::{
     path::Path,
 };
 
-use nom_exif::{Exif, ExifIter, GPSInfo, MediaParser, MediaSource};
+use nom_exif::{EntryValue, Exif, ExifIter, GPSInfo, MediaParser, MediaSource, TrackInfo};
 use postgis_diesel::types::Point;
 
+pub mod iptc;
+pub mod media;
+pub mod metadata;
+pub mod perceptual_hash;
 pub mod progress_reporter;
+pub mod xml;
+pub mod xmp;
 
 /// Convert EXIF GPSInfo to PostGIS Point
 pub fn convert_exif_gps_info_to_postgis_point(gps_info: GPSInfo) -> Option<Point> {
@@ -49,6 +55,48 @@ pub fn convert_gps_coordinate(
     deg + (min / 60.0) + (sec / 3600.0)
 }
 
+/// Convert GPS altitude to meters, negative below sea level
+pub fn convert_gps_altitude(gps_info: &GPSInfo) -> Option<f32> {
+    if gps_info.altitude.1 == 0 {
+        return None;
+    }
+
+    let altitude = gps_info.altitude.as_float() as f32;
+    match gps_info.altitude_ref {
+        1 => Some(-altitude),
+        _ => Some(altitude),
+    }
+}
+
+/// Convert a numeric EXIF value to a float, whatever its storage format
+pub fn exif_value_as_f32(value: &EntryValue) -> Option<f32> {
+    match value {
+        EntryValue::URational(v) if v.1 != 0 => Some(v.as_float() as f32),
+        EntryValue::IRational(v) if v.1 != 0 => Some(v.as_float() as f32),
+        EntryValue::URationalArray(v) => {
+            v.first().filter(|v| v.1 != 0).map(|v| v.as_float() as f32)
+        }
+        EntryValue::F32(v) => Some(*v),
+        EntryValue::F64(v) => Some(*v as f32),
+        _ => exif_value_as_i32(value).map(|v| v as f32),
+    }
+}
+
+/// Convert an integer EXIF value to i32, whatever its storage format
+pub fn exif_value_as_i32(value: &EntryValue) -> Option<i32> {
+    match value {
+        EntryValue::U8(v) => Some(*v as i32),
+        EntryValue::U16(v) => Some(*v as i32),
+        EntryValue::U32(v) => i32::try_from(*v).ok(),
+        EntryValue::I8(v) => Some(*v as i32),
+        EntryValue::I16(v) => Some(*v as i32),
+        EntryValue::I32(v) => Some(*v),
+        EntryValue::U16Array(v) => v.first().map(|v| *v as i32),
+        EntryValue::U32Array(v) => v.first().and_then(|v| i32::try_from(*v).ok()),
+        _ => None,
+    }
+}
+
 /// Efficiently compute BLAKE3 hash of a file
 pub fn compute_file_hash<P: AsRef<Path>>(path: P) -> Result<String, std::io::Error> {
     let file = File::open(path)?;
@@ -77,6 +125,15 @@ pub fn extract_exif<P: AsRef<Path>>(path: P) -> Option<Exif> {
     Some(iter.into())
 }
 
+/// Extract video track metadata
+pub fn extract_track_info<P: AsRef<Path>>(path: P) -> Option<TrackInfo> {
+    let media_source = MediaSource::file_path(path).ok()?;
+    if !media_source.has_track() {
+        return None;
+    }
+    MediaParser::new().parse(media_source).ok()
+}
+
 /// Serializes a float array into PostgreSQL array literal format.
 pub fn serialize_float_array(float_array: &[f32]) -> String {
     format!(
@@ -135,6 +192,46 @@ mod tests {
         assert_eq!(point.srid, Some(4326));
     }
 
+    #[test]
+    fn test_should_convert_gps_altitude_below_sea_level() {
+        let mut gps_info = GPSInfo {
+            altitude: (4305, 10).into(),
+            altitude_ref: 1,
+            ..Default::default()
+        };
+
+        assert_eq!(convert_gps_altitude(&gps_info), Some(-430.5));
+
+        gps_info.altitude = (0, 0).into();
+        assert_eq!(convert_gps_altitude(&gps_info), None);
+    }
+
+    #[test]
+    fn test_should_convert_exif_values_to_numbers() {
+        assert_eq!(
+            exif_value_as_f32(&EntryValue::URational((1, 250).into())),
+            Some(0.004)
+        );
+        assert_eq!(
+            exif_value_as_f32(&EntryValue::URational((28, 10).into())),
+            Some(2.8)
+        );
+        assert_eq!(
+            exif_value_as_f32(&EntryValue::URational((0, 0).into())),
+            None
+        );
+        assert_eq!(exif_value_as_f32(&EntryValue::U16(35)), Some(35.0));
+        assert_eq!(exif_value_as_i32(&EntryValue::U16(400)), Some(400));
+        assert_eq!(
+            exif_value_as_i32(&EntryValue::U16Array(vec![800, 0])),
+            Some(800)
+        );
+        assert_eq!(
+            exif_value_as_i32(&EntryValue::Text("100".to_string())),
+            None
+        );
+    }
+
     #[test]
     fn test_serialize_float_array() {
         assert_eq!(serialize_float_array(&[1.0, 2.0, 3.0]), "[1,2,3]");
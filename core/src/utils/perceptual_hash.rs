@@ -0,0 +1,85 @@
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use image::{DynamicImage, imageops::FilterType};
+
+use crate::{imaging, utils::media::MediaFormat};
+
+/// Minimum size of the shorter side of decoded images, well above the 9x8 grid hashed.
+const DECODED_SIZE: u32 = 64;
+
+/// Computes the difference hash (dHash) of an image file, in its displayed orientation.
+///
+/// Videos are not hashed.
+pub fn compute_perceptual_hash<P: AsRef<Path>>(path: P) -> Result<i64> {
+    let path = path.as_ref();
+
+    let image = match MediaFormat::from_path(path) {
+        Some(MediaFormat::Video) | None => {
+            bail!("Unsupported format for perceptual hash: {}", path.display())
+        }
+        _ => imaging::load_image_scaled(path, DECODED_SIZE)?,
+    };
+
+    Ok(dhash(&image))
+}
+
+/// Hashes an image by comparing the brightness of adjacent pixels of its 9x8 grayscale
+/// thumbnail, so that resized or re-encoded copies get the same or a close hash.
+pub fn dhash(image: &DynamicImage) -> i64 {
+    let thumbnail = image.resize_exact(9, 8, FilterType::Triangle).into_luma8();
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if thumbnail.get_pixel(x, y)[0] < thumbnail.get_pixel(x + 1, y)[0] {
+                hash |= 1;
+            }
+        }
+    }
+
+    hash as i64
+}
+
+/// Returns the number of differing bits between two perceptual hashes.
+pub fn hamming_distance(a: i64, b: i64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma};
+    use tempfile::TempDir;
+
+    fn gradient(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::ImageLuma8(GrayImage::from_fn(width, height, |x, y| {
+            let (x, y) = (x as f32 / width as f32, y as f32 / height as f32);
+            Luma([(128.0 + 60.0 * (x * 7.0).sin() + 60.0 * (y * 5.0).cos()) as u8])
+        }))
+    }
+
+    #[test]
+    fn test_should_give_close_hashes_to_resized_copies() {
+        let original = gradient(640, 480);
+        let resized = original.resize_exact(320, 240, FilterType::Lanczos3);
+        let flipped = original.fliph();
+
+        assert!(hamming_distance(dhash(&original), dhash(&resized)) <= 4);
+        assert!(hamming_distance(dhash(&original), dhash(&flipped)) > 16);
+    }
+
+    #[test]
+    fn test_should_compute_perceptual_hash_of_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let photo_path = temp_dir.path().join("photo.png");
+        gradient(64, 48).save(&photo_path).unwrap();
+
+        assert_eq!(
+            compute_perceptual_hash(&photo_path).unwrap(),
+            dhash(&gradient(64, 48))
+        );
+        assert!(compute_perceptual_hash(temp_dir.path().join("clip.mov")).is_err());
+    }
+}
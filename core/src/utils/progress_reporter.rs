@@ -1,8 +1,14 @@
+use std::time::{Duration, Instant};
+
 /// Trait for reporting progress during long-running operations
+#[cfg_attr(test, mockall::automock)]
 pub trait ProgressReporter {
     /// Set a message to display
     fn set_message(&self, message: String);
 
+    /// Report an error without interrupting the operation
+    fn report_error(&self, message: String);
+
     /// Finish with a final message
     fn finish_with_message(&self, message: String);
 }
@@ -15,7 +21,92 @@ impl ProgressReporter for NoOpProgressReporter {
         // No-op
     }
 
+    fn report_error(&self, _message: String) {
+        // No-op
+    }
+
     fn finish_with_message(&self, _message: String) {
         // No-op
     }
 }
+
+/// Measures the throughput of a long-running operation to report its rate and the estimated
+/// time left.
+pub struct Throughput {
+    start: Instant,
+}
+
+impl Throughput {
+    pub fn start() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+
+    /// Returns a progress message with the rate of processed items and the estimated time to
+    /// process the remaining ones, such as "120 done, 880 remaining, 12.3 images/s, ETA 1m 11s".
+    pub fn message(&self, unit: &str, processed: usize, remaining: usize) -> String {
+        format_progress(unit, processed, remaining, self.start.elapsed())
+    }
+}
+
+fn format_progress(unit: &str, processed: usize, remaining: usize, elapsed: Duration) -> String {
+    let message = format!("{} done, {} remaining", processed, remaining);
+    let seconds = elapsed.as_secs_f64();
+    // The rate is unknown until items were processed
+    if processed == 0 || seconds == 0. {
+        return message;
+    }
+
+    let rate = processed as f64 / seconds;
+    let eta = Duration::from_secs_f64(remaining as f64 / rate);
+    format!(
+        "{}, {:.1} {}/s, ETA {}",
+        message,
+        rate,
+        unit,
+        format_duration(eta)
+    )
+}
+
+/// Formats a duration with its two largest units, such as "1h 05m" or "1m 11s".
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match seconds {
+        0..60 => format!("{}s", seconds),
+        60..3600 => format!("{}m {:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}h {:02}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_report_rate_and_eta() {
+        assert_eq!(
+            format_progress("images", 120, 880, Duration::from_secs(10)),
+            "120 done, 880 remaining, 12.0 images/s, ETA 1m 13s"
+        );
+        assert_eq!(
+            format_progress("images", 30, 100_000, Duration::from_secs(60)),
+            "30 done, 100000 remaining, 0.5 images/s, ETA 55h 33m"
+        );
+    }
+
+    #[test]
+    fn test_should_report_progress_without_rate_before_processing() {
+        assert_eq!(
+            format_progress("images", 0, 880, Duration::from_secs(10)),
+            "0 done, 880 remaining"
+        );
+    }
+
+    #[test]
+    fn test_should_format_durations() {
+        assert_eq!(format_duration(Duration::from_secs(7)), "7s");
+        assert_eq!(format_duration(Duration::from_secs(71)), "1m 11s");
+        assert_eq!(format_duration(Duration::from_secs(3900)), "1h 05m");
+    }
+}
@@ -0,0 +1,222 @@
+use anyhow::{Result, anyhow, bail};
+use quick_xml::{
+    NsReader,
+    events::{BytesStart, Event},
+    name::{Namespace, ResolveResult},
+};
+
+/// Element of a parsed XML document.
+///
+/// Only covers what is needed to read XMP packets: names keep their prefix, and are
+/// matched through the namespace URI they are bound to since XMP writers are free to
+/// pick their own prefixes. Mixed content is concatenated into `text`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct XmlElement {
+    /// Namespace URI of the element, empty when its name is not bound to one.
+    pub namespace: String,
+    pub name: String,
+    pub attributes: Vec<XmlAttribute>,
+    pub children: Vec<XmlElement>,
+    pub text: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct XmlAttribute {
+    /// Namespace URI of the attribute, empty for unprefixed attributes.
+    pub namespace: String,
+    pub name: String,
+    pub value: String,
+}
+
+impl XmlElement {
+    /// Returns whether the element has the given namespace URI and local name.
+    pub fn is(&self, namespace: &str, name: &str) -> bool {
+        self.namespace == namespace && local_name(&self.name) == name
+    }
+
+    pub fn attribute(&self, namespace: &str, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|attribute| {
+                attribute.namespace == namespace && local_name(&attribute.name) == name
+            })
+            .map(|attribute| attribute.value.as_str())
+    }
+
+    pub fn child(&self, namespace: &str, name: &str) -> Option<&XmlElement> {
+        self.children.iter().find(|child| child.is(namespace, name))
+    }
+
+    /// Returns the descendants with the given namespace URI and local name, in
+    /// document order.
+    pub fn descendants<'a>(&'a self, namespace: &'a str, name: &'a str) -> Vec<&'a XmlElement> {
+        let mut found = Vec::new();
+        for child in &self.children {
+            if child.is(namespace, name) {
+                found.push(child);
+            }
+            found.extend(child.descendants(namespace, name));
+        }
+        found
+    }
+}
+
+/// Parses an XML document and returns its root element.
+pub fn parse(input: &str) -> Result<XmlElement> {
+    let mut reader = NsReader::from_str(input);
+    let mut stack: Vec<XmlElement> = vec![XmlElement::default()];
+
+    loop {
+        let (namespace, event) = reader.read_resolved_event()?;
+        let namespace = namespace_uri(namespace);
+
+        match event {
+            Event::Start(start) => stack.push(element(&reader, namespace, &start)?),
+            Event::Empty(start) => {
+                let element = element(&reader, namespace, &start)?;
+                current(&mut stack).children.push(element);
+            }
+            Event::End(_) => {
+                let element = stack.pop().unwrap();
+                current(&mut stack).children.push(element);
+            }
+            Event::Text(text) => current(&mut stack).text.push_str(&text.unescape()?),
+            Event::CData(data) => current(&mut stack).text.push_str(&data.decode()?),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if stack.len() != 1 {
+        bail!("Unclosed element: {}", stack.last().unwrap().name);
+    }
+
+    stack
+        .pop()
+        .unwrap()
+        .children
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("Missing root element"))
+}
+
+fn current(stack: &mut [XmlElement]) -> &mut XmlElement {
+    stack.last_mut().unwrap()
+}
+
+/// Builds an element from its start tag, resolving the namespaces of its attributes.
+fn element(reader: &NsReader<&[u8]>, namespace: String, start: &BytesStart) -> Result<XmlElement> {
+    let mut element = XmlElement {
+        namespace,
+        name: str::from_utf8(start.name().as_ref())?.to_string(),
+        ..Default::default()
+    };
+
+    for attribute in start.attributes() {
+        let attribute = attribute?;
+        let (namespace, _) = reader.resolve_attribute(attribute.key);
+
+        element.attributes.push(XmlAttribute {
+            namespace: namespace_uri(namespace),
+            name: str::from_utf8(attribute.key.as_ref())?.to_string(),
+            value: attribute.unescape_value()?.into_owned(),
+        });
+    }
+
+    Ok(element)
+}
+
+/// Returns the namespace URI a name is bound to, or an empty string for unbound and
+/// unknown prefixes.
+pub fn namespace_uri(namespace: ResolveResult) -> String {
+    match namespace {
+        ResolveResult::Bound(Namespace(uri)) => String::from_utf8_lossy(uri).into_owned(),
+        _ => String::new(),
+    }
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit_once(':')
+        .map_or(name, |(_, local_name)| local_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+
+    #[test]
+    fn test_should_parse_elements_attributes_and_text() {
+        let root = parse(
+            r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+            <!-- comment -->
+            <x:xmpmeta xmlns:x="adobe:ns:meta/">
+                <rdf:li xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+                    xml:lang='x-default'>Fish &amp; chips &#x263A;</rdf:li>
+                <empty a="1"/>
+                <![CDATA[<raw>]]>
+            </x:xmpmeta>"#,
+        )
+        .unwrap();
+
+        assert!(root.is("adobe:ns:meta/", "xmpmeta"));
+        assert_eq!(root.children.len(), 2);
+
+        let li = root.child(RDF, "li").unwrap();
+        assert_eq!(
+            li.attribute("http://www.w3.org/XML/1998/namespace", "lang"),
+            Some("x-default")
+        );
+        assert_eq!(li.text, "Fish & chips \u{263A}");
+        assert_eq!(
+            root.child("", "empty").unwrap().attribute("", "a"),
+            Some("1")
+        );
+        assert!(root.text.contains("<raw>"));
+    }
+
+    #[test]
+    fn test_should_resolve_prefixes_declared_on_nested_elements() {
+        let root = parse(
+            r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+                <r:RDF xmlns:r="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+                    <r:Description xmlns:rdf="http://example.com/other/" rdf:about="">
+                        <rdf:li r:parseType="Resource"/>
+                    </r:Description>
+                </r:RDF>
+            </x:xmpmeta>"#,
+        )
+        .unwrap();
+
+        let description = &root.descendants(RDF, "Description")[0];
+        assert_eq!(
+            description.attribute("http://example.com/other/", "about"),
+            Some("")
+        );
+        assert!(description.child(RDF, "li").is_none());
+        assert_eq!(
+            description.children[0].attribute(RDF, "parseType"),
+            Some("Resource")
+        );
+    }
+
+    #[test]
+    fn test_should_find_descendants() {
+        let root = parse("<a><b><c>1</c></b><c>2</c></a>").unwrap();
+
+        let texts: Vec<&str> = root
+            .descendants("", "c")
+            .iter()
+            .map(|c| c.text.as_str())
+            .collect();
+
+        assert_eq!(texts, vec!["1", "2"]);
+    }
+
+    #[test]
+    fn test_should_fail_on_mismatched_tags() {
+        assert!(parse("<a><b></a>").is_err());
+        assert!(parse("<a>").is_err());
+    }
+}
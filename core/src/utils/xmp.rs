@@ -0,0 +1,1176 @@
+use std::{
+    borrow::Cow,
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Result, bail};
+use quick_xml::{
+    NsReader, Writer,
+    escape::escape,
+    events::{BytesEnd, BytesStart, BytesText, Event},
+    name::PrefixDeclaration,
+};
+
+use crate::utils::{
+    metadata::{MetadataRegion, PhotoMetadata},
+    xml::{self, XmlElement},
+};
+
+/// Number of bytes searched for an embedded XMP packet, which is stored near the
+/// start of the file by most writers.
+const MAX_EMBEDDED_SEARCH_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Reads the XMP sidecar of a file, named either `photo.jpg.xmp` (digiKam, darktable)
+/// or `photo.xmp` (Lightroom).
+pub fn read_sidecar(path: &Path) -> Option<PhotoMetadata> {
+    let packet = sidecar_paths(path)
+        .into_iter()
+        .find(|sidecar| sidecar.is_file())
+        .and_then(|sidecar| fs::read_to_string(sidecar).ok())?;
+
+    parse(&packet).ok()
+}
+
+/// Reads the XMP packet embedded in a file.
+pub fn read_embedded(path: &Path) -> Option<PhotoMetadata> {
+    let mut data = Vec::new();
+    File::open(path)
+        .ok()?
+        .take(MAX_EMBEDDED_SEARCH_SIZE)
+        .read_to_end(&mut data)
+        .ok()?;
+
+    let packet = find_packet(&data)?;
+    parse(&String::from_utf8_lossy(packet)).ok()
+}
+
+/// Returns the sidecar to write metadata of a file to: its existing sidecar, or
+/// `photo.jpg.xmp`, which cannot be shared with another file of the same name.
+pub fn sidecar_path(path: &Path) -> PathBuf {
+    let candidates = sidecar_paths(path);
+
+    candidates
+        .iter()
+        .find(|sidecar| sidecar.is_file())
+        .unwrap_or(&candidates[0])
+        .clone()
+}
+
+/// Merges metadata into an XMP packet, or into a new packet if there is none.
+///
+/// Keywords are added to the existing ones, location fields are replaced when set,
+/// and face regions replace the existing face regions while keeping other regions.
+/// Only these properties are rewritten: the rest of the packet, including its
+/// `xpacket` wrapper, comments and formatting, is copied unchanged.
+pub fn write(packet: Option<&str>, metadata: &PhotoMetadata) -> Result<String> {
+    let packet = packet.unwrap_or(EMPTY_PACKET);
+
+    let mut keywords = parse(packet)?.keywords;
+    for keyword in &metadata.keywords {
+        if !keywords
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(keyword))
+        {
+            keywords.push(keyword.clone());
+        }
+    }
+
+    let mut properties = Vec::new();
+    if !metadata.keywords.is_empty() {
+        properties.push(Property::Keywords(keywords));
+    }
+    if let Some(city) = &metadata.city {
+        properties.push(Property::Location("City", city));
+    }
+    if let Some(country) = &metadata.country {
+        properties.push(Property::Location("Country", country));
+    }
+    if !metadata.regions.is_empty() {
+        properties.push(Property::Regions(&metadata.regions));
+    }
+
+    let written = PacketWriter::new(packet, properties).write()?;
+    Ok(String::from_utf8(written)?)
+}
+
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const XML: &str = "http://www.w3.org/XML/1998/namespace";
+const XMP: &str = "http://ns.adobe.com/xap/1.0/";
+const DC: &str = "http://purl.org/dc/elements/1.1/";
+const PHOTOSHOP: &str = "http://ns.adobe.com/photoshop/1.0/";
+const MWG_RS: &str = "http://www.metadataworkinggroup.com/schemas/regions/";
+const ST_DIM: &str = "http://ns.adobe.com/xap/1.0/sType/Dimensions#";
+const ST_AREA: &str = "http://ns.adobe.com/xmp/sType/Area#";
+
+/// Prefixes bound to the namespaces we write when the packet has none for them.
+const PREFIXES: [(&str, &str); 6] = [
+    (RDF, "rdf"),
+    (DC, "dc"),
+    (PHOTOSHOP, "photoshop"),
+    (MWG_RS, "mwg-rs"),
+    (ST_DIM, "stDim"),
+    (ST_AREA, "stArea"),
+];
+
+const EMPTY_PACKET: &str = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+ </rdf:RDF>
+</x:xmpmeta>
+"#;
+
+/// Property of a description that is written from picasa-rs metadata.
+enum Property<'a> {
+    Keywords(Vec<String>),
+    /// Simple property of the Photoshop namespace, with its local name.
+    Location(&'static str, &'a str),
+    Regions(&'a [MetadataRegion]),
+}
+
+impl Property<'_> {
+    fn is(&self, namespace: &str, name: &str) -> bool {
+        match self {
+            Property::Keywords(_) => namespace == DC && name == "subject",
+            Property::Location(location, _) => namespace == PHOTOSHOP && name == *location,
+            Property::Regions(_) => namespace == MWG_RS && name == "Regions",
+        }
+    }
+
+    fn build<'a>(&self, builder: &mut PropertyBuilder<'a>, items: Vec<ArrayItem<'a>>) {
+        match self {
+            Property::Keywords(keywords) => {
+                builder.start(DC, "subject", &[]);
+                builder.start(RDF, "Bag", &[]);
+                for keyword in keywords {
+                    builder.text(RDF, "li", keyword);
+                }
+                builder.end();
+                builder.end();
+            }
+            Property::Location(name, value) => builder.text(PHOTOSHOP, name, value),
+            Property::Regions(regions) => build_regions(builder, items, regions),
+        }
+    }
+}
+
+/// Item of an existing array property, with the MWG type of the region it describes.
+struct ArrayItem<'a> {
+    kind: Option<String>,
+    events: Vec<Event<'a>>,
+}
+
+/// Copies the events of a packet, replacing the properties we own where they first
+/// appear and dropping their other occurrences.
+///
+/// Properties missing from the packet are added in a new `rdf:Description`.
+struct PacketWriter<'a> {
+    reader: NsReader<&'a [u8]>,
+    writer: Writer<Vec<u8>>,
+    properties: Vec<Property<'a>>,
+    written: Vec<bool>,
+    /// Namespaces and local names of the open elements.
+    path: Vec<(String, String)>,
+    /// Whitespace preceding the current event, held back in case the event is dropped.
+    whitespace: Option<BytesText<'a>>,
+    /// Prefixes in scope in the description being copied.
+    bindings: Vec<(String, String)>,
+    has_rdf: bool,
+}
+
+impl<'a> PacketWriter<'a> {
+    fn new(packet: &'a str, properties: Vec<Property<'a>>) -> Self {
+        Self {
+            reader: NsReader::from_str(packet),
+            writer: Writer::new(Vec::new()),
+            written: vec![false; properties.len()],
+            properties,
+            path: Vec::new(),
+            whitespace: None,
+            bindings: Vec::new(),
+            has_rdf: false,
+        }
+    }
+
+    fn write(mut self) -> Result<Vec<u8>> {
+        loop {
+            let (namespace, event) = self.reader.read_resolved_event()?;
+            let namespace = xml::namespace_uri(namespace);
+
+            match event {
+                Event::Start(start) => self.start(namespace, start, false)?,
+                Event::Empty(start) => self.start(namespace, start, true)?,
+                Event::End(end) => self.end(end)?,
+                Event::Text(text) if text.iter().all(u8::is_ascii_whitespace) => {
+                    self.flush_whitespace()?;
+                    self.whitespace = Some(text);
+                }
+                Event::Eof => break,
+                event => {
+                    self.flush_whitespace()?;
+                    self.writer.write_event(event)?;
+                }
+            }
+        }
+
+        self.flush_whitespace()?;
+        if self.written.contains(&false) {
+            bail!("Missing rdf:RDF element to write properties to");
+        }
+
+        Ok(self.writer.into_inner())
+    }
+
+    fn start(&mut self, namespace: String, start: BytesStart<'a>, is_empty: bool) -> Result<()> {
+        let name = (namespace, local_name(&start)?);
+
+        if self.in_description()
+            && let Some(index) = self
+                .properties
+                .iter()
+                .position(|property| property.is(&name.0, &name.1))
+        {
+            return self.replace(index, &start, is_empty);
+        }
+
+        let start = if name.0 == RDF && name.1 == "Description" && self.in_rdf() {
+            self.bindings = self.bindings();
+            self.replace_attributes(start)?
+        } else {
+            start
+        };
+        self.has_rdf |= name.0 == RDF && name.1 == "RDF";
+
+        self.flush_whitespace()?;
+        if is_empty {
+            self.writer.write_event(Event::Empty(start))?;
+        } else {
+            self.writer.write_event(Event::Start(start))?;
+            self.path.push(name);
+        }
+        Ok(())
+    }
+
+    fn end(&mut self, end: BytesEnd<'a>) -> Result<()> {
+        let name = self.path.pop().unwrap_or_default();
+
+        if self.written.contains(&false) {
+            if name.0 == RDF && name.1 == "RDF" {
+                self.append(false)?;
+            } else if self.path.is_empty() && !self.has_rdf {
+                self.append(true)?;
+            }
+        }
+
+        self.flush_whitespace()?;
+        self.writer.write_event(Event::End(end))?;
+        Ok(())
+    }
+
+    /// Writes a property in place of its first occurrence, and drops the others along
+    /// with the whitespace indenting them.
+    fn replace(&mut self, index: usize, start: &BytesStart<'a>, is_empty: bool) -> Result<()> {
+        let items = if is_empty {
+            Vec::new()
+        } else {
+            self.read_items(start.clone())?
+        };
+
+        if self.written[index] {
+            self.whitespace = None;
+            return Ok(());
+        }
+
+        let indent = self
+            .whitespace
+            .as_ref()
+            .map(line_indent)
+            .unwrap_or_default();
+        self.flush_whitespace()?;
+
+        let mut builder = PropertyBuilder::new(self.bindings.clone(), indent);
+        self.properties[index].build(&mut builder, items);
+        for event in builder.finish() {
+            self.writer.write_event(event)?;
+        }
+
+        self.written[index] = true;
+        Ok(())
+    }
+
+    /// Replaces the properties we own that are written as attributes of a description.
+    ///
+    /// Values are replaced in the raw start tag, to keep the layout of its attributes.
+    fn replace_attributes(&mut self, start: BytesStart<'a>) -> Result<BytesStart<'a>> {
+        let content: &[u8] = &start;
+        let offset = |bytes: &[u8]| bytes.as_ptr() as usize - content.as_ptr() as usize;
+        let mut edits = Vec::new();
+
+        for attribute in start.attributes() {
+            let attribute = attribute?;
+            let (namespace, name) = self.reader.resolve_attribute(attribute.key);
+            let name = (
+                xml::namespace_uri(namespace),
+                str::from_utf8(name.as_ref())?,
+            );
+
+            let index = self.properties.iter().position(|property| {
+                matches!(property, Property::Location(..)) && property.is(&name.0, name.1)
+            });
+            let (Some(index), Cow::Borrowed(value)) = (index, attribute.value) else {
+                continue;
+            };
+            let value_range = offset(value)..offset(value) + value.len();
+
+            if let Property::Location(_, replacement) = self.properties[index]
+                && !self.written[index]
+            {
+                edits.push((value_range, escape(replacement).into_owned()));
+                self.written[index] = true;
+            } else {
+                // Drops the attribute with the whitespace before it and its closing quote
+                let key_start = offset(attribute.key.0);
+                let attribute_start = content[..key_start]
+                    .iter()
+                    .rposition(|byte| !byte.is_ascii_whitespace())
+                    .map_or(0, |position| position + 1);
+                edits.push((attribute_start..value_range.end + 1, String::new()));
+            }
+        }
+
+        if edits.is_empty() {
+            return Ok(start);
+        }
+
+        let mut content = content.to_vec();
+        for (range, replacement) in edits.into_iter().rev() {
+            content.splice(range, replacement.into_bytes());
+        }
+        let name_length = start.name().as_ref().len();
+        Ok(BytesStart::from_content(
+            String::from_utf8(content)?,
+            name_length,
+        ))
+    }
+
+    /// Adds the properties that are not in the packet in a new description, closing
+    /// the `rdf:RDF` element or the root element when there is no `rdf:RDF`.
+    fn append(&mut self, with_rdf: bool) -> Result<()> {
+        let closing = self.whitespace.take();
+        let indent = format!("{} ", closing.as_ref().map(line_indent).unwrap_or_default());
+
+        let mut builder = PropertyBuilder::new(self.bindings(), indent.clone());
+        if with_rdf {
+            builder.start(RDF, "RDF", &[]);
+        }
+        builder.start(RDF, "Description", &[(RDF, "about", "")]);
+        for (property, _) in self
+            .properties
+            .iter()
+            .zip(&self.written)
+            .filter(|(_, written)| !**written)
+        {
+            property.build(&mut builder, Vec::new());
+        }
+        builder.end();
+        if with_rdf {
+            builder.end();
+        }
+
+        if closing.is_some() {
+            self.writer
+                .write_event(Event::Text(BytesText::from_escaped(format!("\n{indent}"))))?;
+        }
+        for event in builder.finish() {
+            self.writer.write_event(event)?;
+        }
+        self.writer.write_event(Event::Text(
+            closing.unwrap_or_else(|| BytesText::from_escaped("")),
+        ))?;
+
+        self.written.fill(true);
+        Ok(())
+    }
+
+    /// Reads the rest of a property, returning the items of its arrays that are not
+    /// nested in another item.
+    ///
+    /// Namespaces declared between the property and an item are declared again on the
+    /// item, so that it can be copied into a new property.
+    fn read_items(&mut self, start: BytesStart<'a>) -> Result<Vec<ArrayItem<'a>>> {
+        let mut ancestors = vec![(false, start)];
+        let mut items = Vec::new();
+        let mut item: Option<ArrayItem> = None;
+        let (mut depth, mut is_type) = (0, false);
+
+        loop {
+            let (namespace, event) = self.reader.read_resolved_event()?;
+            let namespace = xml::namespace_uri(namespace);
+
+            if let Some(current) = &mut item {
+                // The text of a type element is the event that follows it
+                let follows_type = std::mem::take(&mut is_type);
+
+                match &event {
+                    Event::Start(start) | Event::Empty(start) => {
+                        for attribute in start.attributes() {
+                            let attribute = attribute?;
+                            let (namespace, name) = self.reader.resolve_attribute(attribute.key);
+                            if xml::namespace_uri(namespace) == MWG_RS && name.as_ref() == b"Type" {
+                                current.kind = Some(attribute.unescape_value()?.trim().to_string());
+                            }
+                        }
+                        if matches!(event, Event::Start(_)) {
+                            is_type = namespace == MWG_RS && local_name(start)? == "Type";
+                            depth += 1;
+                        }
+                    }
+                    Event::Text(text) if follows_type => {
+                        current.kind = Some(text.unescape()?.trim().to_string());
+                    }
+                    Event::End(_) => depth -= 1,
+                    _ => {}
+                }
+
+                current.events.push(event);
+                if depth == 0 {
+                    items.extend(item.take());
+                }
+                continue;
+            }
+
+            match event {
+                Event::Start(start) if namespace == RDF && local_name(&start)? == "li" => {
+                    if ancestors.last().is_some_and(|(is_array, _)| *is_array) {
+                        let start = redeclare_namespaces(start, &ancestors)?;
+                        item = Some(ArrayItem {
+                            kind: None,
+                            events: vec![Event::Start(start)],
+                        });
+                        depth = 1;
+                    } else {
+                        ancestors.push((false, start));
+                    }
+                }
+                Event::Start(start) => {
+                    let is_array = namespace == RDF
+                        && ["Bag", "Seq", "Alt"].contains(&local_name(&start)?.as_str());
+                    ancestors.push((is_array, start));
+                }
+                Event::End(_) => {
+                    ancestors.pop();
+                    if ancestors.is_empty() {
+                        return Ok(items);
+                    }
+                }
+                Event::Eof => bail!("Unclosed property"),
+                _ => {}
+            }
+        }
+    }
+
+    fn flush_whitespace(&mut self) -> Result<()> {
+        if let Some(whitespace) = self.whitespace.take() {
+            self.writer.write_event(Event::Text(whitespace))?;
+        }
+        Ok(())
+    }
+
+    fn in_rdf(&self) -> bool {
+        self.path
+            .last()
+            .is_some_and(|(namespace, name)| namespace == RDF && name == "RDF")
+    }
+
+    /// Returns whether the current element is a description holding properties, as
+    /// opposed to one describing a structure.
+    fn in_description(&self) -> bool {
+        matches!(
+            self.path.as_slice(),
+            [.., (rdf_namespace, rdf), (namespace, name)]
+                if rdf_namespace == RDF && rdf == "RDF" && namespace == RDF && name == "Description"
+        )
+    }
+
+    /// Returns the prefixes in scope, with the namespaces they are bound to.
+    fn bindings(&self) -> Vec<(String, String)> {
+        self.reader
+            .prefixes()
+            .filter_map(|(prefix, namespace)| match prefix {
+                PrefixDeclaration::Named(prefix) => Some((
+                    String::from_utf8_lossy(prefix).into_owned(),
+                    String::from_utf8_lossy(namespace.as_ref()).into_owned(),
+                )),
+                PrefixDeclaration::Default => None,
+            })
+            .collect()
+    }
+}
+
+/// Builds MWG regions from face regions, keeping the regions of other types.
+fn build_regions<'a>(
+    builder: &mut PropertyBuilder<'a>,
+    items: Vec<ArrayItem<'a>>,
+    regions: &[MetadataRegion],
+) {
+    let coordinate = |value: f64| format!("{:.6}", value);
+
+    builder.start(MWG_RS, "Regions", &[(RDF, "parseType", "Resource")]);
+    if let Some((width, height)) = regions.iter().find_map(|region| region.applied_to) {
+        builder.empty(
+            MWG_RS,
+            "AppliedToDimensions",
+            &[
+                (ST_DIM, "w", &width.to_string()),
+                (ST_DIM, "h", &height.to_string()),
+                (ST_DIM, "unit", "pixel"),
+            ],
+        );
+    }
+    builder.start(MWG_RS, "RegionList", &[]);
+    builder.start(RDF, "Bag", &[]);
+
+    for item in items {
+        if item.kind.as_deref().is_some_and(|kind| kind != "Face") {
+            builder.copy(item.events);
+        }
+    }
+
+    for region in regions {
+        builder.start(RDF, "li", &[(RDF, "parseType", "Resource")]);
+        builder.text(MWG_RS, "Name", &region.name);
+        builder.text(MWG_RS, "Type", "Face");
+        builder.empty(
+            MWG_RS,
+            "Area",
+            &[
+                (ST_AREA, "x", &coordinate(region.left + region.width / 2.0)),
+                (ST_AREA, "y", &coordinate(region.top + region.height / 2.0)),
+                (ST_AREA, "w", &coordinate(region.width)),
+                (ST_AREA, "h", &coordinate(region.height)),
+                (ST_AREA, "unit", "normalized"),
+            ],
+        );
+        builder.end();
+    }
+
+    builder.end();
+    builder.end();
+    builder.end();
+}
+
+/// Builds the events of the properties we own, indenting nested elements by one space
+/// per level.
+///
+/// Names use the prefixes the packet already binds to our namespaces. Other namespaces
+/// are bound on the outermost element, to a prefix that is not in use.
+struct PropertyBuilder<'a> {
+    bindings: Vec<(String, String)>,
+    declarations: Vec<(String, String)>,
+    indent: String,
+    events: Vec<Event<'a>>,
+    /// Names of the open elements, and whether they have child elements.
+    open: Vec<(String, bool)>,
+}
+
+impl<'a> PropertyBuilder<'a> {
+    fn new(bindings: Vec<(String, String)>, indent: String) -> Self {
+        Self {
+            bindings,
+            declarations: Vec::new(),
+            indent,
+            events: Vec::new(),
+            open: Vec::new(),
+        }
+    }
+
+    fn start(&mut self, namespace: &str, name: &str, attributes: &[(&str, &str, &str)]) {
+        let element = self.element(namespace, name, attributes);
+        let name = self.name(namespace, name);
+        self.open.push((name, false));
+        self.events.push(Event::Start(element));
+    }
+
+    fn empty(&mut self, namespace: &str, name: &str, attributes: &[(&str, &str, &str)]) {
+        let element = self.element(namespace, name, attributes);
+        self.events.push(Event::Empty(element));
+    }
+
+    fn text(&mut self, namespace: &str, name: &str, text: &str) {
+        let element = self.element(namespace, name, &[]);
+        let end = element.to_end().into_owned();
+        self.events.push(Event::Start(element));
+        self.events
+            .push(Event::Text(BytesText::new(text).into_owned()));
+        self.events.push(Event::End(end));
+    }
+
+    /// Adds elements copied from the packet.
+    fn copy(&mut self, events: Vec<Event<'a>>) {
+        self.newline(true);
+        self.events.extend(events);
+    }
+
+    fn end(&mut self) {
+        let (name, has_children) = self.open.pop().unwrap();
+        self.newline(has_children);
+        self.events.push(Event::End(BytesEnd::new(name)));
+    }
+
+    fn finish(mut self) -> Vec<Event<'a>> {
+        if let Some(Event::Start(first) | Event::Empty(first)) = self.events.first_mut() {
+            for (prefix, namespace) in &self.declarations {
+                first.push_attribute((format!("xmlns:{prefix}").as_str(), namespace.as_str()));
+            }
+        }
+        self.events
+    }
+
+    fn element(
+        &mut self,
+        namespace: &str,
+        name: &str,
+        attributes: &[(&str, &str, &str)],
+    ) -> BytesStart<'static> {
+        self.newline(true);
+        if let Some((_, has_children)) = self.open.last_mut() {
+            *has_children = true;
+        }
+
+        let mut element = BytesStart::new(self.name(namespace, name));
+        for (namespace, name, value) in attributes {
+            let name = self.name(namespace, name);
+            element.push_attribute((name.as_str(), *value));
+        }
+        element
+    }
+
+    /// Indents the next element, unless it is the first one.
+    fn newline(&mut self, is_needed: bool) {
+        if is_needed && !self.events.is_empty() {
+            let indent = format!("\n{}{}", self.indent, " ".repeat(self.open.len()));
+            self.events
+                .push(Event::Text(BytesText::from_escaped(indent)));
+        }
+    }
+
+    fn name(&mut self, namespace: &str, name: &str) -> String {
+        if let Some((prefix, _)) = self.bindings.iter().find(|(_, bound)| bound == namespace) {
+            return format!("{prefix}:{name}");
+        }
+
+        let default = PREFIXES
+            .iter()
+            .find(|(known, _)| *known == namespace)
+            .map_or("ns", |(_, prefix)| prefix);
+        let mut prefix = default.to_string();
+        for suffix in 1.. {
+            if !self.bindings.iter().any(|(bound, _)| *bound == prefix) {
+                break;
+            }
+            prefix = format!("{default}{suffix}");
+        }
+
+        self.bindings.push((prefix.clone(), namespace.to_string()));
+        self.declarations
+            .push((prefix.clone(), namespace.to_string()));
+        format!("{prefix}:{name}")
+    }
+}
+
+/// Declares on an array item the namespaces declared by its ancestors in a property.
+fn redeclare_namespaces<'a>(
+    item: BytesStart<'a>,
+    ancestors: &[(bool, BytesStart<'a>)],
+) -> Result<BytesStart<'a>> {
+    let mut item = item;
+
+    for (_, ancestor) in ancestors.iter().rev() {
+        for attribute in ancestor.attributes() {
+            let attribute = attribute?;
+            if attribute.key.as_namespace_binding().is_some()
+                && item.try_get_attribute(attribute.key)?.is_none()
+            {
+                item.push_attribute(attribute);
+            }
+        }
+    }
+
+    Ok(item)
+}
+
+fn local_name(start: &BytesStart) -> Result<String> {
+    Ok(str::from_utf8(start.local_name().as_ref())?.to_string())
+}
+
+/// Returns the indentation of the line a whitespace text ends on.
+fn line_indent(whitespace: &BytesText) -> String {
+    let whitespace = String::from_utf8_lossy(whitespace);
+    whitespace
+        .rsplit_once('\n')
+        .map_or(whitespace.as_ref(), |(_, indent)| indent)
+        .to_string()
+}
+
+fn sidecar_paths(path: &Path) -> Vec<PathBuf> {
+    let mut appended = path.as_os_str().to_owned();
+    appended.push(".xmp");
+
+    vec![
+        PathBuf::from(appended),
+        path.with_extension("xmp"),
+        path.with_extension("XMP"),
+    ]
+}
+
+fn find_packet(data: &[u8]) -> Option<&[u8]> {
+    [
+        (b"<x:xmpmeta".as_slice(), b"</x:xmpmeta>".as_slice()),
+        (b"<rdf:RDF".as_slice(), b"</rdf:RDF>".as_slice()),
+    ]
+    .into_iter()
+    .find_map(|(start_tag, end_tag)| {
+        let start = find_bytes(data, start_tag)?;
+        let end = start + find_bytes(&data[start..], end_tag)? + end_tag.len();
+        Some(&data[start..end])
+    })
+}
+
+fn find_bytes(data: &[u8], needle: &[u8]) -> Option<usize> {
+    data.windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Parses the keywords, rating, title, caption, location and MWG face regions of an
+/// XMP packet.
+pub fn parse(packet: &str) -> Result<PhotoMetadata> {
+    let root = xml::parse(packet)?;
+    let mut metadata = PhotoMetadata::default();
+
+    for description in root.descendants(RDF, "Description") {
+        for keyword in array_items(description, DC, "subject") {
+            if !metadata.keywords.contains(&keyword) {
+                metadata.keywords.push(keyword);
+            }
+        }
+
+        if metadata.rating.is_none() {
+            metadata.rating = property(description, XMP, "Rating")
+                .and_then(|rating| rating.parse::<f32>().ok())
+                .map(|rating| rating.round() as i32);
+        }
+
+        if metadata.title.is_none() {
+            metadata.title = alternative_text(description, DC, "title");
+        }
+
+        if metadata.caption.is_none() {
+            metadata.caption = alternative_text(description, DC, "description");
+        }
+
+        if metadata.city.is_none() {
+            metadata.city = property(description, PHOTOSHOP, "City").map(str::to_string);
+        }
+
+        if metadata.country.is_none() {
+            metadata.country = property(description, PHOTOSHOP, "Country").map(str::to_string);
+        }
+
+        if metadata.regions.is_empty()
+            && let Some(regions) = description.child(MWG_RS, "Regions")
+        {
+            metadata.regions = parse_regions(regions);
+        }
+    }
+
+    Ok(metadata)
+}
+
+/// Parses MWG regions of type `Face`. Unnamed regions are ignored, since they cannot
+/// be linked to a person.
+fn parse_regions(regions: &XmlElement) -> Vec<MetadataRegion> {
+    let regions = structure(regions);
+
+    let applied_to = regions
+        .child(MWG_RS, "AppliedToDimensions")
+        .map(structure)
+        .and_then(|dimensions| {
+            let width = property(dimensions, ST_DIM, "w")?.parse::<f64>().ok()?;
+            let height = property(dimensions, ST_DIM, "h")?.parse::<f64>().ok()?;
+            Some((width as u32, height as u32))
+        });
+
+    let Some(list) = regions.child(MWG_RS, "RegionList").and_then(array) else {
+        return Vec::new();
+    };
+
+    list.children
+        .iter()
+        .filter(|item| item.is(RDF, "li"))
+        .map(structure)
+        .filter_map(|region| {
+            if property(region, MWG_RS, "Type").is_some_and(|kind| kind != "Face") {
+                return None;
+            }
+
+            let name = property(region, MWG_RS, "Name")?;
+            let area = structure(region.child(MWG_RS, "Area")?);
+            if property(area, ST_AREA, "unit").is_some_and(|unit| unit != "normalized") {
+                return None;
+            }
+
+            let coordinate = |name: &str| property(area, ST_AREA, name)?.parse::<f64>().ok();
+            let (x, y) = (coordinate("x")?, coordinate("y")?);
+            let (width, height) = (coordinate("w")?, coordinate("h")?);
+
+            // MWG areas are positioned by their center
+            Some(MetadataRegion {
+                name: name.to_string(),
+                left: x - width / 2.0,
+                top: y - height / 2.0,
+                width,
+                height,
+                applied_to,
+            })
+        })
+        .collect()
+}
+
+/// Returns the value of a simple property, written either as an attribute or as an
+/// element.
+fn property<'a>(element: &'a XmlElement, namespace: &str, name: &str) -> Option<&'a str> {
+    element
+        .attribute(namespace, name)
+        .or_else(|| {
+            element
+                .child(namespace, name)
+                .map(|child| child.text.as_str())
+        })
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+/// Returns the element holding the fields of a structure, which are either set on the
+/// property element itself or on a nested `rdf:Description`.
+fn structure(element: &XmlElement) -> &XmlElement {
+    element.child(RDF, "Description").unwrap_or(element)
+}
+
+fn array(element: &XmlElement) -> Option<&XmlElement> {
+    ["Bag", "Seq", "Alt"]
+        .into_iter()
+        .find_map(|name| element.child(RDF, name))
+}
+
+fn array_items(element: &XmlElement, namespace: &str, name: &str) -> Vec<String> {
+    element
+        .child(namespace, name)
+        .map(items)
+        .unwrap_or_default()
+}
+
+/// Returns the text items of an array property.
+fn items(property: &XmlElement) -> Vec<String> {
+    array(property)
+        .map(|array| {
+            array
+                .children
+                .iter()
+                .filter(|item| item.is(RDF, "li"))
+                .map(|item| item.text.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the default language of a language alternative, or its first language.
+fn alternative_text(element: &XmlElement, namespace: &str, name: &str) -> Option<String> {
+    let Some(alternative) = element.child(namespace, name).and_then(array) else {
+        return property(element, namespace, name).map(str::to_string);
+    };
+
+    let items: Vec<&XmlElement> = alternative
+        .children
+        .iter()
+        .filter(|item| item.is(RDF, "li"))
+        .collect();
+
+    items
+        .iter()
+        .find(|item| item.attribute(XML, "lang") == Some("x-default"))
+        .or(items.first())
+        .map(|item| item.text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const LIGHTROOM_PACKET: &str = r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about=""
+    xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+    xmlns:dc="http://purl.org/dc/elements/1.1/"
+    xmlns:mwg-rs="http://www.metadataworkinggroup.com/schemas/regions/"
+    xmlns:stDim="http://ns.adobe.com/xap/1.0/sType/Dimensions#"
+    xmlns:stArea="http://ns.adobe.com/xmp/sType/Area#"
+    xmp:Rating="4">
+   <dc:subject>
+    <rdf:Bag>
+     <rdf:li>Beach</rdf:li>
+     <rdf:li>Summer &amp; holidays</rdf:li>
+    </rdf:Bag>
+   </dc:subject>
+   <dc:title>
+    <rdf:Alt>
+     <rdf:li xml:lang="fr-FR">Coucher de soleil</rdf:li>
+     <rdf:li xml:lang="x-default">Sunset</rdf:li>
+    </rdf:Alt>
+   </dc:title>
+   <dc:description>
+    <rdf:Alt>
+     <rdf:li xml:lang="x-default">Sunset over the bay</rdf:li>
+    </rdf:Alt>
+   </dc:description>
+   <mwg-rs:Regions rdf:parseType="Resource">
+    <mwg-rs:AppliedToDimensions stDim:w="4000" stDim:h="3000" stDim:unit="pixel"/>
+    <mwg-rs:RegionList>
+     <rdf:Bag>
+      <rdf:li>
+       <rdf:Description mwg-rs:Name="Alice" mwg-rs:Type="Face">
+        <mwg-rs:Area stArea:x="0.5" stArea:y="0.25" stArea:w="0.2" stArea:h="0.1" stArea:unit="normalized"/>
+       </rdf:Description>
+      </rdf:li>
+      <rdf:li>
+       <rdf:Description mwg-rs:Name="Dog" mwg-rs:Type="Pet">
+        <mwg-rs:Area stArea:x="0.1" stArea:y="0.1" stArea:w="0.1" stArea:h="0.1" stArea:unit="normalized"/>
+       </rdf:Description>
+      </rdf:li>
+      <rdf:li rdf:parseType="Resource">
+       <mwg-rs:Type>Face</mwg-rs:Type>
+       <mwg-rs:Area stArea:x="0.8" stArea:y="0.8" stArea:w="0.1" stArea:h="0.1" stArea:unit="normalized"/>
+      </rdf:li>
+     </rdf:Bag>
+    </mwg-rs:RegionList>
+   </mwg-rs:Regions>
+  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>"#;
+
+    /// Packet binding Dublin Core to another prefix, and the `photoshop` prefix to
+    /// another namespace.
+    const FOREIGN_PREFIX_PACKET: &str = r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<!-- Written by another tool -->
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about=""
+    xmlns:elements="http://purl.org/dc/elements/1.1/"
+    xmlns:photoshop="http://example.com/not-photoshop/"
+    xmlns:ps="http://ns.adobe.com/photoshop/1.0/"
+    photoshop:City="Elsewhere"
+    ps:Country="Spain">
+   <elements:subject>
+    <rdf:Bag>
+     <rdf:li>Beach</rdf:li>
+    </rdf:Bag>
+   </elements:subject>
+   <elements:rights>All rights reserved</elements:rights>
+  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>
+"#;
+
+    #[test]
+    fn test_should_parse_xmp_packet() {
+        let metadata = parse(LIGHTROOM_PACKET).unwrap();
+
+        assert_eq!(metadata.keywords, vec!["Beach", "Summer & holidays"]);
+        assert_eq!(metadata.rating, Some(4));
+        assert_eq!(metadata.title.as_deref(), Some("Sunset"));
+        assert_eq!(metadata.caption.as_deref(), Some("Sunset over the bay"));
+    }
+
+    #[test]
+    fn test_should_parse_named_face_regions() {
+        let metadata = parse(LIGHTROOM_PACKET).unwrap();
+
+        assert_eq!(metadata.regions.len(), 1);
+        let region = &metadata.regions[0];
+        assert_eq!(region.name, "Alice");
+        assert!((region.left - 0.4).abs() < 1e-9);
+        assert!((region.top - 0.2).abs() < 1e-9);
+        assert!((region.width - 0.2).abs() < 1e-9);
+        assert!((region.height - 0.1).abs() < 1e-9);
+        assert_eq!(region.applied_to, Some((4000, 3000)));
+    }
+
+    #[test]
+    fn test_should_parse_properties_bound_to_other_prefixes() {
+        let metadata = parse(
+            r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <RDF xmlns="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <Description xmlns:elements="http://purl.org/dc/elements/1.1/"
+    xmlns:dc="http://example.com/not-dublin-core/">
+   <elements:subject><Bag><li>Beach</li></Bag></elements:subject>
+   <dc:subject><Bag><li>Unrelated</li></Bag></dc:subject>
+   <regions:Regions xmlns:regions="http://www.metadataworkinggroup.com/schemas/regions/">
+    <regions:RegionList>
+     <Bag>
+      <li>
+       <Description xmlns:area="http://ns.adobe.com/xmp/sType/Area#"
+         regions:Name="Alice" regions:Type="Face">
+        <regions:Area area:x="0.5" area:y="0.5" area:w="0.2" area:h="0.2"/>
+       </Description>
+      </li>
+     </Bag>
+    </regions:RegionList>
+   </regions:Regions>
+  </Description>
+ </RDF>
+</x:xmpmeta>"#,
+        )
+        .unwrap();
+
+        assert_eq!(metadata.keywords, vec!["Beach"]);
+        assert_eq!(metadata.regions.len(), 1);
+        assert_eq!(metadata.regions[0].name, "Alice");
+        assert!((metadata.regions[0].left - 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_should_find_embedded_packet() {
+        let mut data = vec![0xFF, 0xD8, 0xFF, 0xE1, 0x00, 0x10];
+        data.extend_from_slice(b"http://ns.adobe.com/xap/1.0/\0");
+        data.extend_from_slice(LIGHTROOM_PACKET.as_bytes());
+        data.extend_from_slice(&[0xFF, 0xD9]);
+
+        let packet = find_packet(&data).unwrap();
+
+        assert!(packet.starts_with(b"<x:xmpmeta"));
+        assert!(packet.ends_with(b"</x:xmpmeta>"));
+    }
+
+    #[test]
+    fn test_should_merge_metadata_into_existing_packet() {
+        let metadata = PhotoMetadata {
+            keywords: vec!["beach".to_string(), "Paris".to_string()],
+            city: Some("Paris".to_string()),
+            regions: vec![MetadataRegion {
+                name: "Bob".to_string(),
+                left: 0.1,
+                top: 0.1,
+                width: 0.2,
+                height: 0.2,
+                applied_to: Some((4000, 3000)),
+            }],
+            ..Default::default()
+        };
+
+        let packet = write(Some(LIGHTROOM_PACKET), &metadata).unwrap();
+        let written = parse(&packet).unwrap();
+
+        assert_eq!(
+            written.keywords,
+            vec!["Beach", "Summer & holidays", "Paris"]
+        );
+        assert_eq!(written.rating, Some(4));
+        assert_eq!(written.title.as_deref(), Some("Sunset"));
+        assert_eq!(written.city.as_deref(), Some("Paris"));
+        assert_eq!(written.regions.len(), 1);
+        assert_eq!(written.regions[0].name, "Bob");
+        assert!((written.regions[0].left - 0.1).abs() < 1e-6);
+        assert!(packet.contains(r#"mwg-rs:Name="Dog""#));
+        assert!(!packet.contains("Alice"));
+
+        assert_eq!(write(Some(&packet), &metadata).unwrap(), packet);
+    }
+
+    #[test]
+    fn test_should_leave_packet_unchanged_without_metadata() {
+        let packet = write(Some(FOREIGN_PREFIX_PACKET), &PhotoMetadata::default()).unwrap();
+
+        assert_eq!(packet, FOREIGN_PREFIX_PACKET);
+    }
+
+    #[test]
+    fn test_should_only_rewrite_owned_properties() {
+        let metadata = PhotoMetadata {
+            keywords: vec!["Paris".to_string()],
+            city: Some("Paris".to_string()),
+            country: Some("France".to_string()),
+            ..Default::default()
+        };
+
+        let packet = write(Some(FOREIGN_PREFIX_PACKET), &metadata).unwrap();
+
+        assert_eq!(
+            packet,
+            FOREIGN_PREFIX_PACKET
+                .replace(r#"ps:Country="Spain""#, r#"ps:Country="France""#)
+                .replace(
+                    "     <rdf:li>Beach</rdf:li>\n",
+                    "     <rdf:li>Beach</rdf:li>\n     <rdf:li>Paris</rdf:li>\n"
+                )
+                .replace(
+                    "  </rdf:Description>\n",
+                    "  </rdf:Description>\n  <rdf:Description rdf:about=\"\" \
+                     xmlns:photoshop=\"http://ns.adobe.com/photoshop/1.0/\">\n   \
+                     <photoshop:City>Paris</photoshop:City>\n  </rdf:Description>\n"
+                )
+        );
+        assert_eq!(write(Some(&packet), &metadata).unwrap(), packet);
+
+        let written = parse(&packet).unwrap();
+        assert_eq!(written.keywords, vec!["Beach", "Paris"]);
+        assert_eq!(written.city.as_deref(), Some("Paris"));
+        assert_eq!(written.country.as_deref(), Some("France"));
+    }
+
+    #[test]
+    fn test_should_write_new_packet() {
+        let metadata = PhotoMetadata {
+            keywords: vec!["Beach".to_string()],
+            country: Some("France".to_string()),
+            ..Default::default()
+        };
+
+        let written = parse(&write(None, &metadata).unwrap()).unwrap();
+
+        assert_eq!(written.keywords, vec!["Beach"]);
+        assert_eq!(written.country.as_deref(), Some("France"));
+    }
+
+    #[test]
+    fn test_should_write_to_existing_sidecar() {
+        let temp_dir = TempDir::new().unwrap();
+        let photo_path = temp_dir.path().join("photo.jpg");
+
+        assert_eq!(
+            sidecar_path(&photo_path),
+            temp_dir.path().join("photo.jpg.xmp")
+        );
+
+        fs::write(temp_dir.path().join("photo.xmp"), LIGHTROOM_PACKET).unwrap();
+
+        assert_eq!(sidecar_path(&photo_path), temp_dir.path().join("photo.xmp"));
+    }
+
+    #[test]
+    fn test_should_read_digikam_sidecar() {
+        let temp_dir = TempDir::new().unwrap();
+        let photo_path = temp_dir.path().join("photo.jpg");
+        fs::write(&photo_path, b"image").unwrap();
+        fs::write(temp_dir.path().join("photo.jpg.xmp"), LIGHTROOM_PACKET).unwrap();
+
+        let metadata = read_sidecar(&photo_path).unwrap();
+
+        assert_eq!(metadata.rating, Some(4));
+    }
+}
@@ -0,0 +1,94 @@
+use picasa_core::services::embedders::{
+    ClipImageEmbedder, ClipTextEmbedder, image::ImageEmbedder, text::TextEmbedder,
+};
+
+mod utils;
+use utils::load_config;
+
+const DESK_PATH: &str = "tests/data/images/sub/desk_vietnam.heic";
+const SUNRISE_PATH: &str = "tests/data/images/sunrise_laos.heic";
+/// COCO val2017 image 000000039769, two cats lying on a couch, downloaded by
+/// `cargo make test-images`.
+const CATS_PATH: &str = "tests/data/images/reference/coco_cats.jpg";
+
+/// Logits of clip-vit-base-patch32 for the cats image and the captions below, as computed
+/// by the reference implementation in the transformers test suite.
+const REFERENCE_CAPTIONS: [&str; 2] = ["a photo of a cat", "a photo of a dog"];
+const REFERENCE_LOGITS: [f32; 2] = [24.5701, 19.3049];
+/// Scale of the logits, the exponential of the trained logit scale of the model.
+const LOGIT_SCALE: f32 = 100.0;
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+fn norm(a: &[f32]) -> f32 {
+    dot(a, a).sqrt()
+}
+
+#[test]
+fn test_should_match_images_with_their_descriptions() {
+    let config = load_config();
+    let image_embedder = ClipImageEmbedder::new(&config.embeddings, &config.embeddings.model)
+        .expect("Failed to create image embedder");
+    let text_embedder = ClipTextEmbedder::new(&config.embeddings, &config.embeddings.model)
+        .expect("Failed to create text embedder");
+
+    let image_embeddings = image_embedder
+        .embed(&vec![DESK_PATH.to_string(), SUNRISE_PATH.to_string()])
+        .expect("Failed to embed images");
+    let desk = text_embedder
+        .embed("a photo of a desk")
+        .expect("Failed to embed text");
+    let sunrise = text_embedder
+        .embed("a photo of a sunrise over the mountains")
+        .expect("Failed to embed text");
+
+    for embedding in image_embeddings.iter().chain([&desk, &sunrise]) {
+        assert!((norm(embedding) - 1.0).abs() < 1e-4);
+    }
+    assert!(dot(&image_embeddings[0], &desk) > dot(&image_embeddings[0], &sunrise));
+    assert!(dot(&image_embeddings[1], &sunrise) > dot(&image_embeddings[1], &desk));
+}
+
+#[test]
+fn test_should_embed_queries_longer_than_context() {
+    let config = load_config();
+    let text_embedder = ClipTextEmbedder::new(&config.embeddings, &config.embeddings.model)
+        .expect("Failed to create text embedder");
+
+    let embedding = text_embedder
+        .embed(&"a white building by the river at night ".repeat(20))
+        .expect("Failed to embed long text");
+
+    assert_eq!(embedding.len(), 512);
+    assert!((norm(&embedding) - 1.0).abs() < 1e-4);
+}
+
+#[test]
+fn test_should_match_reference_logits() {
+    let config = load_config();
+    let image_embedder = ClipImageEmbedder::new(&config.embeddings, "clip-vit-base-patch32")
+        .expect("Failed to create image embedder");
+    let text_embedder = ClipTextEmbedder::new(&config.embeddings, "clip-vit-base-patch32")
+        .expect("Failed to create text embedder");
+
+    let image_embedding = image_embedder
+        .embed(&vec![CATS_PATH.to_string()])
+        .expect("Failed to embed image")
+        .remove(0);
+
+    for (caption, reference_logit) in REFERENCE_CAPTIONS.iter().zip(REFERENCE_LOGITS) {
+        let text_embedding = text_embedder.embed(caption).expect("Failed to embed text");
+        let logit = LOGIT_SCALE * dot(&image_embedding, &text_embedding);
+
+        // Resampling and float rounding differ slightly from the reference implementation
+        assert!(
+            (logit - reference_logit).abs() < 0.01,
+            "Logit of \"{}\" is {}, expected {}",
+            caption,
+            logit,
+            reference_logit
+        );
+    }
+}
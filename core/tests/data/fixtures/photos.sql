@@ -1,3 +1,6 @@
-INSERT INTO public.photos (path, file_name, file_size, created_at, modified_at, indexed_at, hash, camera_make, camera_model, lens_model, orientation, date_taken_local, date_taken_utc, gps_location, image_width, image_height, embedding) VALUES ('tests/data/images/sunrise_laos.heic', 'sunrise_laos.heic', 1377703, '2025-06-27 07:22:54.863388+00', '2025-06-27 07:22:54.863388+00', '2025-06-28 12:43:57.956159+00', 'd320984f4123079ec52092ad9a9318dd8f5b325238d12f3ce7592f44ea828e5f', 'Apple', 'iPhone 15', 'iPhone 15 back dual wide camera 1.54mm f/2.4', NULL, '2025-02-22 06:55:25', '2025-02-21 23:55:25+00', '0101000020E61000001EEFCA7DD499594032E82249C1E93240', NULL, NULL, '[-0.09428595,0.007138477,-0.39367875,-0.038943876,-0.062302362,-0.3607945,0.33876407,-0.0884477,0.40466365,0.47804537,0.27915722,-0.26219833,-0.19444285,-0.13364209,0.20696244,-0.35118473,-0.685174,-0.20015763,-0.047927205,-0.16885184,1.1818101,0.372717,0.21915504,-0.75631624,-0.40021166,-0.1084184,0.09472723,-0.35562357,-0.45012715,0.41086152,-0.40197346,0.45333874,0.13158019,-0.41818032,-0.24652725,-0.085260265,0.004769221,0.10992374,0.034073446,-0.13225187,-0.10043166,0.35298678,-0.30092835,-0.1344205,-0.25776356,-0.69168794,0.026983833,-0.23731786,-0.099355236,-0.2579292,0.5480238,-0.20350777,0.49836633,0.25346303,0.26563674,0.01896142,0.33298433,-0.35151282,0.74379224,0.494871,-0.1975045,0.15635428,0.22188501,-0.21614501,-0.4444685,-0.21377757,-0.4248644,0.13189618,-0.049979776,-0.34964037,-0.41241774,-0.37605327,0.20527391,0.01059377,0.2737804,-0.32704535,0.15904838,0.76575285,0.2664512,-0.326705,-0.053643204,-0.072065696,0.3852663,0.39412758,0.2831664,-0.26948974,-0.23738016,-0.49315995,-0.013679735,0.3086236,-0.15739422,0.26800892,-4.6900315,0.6323519,0.13285312,0.44484523,-0.5091965,0.16741256,0.28573847,-1.3915869,-0.048099283,-0.34708205,-0.28977644,-0.17793114,-0.9949285,-0.08229464,-1.3273616,0.2612884,0.16273503,0.1269823,-0.013634255,-0.6218278,0.12063302,0.27861398,-0.032038193,0.1064457,-0.19744281,0.17821722,0.12372398,0.0041397703,0.3358629,0.22070597,0.09238358,-0.42499614,-0.14933607,-0.03255135,0.11539644,-0.17083043,-0.16057606,0.21601138,-0.0062538637,0.21162626,0.43945864,0.7719097,0.5200926,0.023587728,0.03738524,-0.44689545,-0.46166506,-0.10013772,-0.20141165,0.33604318,-0.24342969,-0.4380231,-0.2965186,-0.2639957,0.09126422,0.36045644,0.24712989,0.018309502,0.031678714,0.31932405,-0.925494,0.042466573,-0.44806567,-0.46600288,0.001434687,0.18770164,0.05508512,-0.10291266,-0.11972141,-0.0032860548,-0.050566114,0.44574043,-0.23383804,-0.34169224,0.947088,-0.1965763,0.21876457,0.07045588,0.077025,-0.15266673,-0.6276299,-0.5826132,-0.3306159,0.16898394,-1.8865881,-0.26621085,-0.30573454,-0.15184996,0.12299425,0.53322023,0.09889291,-0.4594533,0.48232043,0.18628792,0.3889547,0.64631134,-0.06728459,-0.057665233,0.42841357,-0.17009614,-0.12788802,-0.13571921,-0.78107524,0.3946866,0.5523207,-0.3324498,0.036140874,0.05896863,0.16147529,0.018147595,-0.06909646,0.29303136,0.25580174,-0.112311706,-0.05617944,-0.3117124,0.054760236,-0.3551148,-0.4072044,0.116201766,0.21168177,-0.05364261,0.64767104,-0.47992405,0.2405685,0.113736495,-0.28811777,-0.03461781,-0.22905992,0.40616181,-0.24885298,-0.25216007,-0.09344563,0.06549679,-0.42950863,-0.33008566,-0.28402308,-0.2230117,-0.15874416,0.031821523,0.18683875,-0.48126218,0.6348817,-0.08101846,0.15684818,0.0075395615,0.47177958,-0.23749265,0.37749022,0.03920921,0.17201962,-0.11007639,-0.20850593,-0.31412727,0.14256343,-0.24779266,-0.014469693,-0.12675777,-0.100359045,0.068291,0.20233828,0.14994712,-0.17449984,-0.0036323327,-0.24497563,-0.2098579,0.4431187,-0.16131485,0.04297394,-0.27399102,0.0022418578,0.47057754,-0.06997244,0.27712137,-0.47529337,0.052363615,0.32529202,-0.35979003,0.24150907,0.36048403,0.10420732,-0.029187419,-0.025691563,0.63980776,-0.14509656,-0.047995556,-0.07389011,-0.18261164,0.0574644,-0.06417832,0.1954076,-0.29786566,0.07041707,0.44694325,0.7648319,0.6025626,0.092845894,-0.20356765,0.2628601,0.14295927,0.072322324,0.17927754,0.18005021,-0.17563534,0.4390983,0.07088276,0.30056345,-0.011835696,0.40045875,0.14504899,-0.6232829,0.04804924,0.38983414,-0.3221603,0.08947801,0.28580526,0.47245106,-0.18827115,-0.15180737,-0.090391025,0.7688197,-0.38970408,-0.033321083,0.061403215,0.26417372,-0.101792045,-0.20093553,-0.1644779,-0.068985574,2.427257,-0.2907649,-0.46357056,-0.12892424,-0.44230968,-0.1418605,-0.24658969,-0.107522,-0.8229766,-0.46565312,0.41958767,-0.17409128,0.049530238,-0.13343757,0.5053142,0.14766516,-0.48801932,0.52153,0.29178584,-0.11015727,-0.69691414,0.21687043,0.5672732,0.0842851,-0.033312898,0.076487735,-0.3577775,0.026548984,0.6509431,-0.0015428782,0.3062039,0.25255617,0.16662247,0.1951438,-0.65544367,-0.6062573,-0.6569153,-0.36536577,-0.3533324,-0.13381101,-0.07693684,-0.48691475,1.0382445,0.019571323,-0.29041025,-0.33240303,-0.47623348,0.07373459,-0.43135673,0.0079977885,-0.056810535,-0.29512134,0.10650166,-0.362561,0.23053588,-0.1609801,-0.22932182,0.61181515,-0.18213016,0.43661615,-0.19704868,-0.030062322,-0.3270976,0.689899,-0.2797793,-0.10787389,0.6597479,-0.6063032,-0.12594242,-1.0137023,-0.09217647,-0.12691897,-0.033102874,0.28976998,0.01307938,0.36515614,0.09775691,-0.78829587,-0.28711534,0.047272284,-0.06674392,0.61070615,-0.34767738,0.21854222,0.3488942,0.14130919,-0.03215904,-0.048761208,-0.4886355,-0.032554347,0.5158605,0.16063601,0.10919135,-0.19667228,-0.05824577,-0.018219996,0.09245172,-0.19413707,0.07190733,-0.25297883,-0.18109708,-0.023160035,-0.47498128,-0.532032,0.16079436,-0.043907546,-0.07806894,0.67514575,0.7401905,-0.42761427,0.1419661,-1.1507757,-0.22205499,-0.038083173,-0.39607793,-0.2769954,-0.27584177,-0.38884622,-0.07317706,-0.00846503,-0.50167125,0.23107457,0.28145716,0.025316892,-0.11187766,-0.19327872,0.1398804,-0.24441165,-0.24083748,0.06396187,0.518985,-0.31502464,-0.09352878,0.16826995,0.05009358,0.28922194,-0.42082778,-0.23866415,-0.75016135,0.034988202,-0.18978515,-0.39960036,-0.3511516,-0.08940607,0.27704984,0.29970306,0.09124665,0.2000303,-0.1195234,-0.04960972,0.15864249,-0.060894545,-0.37294668,-0.045420475,-0.06950304,0.25311032,-0.02371519,-0.6212659,-0.25473243,0.23367476,-0.8400048,-0.18930593,-0.13053748,-0.06494283,-0.40457702,-0.075638935,0.14606264,-0.25129616,-0.03325117,0.2363678,-0.2651419,-0.07005284,-0.45459577,-0.1561978,-0.07738784,0.2802522,-0.11173048,-0.27464613,0.104104616,-0.16409282,0.4299495,-0.021105954,-0.25346205,0.57346046,-0.44535634,-0.8893107,-0.36920685,0.2991137,-0.13136375,-0.28526756,-0.0013955798]');
-INSERT INTO public.photos (path, file_name, file_size, created_at, modified_at, indexed_at, hash, camera_make, camera_model, lens_model, orientation, date_taken_local, date_taken_utc, gps_location, image_width, image_height, embedding) VALUES ('tests/data/images/sub/desk_vietnam.heic', 'desk_vietnam.heic', 2924690, '2025-06-27 07:22:54.806103+00', '2025-06-27 07:22:54.806103+00', '2025-06-28 12:43:57.977522+00', '40a1d834dfa6ab98dd3d5b4196a4d10f3409f76b28ac5f9a520daf20ae221bc3', 'Apple', 'iPhone 15', 'iPhone 15 back dual wide camera 5.96mm f/1.6', NULL, '2025-03-21 14:25:20', '2025-03-21 07:25:20+00', '0101000020E61000006B74925E72AC5A40BE5296218E8D2540', NULL, NULL, '[0.09326888,-0.035485577,-0.19403689,0.22151734,0.071413785,-0.46993926,0.41657194,0.27683997,-0.21607828,0.11936089,0.16215868,-0.15396191,-0.025384499,-0.06167898,0.166727,-0.42861703,0.024264397,0.39349744,-0.14479835,-0.0933606,1.3415992,0.1209454,0.5150157,-0.16966403,0.07206543,0.23700364,0.26893583,-0.4921209,-0.4011577,-0.014689595,0.34894538,0.37848732,-0.2332289,-0.033474382,-0.6284356,-0.39506444,-0.37558225,-0.114362575,-0.0078090318,-0.942655,-0.7656015,0.19837482,-0.39676392,-0.5258945,-0.04231727,-1.295786,0.17526127,-0.046053775,-0.108545765,0.0474208,0.18945985,-0.23400946,-0.0037635632,-0.22704051,0.030173542,0.59097046,-0.4267286,-0.19393453,0.34834105,0.11747435,-0.86226064,0.24532928,-0.052709166,-0.27568865,-0.11793588,0.37609622,-0.063672446,-0.614676,0.30675673,0.0020432856,-0.22835685,-0.25728232,0.6289749,-0.5756664,-0.32886243,-0.19138184,0.3457393,0.36644378,-0.15791804,-0.032621387,0.28406543,0.16088577,-0.1247306,0.12135763,-0.2517246,-0.2545281,0.014620731,0.09254778,-0.28556815,0.26921666,-0.07862486,0.17132874,-6.061267,0.22323748,0.44706413,0.42673746,0.20172486,0.08130866,0.39047214,0.48154682,0.057504363,0.11274782,-0.088580646,0.049835626,-0.07949665,-0.13622741,-2.2092752,0.36201587,0.3275436,0.053910058,-0.0783423,0.15049899,-0.15606233,-0.18684083,0.4402203,-0.48364672,-0.13864452,0.017871141,-0.0016995048,0.18905796,0.6998864,-0.37469673,0.21476494,-0.3211124,0.10135597,0.43563408,-0.035170417,-0.06451226,0.005874235,0.19185501,-0.33486128,0.028611153,0.07157444,0.8519859,0.13259466,0.42302468,0.058189817,-0.40286466,-0.3037554,-0.14895436,0.6529605,-0.1511731,-0.24761282,-0.266679,-0.5338832,-0.20085564,0.3108234,0.09124875,0.13505696,0.011375015,0.09442667,0.22627169,-0.94104755,0.34483284,-0.26116484,-0.026897177,-0.2665507,-0.25645176,0.10526649,-0.020434655,-0.40113458,-0.042929493,-0.15345639,0.0067691943,0.14229581,0.67078996,1.1308588,-0.003393634,0.12507328,-0.03130306,0.18491247,-0.58553046,-0.004337876,0.085297905,-0.52863145,0.58301055,-0.43331808,0.08569968,0.34361812,-0.38685203,0.47042572,0.05056346,0.15453301,-0.05846451,-0.107726514,-0.088027336,0.17431988,-0.108911574,-0.0007100985,0.016444802,0.64202005,-0.1550374,0.06433715,0.17284112,-0.6141376,-0.10564544,0.10501663,-0.031756,-1.4660373,0.08013111,0.13395298,-0.067035615,0.058287527,0.16853806,0.2684203,0.35697806,0.1456349,-0.23565558,0.003308095,-0.10459329,0.4989644,0.29533365,-0.10853734,-0.1885023,0.27014637,0.12592873,0.19401897,0.19552374,-0.33489284,0.16679366,-0.24900593,0.36210048,-0.43927884,-0.43144342,-0.00206019,0.12702228,-0.16695537,0.17493089,-0.27439186,-0.12535389,0.030463992,0.24344382,0.4292603,-0.0075825783,0.226533,0.55151975,0.2692593,0.45908898,0.5611976,0.13248792,0.18780582,-0.39437667,0.054646213,-0.04235629,-0.0813667,0.6533665,0.2638077,-0.17130926,0.17890443,-0.58087677,0.05279418,-0.23524684,0.42474443,0.25439477,-0.07759546,0.3153868,-0.07168813,-0.044103894,-1.612984,0.14870979,0.19594952,0.56626076,-0.4188928,-0.3754591,0.06338433,0.26229244,0.1315864,-0.45939526,0.027425937,0.13082968,0.21078287,-0.16645478,0.15348509,0.3265249,-0.1224076,0.34926847,-0.39219218,0.46852937,-0.11398095,-0.2492445,0.46227652,0.152933,0.15761477,-0.23499155,-0.05138612,0.07728813,0.48979318,0.098559365,-0.035889026,-0.05188032,0.5240879,-0.26700237,0.5246991,0.089134805,0.014721192,-0.026526734,-0.15070479,-0.02805834,0.18342349,-0.28014806,-0.2366454,0.12314702,-0.5827303,-0.12664679,0.19833565,-0.15866223,-0.6973152,0.19856234,0.29412943,-0.22312462,-0.50632226,0.55140847,0.84873766,-0.21538252,0.32224753,-0.17662728,0.38899764,-0.20176677,0.042104557,-0.76033455,0.4214432,2.134712,0.33962536,-0.51869917,-0.33366424,-0.6967055,0.4125172,0.05259973,-0.26250052,-0.5047127,0.28044444,-0.26115832,0.15070698,-0.031762864,-0.072779365,0.49058756,0.30286258,-0.45286867,-0.1985399,-0.04215786,-0.1522322,-0.7903891,-0.11277615,0.2400926,0.35149997,0.1541727,-0.1476226,-0.15104344,-0.067619346,-0.16481127,0.21846052,-0.30124533,0.045698553,0.38941357,-0.10810472,-0.71925175,-0.18955056,0.21814509,-0.007830826,-0.084871165,-1.661617,0.36890027,-0.04803907,0.4230195,0.007371555,-0.13465434,-0.28911352,0.31149048,0.52282137,-0.21870002,-0.07622825,-0.35062796,0.08141248,-0.19145869,-0.13988546,0.0745427,0.3848922,-0.14236186,0.13666363,-0.34605712,-0.04734612,-0.61792105,0.26964593,0.58009416,0.29835963,-0.5552955,0.24713048,0.26792675,-0.24853806,-1.0508811,-0.41234547,0.48150936,0.118850425,0.10623887,-0.40978006,0.794509,0.04916128,0.184393,-0.44202974,0.092311405,-0.21542746,-0.19697665,0.3475663,-0.3352781,0.26198903,0.35324204,0.23053844,0.27943027,-0.38737315,-0.0074736206,-0.56749517,0.16836305,0.13101311,0.1300742,-0.091848955,-0.15859531,-0.019197378,0.20404896,-0.55928105,-0.0018080965,-0.25833327,-0.14486693,0.1729433,-0.13557348,-0.24484064,-0.15548855,-0.06787563,0.27250132,1.8487904,0.09864328,-0.07601436,0.125998,0.5761682,0.17214145,-0.100243114,0.020097302,-0.07567977,-0.28230727,-0.32835418,-0.11709316,0.5548923,-0.5643981,0.027739888,0.4113075,-0.28430724,-0.48949155,-0.20032153,0.34179676,-0.00087322795,-0.0467108,-0.2573292,0.004911595,-0.017785378,0.109745294,0.4374557,0.21395196,0.25201428,-0.28252816,0.23209867,-0.2143336,-0.37659666,-0.17087261,0.080155455,-0.38134557,0.752346,0.19551505,0.08048894,-0.038924925,0.007970118,-0.10932087,-0.31484035,-0.026098477,0.3035418,-0.32112986,0.04762649,-0.3638137,0.1858679,-0.058276672,-0.13469857,0.068111725,0.09578244,-0.42729372,-0.15250754,0.28811988,0.025063688,0.17742571,0.21514256,0.104601435,-0.43216527,-0.15890463,0.21163309,0.16059124,0.017169256,-0.29099703,0.23572385,-0.013026644,0.55426186,0.016061338,-0.05531415,-0.2371357,-0.053715125,-0.066305235,0.176894,0.1323304,0.69017386,0.17562194,-0.06800517,-0.18579629,0.62259984,0.024958383,-0.354558,-0.27101418]');
-INSERT INTO public.photos (path, file_name, file_size, created_at, modified_at, indexed_at, hash, camera_make, camera_model, lens_model, orientation, date_taken_local, date_taken_utc, gps_location, image_width, image_height, embedding) VALUES ('tests/data/images/sub/sub/building_vietnam.jpg', 'building_vietnam.jpg', 7956445, '2025-06-27 07:22:54.817069+00', '2025-06-27 07:22:54.817069+00', '2025-06-28 12:43:58.019797+00', '3fc9d4d812e06a088f7bcfd9dcdab08c2637057aece10fa327e95837a051e0aa', 'Apple', 'iPhone 15', 'iPhone 15 back dual wide camera 5.96mm f/1.6', NULL, '2025-03-21 14:32:40', '2025-03-21 07:32:40+00', '0101000020E61000002D8BFE1F89AC5A40262D2065628E2540', NULL, NULL, '[-0.13416837,0.09264122,0.094666064,0.16259024,0.28597602,-0.18279989,-0.18129696,0.10996218,-0.6670256,0.4051555,0.2921193,-0.16026358,-0.15843678,-0.025450904,0.38262138,-0.17653787,-0.25993782,0.121207036,0.43205184,-0.21303152,0.45351103,0.24407214,0.10193385,-0.115270086,0.5512354,0.2148693,0.30429178,-0.0017412265,-0.18887804,-0.2745289,-0.25144857,0.19584104,-0.62788683,0.14527968,0.49677953,0.3972945,-0.12080337,0.12696806,-0.16815645,0.12447574,-0.40296248,0.07665792,0.1775629,-0.1433406,0.08612952,1.2537972,-0.020678097,0.34960935,0.35106522,-0.4173498,0.20654312,-0.41053167,0.57422686,0.733116,-0.05034118,0.3615212,0.60590374,-0.01168777,0.4753688,-0.06302203,0.8339307,-0.19208834,0.23527372,0.021157576,0.0474313,0.15602574,0.4444313,0.4868498,-0.07817209,0.20221719,0.027607596,-0.28691828,-0.30481914,-0.053799137,0.03278223,0.1090031,0.07527795,0.075990096,0.06308082,0.2707887,0.78511083,-0.0069846557,-0.48331305,0.2105598,0.2463195,0.27080244,-0.35529685,0.059295878,0.49648526,-0.27677763,-0.12569503,0.24521841,-5.165992,0.88212425,0.06489655,0.48478392,0.09532106,0.106484145,-0.056007534,-0.64237595,-0.08186424,0.07040186,0.274822,0.28595847,0.5490208,-0.19999842,-1.9027708,0.220279,-0.0040602977,-0.019773284,-0.54318976,-0.7411033,-0.18738034,0.13763736,0.1412163,0.58492357,0.30009022,-0.027575675,0.08420648,-0.2460578,0.45896226,0.7853515,0.27536508,0.017585292,0.08931584,0.0929517,0.093644574,0.15802585,0.08136682,0.34721127,-0.3884868,-0.5914739,-0.34005877,0.7992304,0.083648495,0.3537319,0.5419687,-0.5738764,0.7739777,0.23901606,-0.37050554,0.3535543,-0.1663638,-0.13561854,-0.25610307,0.7189878,-0.1722726,0.15834115,-0.18240875,-0.064251885,-0.15087073,0.11714718,0.6421319,-0.29574814,0.4024506,-0.3611415,0.01772305,-0.07931995,0.4213614,-0.23008461,0.056550212,-0.057189453,0.04945926,-0.32639956,-0.39981574,-0.41394493,1.2309535,0.10900116,-0.19588326,-0.2958173,0.19121282,0.027891457,0.29116255,-0.33959997,-0.45899945,0.56081045,-0.3478307,-0.041051943,0.040096734,-0.042345732,0.12010412,0.56224334,0.3261313,-0.22325113,-0.09166859,-0.48850822,-0.14539969,-0.07140083,-0.23277959,0.02647123,0.28125754,0.045884717,0.0711931,0.24513733,-0.044016555,-0.62479657,-0.087672144,-0.27043292,-0.691985,0.06354529,-0.24554299,0.19196202,-0.122547306,-0.229204,0.47510502,0.0049943742,0.36006704,-0.48104712,0.122405104,-0.13324219,-0.6274942,0.22568983,0.4603234,0.07877931,0.2298372,0.20349967,0.52979434,0.18140125,-0.10797011,-0.25001872,-0.2579057,0.16730486,0.012947803,-0.10329279,-0.24862713,-0.008969944,0.38764182,-0.27052122,-0.46948686,0.42440513,-0.39135215,0.13305081,0.16573893,-0.035563946,0.022205595,0.2671088,-0.14819959,-0.042893667,0.013436188,-0.28666288,-0.11548413,-0.11964298,0.36700884,-0.63706213,-0.051300924,-0.0029491528,-0.13527153,-0.02478182,-0.2517211,0.13063496,-0.21552885,0.5225309,0.157513,0.20887439,0.076711245,0.19304913,0.26109844,0.2109554,2.687529,-0.07726099,0.21566464,0.35390702,-0.2635359,1.547164,0.2912285,-0.07862701,0.13059127,0.33211398,0.49717078,-0.20584251,0.024015484,-0.025703095,0.10210977,0.23514995,-0.047217797,-0.13537163,0.17773636,0.5817985,-0.13014755,-0.051592432,-0.07626546,0.74029034,-0.7956788,-0.14970063,0.12545626,-0.2546315,-0.8754168,0.06021966,-0.98864293,-0.2517725,0.23569195,-0.4507845,-0.026363581,-0.06285689,-0.3116906,0.14780296,0.7308147,0.2230407,0.058856193,0.14350292,-0.10608803,-0.2938375,-0.12885073,0.41753173,0.062267084,0.114274465,0.25461355,0.29437295,0.44804978,-0.31171584,-0.124275014,0.36327255,0.7982886,0.24591348,0.047529295,-0.2923156,0.12834564,-0.3207324,0.117529854,0.50227666,-0.34946093,0.2320628,-0.79395723,-0.48605677,0.10121325,-0.0065113395,0.5513257,0.49157903,-0.10696024,-0.2055537,-0.25578895,-0.0497256,0.17483258,-0.2651298,-0.28826135,0.11367182,0.25063586,-0.032516196,0.16799149,0.050795786,-0.36407697,-0.32687604,-0.1558174,0.069903724,0.00800317,0.2196623,-0.1252809,0.06633334,-0.2565128,0.106622,0.019755531,0.076572366,0.18284675,0.054154504,-0.12744755,-0.59977794,-0.537975,0.6671225,0.018648595,-0.16406615,-0.9918294,-0.037663355,-0.34277242,0.43573874,-1.3570687,-0.014580506,-0.24873121,-0.8705667,-0.28727114,-0.13207594,-0.23841812,0.038226552,-0.04479203,-0.0809114,-0.10411893,-0.36960924,-0.20526068,-0.057082456,0.8044066,0.69514275,0.067283235,0.23835137,-0.35634384,0.44325837,0.09425662,-0.5125402,-0.23039934,0.089141935,-0.006589194,0.68834937,0.07060819,0.055508133,0.19907722,-0.16420576,0.07393053,-0.18580464,-0.26470977,-0.24114229,-0.3541739,0.5211109,-0.59235376,-0.5101996,0.05763938,-0.13684875,-0.07649267,-0.126564,-0.43967184,0.05453914,-0.11604993,-0.008423377,-0.5223209,-0.15554366,0.059932645,0.16725314,-0.22038151,0.24060516,0.016879173,0.19734368,-0.39243484,-0.17968434,-0.084707215,0.12441058,0.38224912,0.11556965,-0.1266817,0.17462681,-0.8789409,-0.26766574,-1.8054137,-0.12883522,0.34660333,-0.06227505,0.062097114,-0.24090767,-0.25348857,0.5017869,-0.18204026,-0.27155468,0.087209955,0.26110137,-0.4488328,0.29812235,-0.19247678,0.059136033,0.028483799,0.24619952,0.13903378,-0.18645655,0.090245515,0.15479341,-0.75228935,0.06673141,0.19267368,0.5220579,-0.05915071,-0.26559815,0.3188743,-0.3228074,0.1442824,-0.014047147,-0.23962285,0.009255777,0.24723631,-0.42946434,0.0836367,-0.03047648,0.36848688,-0.06250067,-0.5562318,-0.09845178,-0.16703427,-0.016998947,-0.06467604,-0.30575094,0.37453964,0.24609706,0.0743353,-0.118017375,-0.2709126,-0.0059077297,-0.41075075,-0.8452766,0.13295224,0.17237677,0.9136284,-0.32242423,-0.48652044,0.07130274,-0.6280312,-0.6469719,0.19475076,-0.31094363,0.05918207,-0.6049925,-0.06917072,0.016422164,-0.27308652,0.14047305,-0.20418844,0.031001786,0.1404443,-0.045135204,-0.18529476,0.13634858,0.5117572,0.19531792,0.23491381,0.47289342,0.3471082,0.43589064,-0.29950893,-0.2288426]');
+INSERT INTO public.photos (path, file_name, file_size, created_at, modified_at, indexed_at, hash, camera_make, camera_model, lens_model, orientation, date_taken_local, date_taken_utc, gps_location, image_width, image_height) VALUES ('tests/data/images/sunrise_laos.heic', 'sunrise_laos.heic', 1377703, '2025-06-27 07:22:54.863388+00', '2025-06-27 07:22:54.863388+00', '2025-06-28 12:43:57.956159+00', 'd320984f4123079ec52092ad9a9318dd8f5b325238d12f3ce7592f44ea828e5f', 'Apple', 'iPhone 15', 'iPhone 15 back dual wide camera 1.54mm f/2.4', NULL, '2025-02-22 06:55:25', '2025-02-21 23:55:25+00', '0101000020E61000001EEFCA7DD499594032E82249C1E93240', NULL, NULL);
+INSERT INTO public.photo_embeddings (photo_id, model, embedding) SELECT id, 'clip-vit-base-patch32', '[-0.09428595,0.007138477,-0.39367875,-0.038943876,-0.062302362,-0.3607945,0.33876407,-0.0884477,0.40466365,0.47804537,0.27915722,-0.26219833,-0.19444285,-0.13364209,0.20696244,-0.35118473,-0.685174,-0.20015763,-0.047927205,-0.16885184,1.1818101,0.372717,0.21915504,-0.75631624,-0.40021166,-0.1084184,0.09472723,-0.35562357,-0.45012715,0.41086152,-0.40197346,0.45333874,0.13158019,-0.41818032,-0.24652725,-0.085260265,0.004769221,0.10992374,0.034073446,-0.13225187,-0.10043166,0.35298678,-0.30092835,-0.1344205,-0.25776356,-0.69168794,0.026983833,-0.23731786,-0.099355236,-0.2579292,0.5480238,-0.20350777,0.49836633,0.25346303,0.26563674,0.01896142,0.33298433,-0.35151282,0.74379224,0.494871,-0.1975045,0.15635428,0.22188501,-0.21614501,-0.4444685,-0.21377757,-0.4248644,0.13189618,-0.049979776,-0.34964037,-0.41241774,-0.37605327,0.20527391,0.01059377,0.2737804,-0.32704535,0.15904838,0.76575285,0.2664512,-0.326705,-0.053643204,-0.072065696,0.3852663,0.39412758,0.2831664,-0.26948974,-0.23738016,-0.49315995,-0.013679735,0.3086236,-0.15739422,0.26800892,-4.6900315,0.6323519,0.13285312,0.44484523,-0.5091965,0.16741256,0.28573847,-1.3915869,-0.048099283,-0.34708205,-0.28977644,-0.17793114,-0.9949285,-0.08229464,-1.3273616,0.2612884,0.16273503,0.1269823,-0.013634255,-0.6218278,0.12063302,0.27861398,-0.032038193,0.1064457,-0.19744281,0.17821722,0.12372398,0.0041397703,0.3358629,0.22070597,0.09238358,-0.42499614,-0.14933607,-0.03255135,0.11539644,-0.17083043,-0.16057606,0.21601138,-0.0062538637,0.21162626,0.43945864,0.7719097,0.5200926,0.023587728,0.03738524,-0.44689545,-0.46166506,-0.10013772,-0.20141165,0.33604318,-0.24342969,-0.4380231,-0.2965186,-0.2639957,0.09126422,0.36045644,0.24712989,0.018309502,0.031678714,0.31932405,-0.925494,0.042466573,-0.44806567,-0.46600288,0.001434687,0.18770164,0.05508512,-0.10291266,-0.11972141,-0.0032860548,-0.050566114,0.44574043,-0.23383804,-0.34169224,0.947088,-0.1965763,0.21876457,0.07045588,0.077025,-0.15266673,-0.6276299,-0.5826132,-0.3306159,0.16898394,-1.8865881,-0.26621085,-0.30573454,-0.15184996,0.12299425,0.53322023,0.09889291,-0.4594533,0.48232043,0.18628792,0.3889547,0.64631134,-0.06728459,-0.057665233,0.42841357,-0.17009614,-0.12788802,-0.13571921,-0.78107524,0.3946866,0.5523207,-0.3324498,0.036140874,0.05896863,0.16147529,0.018147595,-0.06909646,0.29303136,0.25580174,-0.112311706,-0.05617944,-0.3117124,0.054760236,-0.3551148,-0.4072044,0.116201766,0.21168177,-0.05364261,0.64767104,-0.47992405,0.2405685,0.113736495,-0.28811777,-0.03461781,-0.22905992,0.40616181,-0.24885298,-0.25216007,-0.09344563,0.06549679,-0.42950863,-0.33008566,-0.28402308,-0.2230117,-0.15874416,0.031821523,0.18683875,-0.48126218,0.6348817,-0.08101846,0.15684818,0.0075395615,0.47177958,-0.23749265,0.37749022,0.03920921,0.17201962,-0.11007639,-0.20850593,-0.31412727,0.14256343,-0.24779266,-0.014469693,-0.12675777,-0.100359045,0.068291,0.20233828,0.14994712,-0.17449984,-0.0036323327,-0.24497563,-0.2098579,0.4431187,-0.16131485,0.04297394,-0.27399102,0.0022418578,0.47057754,-0.06997244,0.27712137,-0.47529337,0.052363615,0.32529202,-0.35979003,0.24150907,0.36048403,0.10420732,-0.029187419,-0.025691563,0.63980776,-0.14509656,-0.047995556,-0.07389011,-0.18261164,0.0574644,-0.06417832,0.1954076,-0.29786566,0.07041707,0.44694325,0.7648319,0.6025626,0.092845894,-0.20356765,0.2628601,0.14295927,0.072322324,0.17927754,0.18005021,-0.17563534,0.4390983,0.07088276,0.30056345,-0.011835696,0.40045875,0.14504899,-0.6232829,0.04804924,0.38983414,-0.3221603,0.08947801,0.28580526,0.47245106,-0.18827115,-0.15180737,-0.090391025,0.7688197,-0.38970408,-0.033321083,0.061403215,0.26417372,-0.101792045,-0.20093553,-0.1644779,-0.068985574,2.427257,-0.2907649,-0.46357056,-0.12892424,-0.44230968,-0.1418605,-0.24658969,-0.107522,-0.8229766,-0.46565312,0.41958767,-0.17409128,0.049530238,-0.13343757,0.5053142,0.14766516,-0.48801932,0.52153,0.29178584,-0.11015727,-0.69691414,0.21687043,0.5672732,0.0842851,-0.033312898,0.076487735,-0.3577775,0.026548984,0.6509431,-0.0015428782,0.3062039,0.25255617,0.16662247,0.1951438,-0.65544367,-0.6062573,-0.6569153,-0.36536577,-0.3533324,-0.13381101,-0.07693684,-0.48691475,1.0382445,0.019571323,-0.29041025,-0.33240303,-0.47623348,0.07373459,-0.43135673,0.0079977885,-0.056810535,-0.29512134,0.10650166,-0.362561,0.23053588,-0.1609801,-0.22932182,0.61181515,-0.18213016,0.43661615,-0.19704868,-0.030062322,-0.3270976,0.689899,-0.2797793,-0.10787389,0.6597479,-0.6063032,-0.12594242,-1.0137023,-0.09217647,-0.12691897,-0.033102874,0.28976998,0.01307938,0.36515614,0.09775691,-0.78829587,-0.28711534,0.047272284,-0.06674392,0.61070615,-0.34767738,0.21854222,0.3488942,0.14130919,-0.03215904,-0.048761208,-0.4886355,-0.032554347,0.5158605,0.16063601,0.10919135,-0.19667228,-0.05824577,-0.018219996,0.09245172,-0.19413707,0.07190733,-0.25297883,-0.18109708,-0.023160035,-0.47498128,-0.532032,0.16079436,-0.043907546,-0.07806894,0.67514575,0.7401905,-0.42761427,0.1419661,-1.1507757,-0.22205499,-0.038083173,-0.39607793,-0.2769954,-0.27584177,-0.38884622,-0.07317706,-0.00846503,-0.50167125,0.23107457,0.28145716,0.025316892,-0.11187766,-0.19327872,0.1398804,-0.24441165,-0.24083748,0.06396187,0.518985,-0.31502464,-0.09352878,0.16826995,0.05009358,0.28922194,-0.42082778,-0.23866415,-0.75016135,0.034988202,-0.18978515,-0.39960036,-0.3511516,-0.08940607,0.27704984,0.29970306,0.09124665,0.2000303,-0.1195234,-0.04960972,0.15864249,-0.060894545,-0.37294668,-0.045420475,-0.06950304,0.25311032,-0.02371519,-0.6212659,-0.25473243,0.23367476,-0.8400048,-0.18930593,-0.13053748,-0.06494283,-0.40457702,-0.075638935,0.14606264,-0.25129616,-0.03325117,0.2363678,-0.2651419,-0.07005284,-0.45459577,-0.1561978,-0.07738784,0.2802522,-0.11173048,-0.27464613,0.104104616,-0.16409282,0.4299495,-0.021105954,-0.25346205,0.57346046,-0.44535634,-0.8893107,-0.36920685,0.2991137,-0.13136375,-0.28526756,-0.0013955798]' FROM public.photos WHERE path = 'tests/data/images/sunrise_laos.heic';
+INSERT INTO public.photos (path, file_name, file_size, created_at, modified_at, indexed_at, hash, camera_make, camera_model, lens_model, orientation, date_taken_local, date_taken_utc, gps_location, image_width, image_height) VALUES ('tests/data/images/sub/desk_vietnam.heic', 'desk_vietnam.heic', 2924690, '2025-06-27 07:22:54.806103+00', '2025-06-27 07:22:54.806103+00', '2025-06-28 12:43:57.977522+00', '40a1d834dfa6ab98dd3d5b4196a4d10f3409f76b28ac5f9a520daf20ae221bc3', 'Apple', 'iPhone 15', 'iPhone 15 back dual wide camera 5.96mm f/1.6', NULL, '2025-03-21 14:25:20', '2025-03-21 07:25:20+00', '0101000020E61000006B74925E72AC5A40BE5296218E8D2540', NULL, NULL);
+INSERT INTO public.photo_embeddings (photo_id, model, embedding) SELECT id, 'clip-vit-base-patch32', '[0.09326888,-0.035485577,-0.19403689,0.22151734,0.071413785,-0.46993926,0.41657194,0.27683997,-0.21607828,0.11936089,0.16215868,-0.15396191,-0.025384499,-0.06167898,0.166727,-0.42861703,0.024264397,0.39349744,-0.14479835,-0.0933606,1.3415992,0.1209454,0.5150157,-0.16966403,0.07206543,0.23700364,0.26893583,-0.4921209,-0.4011577,-0.014689595,0.34894538,0.37848732,-0.2332289,-0.033474382,-0.6284356,-0.39506444,-0.37558225,-0.114362575,-0.0078090318,-0.942655,-0.7656015,0.19837482,-0.39676392,-0.5258945,-0.04231727,-1.295786,0.17526127,-0.046053775,-0.108545765,0.0474208,0.18945985,-0.23400946,-0.0037635632,-0.22704051,0.030173542,0.59097046,-0.4267286,-0.19393453,0.34834105,0.11747435,-0.86226064,0.24532928,-0.052709166,-0.27568865,-0.11793588,0.37609622,-0.063672446,-0.614676,0.30675673,0.0020432856,-0.22835685,-0.25728232,0.6289749,-0.5756664,-0.32886243,-0.19138184,0.3457393,0.36644378,-0.15791804,-0.032621387,0.28406543,0.16088577,-0.1247306,0.12135763,-0.2517246,-0.2545281,0.014620731,0.09254778,-0.28556815,0.26921666,-0.07862486,0.17132874,-6.061267,0.22323748,0.44706413,0.42673746,0.20172486,0.08130866,0.39047214,0.48154682,0.057504363,0.11274782,-0.088580646,0.049835626,-0.07949665,-0.13622741,-2.2092752,0.36201587,0.3275436,0.053910058,-0.0783423,0.15049899,-0.15606233,-0.18684083,0.4402203,-0.48364672,-0.13864452,0.017871141,-0.0016995048,0.18905796,0.6998864,-0.37469673,0.21476494,-0.3211124,0.10135597,0.43563408,-0.035170417,-0.06451226,0.005874235,0.19185501,-0.33486128,0.028611153,0.07157444,0.8519859,0.13259466,0.42302468,0.058189817,-0.40286466,-0.3037554,-0.14895436,0.6529605,-0.1511731,-0.24761282,-0.266679,-0.5338832,-0.20085564,0.3108234,0.09124875,0.13505696,0.011375015,0.09442667,0.22627169,-0.94104755,0.34483284,-0.26116484,-0.026897177,-0.2665507,-0.25645176,0.10526649,-0.020434655,-0.40113458,-0.042929493,-0.15345639,0.0067691943,0.14229581,0.67078996,1.1308588,-0.003393634,0.12507328,-0.03130306,0.18491247,-0.58553046,-0.004337876,0.085297905,-0.52863145,0.58301055,-0.43331808,0.08569968,0.34361812,-0.38685203,0.47042572,0.05056346,0.15453301,-0.05846451,-0.107726514,-0.088027336,0.17431988,-0.108911574,-0.0007100985,0.016444802,0.64202005,-0.1550374,0.06433715,0.17284112,-0.6141376,-0.10564544,0.10501663,-0.031756,-1.4660373,0.08013111,0.13395298,-0.067035615,0.058287527,0.16853806,0.2684203,0.35697806,0.1456349,-0.23565558,0.003308095,-0.10459329,0.4989644,0.29533365,-0.10853734,-0.1885023,0.27014637,0.12592873,0.19401897,0.19552374,-0.33489284,0.16679366,-0.24900593,0.36210048,-0.43927884,-0.43144342,-0.00206019,0.12702228,-0.16695537,0.17493089,-0.27439186,-0.12535389,0.030463992,0.24344382,0.4292603,-0.0075825783,0.226533,0.55151975,0.2692593,0.45908898,0.5611976,0.13248792,0.18780582,-0.39437667,0.054646213,-0.04235629,-0.0813667,0.6533665,0.2638077,-0.17130926,0.17890443,-0.58087677,0.05279418,-0.23524684,0.42474443,0.25439477,-0.07759546,0.3153868,-0.07168813,-0.044103894,-1.612984,0.14870979,0.19594952,0.56626076,-0.4188928,-0.3754591,0.06338433,0.26229244,0.1315864,-0.45939526,0.027425937,0.13082968,0.21078287,-0.16645478,0.15348509,0.3265249,-0.1224076,0.34926847,-0.39219218,0.46852937,-0.11398095,-0.2492445,0.46227652,0.152933,0.15761477,-0.23499155,-0.05138612,0.07728813,0.48979318,0.098559365,-0.035889026,-0.05188032,0.5240879,-0.26700237,0.5246991,0.089134805,0.014721192,-0.026526734,-0.15070479,-0.02805834,0.18342349,-0.28014806,-0.2366454,0.12314702,-0.5827303,-0.12664679,0.19833565,-0.15866223,-0.6973152,0.19856234,0.29412943,-0.22312462,-0.50632226,0.55140847,0.84873766,-0.21538252,0.32224753,-0.17662728,0.38899764,-0.20176677,0.042104557,-0.76033455,0.4214432,2.134712,0.33962536,-0.51869917,-0.33366424,-0.6967055,0.4125172,0.05259973,-0.26250052,-0.5047127,0.28044444,-0.26115832,0.15070698,-0.031762864,-0.072779365,0.49058756,0.30286258,-0.45286867,-0.1985399,-0.04215786,-0.1522322,-0.7903891,-0.11277615,0.2400926,0.35149997,0.1541727,-0.1476226,-0.15104344,-0.067619346,-0.16481127,0.21846052,-0.30124533,0.045698553,0.38941357,-0.10810472,-0.71925175,-0.18955056,0.21814509,-0.007830826,-0.084871165,-1.661617,0.36890027,-0.04803907,0.4230195,0.007371555,-0.13465434,-0.28911352,0.31149048,0.52282137,-0.21870002,-0.07622825,-0.35062796,0.08141248,-0.19145869,-0.13988546,0.0745427,0.3848922,-0.14236186,0.13666363,-0.34605712,-0.04734612,-0.61792105,0.26964593,0.58009416,0.29835963,-0.5552955,0.24713048,0.26792675,-0.24853806,-1.0508811,-0.41234547,0.48150936,0.118850425,0.10623887,-0.40978006,0.794509,0.04916128,0.184393,-0.44202974,0.092311405,-0.21542746,-0.19697665,0.3475663,-0.3352781,0.26198903,0.35324204,0.23053844,0.27943027,-0.38737315,-0.0074736206,-0.56749517,0.16836305,0.13101311,0.1300742,-0.091848955,-0.15859531,-0.019197378,0.20404896,-0.55928105,-0.0018080965,-0.25833327,-0.14486693,0.1729433,-0.13557348,-0.24484064,-0.15548855,-0.06787563,0.27250132,1.8487904,0.09864328,-0.07601436,0.125998,0.5761682,0.17214145,-0.100243114,0.020097302,-0.07567977,-0.28230727,-0.32835418,-0.11709316,0.5548923,-0.5643981,0.027739888,0.4113075,-0.28430724,-0.48949155,-0.20032153,0.34179676,-0.00087322795,-0.0467108,-0.2573292,0.004911595,-0.017785378,0.109745294,0.4374557,0.21395196,0.25201428,-0.28252816,0.23209867,-0.2143336,-0.37659666,-0.17087261,0.080155455,-0.38134557,0.752346,0.19551505,0.08048894,-0.038924925,0.007970118,-0.10932087,-0.31484035,-0.026098477,0.3035418,-0.32112986,0.04762649,-0.3638137,0.1858679,-0.058276672,-0.13469857,0.068111725,0.09578244,-0.42729372,-0.15250754,0.28811988,0.025063688,0.17742571,0.21514256,0.104601435,-0.43216527,-0.15890463,0.21163309,0.16059124,0.017169256,-0.29099703,0.23572385,-0.013026644,0.55426186,0.016061338,-0.05531415,-0.2371357,-0.053715125,-0.066305235,0.176894,0.1323304,0.69017386,0.17562194,-0.06800517,-0.18579629,0.62259984,0.024958383,-0.354558,-0.27101418]' FROM public.photos WHERE path = 'tests/data/images/sub/desk_vietnam.heic';
+INSERT INTO public.photos (path, file_name, file_size, created_at, modified_at, indexed_at, hash, camera_make, camera_model, lens_model, orientation, date_taken_local, date_taken_utc, gps_location, image_width, image_height) VALUES ('tests/data/images/sub/sub/building_vietnam.jpg', 'building_vietnam.jpg', 7956445, '2025-06-27 07:22:54.817069+00', '2025-06-27 07:22:54.817069+00', '2025-06-28 12:43:58.019797+00', '3fc9d4d812e06a088f7bcfd9dcdab08c2637057aece10fa327e95837a051e0aa', 'Apple', 'iPhone 15', 'iPhone 15 back dual wide camera 5.96mm f/1.6', NULL, '2025-03-21 14:32:40', '2025-03-21 07:32:40+00', '0101000020E61000002D8BFE1F89AC5A40262D2065628E2540', NULL, NULL);
+INSERT INTO public.photo_embeddings (photo_id, model, embedding) SELECT id, 'clip-vit-base-patch32', '[-0.13416837,0.09264122,0.094666064,0.16259024,0.28597602,-0.18279989,-0.18129696,0.10996218,-0.6670256,0.4051555,0.2921193,-0.16026358,-0.15843678,-0.025450904,0.38262138,-0.17653787,-0.25993782,0.121207036,0.43205184,-0.21303152,0.45351103,0.24407214,0.10193385,-0.115270086,0.5512354,0.2148693,0.30429178,-0.0017412265,-0.18887804,-0.2745289,-0.25144857,0.19584104,-0.62788683,0.14527968,0.49677953,0.3972945,-0.12080337,0.12696806,-0.16815645,0.12447574,-0.40296248,0.07665792,0.1775629,-0.1433406,0.08612952,1.2537972,-0.020678097,0.34960935,0.35106522,-0.4173498,0.20654312,-0.41053167,0.57422686,0.733116,-0.05034118,0.3615212,0.60590374,-0.01168777,0.4753688,-0.06302203,0.8339307,-0.19208834,0.23527372,0.021157576,0.0474313,0.15602574,0.4444313,0.4868498,-0.07817209,0.20221719,0.027607596,-0.28691828,-0.30481914,-0.053799137,0.03278223,0.1090031,0.07527795,0.075990096,0.06308082,0.2707887,0.78511083,-0.0069846557,-0.48331305,0.2105598,0.2463195,0.27080244,-0.35529685,0.059295878,0.49648526,-0.27677763,-0.12569503,0.24521841,-5.165992,0.88212425,0.06489655,0.48478392,0.09532106,0.106484145,-0.056007534,-0.64237595,-0.08186424,0.07040186,0.274822,0.28595847,0.5490208,-0.19999842,-1.9027708,0.220279,-0.0040602977,-0.019773284,-0.54318976,-0.7411033,-0.18738034,0.13763736,0.1412163,0.58492357,0.30009022,-0.027575675,0.08420648,-0.2460578,0.45896226,0.7853515,0.27536508,0.017585292,0.08931584,0.0929517,0.093644574,0.15802585,0.08136682,0.34721127,-0.3884868,-0.5914739,-0.34005877,0.7992304,0.083648495,0.3537319,0.5419687,-0.5738764,0.7739777,0.23901606,-0.37050554,0.3535543,-0.1663638,-0.13561854,-0.25610307,0.7189878,-0.1722726,0.15834115,-0.18240875,-0.064251885,-0.15087073,0.11714718,0.6421319,-0.29574814,0.4024506,-0.3611415,0.01772305,-0.07931995,0.4213614,-0.23008461,0.056550212,-0.057189453,0.04945926,-0.32639956,-0.39981574,-0.41394493,1.2309535,0.10900116,-0.19588326,-0.2958173,0.19121282,0.027891457,0.29116255,-0.33959997,-0.45899945,0.56081045,-0.3478307,-0.041051943,0.040096734,-0.042345732,0.12010412,0.56224334,0.3261313,-0.22325113,-0.09166859,-0.48850822,-0.14539969,-0.07140083,-0.23277959,0.02647123,0.28125754,0.045884717,0.0711931,0.24513733,-0.044016555,-0.62479657,-0.087672144,-0.27043292,-0.691985,0.06354529,-0.24554299,0.19196202,-0.122547306,-0.229204,0.47510502,0.0049943742,0.36006704,-0.48104712,0.122405104,-0.13324219,-0.6274942,0.22568983,0.4603234,0.07877931,0.2298372,0.20349967,0.52979434,0.18140125,-0.10797011,-0.25001872,-0.2579057,0.16730486,0.012947803,-0.10329279,-0.24862713,-0.008969944,0.38764182,-0.27052122,-0.46948686,0.42440513,-0.39135215,0.13305081,0.16573893,-0.035563946,0.022205595,0.2671088,-0.14819959,-0.042893667,0.013436188,-0.28666288,-0.11548413,-0.11964298,0.36700884,-0.63706213,-0.051300924,-0.0029491528,-0.13527153,-0.02478182,-0.2517211,0.13063496,-0.21552885,0.5225309,0.157513,0.20887439,0.076711245,0.19304913,0.26109844,0.2109554,2.687529,-0.07726099,0.21566464,0.35390702,-0.2635359,1.547164,0.2912285,-0.07862701,0.13059127,0.33211398,0.49717078,-0.20584251,0.024015484,-0.025703095,0.10210977,0.23514995,-0.047217797,-0.13537163,0.17773636,0.5817985,-0.13014755,-0.051592432,-0.07626546,0.74029034,-0.7956788,-0.14970063,0.12545626,-0.2546315,-0.8754168,0.06021966,-0.98864293,-0.2517725,0.23569195,-0.4507845,-0.026363581,-0.06285689,-0.3116906,0.14780296,0.7308147,0.2230407,0.058856193,0.14350292,-0.10608803,-0.2938375,-0.12885073,0.41753173,0.062267084,0.114274465,0.25461355,0.29437295,0.44804978,-0.31171584,-0.124275014,0.36327255,0.7982886,0.24591348,0.047529295,-0.2923156,0.12834564,-0.3207324,0.117529854,0.50227666,-0.34946093,0.2320628,-0.79395723,-0.48605677,0.10121325,-0.0065113395,0.5513257,0.49157903,-0.10696024,-0.2055537,-0.25578895,-0.0497256,0.17483258,-0.2651298,-0.28826135,0.11367182,0.25063586,-0.032516196,0.16799149,0.050795786,-0.36407697,-0.32687604,-0.1558174,0.069903724,0.00800317,0.2196623,-0.1252809,0.06633334,-0.2565128,0.106622,0.019755531,0.076572366,0.18284675,0.054154504,-0.12744755,-0.59977794,-0.537975,0.6671225,0.018648595,-0.16406615,-0.9918294,-0.037663355,-0.34277242,0.43573874,-1.3570687,-0.014580506,-0.24873121,-0.8705667,-0.28727114,-0.13207594,-0.23841812,0.038226552,-0.04479203,-0.0809114,-0.10411893,-0.36960924,-0.20526068,-0.057082456,0.8044066,0.69514275,0.067283235,0.23835137,-0.35634384,0.44325837,0.09425662,-0.5125402,-0.23039934,0.089141935,-0.006589194,0.68834937,0.07060819,0.055508133,0.19907722,-0.16420576,0.07393053,-0.18580464,-0.26470977,-0.24114229,-0.3541739,0.5211109,-0.59235376,-0.5101996,0.05763938,-0.13684875,-0.07649267,-0.126564,-0.43967184,0.05453914,-0.11604993,-0.008423377,-0.5223209,-0.15554366,0.059932645,0.16725314,-0.22038151,0.24060516,0.016879173,0.19734368,-0.39243484,-0.17968434,-0.084707215,0.12441058,0.38224912,0.11556965,-0.1266817,0.17462681,-0.8789409,-0.26766574,-1.8054137,-0.12883522,0.34660333,-0.06227505,0.062097114,-0.24090767,-0.25348857,0.5017869,-0.18204026,-0.27155468,0.087209955,0.26110137,-0.4488328,0.29812235,-0.19247678,0.059136033,0.028483799,0.24619952,0.13903378,-0.18645655,0.090245515,0.15479341,-0.75228935,0.06673141,0.19267368,0.5220579,-0.05915071,-0.26559815,0.3188743,-0.3228074,0.1442824,-0.014047147,-0.23962285,0.009255777,0.24723631,-0.42946434,0.0836367,-0.03047648,0.36848688,-0.06250067,-0.5562318,-0.09845178,-0.16703427,-0.016998947,-0.06467604,-0.30575094,0.37453964,0.24609706,0.0743353,-0.118017375,-0.2709126,-0.0059077297,-0.41075075,-0.8452766,0.13295224,0.17237677,0.9136284,-0.32242423,-0.48652044,0.07130274,-0.6280312,-0.6469719,0.19475076,-0.31094363,0.05918207,-0.6049925,-0.06917072,0.016422164,-0.27308652,0.14047305,-0.20418844,0.031001786,0.1404443,-0.045135204,-0.18529476,0.13634858,0.5117572,0.19531792,0.23491381,0.47289342,0.3471082,0.43589064,-0.29950893,-0.2288426]' FROM public.photos WHERE path = 'tests/data/images/sub/sub/building_vietnam.jpg';
@@ -2,8 +2,10 @@ use diesel::RunQueryDsl;
 use pgvector::Vector;
 use picasa_core::{
     database::schema,
-    models::{NewFace, PaginationFilter},
-    repositories::{FaceFindFilters, FaceRepository, PgFaceRepository},
+    models::{FaceRegion, NewFace, NewPhoto, PaginationFilter},
+    repositories::{
+        FaceFindFilters, FaceRepository, PgFaceRepository, PgPhotoRepository, PhotoRepository,
+    },
 };
 use serial_test::serial;
 
@@ -103,3 +105,52 @@ fn test_should_find_faces_by_photo_id() {
     assert_eq!(result.items[0].photo_id, photos[0].id);
     assert_eq!(result.items[0].bbox_x, 100);
 }
+
+#[test]
+#[serial]
+fn test_should_import_face_regions_as_faces() {
+    let pool = get_pool();
+
+    let face_regions = vec![FaceRegion {
+        name: "Imported Person".to_string(),
+        x: 10,
+        y: 20,
+        width: 30,
+        height: 40,
+    }];
+    let new_photo = NewPhoto {
+        path: "regions.jpg".to_string(),
+        face_regions: Some(serde_json::to_value(&face_regions).unwrap()),
+        ..Default::default()
+    };
+
+    let mut photo_repo = PgPhotoRepository::new(pool.clone());
+    photo_repo
+        .insert_batch(vec![new_photo.clone()])
+        .expect("Failed to insert photo");
+    // Rescanning the photo replaces its imported faces
+    photo_repo
+        .insert_batch(vec![new_photo])
+        .expect("Failed to upsert photo");
+
+    let photos = load_photos(pool.clone());
+    let mut repo = PgFaceRepository::new(pool);
+    let result = repo
+        .find(
+            PaginationFilter {
+                page: 1,
+                per_page: 10,
+            },
+            FaceFindFilters {
+                photo_id: Some(photos[0].id),
+                ..Default::default()
+            },
+        )
+        .expect("Failed to find imported faces");
+
+    assert_eq!(result.items.len(), 1);
+    assert_eq!(result.items[0].bbox_x, 10);
+    assert_eq!(result.items[0].bbox_height, 40);
+    assert_eq!(result.items[0].source, "xmp");
+    assert!(result.items[0].person_id.is_some());
+}
@@ -0,0 +1,17 @@
+use picasa_core::imaging;
+
+const HEIC_PATH: &str = "tests/data/images/sunrise_laos.heic";
+
+#[test]
+fn test_should_load_heic_image() {
+    let image = imaging::load_image(HEIC_PATH).unwrap();
+
+    assert_eq!((image.width(), image.height()), (4032, 3024));
+}
+
+#[test]
+fn test_should_scale_heic_image_while_decoding() {
+    let image = imaging::load_image_scaled(HEIC_PATH, 224).unwrap();
+
+    assert_eq!((image.width(), image.height()), (299, 224));
+}
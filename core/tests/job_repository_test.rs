@@ -0,0 +1,176 @@
+use std::{thread, time::Duration};
+
+use picasa_core::{
+    database::DbPool,
+    models::{JobStage, JobStatus, NewJob, NewPhoto},
+    repositories::{
+        JobFindFilters, JobRepository, PgJobRepository, PgPhotoRepository, PhotoRepository,
+    },
+};
+use serial_test::serial;
+
+mod utils;
+use utils::{get_pool, load_photos};
+
+fn insert_photos(pool: DbPool) -> Vec<i32> {
+    PgPhotoRepository::new(pool.clone())
+        .insert_batch(vec![
+            NewPhoto {
+                path: "path1".to_string(),
+                ..Default::default()
+            },
+            NewPhoto {
+                path: "path2".to_string(),
+                ..Default::default()
+            },
+        ])
+        .expect("Failed to insert photos");
+
+    let mut ids: Vec<i32> = load_photos(pool).iter().map(|photo| photo.id).collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+#[serial]
+fn test_should_enqueue_jobs_once_per_target() {
+    let pool = get_pool();
+    let photo_ids = insert_photos(pool.clone());
+    let mut repo = PgJobRepository::new(pool.clone());
+
+    let count = repo
+        .enqueue(vec![
+            NewJob::photo(JobStage::Hash, photo_ids[0]),
+            NewJob::photo(JobStage::Hash, photo_ids[0]),
+            NewJob::photo(JobStage::Hash, photo_ids[1]),
+            NewJob::global(JobStage::Recognize),
+        ])
+        .expect("Failed to enqueue jobs");
+    repo.enqueue(vec![NewJob::global(JobStage::Recognize)])
+        .expect("Failed to enqueue jobs");
+
+    let jobs = repo
+        .find_many(JobFindFilters::default(), 10)
+        .expect("Failed to find jobs");
+
+    assert_eq!(count, 3);
+    assert_eq!(jobs.len(), 3);
+    assert!(jobs.iter().all(|job| job.status == JobStatus::Pending));
+}
+
+#[test]
+#[serial]
+fn test_should_claim_complete_and_fail_jobs() {
+    let pool = get_pool();
+    let photo_ids = insert_photos(pool.clone());
+    let mut repo = PgJobRepository::new(pool.clone());
+
+    repo.enqueue(vec![
+        NewJob::photo(JobStage::Embed, photo_ids[0]),
+        NewJob::photo(JobStage::Embed, photo_ids[1]),
+    ])
+    .expect("Failed to enqueue jobs");
+
+    let jobs = repo
+        .claim(JobStage::Embed, 10)
+        .expect("Failed to claim jobs");
+    let claimed_again = repo
+        .claim(JobStage::Embed, 10)
+        .expect("Failed to claim jobs");
+
+    assert_eq!(jobs.len(), 2);
+    assert!(claimed_again.is_empty());
+    assert!(
+        jobs.iter()
+            .all(|job| job.status == JobStatus::Running && job.attempts == 1)
+    );
+
+    repo.complete(jobs[0].id).expect("Failed to complete job");
+    repo.fail(jobs[1].id, "Corrupt file".to_string(), None)
+        .expect("Failed to fail job");
+
+    let counts = repo.count_by_status().expect("Failed to count jobs");
+    assert!(counts.contains(&(JobStage::Embed, JobStatus::Done, 1)));
+    assert!(counts.contains(&(JobStage::Embed, JobStatus::Failed, 1)));
+
+    let failed = repo
+        .find_many(
+            JobFindFilters {
+                status: Some(JobStatus::Failed),
+                ..Default::default()
+            },
+            10,
+        )
+        .expect("Failed to find jobs");
+    assert_eq!(failed[0].last_error.as_deref(), Some("Corrupt file"));
+
+    let retried = repo
+        .retry_failed(Some(JobStage::Embed))
+        .expect("Failed to retry jobs");
+    assert_eq!(retried, 1);
+    assert_eq!(
+        repo.count_active(vec![JobStage::Embed])
+            .expect("Failed to count jobs"),
+        1
+    );
+}
+
+#[test]
+#[serial]
+fn test_should_not_claim_jobs_before_retry_time() {
+    let pool = get_pool();
+    let photo_ids = insert_photos(pool.clone());
+    let mut repo = PgJobRepository::new(pool.clone());
+
+    repo.enqueue(vec![NewJob::photo(JobStage::Exif, photo_ids[0])])
+        .expect("Failed to enqueue jobs");
+    let jobs = repo.claim(JobStage::Exif, 1).expect("Failed to claim jobs");
+    repo.fail(
+        jobs[0].id,
+        "Timeout".to_string(),
+        Some(Duration::from_secs(3600)),
+    )
+    .expect("Failed to fail job");
+
+    let claimed = repo.claim(JobStage::Exif, 1).expect("Failed to claim jobs");
+
+    assert!(claimed.is_empty());
+}
+
+#[test]
+#[serial]
+fn test_should_only_requeue_jobs_without_heartbeat() {
+    let pool = get_pool();
+    let photo_ids = insert_photos(pool.clone());
+    let mut repo = PgJobRepository::new(pool.clone());
+
+    repo.enqueue(vec![
+        NewJob::photo(JobStage::Hash, photo_ids[0]),
+        NewJob::photo(JobStage::Hash, photo_ids[1]),
+    ])
+    .expect("Failed to enqueue jobs");
+    let jobs = repo.claim(JobStage::Hash, 2).expect("Failed to claim jobs");
+
+    // The first job runs for longer than the stale delay, refreshed by its worker,
+    // while the worker of the second one was interrupted
+    thread::sleep(Duration::from_millis(1500));
+    repo.heartbeat(vec![jobs[0].id])
+        .expect("Failed to refresh jobs");
+
+    let requeued = repo
+        .requeue_stale(Duration::from_secs(1))
+        .expect("Failed to requeue jobs");
+    let running = repo
+        .find_many(
+            JobFindFilters {
+                status: Some(JobStatus::Running),
+                ..Default::default()
+            },
+            10,
+        )
+        .expect("Failed to find jobs");
+
+    assert_eq!(requeued, 1);
+    assert_eq!(running.len(), 1);
+    assert_eq!(running[0].id, jobs[0].id);
+}
@@ -0,0 +1,145 @@
+use picasa_core::{
+    models::{NewLibrary, NewPhoto, UpdatedLibrary},
+    repositories::{LibraryRepository, PgLibraryRepository, PgPhotoRepository, PhotoRepository},
+};
+use serial_test::serial;
+
+mod utils;
+use utils::{get_pool, load_photos};
+
+fn new_library(name: &str, root_path: &str) -> NewLibrary {
+    NewLibrary {
+        name: name.to_string(),
+        root_path: root_path.to_string(),
+        enabled: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+#[serial]
+fn test_should_insert_and_find_library() {
+    let pool = get_pool();
+    let mut repo = PgLibraryRepository::new(pool.clone());
+
+    repo.insert_one(new_library("photos", "/mnt/photos"))
+        .expect("Failed to insert library");
+    repo.insert_one(new_library("archive", "/mnt/archive"))
+        .expect("Failed to insert library");
+
+    let libraries = repo.find_many().expect("Failed to find libraries");
+    let library = repo.find_by_name("photos").expect("Failed to find library");
+
+    assert_eq!(libraries.len(), 2);
+    assert_eq!(libraries[0].name, "archive");
+    assert_eq!(
+        library.map(|l| l.root_path),
+        Some("/mnt/photos".to_string())
+    );
+}
+
+#[test]
+#[serial]
+fn test_should_rewrite_photo_paths_when_relocating_library() {
+    let pool = get_pool();
+    let mut repo = PgLibraryRepository::new(pool.clone());
+    let library = repo
+        .insert_one(new_library("photos", "/mnt/old"))
+        .expect("Failed to insert library");
+
+    PgPhotoRepository::new(pool.clone())
+        .insert_batch(vec![NewPhoto {
+            path: "/mnt/old/2024/photo1.jpg".to_string(),
+            library_id: Some(library.id),
+            relative_path: Some("2024/photo1.jpg".to_string()),
+            ..Default::default()
+        }])
+        .expect("Failed to insert photos");
+
+    repo.update_one(
+        library.id,
+        UpdatedLibrary {
+            root_path: Some("/mnt/new".to_string()),
+            ..Default::default()
+        },
+    )
+    .expect("Failed to relocate library");
+
+    let photos = load_photos(pool.clone());
+
+    assert_eq!(photos[0].path, "/mnt/new/2024/photo1.jpg");
+    assert_eq!(photos[0].relative_path.as_deref(), Some("2024/photo1.jpg"));
+}
+
+#[test]
+#[serial]
+fn test_should_delete_library_photos() {
+    let pool = get_pool();
+    let mut repo = PgLibraryRepository::new(pool.clone());
+    let library = repo
+        .insert_one(new_library("photos", "/mnt/photos"))
+        .expect("Failed to insert library");
+
+    PgPhotoRepository::new(pool.clone())
+        .insert_batch(vec![
+            NewPhoto {
+                path: "/mnt/photos/photo1.jpg".to_string(),
+                library_id: Some(library.id),
+                relative_path: Some("photo1.jpg".to_string()),
+                ..Default::default()
+            },
+            NewPhoto {
+                path: "/elsewhere/photo2.jpg".to_string(),
+                ..Default::default()
+            },
+        ])
+        .expect("Failed to insert photos");
+
+    let count = repo
+        .delete_one(library.id)
+        .expect("Failed to delete library");
+    let photos = load_photos(pool.clone());
+
+    assert_eq!(count, 1);
+    assert_eq!(photos.len(), 1);
+    assert_eq!(photos[0].path, "/elsewhere/photo2.jpg");
+}
+
+#[test]
+#[serial]
+fn test_should_find_sidecar_metadata_of_library_photos() {
+    let pool = get_pool();
+    let library = PgLibraryRepository::new(pool.clone())
+        .insert_one(new_library("photos", "/mnt/photos"))
+        .expect("Failed to insert library");
+
+    PgPhotoRepository::new(pool.clone())
+        .insert_batch(vec![
+            NewPhoto {
+                path: "/mnt/photos/photo1.jpg".to_string(),
+                library_id: Some(library.id),
+                relative_path: Some("photo1.jpg".to_string()),
+                keywords: vec!["Beach".to_string()],
+                face_regions: Some(serde_json::json!([
+                    {"name": "Sidecar Person", "x": 10, "y": 20, "width": 30, "height": 40}
+                ])),
+                ..Default::default()
+            },
+            NewPhoto {
+                path: "/elsewhere/photo2.jpg".to_string(),
+                ..Default::default()
+            },
+        ])
+        .expect("Failed to insert photos");
+
+    let metadata = PgPhotoRepository::new(pool.clone())
+        .find_sidecar_metadata(library.id)
+        .expect("Failed to find sidecar metadata");
+
+    assert_eq!(metadata.len(), 1);
+    assert_eq!(metadata[0].path, "/mnt/photos/photo1.jpg");
+    assert_eq!(metadata[0].keywords, vec!["Beach"]);
+    assert_eq!(metadata[0].faces.len(), 1);
+    assert_eq!(metadata[0].faces[0].person_name, "Sidecar Person");
+    assert_eq!(metadata[0].faces[0].bbox_width, 30);
+}
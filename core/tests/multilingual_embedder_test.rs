@@ -0,0 +1,89 @@
+use picasa_core::services::embedders::multilingual::{encode, load_tokenizer};
+use tokenizers::Tokenizer;
+
+mod utils;
+use utils::load_config;
+
+/// Ids of the special tokens of the XLM-RoBERTa vocabulary.
+const BOS_TOKEN_ID: u32 = 0;
+const EOS_TOKEN_ID: u32 = 2;
+const UNK_TOKEN_ID: u32 = 3;
+/// Positions of the XLM-RoBERTa transformer, less the two offset by the padding token.
+const MAX_LENGTH: usize = 512;
+
+/// Returns the tokenizer of the configured text encoder, or `None` when no text encoder is
+/// configured, as its files are only downloaded along with it. Tests are run with one by
+/// setting `PICASA__EMBEDDINGS__TEXT_ENCODER`.
+fn tokenizer() -> Option<Tokenizer> {
+    let config = load_config();
+    let text_encoder_id = config.embeddings.text_encoder.as_ref()?;
+
+    Some(load_tokenizer(&config.embeddings, text_encoder_id).expect("Failed to load tokenizer"))
+}
+
+#[test]
+fn test_should_wrap_texts_in_special_tokens() {
+    let Some(tokenizer) = tokenizer() else {
+        return;
+    };
+
+    for text in ["東京の夜景", "plage en été", "пляж на закате", "شاطئ البحر"]
+    {
+        let encoding = encode(&tokenizer, text).expect("Failed to encode text");
+        let ids = encoding.get_ids();
+
+        assert_eq!(ids.first(), Some(&BOS_TOKEN_ID));
+        assert_eq!(ids.last(), Some(&EOS_TOKEN_ID));
+        assert!(ids.len() > 2);
+        assert!(
+            !ids.contains(&UNK_TOKEN_ID),
+            "Unknown token in \"{}\"",
+            text
+        );
+    }
+}
+
+#[test]
+fn test_should_normalize_texts_before_tokenizing() {
+    let Some(tokenizer) = tokenizer() else {
+        return;
+    };
+
+    // Decomposed accents and full-width characters are encoded as their composed forms
+    let decomposed =
+        encode(&tokenizer, "plage e\u{301}te\u{301} à Ｐａｒｉｓ").expect("Failed to encode text");
+    let composed = encode(&tokenizer, "plage été à Paris").expect("Failed to encode text");
+
+    assert_eq!(decomposed.get_ids(), composed.get_ids());
+}
+
+#[test]
+fn test_should_truncate_texts_to_max_length() {
+    let Some(tokenizer) = tokenizer() else {
+        return;
+    };
+
+    let encoding = encode(&tokenizer, &"東京の夜景、".repeat(400)).expect("Failed to encode text");
+    let ids = encoding.get_ids();
+
+    assert_eq!(ids.len(), MAX_LENGTH);
+    assert_eq!(ids.first(), Some(&BOS_TOKEN_ID));
+    assert_eq!(ids.last(), Some(&EOS_TOKEN_ID));
+}
+
+#[test]
+fn test_should_attend_to_all_tokens() {
+    let Some(tokenizer) = tokenizer() else {
+        return;
+    };
+
+    for text in ["猫", &"a white building by the river at night ".repeat(100)] {
+        let encoding = encode(&tokenizer, text).expect("Failed to encode text");
+
+        assert_eq!(
+            encoding.get_attention_mask().len(),
+            encoding.get_ids().len()
+        );
+        assert!(encoding.get_attention_mask().iter().all(|&mask| mask == 1));
+    }
+}
@@ -1,8 +1,12 @@
 use std::path::Path;
 
+use chrono::{DateTime, NaiveDate, Utc};
 use picasa_core::{
-    models::new_photo::NewPhoto,
-    utils::{compute_file_hash, extract_exif},
+    models::{DateTakenSource, FaceRegion, new_photo::NewPhoto},
+    utils::{
+        compute_file_hash, extract_exif,
+        metadata::{MetadataRegion, PhotoMetadata},
+    },
 };
 
 #[test]
@@ -21,6 +25,125 @@ fn test_should_create_new_photo() {
         format!("{:?}", new_photo.date_taken_utc),
         "Some(2025-02-21T23:55:25Z)"
     );
+    assert_eq!(new_photo.date_taken_source, Some(DateTakenSource::Offset));
+    assert_eq!(new_photo.date_taken_timezone.as_deref(), Some("+07:00"));
     assert_eq!(new_photo.image_width, Some(4032));
     assert_eq!(new_photo.image_height, Some(3024));
 }
+
+#[test]
+fn test_should_read_file_modification_time() {
+    let path = Path::new("tests/data/images/sunrise_laos.heic");
+    let modified = path.metadata().unwrap().modified().unwrap();
+
+    let new_photo = NewPhoto::new(path).unwrap();
+
+    assert_eq!(new_photo.modified_at, DateTime::<Utc>::from(modified));
+}
+
+#[test]
+fn test_should_set_default_timezone_of_dates_without_offset() {
+    let new_photo = NewPhoto {
+        date_taken_local: NaiveDate::from_ymd_opt(2025, 2, 22)
+            .unwrap()
+            .and_hms_opt(6, 55, 25),
+        ..Default::default()
+    }
+    .with_default_timezone("Europe/Paris");
+
+    assert_eq!(new_photo.date_taken_utc, None);
+    assert_eq!(
+        new_photo.date_taken_timezone.as_deref(),
+        Some("Europe/Paris")
+    );
+    assert_eq!(
+        new_photo.date_taken_source,
+        Some(DateTakenSource::DefaultTimezone)
+    );
+}
+
+#[test]
+fn test_should_not_set_default_timezone_of_dates_with_offset() {
+    let path = Path::new("tests/data/images/sunrise_laos.heic");
+
+    let new_photo = NewPhoto::new(path)
+        .unwrap()
+        .with_exif(extract_exif(path).unwrap())
+        .with_default_timezone("Europe/Paris");
+
+    assert_eq!(new_photo.date_taken_timezone.as_deref(), Some("+07:00"));
+    assert_eq!(new_photo.date_taken_source, Some(DateTakenSource::Offset));
+}
+
+#[test]
+fn test_should_scale_metadata_face_regions_to_image_dimensions() {
+    let new_photo = NewPhoto {
+        image_width: Some(4000),
+        image_height: Some(3000),
+        ..Default::default()
+    }
+    .with_metadata(PhotoMetadata {
+        keywords: vec!["Beach".to_string()],
+        rating: Some(4),
+        regions: vec![MetadataRegion {
+            name: "Alice".to_string(),
+            left: 0.4,
+            top: 0.2,
+            width: 0.2,
+            height: 0.1,
+            applied_to: Some((2000, 1500)),
+        }],
+        ..Default::default()
+    });
+
+    let face_regions: Vec<FaceRegion> =
+        serde_json::from_value(new_photo.face_regions.unwrap()).unwrap();
+
+    assert_eq!(new_photo.keywords, vec!["Beach"]);
+    assert_eq!(new_photo.rating, Some(4));
+    assert_eq!(
+        face_regions,
+        vec![FaceRegion {
+            name: "Alice".to_string(),
+            x: 1600,
+            y: 600,
+            width: 800,
+            height: 300,
+        }]
+    );
+}
+
+#[test]
+fn test_should_scale_metadata_face_regions_to_displayed_dimensions() {
+    let new_photo = NewPhoto {
+        image_width: Some(4000),
+        image_height: Some(3000),
+        orientation: Some(6),
+        ..Default::default()
+    }
+    .with_metadata(PhotoMetadata {
+        regions: vec![MetadataRegion {
+            name: "Alice".to_string(),
+            left: 0.4,
+            top: 0.2,
+            width: 0.2,
+            height: 0.1,
+            applied_to: None,
+        }],
+        ..Default::default()
+    });
+
+    let face_regions: Vec<FaceRegion> =
+        serde_json::from_value(new_photo.face_regions.unwrap()).unwrap();
+
+    assert_eq!(
+        face_regions,
+        vec![FaceRegion {
+            name: "Alice".to_string(),
+            x: 1200,
+            y: 800,
+            width: 600,
+            height: 400,
+        }]
+    );
+}
@@ -0,0 +1,139 @@
+use chrono::{Duration, Utc};
+use picasa_core::{
+    database::DbPool,
+    models::{JobStage, NewPhoto, PaginationFilter},
+    repositories::{
+        PgPhotoErrorRepository, PgPhotoRepository, PhotoErrorFindFilters, PhotoErrorRepository,
+        PhotoFindPathFilters, PhotoRepository, SkipFailedFilter,
+    },
+};
+use serial_test::serial;
+
+mod utils;
+use utils::{get_pool, load_photos};
+
+fn insert_photos(pool: DbPool) -> Vec<i32> {
+    PgPhotoRepository::new(pool.clone())
+        .insert_batch(vec![
+            NewPhoto {
+                path: "path1".to_string(),
+                ..Default::default()
+            },
+            NewPhoto {
+                path: "path2".to_string(),
+                ..Default::default()
+            },
+        ])
+        .expect("Failed to insert photos");
+
+    let mut ids: Vec<i32> = load_photos(pool).iter().map(|photo| photo.id).collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+#[serial]
+fn test_should_count_attempts_of_recorded_errors() {
+    let pool = get_pool();
+    let photo_ids = insert_photos(pool.clone());
+    let mut repo = PgPhotoErrorRepository::new(pool.clone());
+
+    repo.record(photo_ids[0], JobStage::Embed, "Corrupt file".to_string())
+        .expect("Failed to record error");
+    let attempts = repo
+        .record(photo_ids[0], JobStage::Embed, "Still corrupt".to_string())
+        .expect("Failed to record error");
+    repo.record(photo_ids[1], JobStage::DetectFaces, "Timeout".to_string())
+        .expect("Failed to record error");
+
+    let errors = repo
+        .find_many(
+            PhotoErrorFindFilters {
+                stage: Some(JobStage::Embed),
+                ..Default::default()
+            },
+            10,
+        )
+        .expect("Failed to find errors");
+
+    assert_eq!(attempts, 2);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].photo_path, "path1");
+    assert_eq!(errors[0].photo_error.error, "Still corrupt");
+}
+
+#[test]
+#[serial]
+fn test_should_resolve_and_retry_errors() {
+    let pool = get_pool();
+    let photo_ids = insert_photos(pool.clone());
+    let mut repo = PgPhotoErrorRepository::new(pool.clone());
+
+    repo.record(photo_ids[0], JobStage::Embed, "Corrupt file".to_string())
+        .expect("Failed to record error");
+    repo.record(photo_ids[1], JobStage::Embed, "Corrupt file".to_string())
+        .expect("Failed to record error");
+    repo.record(
+        photo_ids[1],
+        JobStage::Hash,
+        "Permission denied".to_string(),
+    )
+    .expect("Failed to record error");
+
+    let resolved = repo
+        .resolve(JobStage::Embed, vec![photo_ids[0]])
+        .expect("Failed to resolve errors");
+    let retried = repo
+        .delete_many(PhotoErrorFindFilters {
+            photo_id: Some(photo_ids[1]),
+            ..Default::default()
+        })
+        .expect("Failed to retry errors");
+    let errors = repo
+        .find_many(PhotoErrorFindFilters::default(), 10)
+        .expect("Failed to find errors");
+
+    assert_eq!(resolved, 1);
+    assert_eq!(retried, 2);
+    assert!(errors.is_empty());
+}
+
+#[test]
+#[serial]
+fn test_should_skip_failed_photos_when_finding_paths() {
+    let pool = get_pool();
+    let photo_ids = insert_photos(pool.clone());
+    let mut repo = PgPhotoErrorRepository::new(pool.clone());
+
+    repo.record(photo_ids[0], JobStage::Embed, "Corrupt file".to_string())
+        .expect("Failed to record error");
+
+    let find_paths = |max_attempts: i32, failed_since| {
+        PgPhotoRepository::new(pool.clone())
+            .find_path(
+                PaginationFilter {
+                    page: 1,
+                    per_page: 10,
+                },
+                PhotoFindPathFilters {
+                    has_embedding: Some(false),
+                    skip_failed: Some(SkipFailedFilter {
+                        stage: JobStage::Embed,
+                        max_attempts,
+                        failed_since,
+                    }),
+                    ..Default::default()
+                },
+            )
+            .expect("Failed to find paths")
+    };
+
+    let failed_in_previous_run = find_paths(3, Utc::now() + Duration::hours(1));
+    let during_run = find_paths(3, Utc::now() - Duration::hours(1));
+    let after_max_attempts = find_paths(1, Utc::now() + Duration::hours(1));
+
+    assert_eq!(failed_in_previous_run.total, 2);
+    assert_eq!(during_run.total, 1);
+    assert_eq!(during_run.items[0].id, photo_ids[1]);
+    assert_eq!(after_max_attempts.total, 1);
+}
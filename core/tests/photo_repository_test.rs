@@ -1,10 +1,12 @@
 use chrono::{DateTime, NaiveDate, Utc};
-use diesel::RunQueryDsl;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, connection::SimpleConnection};
 use pgvector::Vector;
 use picasa_core::{
-    database::schema,
-    models::{NewPhoto, PaginationFilter, UpdatedPhoto},
-    repositories::{PgPhotoRepository, PhotoFindFilters, PhotoFindPathFilters, PhotoRepository},
+    database::{DbPool, schema},
+    models::{DateTakenSource, MediaType, NewPhoto, NewPhotoEmbedding, PaginationFilter},
+    repositories::{
+        PgPhotoRepository, PhotoFindFilters, PhotoFindPathFilters, PhotoRepository, ValueRange,
+    },
     services::embedders::text::{ClipTextEmbedder, TextEmbedder},
 };
 use serial_test::serial;
@@ -14,6 +16,27 @@ use utils::{get_pool, insert_photo_fixtures, load_photos};
 
 use crate::utils::load_config;
 
+fn new_embedding(photo_id: i32, model: &str) -> NewPhotoEmbedding {
+    NewPhotoEmbedding {
+        photo_id,
+        model: model.to_string(),
+        embedding: Vector::from(vec![0.1_f32; 512]),
+    }
+}
+
+fn load_embedding_models(pool: DbPool) -> Vec<(i32, String)> {
+    let mut conn = pool.get().unwrap();
+
+    schema::photo_embeddings::table
+        .select((
+            schema::photo_embeddings::photo_id,
+            schema::photo_embeddings::model,
+        ))
+        .order(schema::photo_embeddings::model.asc())
+        .load(&mut conn)
+        .expect("Failed to load embeddings")
+}
+
 #[test]
 #[serial]
 fn test_should_insert_batch() {
@@ -97,72 +120,63 @@ fn test_should_handle_insert_conflicts_with_upsert() {
 fn test_should_preserve_embeddings_on_conflict() {
     let pool = get_pool();
 
-    let embedding_vector = Vector::from(vec![0.1_f32; 512]);
     let original_photo = NewPhoto {
         path: "test/photo_with_embedding.jpg".to_string(),
-        embedding: Some(embedding_vector.clone()),
         ..Default::default()
     };
 
     let mut repo = PgPhotoRepository::new(pool.clone());
-    repo.insert_batch(vec![original_photo])
+    repo.insert_batch(vec![original_photo.clone()])
         .expect("Failed to insert original photo");
+    let photo_id = load_photos(pool.clone())[0].id;
+    repo.save_embeddings(vec![new_embedding(photo_id, "clip-vit-base-patch32")])
+        .expect("Failed to save embedding");
 
-    let updated_photo = NewPhoto {
-        path: "test/photo_with_embedding.jpg".to_string(),
-        embedding: None,
-        ..Default::default()
-    };
-
-    repo.insert_batch(vec![updated_photo])
+    repo.insert_batch(vec![original_photo])
         .expect("Failed to insert updated photo");
 
-    let photos = load_photos(pool.clone());
-
-    assert_eq!(photos.len(), 1);
-    let photo = &photos[0];
-    assert!(photo.embedding.is_some());
     assert_eq!(
-        photo.embedding.as_ref().unwrap().as_slice(),
-        embedding_vector.as_slice()
+        load_embedding_models(pool),
+        vec![(photo_id, "clip-vit-base-patch32".to_string())]
     );
 }
 
 #[test]
 #[serial]
-fn test_should_clear_embedding_on_hash_change() {
+fn test_should_delete_embeddings_on_hash_change() {
     let pool = get_pool();
 
-    let embedding_vector = Vector::from(vec![0.1_f32; 512]);
     let original_photo = NewPhoto {
         path: "test/photo_with_embedding.jpg".to_string(),
         hash: Some("original_hash".to_string()),
-        embedding: Some(embedding_vector.clone()),
         ..Default::default()
     };
 
     let mut repo = PgPhotoRepository::new(pool.clone());
     repo.insert_batch(vec![original_photo])
         .expect("Failed to insert original photo");
+    let photo_id = load_photos(pool.clone())[0].id;
+    repo.save_embeddings(vec![
+        new_embedding(photo_id, "clip-vit-base-patch32"),
+        new_embedding(photo_id, "clip-vit-large-patch14"),
+    ])
+    .expect("Failed to save embeddings");
 
     let updated_photo = NewPhoto {
         path: "test/photo_with_embedding.jpg".to_string(),
         hash: Some("new_hash".to_string()),
-        embedding: Some(embedding_vector.clone()),
         ..Default::default()
     };
 
     repo.insert_batch(vec![updated_photo])
         .expect("Failed to insert updated photo");
 
-    let photos = load_photos(pool.clone());
-
-    assert!(photos[0].embedding.is_none());
+    assert!(load_embedding_models(pool).is_empty());
 }
 
 #[test]
 #[serial]
-fn test_should_list_paths_without_embedding() {
+fn test_should_list_paths_without_embedding_of_model() {
     let pool = get_pool();
     let mut conn = pool.get().unwrap();
 
@@ -173,35 +187,45 @@ fn test_should_list_paths_without_embedding() {
         },
         NewPhoto {
             path: "path2".to_string(),
-            embedding: Some(Vector::from(vec![0.1_f32; 512])),
             ..Default::default()
         },
     ];
-    diesel::insert_into(schema::photos::table)
+    let photo_ids: Vec<i32> = diesel::insert_into(schema::photos::table)
         .values(photos)
-        .execute(&mut conn)
+        .returning(schema::photos::id)
+        .get_results(&mut conn)
         .expect("Failed to insert photos");
 
     let mut repo = PgPhotoRepository::new(pool);
-    let paginated_paths = repo
-        .find_path(
+    repo.save_embeddings(vec![new_embedding(photo_ids[1], "clip-vit-base-patch32")])
+        .expect("Failed to save embedding");
+
+    let mut find_paths_without_embedding = |model: &str| {
+        repo.find_path(
             PaginationFilter {
                 page: 1,
                 per_page: 10,
             },
             PhotoFindPathFilters {
                 has_embedding: Some(false),
+                embedding_model: Some(model.to_string()),
                 ..Default::default()
             },
         )
-        .expect("Failed to list paths");
+        .expect("Failed to list paths")
+    };
 
+    let paginated_paths = find_paths_without_embedding("clip-vit-base-patch32");
+    assert_eq!(paginated_paths.total, 1);
     assert_eq!(paginated_paths.items[0].path, "path1");
+
+    let paginated_paths = find_paths_without_embedding("clip-vit-large-patch14");
+    assert_eq!(paginated_paths.total, 2);
 }
 
 #[test]
 #[serial]
-fn test_should_update_embeddings() {
+fn test_should_save_embeddings_per_model() {
     let pool = get_pool();
     let mut conn = pool.clone().get().unwrap();
 
@@ -215,23 +239,137 @@ fn test_should_update_embeddings() {
         .returning(schema::photos::id)
         .get_result(&mut conn)
         .expect("Failed to insert photo");
-    let embedding_vector = Vector::from(vec![0.1_f32; 512]);
 
     let mut repo = PgPhotoRepository::new(pool.clone());
-    let updated_photo = repo
-        .update_one(
-            photo_id,
-            UpdatedPhoto {
-                embedding: Some(Some(embedding_vector.clone())),
-                ..Default::default()
+    let saved_count = repo
+        .save_embeddings(vec![
+            new_embedding(photo_id, "clip-vit-base-patch32"),
+            NewPhotoEmbedding {
+                photo_id,
+                model: "clip-vit-large-patch14".to_string(),
+                embedding: Vector::from(vec![0.2_f32; 768]),
             },
+        ])
+        .expect("Failed to save embeddings");
+    repo.save_embeddings(vec![NewPhotoEmbedding {
+        photo_id,
+        model: "clip-vit-base-patch32".to_string(),
+        embedding: Vector::from(vec![0.3_f32; 512]),
+    }])
+    .expect("Failed to replace embedding");
+
+    let embeddings: Vec<(String, Vector)> = schema::photo_embeddings::table
+        .select((
+            schema::photo_embeddings::model,
+            schema::photo_embeddings::embedding,
+        ))
+        .order(schema::photo_embeddings::model.asc())
+        .load(&mut conn)
+        .expect("Failed to load embeddings");
+
+    assert_eq!(saved_count, 2);
+    assert_eq!(embeddings.len(), 2);
+    assert_eq!(embeddings[0].1.as_slice(), vec![0.3_f32; 512].as_slice());
+    assert_eq!(embeddings[1].1.as_slice().len(), 768);
+}
+
+#[test]
+#[serial]
+fn test_should_find_photos_similar_to_photo() {
+    let pool = get_pool();
+    let mut conn = pool.get().unwrap();
+
+    let photo_ids: Vec<i32> = diesel::insert_into(schema::photos::table)
+        .values(
+            ["beach.jpg", "beach_again.jpg", "mountain.jpg"]
+                .map(|path| NewPhoto {
+                    path: path.to_string(),
+                    ..Default::default()
+                })
+                .to_vec(),
         )
-        .expect("Failed to update embeddings");
+        .returning(schema::photos::id)
+        .get_results(&mut conn)
+        .expect("Failed to insert photos");
 
+    // Embeddings of registered models have their dimensions enforced by their index
+    let mut repo = PgPhotoRepository::new(pool);
+    repo.save_embeddings(
+        [[1.0, 0.0, 0.0], [0.8, 0.6, 0.0], [0.0, 0.0, 1.0]]
+            .into_iter()
+            .zip(&photo_ids)
+            .map(|(embedding, &photo_id)| NewPhotoEmbedding {
+                photo_id,
+                model: "test-3d".to_string(),
+                embedding: Vector::from(embedding.to_vec()),
+            })
+            .collect(),
+    )
+    .expect("Failed to save embeddings");
+
+    let embedding = repo
+        .find_embedding(photo_ids[0], "test-3d".to_string())
+        .expect("Failed to find embedding");
+    assert_eq!(embedding, Some(vec![1.0, 0.0, 0.0]));
     assert_eq!(
-        updated_photo.embedding.as_ref().unwrap().as_slice(),
-        embedding_vector.as_slice()
+        repo.find_embedding(photo_ids[0], "clip-vit-large-patch14".to_string())
+            .expect("Failed to find embedding"),
+        None
     );
+
+    let result = repo
+        .find(
+            PaginationFilter {
+                page: 1,
+                per_page: 10,
+            },
+            PhotoFindFilters {
+                query_embedding: embedding,
+                embedding_model: Some("test-3d".to_string()),
+                threshold: Some(-1.0),
+                exclude_id: Some(photo_ids[0]),
+                ..Default::default()
+            },
+        )
+        .expect("Failed to search similar photos");
+
+    let paths: Vec<&str> = result.items.iter().map(|p| p.path.as_str()).collect();
+    assert_eq!(paths, vec!["beach_again.jpg", "mountain.jpg"]);
+    assert_eq!(result.total, 2);
+}
+
+#[test]
+#[serial]
+fn test_should_create_embedding_index_of_model() {
+    let pool = get_pool();
+    let mut conn = pool.get().unwrap();
+
+    let photo_id: i32 = diesel::insert_into(schema::photos::table)
+        .values(NewPhoto {
+            path: "beach.jpg".to_string(),
+            ..Default::default()
+        })
+        .returning(schema::photos::id)
+        .get_result(&mut conn)
+        .expect("Failed to insert photo");
+
+    let mut repo = PgPhotoRepository::new(pool);
+    for _ in 0..2 {
+        repo.create_embedding_index("test-index".to_string(), 3)
+            .expect("Failed to create embedding index");
+    }
+
+    // The index casts embeddings of the model to its dimensions
+    let embedding = |dimensions: usize| NewPhotoEmbedding {
+        photo_id,
+        model: "test-index".to_string(),
+        embedding: Vector::from(vec![0.5_f32; dimensions]),
+    };
+    assert!(repo.save_embeddings(vec![embedding(2)]).is_err());
+    assert!(repo.save_embeddings(vec![embedding(3)]).is_ok());
+
+    conn.batch_execute("DROP INDEX photo_embeddings_test_index_idx")
+        .expect("Failed to drop embedding index");
 }
 
 #[test]
@@ -356,13 +494,14 @@ fn test_should_find_photos_by_semantic_query() {
     insert_photo_fixtures(pool.clone());
 
     let mut repo = PgPhotoRepository::new(pool.clone());
-    let text_embedder =
-        ClipTextEmbedder::new(&config.clip_model).expect("Failed to create embedder");
+    let text_embedder = ClipTextEmbedder::new(&config.embeddings, &config.embeddings.model)
+        .expect("Failed to create embedder");
     let text_embedding = text_embedder
         .embed("white building")
         .expect("Failed to create embedding");
     let filters = PhotoFindFilters {
-        text_embedding: Some(text_embedding),
+        query_embedding: Some(text_embedding),
+        embedding_model: Some(config.embeddings.model.clone()),
         threshold: Some(0.0),
         ..Default::default()
     };
@@ -430,13 +569,14 @@ fn test_should_find_photos_with_combined_filters() {
         .and_hms_opt(0, 0, 0)
         .unwrap();
 
-    let text_embedder =
-        ClipTextEmbedder::new(&config.clip_model).expect("Failed to create embedder");
+    let text_embedder = ClipTextEmbedder::new(&config.embeddings, &config.embeddings.model)
+        .expect("Failed to create embedder");
     let text_embedding = text_embedder
         .embed("white building")
         .expect("Failed to create embedding");
     let filters = PhotoFindFilters {
-        text_embedding: Some(text_embedding),
+        query_embedding: Some(text_embedding),
+        embedding_model: Some(config.embeddings.model.clone()),
         threshold: Some(0.23),
         country_id: Some(56),
         date_from: Some(DateTime::<Utc>::from_naive_utc_and_offset(date_from, Utc)),
@@ -502,3 +642,404 @@ fn test_should_find_photos_by_city() {
         "tests/data/images/sub/desk_vietnam.heic"
     );
 }
+
+#[test]
+#[serial]
+fn test_should_find_photos_by_media_type() {
+    let pool = get_pool();
+
+    let photos = vec![
+        NewPhoto {
+            path: "photo.jpg".to_string(),
+            ..Default::default()
+        },
+        NewPhoto {
+            path: "video.mp4".to_string(),
+            media_type: MediaType::Video,
+            ..Default::default()
+        },
+    ];
+
+    let mut repo = PgPhotoRepository::new(pool.clone());
+    repo.insert_batch(photos).expect("Failed to insert photos");
+
+    let filters = PhotoFindFilters {
+        media_type: Some(MediaType::Video),
+        ..Default::default()
+    };
+
+    let result = repo
+        .find(
+            PaginationFilter {
+                page: 1,
+                per_page: 10,
+            },
+            filters,
+        )
+        .expect("Failed to search photos by media type");
+
+    assert_eq!(result.total, 1);
+    assert_eq!(result.items[0].path, "video.mp4");
+    assert_eq!(result.items[0].media_type, MediaType::Video);
+}
+
+#[test]
+#[serial]
+fn test_should_find_photos_by_exposure_ranges() {
+    let pool = get_pool();
+
+    let photos = vec![
+        NewPhoto {
+            path: "portrait.jpg".to_string(),
+            aperture: Some(1.8),
+            iso: Some(100),
+            focal_length: Some(85.0),
+            flash: Some(false),
+            ..Default::default()
+        },
+        NewPhoto {
+            path: "night.jpg".to_string(),
+            aperture: Some(2.8),
+            iso: Some(6400),
+            focal_length: Some(24.0),
+            flash: Some(true),
+            ..Default::default()
+        },
+        NewPhoto {
+            path: "no_exif.jpg".to_string(),
+            ..Default::default()
+        },
+    ];
+
+    let mut repo = PgPhotoRepository::new(pool.clone());
+    repo.insert_batch(photos).expect("Failed to insert photos");
+
+    let filters = PhotoFindFilters {
+        aperture: ValueRange::new(None, Some(2.0)),
+        iso: ValueRange::new(Some(100), Some(400)),
+        flash: Some(false),
+        ..Default::default()
+    };
+
+    let result = repo
+        .find(
+            PaginationFilter {
+                page: 1,
+                per_page: 10,
+            },
+            filters,
+        )
+        .expect("Failed to search photos by exposure");
+
+    assert_eq!(result.total, 1);
+    assert_eq!(result.items[0].path, "portrait.jpg");
+    assert_eq!(result.items[0].focal_length, Some(85.0));
+}
+
+#[test]
+#[serial]
+fn test_should_convert_date_taken_to_utc_with_default_timezone() {
+    let pool = get_pool();
+
+    let photos = vec![
+        NewPhoto {
+            path: "no_offset.jpg".to_string(),
+            date_taken_local: NaiveDate::from_ymd_opt(2024, 7, 14)
+                .unwrap()
+                .and_hms_opt(12, 0, 0),
+            ..Default::default()
+        }
+        .with_default_timezone("Europe/Paris"),
+    ];
+
+    let mut repo = PgPhotoRepository::new(pool.clone());
+    repo.insert_batch(photos).expect("Failed to insert photos");
+
+    let photos = load_photos(pool.clone());
+
+    assert_eq!(
+        photos[0].date_taken_utc,
+        Some("2024-07-14T10:00:00Z".parse::<DateTime<Utc>>().unwrap())
+    );
+    assert_eq!(
+        photos[0].date_taken_source,
+        Some(DateTakenSource::DefaultTimezone)
+    );
+}
+
+#[test]
+#[serial]
+fn test_should_find_files_by_path_prefix() {
+    let pool = get_pool();
+
+    let photos = vec![
+        NewPhoto {
+            path: "library/photo_1.jpg".to_string(),
+            ..Default::default()
+        },
+        NewPhoto {
+            path: "library/sub/photo_2.jpg".to_string(),
+            ..Default::default()
+        },
+        NewPhoto {
+            path: "library_2/photo_3.jpg".to_string(),
+            ..Default::default()
+        },
+    ];
+
+    let mut repo = PgPhotoRepository::new(pool.clone());
+    repo.insert_batch(photos).expect("Failed to insert photos");
+
+    let files = repo
+        .find_files("library/")
+        .expect("Failed to find files by prefix");
+    let mut paths: Vec<String> = files.into_iter().map(|file| file.path).collect();
+    paths.sort();
+
+    assert_eq!(
+        paths,
+        vec!["library/photo_1.jpg", "library/sub/photo_2.jpg"]
+    );
+}
+
+#[test]
+#[serial]
+fn test_should_find_file_by_id() {
+    let pool = get_pool();
+
+    let mut repo = PgPhotoRepository::new(pool.clone());
+    repo.insert_batch(vec![NewPhoto {
+        path: "photo_1.jpg".to_string(),
+        hash: Some("hash_1".to_string()),
+        ..Default::default()
+    }])
+    .expect("Failed to insert photos");
+
+    let id = repo.find_files("").expect("Failed to find files")[0].id;
+
+    let file = repo
+        .find_file(id)
+        .expect("Failed to find file")
+        .expect("File not found");
+
+    assert_eq!(file.path, "photo_1.jpg");
+    assert_eq!(file.hash, Some("hash_1".to_string()));
+    assert!(
+        repo.find_file(id + 1)
+            .expect("Failed to find file")
+            .is_none()
+    );
+}
+
+#[test]
+#[serial]
+fn test_should_delete_many() {
+    let pool = get_pool();
+
+    let photos = vec![
+        NewPhoto {
+            path: "path1".to_string(),
+            ..Default::default()
+        },
+        NewPhoto {
+            path: "path2".to_string(),
+            ..Default::default()
+        },
+    ];
+
+    let mut repo = PgPhotoRepository::new(pool.clone());
+    repo.insert_batch(photos).expect("Failed to insert photos");
+
+    let photo_id = load_photos(pool.clone())
+        .into_iter()
+        .find(|photo| photo.path == "path1")
+        .unwrap()
+        .id;
+
+    let count = repo
+        .delete_many(vec![photo_id])
+        .expect("Failed to delete photos");
+
+    let photos = load_photos(pool.clone());
+
+    assert_eq!(count, 1);
+    assert_eq!(photos.len(), 1);
+    assert_eq!(photos[0].path, "path2");
+}
+
+#[test]
+#[serial]
+fn test_should_find_duplicates() {
+    let pool = get_pool();
+
+    let photo = |path: &str, hash: Option<&str>| NewPhoto {
+        path: path.to_string(),
+        hash: hash.map(str::to_string),
+        ..Default::default()
+    };
+
+    let mut repo = PgPhotoRepository::new(pool.clone());
+    repo.insert_batch(vec![
+        photo("a/photo_1.jpg", Some("hash_1")),
+        photo("b/photo_1.jpg", Some("hash_1")),
+        photo("a/photo_2.jpg", Some("hash_2")),
+        photo("a/photo_3.jpg", None),
+        photo("b/photo_3.jpg", None),
+    ])
+    .expect("Failed to insert photos");
+
+    let photos = repo.find_duplicates().expect("Failed to find duplicates");
+    let paths: Vec<&str> = photos.iter().map(|photo| photo.path.as_str()).collect();
+
+    assert_eq!(paths.len(), 2);
+    assert!(paths.contains(&"a/photo_1.jpg"));
+    assert!(paths.contains(&"b/photo_1.jpg"));
+}
+
+#[test]
+#[serial]
+fn test_should_find_existing_hashes() {
+    let pool = get_pool();
+
+    let mut repo = PgPhotoRepository::new(pool.clone());
+    repo.insert_batch(vec![
+        NewPhoto {
+            path: "photo_1.jpg".to_string(),
+            hash: Some("hash_1".to_string()),
+            ..Default::default()
+        },
+        NewPhoto {
+            path: "photo_2.jpg".to_string(),
+            hash: Some("hash_2".to_string()),
+            ..Default::default()
+        },
+    ])
+    .expect("Failed to insert photos");
+
+    let hashes = repo
+        .find_existing_hashes(vec!["hash_1".to_string(), "hash_3".to_string()])
+        .expect("Failed to find existing hashes");
+
+    assert_eq!(hashes, vec!["hash_1".to_string()]);
+}
+
+#[test]
+#[serial]
+fn test_should_find_similarity_candidates() {
+    let pool = get_pool();
+
+    let mut repo = PgPhotoRepository::new(pool.clone());
+    repo.insert_batch(vec![
+        NewPhoto {
+            path: "hashed.jpg".to_string(),
+            perceptual_hash: Some(42),
+            ..Default::default()
+        },
+        NewPhoto {
+            path: "not_hashed.jpg".to_string(),
+            ..Default::default()
+        },
+        NewPhoto {
+            path: "clip.mp4".to_string(),
+            media_type: MediaType::Video,
+            perceptual_hash: Some(42),
+            ..Default::default()
+        },
+    ])
+    .expect("Failed to insert photos");
+
+    let not_hashed_id = load_photos(pool.clone())
+        .into_iter()
+        .find(|photo| photo.path == "not_hashed.jpg")
+        .unwrap()
+        .id;
+    repo.save_embeddings(vec![new_embedding(not_hashed_id, "clip-vit-large-patch14")])
+        .expect("Failed to save embedding");
+
+    let candidates = repo
+        .find_similarity_candidates("clip-vit-base-patch32".to_string(), None, 10)
+        .expect("Failed to find similarity candidates");
+
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0].path, "hashed.jpg");
+    assert_eq!(candidates[0].perceptual_hash, Some(42));
+    assert!(!candidates[0].has_embedding);
+
+    let candidates = repo
+        .find_similarity_candidates("clip-vit-large-patch14".to_string(), None, 10)
+        .expect("Failed to find similarity candidates");
+
+    assert_eq!(candidates.len(), 2);
+    assert!(
+        candidates
+            .iter()
+            .any(|candidate| candidate.path == "not_hashed.jpg" && candidate.has_embedding)
+    );
+
+    let candidates = repo
+        .find_similarity_candidates(
+            "clip-vit-large-patch14".to_string(),
+            Some(candidates[0].id),
+            10,
+        )
+        .expect("Failed to find similarity candidates");
+
+    assert_eq!(candidates.len(), 1);
+}
+
+#[test]
+#[serial]
+fn test_should_find_embedding_similarities() {
+    let pool = get_pool();
+
+    let mut repo = PgPhotoRepository::new(pool.clone());
+    repo.insert_batch(
+        (0..4)
+            .map(|index| NewPhoto {
+                path: format!("photo_{}.jpg", index),
+                ..Default::default()
+            })
+            .collect(),
+    )
+    .expect("Failed to insert photos");
+
+    let mut photo_ids: Vec<i32> = load_photos(pool.clone())
+        .iter()
+        .map(|photo| photo.id)
+        .collect();
+    photo_ids.sort();
+    let mut orthogonal = vec![0.0_f32; 512];
+    orthogonal[0] = 1.0;
+    repo.save_embeddings(vec![
+        new_embedding(photo_ids[0], "clip-vit-large-patch14"),
+        new_embedding(photo_ids[1], "clip-vit-large-patch14"),
+        NewPhotoEmbedding {
+            photo_id: photo_ids[2],
+            model: "clip-vit-large-patch14".to_string(),
+            embedding: Vector::from(orthogonal),
+        },
+    ])
+    .expect("Failed to save embeddings");
+
+    let similarities = repo
+        .find_embedding_similarities(
+            "clip-vit-large-patch14".to_string(),
+            vec![
+                (photo_ids[0], photo_ids[1]),
+                (photo_ids[0], photo_ids[2]),
+                (photo_ids[0], photo_ids[3]),
+            ],
+        )
+        .expect("Failed to find embedding similarities");
+
+    assert_eq!(similarities.len(), 2);
+    let similarity = |other_photo_id: i32| {
+        similarities
+            .iter()
+            .find(|similarity| similarity.other_photo_id == other_photo_id)
+            .unwrap()
+            .similarity
+    };
+    assert!((similarity(photo_ids[1]) - 1.0).abs() < 1e-5);
+    assert!((similarity(photo_ids[2]) - 1.0 / 512_f32.sqrt()).abs() < 1e-5);
+}
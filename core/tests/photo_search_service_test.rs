@@ -1,6 +1,6 @@
 use picasa_core::{
     repositories::{PgFaceRepository, PgGeoRepository, PgPersonRepository, PgPhotoRepository},
-    services::{embedders::ClipTextEmbedder, photo_search::PhotoSearchService},
+    services::photo_search::PhotoSearchService,
 };
 use serial_test::serial;
 
@@ -21,14 +21,12 @@ fn test_should_return_search_options() {
     let geo_repository = PgGeoRepository::new(pool.clone());
     let person_repository = PgPersonRepository::new(pool.clone());
     let face_repository = PgFaceRepository::new(pool.clone());
-    let text_embedder = ClipTextEmbedder::new(&config.clip_model).unwrap();
-
     let mut service = PhotoSearchService::new(
         photo_repository,
         geo_repository,
         person_repository,
         face_repository,
-        text_embedder,
+        &config.embeddings.model,
     );
 
     let options = service.get_search_options().unwrap();
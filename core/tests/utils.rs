@@ -18,9 +18,15 @@ pub fn get_pool() -> DbPool {
         database::run_migrations(&mut pool);
     });
 
+    diesel::delete(schema::jobs::table)
+        .execute(&mut conn)
+        .expect("Failed to clean test data");
     diesel::delete(schema::photos::table)
         .execute(&mut conn)
         .expect("Failed to clean test data");
+    diesel::delete(schema::libraries::table)
+        .execute(&mut conn)
+        .expect("Failed to clean test data");
 
     pool
 }
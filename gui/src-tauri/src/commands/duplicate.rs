@@ -0,0 +1,23 @@
+use picasa_core::{
+    repositories::PgPhotoRepository,
+    services::{duplicates::KeeperPreference, DuplicateService},
+    utils::progress_reporter::NoOpProgressReporter,
+};
+use tauri::State;
+
+use crate::{types::DuplicateGroup, AppState};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_duplicate_groups(
+    state: State<'_, AppState>,
+) -> Result<Vec<DuplicateGroup>, String> {
+    let photo_repository = PgPhotoRepository::new(state.db_pool.clone());
+
+    let mut duplicate_service = DuplicateService::new(photo_repository, NoOpProgressReporter);
+
+    duplicate_service
+        .find_groups(&KeeperPreference::default())
+        .map(|g| g.into_iter().map(DuplicateGroup::from).collect())
+        .map_err(|e| format!("Failed to list duplicate groups: {}", e))
+}
@@ -4,11 +4,10 @@ use picasa_core::{
     services::{face_recognition::RecognitionAction, FaceRecognitionService, FaceService},
     utils::progress_reporter::NoOpProgressReporter,
 };
-use std::path::Path;
 use tauri::State;
 
 use crate::{
-    services::image::{BoundingBox, ImageService},
+    services::image::BoundingBox,
     types::{face::Face, PaginatedFaces, PendingFaceReview},
     AppState,
 };
@@ -30,8 +29,8 @@ pub async fn load_face_image(face_id: i32, state: State<'_, AppState>) -> Result
         height: face_with_photo.face.bbox_height,
     };
 
-    let img_service = ImageService::new(Path::new("../../cache_dir").to_path_buf());
-    let data = img_service
+    let data = state
+        .image_service
         .crop_image(face_with_photo.photo_path, bbox)
         .map_err(|e| format!("Failed to crop image: {}", e))?;
 
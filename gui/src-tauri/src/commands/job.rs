@@ -0,0 +1,61 @@
+use picasa_core::{
+    models::{JobStage, NewJob},
+    repositories::{PgJobRepository, PgLibraryRepository},
+    services::{JobService, LibraryService},
+    utils::progress_reporter::NoOpProgressReporter,
+};
+use tauri::State;
+
+use crate::{types::JobStageSummary, AppState};
+
+/// Enqueues scans of a library, or of all enabled libraries, and returns the number of
+/// enqueued jobs.
+#[tauri::command]
+#[specta::specta]
+pub async fn enqueue_library_scans(
+    state: State<'_, AppState>,
+    library: Option<String>,
+) -> Result<usize, String> {
+    let mut library_service = LibraryService::new(PgLibraryRepository::new(state.db_pool.clone()));
+
+    let libraries = match &library {
+        Some(name) => library_service.get(name).map(|library| vec![library]),
+        None => library_service.list().map(|libraries| {
+            libraries
+                .into_iter()
+                .filter(|library| library.enabled)
+                .collect()
+        }),
+    }
+    .map_err(|e| format!("Failed to get libraries: {}", e))?;
+
+    let mut job_service = JobService::new(
+        PgJobRepository::new(state.db_pool.clone()),
+        NoOpProgressReporter,
+        &state.config.jobs,
+    );
+
+    job_service
+        .enqueue(
+            libraries
+                .iter()
+                .map(|library| NewJob::library(JobStage::Scan, library.id))
+                .collect(),
+        )
+        .map_err(|e| format!("Failed to enqueue library scans: {}", e))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_job_summaries(state: State<'_, AppState>) -> Result<Vec<JobStageSummary>, String> {
+    let mut job_service = JobService::new(
+        PgJobRepository::new(state.db_pool.clone()),
+        NoOpProgressReporter,
+        &state.config.jobs,
+    );
+
+    job_service
+        .summarize()
+        .map(|summaries| summaries.into_iter().map(JobStageSummary::from).collect())
+        .map_err(|e| format!("Failed to get job summaries: {}", e))
+}
@@ -1,3 +1,6 @@
+pub mod duplicate;
 pub mod face;
+pub mod job;
+pub mod near_duplicate;
 pub mod person;
 pub mod photo;
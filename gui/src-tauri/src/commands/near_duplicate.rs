@@ -0,0 +1,20 @@
+use picasa_core::{repositories::PgPhotoRepository, services::NearDuplicateService};
+use tauri::State;
+
+use crate::{types::NearDuplicateCluster, AppState};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_near_duplicate_clusters(
+    state: State<'_, AppState>,
+) -> Result<Vec<NearDuplicateCluster>, String> {
+    let photo_repository = PgPhotoRepository::new(state.db_pool.clone());
+
+    let mut near_duplicate_service =
+        NearDuplicateService::new(photo_repository, &state.config.embeddings.model, None);
+
+    near_duplicate_service
+        .find_clusters()
+        .map(|c| c.into_iter().map(NearDuplicateCluster::from).collect())
+        .map_err(|e| format!("Failed to list near-duplicate clusters: {}", e))
+}
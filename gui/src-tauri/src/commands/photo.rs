@@ -1,6 +1,11 @@
 use picasa_core::{
-    repositories::{PgFaceRepository, PgGeoRepository, PgPersonRepository, PgPhotoRepository},
-    services::{embedders::ClipTextEmbedder, PhotoSearchService},
+    repositories::{
+        PgFaceRepository, PgGeoRepository, PgPersonRepository, PgPhotoRepository, PhotoRepository,
+    },
+    services::{
+        embedders::{load_text_embedder, ClipImageEmbedder},
+        PhotoSearchService,
+    },
 };
 use tauri::State;
 
@@ -19,13 +24,12 @@ pub async fn get_photo_with_faces_and_people(
     let face_repository = PgFaceRepository::new(state.db_pool.clone());
     let photo_repository = PgPhotoRepository::new(state.db_pool.clone());
     let geo_repository = PgGeoRepository::new(state.db_pool.clone());
-    let text_embedder = ClipTextEmbedder::new(&state.config.clip_model).unwrap();
     let mut photo_search = PhotoSearchService::new(
         photo_repository,
         geo_repository,
         person_repository,
         face_repository,
-        text_embedder,
+        &state.config.embeddings.model,
     );
 
     let photo_with_faces_and_people = photo_search
@@ -48,13 +52,16 @@ pub async fn load_photo(path: &str, state: State<'_, AppState>) -> Result<Vec<u8
 
 #[tauri::command]
 #[specta::specta]
-pub async fn load_photo_thumbnail(
-    path: &str,
-    state: State<'_, AppState>,
-) -> Result<Vec<u8>, String> {
+pub async fn load_photo_thumbnail(id: i32, state: State<'_, AppState>) -> Result<Vec<u8>, String> {
+    let mut photo_repository = PgPhotoRepository::new(state.db_pool.clone());
+    let photo = photo_repository
+        .find_file(id)
+        .map_err(|e| format!("Failed to get photo: {}", e))?
+        .ok_or_else(|| format!("Photo with id {} not found", id))?;
+
     state
         .image_service
-        .get_thumbnail(path)
+        .get_thumbnail(&photo)
         .await
         .map_err(|e| format!("Failed to load photo: {}", e))
 }
@@ -69,16 +76,29 @@ pub async fn search_photos(
     let face_repository = PgFaceRepository::new(state.db_pool.clone());
     let photo_repository = PgPhotoRepository::new(state.db_pool.clone());
     let geo_repository = PgGeoRepository::new(state.db_pool.clone());
-    let text_embedder = ClipTextEmbedder::new(&state.config.clip_model)
-        .map_err(|e| format!("Failed to create text embedder: {}", e))?;
     let mut photo_search = PhotoSearchService::new(
         photo_repository,
         geo_repository,
         person_repository,
         face_repository,
-        text_embedder,
+        &state.config.embeddings.model,
     );
 
+    // Texts are only embedded when searching photos matching a text
+    if params.text.is_some() {
+        let text_embedder = load_text_embedder(&state.config.embeddings)
+            .map_err(|e| format!("Failed to create text embedder: {}", e))?;
+        photo_search = photo_search.with_text_embedder(text_embedder);
+    }
+
+    // Images are only embedded when searching photos similar to a file
+    if params.similar_to.is_none() && params.similar_to_image.is_some() {
+        let image_embedder =
+            ClipImageEmbedder::new(&state.config.embeddings, &state.config.embeddings.model)
+                .map_err(|e| format!("Failed to create image embedder: {}", e))?;
+        photo_search = photo_search.with_image_embedder(Box::new(image_embedder));
+    }
+
     photo_search
         .search(params.into())
         .map(PaginatedPhotos::from)
@@ -92,14 +112,12 @@ pub async fn get_search_options(state: State<'_, AppState>) -> Result<PhotoSearc
     let face_repository = PgFaceRepository::new(state.db_pool.clone());
     let photo_repository = PgPhotoRepository::new(state.db_pool.clone());
     let geo_repository = PgGeoRepository::new(state.db_pool.clone());
-    let text_embedder = ClipTextEmbedder::new(&state.config.clip_model)
-        .map_err(|e| format!("Failed to create text embedder: {}", e))?;
     let mut photo_search = PhotoSearchService::new(
         photo_repository,
         geo_repository,
         person_repository,
         face_repository,
-        text_embedder,
+        &state.config.embeddings.model,
     );
 
     photo_search
@@ -4,7 +4,6 @@ use picasa_core::{
 };
 #[cfg(debug_assertions)]
 use specta_typescript::Typescript;
-use std::path::Path;
 use tauri_specta::{collect_commands, Builder};
 
 pub mod commands;
@@ -21,8 +20,7 @@ impl AppState {
     pub fn new() -> anyhow::Result<Self> {
         let config = Config::load()?;
         let db_pool = database::create_pool(&config.database)?;
-        let image_service =
-            services::image::ImageService::new(Path::new("../cache_dir").to_path_buf());
+        let image_service = services::image::ImageService::new(&config.thumbnails);
 
         Ok(Self {
             db_pool,
@@ -47,6 +45,10 @@ pub fn run() {
         commands::person::create_person_from_faces,
         commands::person::list_persons,
         commands::person::get_person,
+        commands::duplicate::list_duplicate_groups,
+        commands::near_duplicate::list_near_duplicate_clusters,
+        commands::job::enqueue_library_scans,
+        commands::job::get_job_summaries,
     ]);
 
     #[cfg(debug_assertions)]
@@ -1,16 +1,16 @@
 use image::{DynamicImage, GenericImageView, ImageFormat};
 use lru::LruCache;
+use picasa_core::{
+    config::ThumbnailsConfig, imaging, models::PhotoFile, services::thumbnails::ThumbnailCache,
+};
 use std::num::NonZero;
-use std::path::{Path, PathBuf};
 use tokio::sync::RwLock;
 
 pub struct ImageService {
     // LRU cache for thumbnails in memory
-    thumbnail_cache: RwLock<LruCache<String, Vec<u8>>>,
-    // Cache directory for generated thumbnails
-    cache_dir: PathBuf,
-    // Thumbnail size
-    thumbnail_width: u32,
+    thumbnail_cache: RwLock<LruCache<(String, u32), Vec<u8>>>,
+    // Thumbnails stored on disk, shared with the CLI
+    thumbnails: ThumbnailCache,
 }
 
 #[derive(Debug, Clone)]
@@ -22,11 +22,10 @@ pub struct BoundingBox {
 }
 
 impl ImageService {
-    pub fn new(cache_dir: PathBuf) -> Self {
+    pub fn new(config: &ThumbnailsConfig) -> Self {
         Self {
             thumbnail_cache: RwLock::new(LruCache::new(NonZero::new(500).unwrap())),
-            cache_dir,
-            thumbnail_width: 200,
+            thumbnails: ThumbnailCache::new(config),
         }
     }
 
@@ -40,9 +39,14 @@ impl ImageService {
         Ok(buffer)
     }
 
-    /// Generate and cache thumbnail if it doesn't exist
-    pub async fn get_thumbnail(&self, photo_path: &str) -> anyhow::Result<Vec<u8>> {
-        let cache_key = format!("thumb_{}", self.hash_path(photo_path));
+    /// Get the smallest thumbnail of a photo, generating it when missing
+    pub async fn get_thumbnail(&self, photo: &PhotoFile) -> anyhow::Result<Vec<u8>> {
+        let size = *self
+            .thumbnails
+            .sizes()
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("No thumbnail size configured"))?;
+        let cache_key = (ThumbnailCache::key(photo), size);
 
         // Check memory cache first
         {
@@ -52,28 +56,10 @@ impl ImageService {
             }
         }
 
-        // Check disk cache
-        let thumb_path = self.cache_dir.join(format!("{}.webp", cache_key));
-        if thumb_path.exists() {
-            let data = tokio::fs::read(&thumb_path).await?;
-
-            // Store in memory cache
-            {
-                let mut cache = self.thumbnail_cache.write().await;
-                cache.put(cache_key, data.clone());
-            }
-
-            return Ok(data);
-        }
-
-        // Generate thumbnail
-        let data = self
-            .generate_thumbnail(photo_path, self.thumbnail_width)
-            .await?;
-
-        // Save to disk cache
-        tokio::fs::create_dir_all(&self.cache_dir).await?;
-        tokio::fs::write(&thumb_path, &data).await?;
+        // Read from disk cache, generating thumbnails when missing
+        let thumbnails = self.thumbnails.clone();
+        let photo = photo.clone();
+        let data = tokio::task::spawn_blocking(move || thumbnails.get(&photo, size)).await??;
 
         // Store in memory cache
         {
@@ -84,79 +70,9 @@ impl ImageService {
         Ok(data)
     }
 
-    async fn generate_thumbnail(&self, photo_path: &str, width: u32) -> anyhow::Result<Vec<u8>> {
-        let img = self.load_image(photo_path)?;
-        let (original_width, original_height) = img.dimensions();
-
-        // Calculate proportional height based on desired width
-        let height = (width as f32 * original_height as f32 / original_width as f32) as u32;
-
-        let thumbnail = img.thumbnail(width, height);
-
-        let mut buffer = Vec::new();
-        thumbnail.write_to(&mut std::io::Cursor::new(&mut buffer), ImageFormat::WebP)?;
-
-        Ok(buffer)
-    }
-
+    /// Decodes a photo in its displayed orientation, so that face boxes match it.
     fn load_image(&self, photo_path: &str) -> anyhow::Result<DynamicImage> {
-        let path = Path::new(photo_path);
-        let extension = path
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .unwrap_or("")
-            .to_lowercase();
-
-        match extension.as_str() {
-            "heif" | "heic" => self.load_heif_image(photo_path),
-            _ => Ok(image::open(photo_path)?),
-        }
-    }
-
-    fn load_heif_image(&self, photo_path: &str) -> anyhow::Result<DynamicImage> {
-        use anyhow::Context;
-        use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
-
-        let lib_heif = LibHeif::new();
-        let ctx = HeifContext::read_from_file(photo_path).context("Failed to read HEIC file")?;
-        let handle = ctx
-            .primary_image_handle()
-            .context("Failed to get primary image handle")?;
-
-        let width = handle.width();
-        let height = handle.height();
-
-        let image = lib_heif
-            .decode(&handle, ColorSpace::Rgb(RgbChroma::Rgb), None)
-            .context("Failed to decode HEIC image")?;
-
-        let planes = image.planes();
-        let interleaved_plane = planes
-            .interleaved
-            .ok_or_else(|| anyhow::anyhow!("No interleaved plane available"))?;
-
-        let stride = interleaved_plane.stride as u32;
-        let expected_row_size = width * 3; // 3 bytes per pixel (RGB)
-
-        let mut img_data = Vec::with_capacity((width * height * 3) as usize);
-
-        // Handle row padding by copying only the actual image data
-        if stride == expected_row_size {
-            // No padding, can use data directly
-            img_data = interleaved_plane.data.to_vec();
-        } else {
-            // Has padding, need to copy row by row
-            for row in 0..height {
-                let row_start = (row * stride) as usize;
-                let row_end = row_start + expected_row_size as usize;
-                img_data.extend_from_slice(&interleaved_plane.data[row_start..row_end]);
-            }
-        }
-
-        let image_buffer = image::RgbImage::from_raw(width, height, img_data)
-            .ok_or_else(|| anyhow::anyhow!("Failed to create image buffer from HEIF data"))?;
-
-        Ok(DynamicImage::ImageRgb8(image_buffer))
+        imaging::load_image(photo_path)
     }
 
     pub fn crop_image(&self, photo_path: String, bbox: BoundingBox) -> anyhow::Result<Vec<u8>> {
@@ -182,13 +98,4 @@ impl ImageService {
 
         Ok(buffer)
     }
-
-    fn hash_path(&self, path: &str) -> String {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-
-        let mut hasher = DefaultHasher::new();
-        path.hash(&mut hasher);
-        format!("{:x}", hasher.finish())
-    }
 }
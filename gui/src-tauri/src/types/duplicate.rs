@@ -0,0 +1,26 @@
+use picasa_core::services;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::types::Photo;
+
+#[derive(Debug, Serialize, Deserialize, Type)]
+pub struct DuplicateGroup {
+    pub hash: String,
+    pub keeper: Photo,
+    pub duplicates: Vec<Photo>,
+}
+
+impl From<services::duplicates::DuplicateGroup> for DuplicateGroup {
+    fn from(core_group: services::duplicates::DuplicateGroup) -> Self {
+        Self {
+            hash: core_group.hash,
+            keeper: Photo::from(core_group.keeper),
+            duplicates: core_group
+                .duplicates
+                .into_iter()
+                .map(Photo::from)
+                .collect(),
+        }
+    }
+}
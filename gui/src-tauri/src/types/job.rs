@@ -0,0 +1,24 @@
+use picasa_core::models;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+#[derive(Debug, Serialize, Deserialize, Type)]
+pub struct JobStageSummary {
+    pub stage: String,
+    pub pending: i64,
+    pub running: i64,
+    pub done: i64,
+    pub failed: i64,
+}
+
+impl From<models::JobStageSummary> for JobStageSummary {
+    fn from(core_summary: models::JobStageSummary) -> Self {
+        Self {
+            stage: core_summary.stage.to_string(),
+            pending: core_summary.pending,
+            running: core_summary.running,
+            done: core_summary.done,
+            failed: core_summary.failed,
+        }
+    }
+}
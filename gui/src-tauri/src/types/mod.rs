@@ -1,10 +1,16 @@
+pub mod duplicate;
 pub mod face;
 pub mod geo;
+pub mod job;
+pub mod near_duplicate;
 pub mod person;
 pub mod photo;
 
+pub use duplicate::DuplicateGroup;
 pub use face::{PaginatedFaces, PendingFaceReview};
 pub use geo::{CityName, CountryName};
+pub use job::JobStageSummary;
+pub use near_duplicate::{NearDuplicateCluster, SimilarPhoto};
 pub use person::Person;
 pub use photo::{
     PaginatedPhotos, Photo, PhotoSearchOptions, PhotoSearchParams, PhotoWithFacesAndPeople,
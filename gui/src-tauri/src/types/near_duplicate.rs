@@ -0,0 +1,49 @@
+use picasa_core::{models, services};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+#[derive(Debug, Serialize, Deserialize, Type)]
+pub struct SimilarPhoto {
+    pub id: i32,
+    pub path: String,
+    pub file_size: i64,
+    pub image_width: Option<i32>,
+    pub image_height: Option<i32>,
+    pub date_taken_utc: Option<String>,
+    pub rating: Option<i32>,
+}
+
+impl From<models::SimilarityCandidate> for SimilarPhoto {
+    fn from(candidate: models::SimilarityCandidate) -> Self {
+        Self {
+            id: candidate.id,
+            path: candidate.path,
+            file_size: candidate.file_size,
+            image_width: candidate.image_width,
+            image_height: candidate.image_height,
+            date_taken_utc: candidate.date_taken_utc.map(|dt| dt.to_rfc3339()),
+            rating: candidate.rating,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Type)]
+pub struct NearDuplicateCluster {
+    pub kind: String,
+    pub best_shot: SimilarPhoto,
+    pub others: Vec<SimilarPhoto>,
+}
+
+impl From<services::near_duplicates::NearDuplicateCluster> for NearDuplicateCluster {
+    fn from(core_cluster: services::near_duplicates::NearDuplicateCluster) -> Self {
+        Self {
+            kind: core_cluster.kind.to_string(),
+            best_shot: SimilarPhoto::from(core_cluster.best_shot),
+            others: core_cluster
+                .others
+                .into_iter()
+                .map(SimilarPhoto::from)
+                .collect(),
+        }
+    }
+}
@@ -24,6 +24,22 @@ pub struct Photo {
     pub face_detection_completed: bool,
     pub country_id: Option<i32>,
     pub city_id: Option<i32>,
+    pub media_type: MediaType,
+    pub aperture: Option<f32>,
+    pub exposure_time: Option<f32>,
+    pub iso: Option<i32>,
+    pub focal_length: Option<f32>,
+    pub focal_length_35mm: Option<i32>,
+    pub flash: Option<bool>,
+    pub altitude: Option<f32>,
+    pub gps_heading: Option<f32>,
+    pub offset_time: Option<String>,
+    pub date_taken_timezone: Option<String>,
+    pub date_taken_source: Option<String>,
+    pub keywords: Vec<String>,
+    pub rating: Option<i32>,
+    pub title: Option<String>,
+    pub caption: Option<String>,
 }
 
 impl From<models::Photo> for Photo {
@@ -47,6 +63,24 @@ impl From<models::Photo> for Photo {
             face_detection_completed: core_photo.face_detection_completed,
             country_id: core_photo.country_id,
             city_id: core_photo.city_id,
+            media_type: core_photo.media_type.into(),
+            aperture: core_photo.aperture,
+            exposure_time: core_photo.exposure_time,
+            iso: core_photo.iso,
+            focal_length: core_photo.focal_length,
+            focal_length_35mm: core_photo.focal_length_35mm,
+            flash: core_photo.flash,
+            altitude: core_photo.altitude,
+            gps_heading: core_photo.gps_heading,
+            offset_time: core_photo.offset_time,
+            date_taken_timezone: core_photo.date_taken_timezone,
+            date_taken_source: core_photo
+                .date_taken_source
+                .map(|source| source.to_string()),
+            keywords: core_photo.keywords,
+            rating: core_photo.rating,
+            title: core_photo.title,
+            caption: core_photo.caption,
         }
     }
 }
@@ -88,9 +122,35 @@ impl Default for PersonMatchMode {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, Type)]
+pub enum MediaType {
+    Photo,
+    Video,
+}
+
+impl From<models::MediaType> for MediaType {
+    fn from(media_type: models::MediaType) -> Self {
+        match media_type {
+            models::MediaType::Photo => MediaType::Photo,
+            models::MediaType::Video => MediaType::Video,
+        }
+    }
+}
+
+impl From<MediaType> for models::MediaType {
+    fn from(media_type: MediaType) -> Self {
+        match media_type {
+            MediaType::Photo => models::MediaType::Photo,
+            MediaType::Video => models::MediaType::Video,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Type, Default)]
 pub struct PhotoSearchParams {
     pub text: Option<String>,
+    pub similar_to: Option<i32>,
+    pub similar_to_image: Option<String>,
     pub threshold: Option<f32>,
 
     pub country: Option<String>,
@@ -104,14 +164,24 @@ pub struct PhotoSearchParams {
     pub person_ids: Option<Vec<i32>>,
     pub person_match_mode: Option<PersonMatchMode>,
 
+    pub media_type: Option<MediaType>,
+
     pub page: u32,
     pub per_page: u32,
 }
 
 impl From<services::PhotoSearchParams> for PhotoSearchParams {
     fn from(photo_search_params: services::PhotoSearchParams) -> Self {
+        let (similar_to, similar_to_image) = match photo_search_params.similar_to {
+            Some(services::SimilarTo::Photo(id)) => (Some(id), None),
+            Some(services::SimilarTo::Image(path)) => (None, Some(path)),
+            None => (None, None),
+        };
+
         Self {
             text: photo_search_params.text,
+            similar_to,
+            similar_to_image,
             threshold: photo_search_params.threshold,
             country: photo_search_params.country,
             country_id: photo_search_params.country_id,
@@ -127,6 +197,7 @@ impl From<services::PhotoSearchParams> for PhotoSearchParams {
                 },
                 None => None,
             },
+            media_type: photo_search_params.media_type.map(Into::into),
             page: photo_search_params.page,
             per_page: photo_search_params.per_page,
         }
@@ -135,8 +206,18 @@ impl From<services::PhotoSearchParams> for PhotoSearchParams {
 
 impl From<PhotoSearchParams> for services::PhotoSearchParams {
     fn from(photo_search_params: PhotoSearchParams) -> Self {
+        let similar_to = match (
+            photo_search_params.similar_to,
+            photo_search_params.similar_to_image,
+        ) {
+            (Some(id), _) => Some(services::SimilarTo::Photo(id)),
+            (None, Some(path)) => Some(services::SimilarTo::Image(path)),
+            (None, None) => None,
+        };
+
         Self {
             text: photo_search_params.text,
+            similar_to,
             threshold: photo_search_params.threshold,
             country: photo_search_params.country,
             country_id: photo_search_params.country_id,
@@ -152,8 +233,10 @@ impl From<PhotoSearchParams> for services::PhotoSearchParams {
                 },
                 None => None,
             },
+            media_type: photo_search_params.media_type.map(Into::into),
             page: photo_search_params.page,
             per_page: photo_search_params.per_page,
+            ..Default::default()
         }
     }
 }
@@ -13,9 +13,9 @@ async searchPhotos(params: PhotoSearchParams) : Promise<Result<PaginatedPhotos,
     else return { status: "error", error: e  as any };
 }
 },
-async loadPhotoThumbnail(path: string) : Promise<Result<number[], string>> {
+async loadPhotoThumbnail(id: number) : Promise<Result<number[], string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("load_photo_thumbnail", { path }) };
+    return { status: "ok", data: await TAURI_INVOKE("load_photo_thumbnail", { id }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
@@ -100,6 +100,42 @@ async getPerson(id: number) : Promise<Result<Person, string>> {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
+},
+async listDuplicateGroups() : Promise<Result<DuplicateGroup[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_duplicate_groups") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listNearDuplicateClusters() : Promise<Result<NearDuplicateCluster[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_near_duplicate_clusters") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Enqueues scans of a library, or of all enabled libraries, and returns the number of
+ * enqueued jobs.
+ */
+async enqueueLibraryScans(library: string | null) : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("enqueue_library_scans", { library }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getJobSummaries() : Promise<Result<JobStageSummary[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_job_summaries") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
 }
 }
 
@@ -115,17 +151,22 @@ async getPerson(id: number) : Promise<Result<Person, string>> {
 
 export type CityName = { id: number; name: string }
 export type CountryName = { id: number; name: string | null }
+export type DuplicateGroup = { hash: string; keeper: Photo; duplicates: Photo[] }
 export type Face = { id: number; photo_id: number; bbox_x: number; bbox_y: number; bbox_width: number; bbox_height: number; confidence: number; gender: string | null; person_id: number | null; created_at: string; updated_at: string }
 export type FaceWithPerson = { face: Face; person: Person | null }
+export type JobStageSummary = { stage: string; pending: number; running: number; done: number; failed: number }
+export type MediaType = "Photo" | "Video"
+export type NearDuplicateCluster = { kind: string; best_shot: SimilarPhoto; others: SimilarPhoto[] }
 export type PaginatedFaces = { items: Face[]; total: number; page: number; per_page: number; total_pages: number }
 export type PaginatedPhotos = { items: Photo[]; total: number; page: number; per_page: number; total_pages: number }
 export type PendingFaceReview = { cluster_id: number; face_ids: number[]; confidence: number; face_count: number }
 export type Person = { id: number; name: string }
 export type PersonMatchMode = "Any" | "All"
-export type Photo = { id: number; path: string; file_name: string; file_size: number; created_at: string; modified_at: string; hash: string | null; camera_make: string | null; camera_model: string | null; lens_model: string | null; orientation: number | null; date_taken_local: string | null; date_taken_utc: string | null; image_width: number | null; image_height: number | null; face_detection_completed: boolean; country_id: number | null; city_id: number | null }
+export type Photo = { id: number; path: string; file_name: string; file_size: number; created_at: string; modified_at: string; hash: string | null; camera_make: string | null; camera_model: string | null; lens_model: string | null; orientation: number | null; date_taken_local: string | null; date_taken_utc: string | null; image_width: number | null; image_height: number | null; face_detection_completed: boolean; country_id: number | null; city_id: number | null; media_type: MediaType; aperture: number | null; exposure_time: number | null; iso: number | null; focal_length: number | null; focal_length_35mm: number | null; flash: boolean | null; altitude: number | null; gps_heading: number | null; offset_time: string | null; date_taken_timezone: string | null; date_taken_source: string | null; keywords: string[]; rating: number | null; title: string | null; caption: string | null }
 export type PhotoSearchOptions = { cities: CityName[]; countries: CountryName[]; persons: Person[] }
-export type PhotoSearchParams = { text: string | null; threshold: number | null; country: string | null; country_id: number | null; city: string | null; city_id: number | null; date_from: string | null; date_to: string | null; person_ids: number[] | null; person_match_mode: PersonMatchMode | null; page: number; per_page: number }
+export type PhotoSearchParams = { text: string | null; similar_to: number | null; similar_to_image: string | null; threshold: number | null; country: string | null; country_id: number | null; city: string | null; city_id: number | null; date_from: string | null; date_to: string | null; person_ids: number[] | null; person_match_mode: PersonMatchMode | null; media_type: MediaType | null; page: number; per_page: number }
 export type PhotoWithFacesAndPeople = { photo: Photo; faces: FaceWithPerson[] }
+export type SimilarPhoto = { id: number; path: string; file_size: number; image_width: number | null; image_height: number | null; date_taken_utc: string | null; rating: number | null }
 
 /** tauri-specta globals **/
 
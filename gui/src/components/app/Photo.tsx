@@ -6,7 +6,13 @@ import { Skeleton } from '@/components/ui/skeleton';
 
 import { FacesOverlay } from './FacesOverlay';
 
-const Photo: React.FC<{ photoPath: string; asThumbnail?: boolean; faces?: FaceWithPerson[] }> = ({
+const Photo: React.FC<{
+  photoId: number;
+  photoPath: string;
+  asThumbnail?: boolean;
+  faces?: FaceWithPerson[];
+}> = ({
+  photoId,
   photoPath,
   asThumbnail,
   faces = [],
@@ -32,7 +38,7 @@ const Photo: React.FC<{ photoPath: string; asThumbnail?: boolean; faces?: FaceWi
       setError(false);
 
       const result = asThumbnail
-        ? await commands.loadPhotoThumbnail(photoPath)
+        ? await commands.loadPhotoThumbnail(photoId)
         : await commands.loadPhoto(photoPath);
 
       if (result.status === 'ok') {
@@ -57,7 +63,7 @@ const Photo: React.FC<{ photoPath: string; asThumbnail?: boolean; faces?: FaceWi
       }
     };
     // eslint-disable-next-line react-hooks/exhaustive-deps
-  }, [photoPath]);
+  }, [photoId, photoPath]);
 
   const calculateImageDimensions = useCallback(() => {
     if (!imgRef.current || !imageNaturalDimensions) return;
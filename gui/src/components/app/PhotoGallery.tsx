@@ -43,7 +43,7 @@ export function PhotoGallery({ photos, totalPages }: PhotoGalleryProps) {
             params={{ id: p.id.toString() }}
             className="aspect-square cursor-pointer overflow-hidden rounded-lg shadow-sm transition-all duration-300 hover:scale-105 hover:shadow-lg"
           >
-            <PhotoComponent photoPath={p.path} asThumbnail />
+            <PhotoComponent photoId={p.id} photoPath={p.path} asThumbnail />
           </Link>
         ))}
       </div>
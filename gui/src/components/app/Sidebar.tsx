@@ -1,5 +1,16 @@
 import { Link } from '@tanstack/react-router';
-import { Album, Image, Import, LucideUser, MapPin, Search, Settings } from 'lucide-react';
+import {
+  Album,
+  Copy,
+  Image,
+  Import,
+  Layers,
+  ListChecks,
+  LucideUser,
+  MapPin,
+  Search,
+  Settings,
+} from 'lucide-react';
 
 import {
   Sidebar as UiSidebar,
@@ -17,6 +28,9 @@ const mainMenuItems = [
   { icon: Album, label: 'Albums', path: '/' },
   { icon: LucideUser, label: 'People', path: '/people' },
   { icon: MapPin, label: 'Places', path: '/' },
+  { icon: Copy, label: 'Duplicates', path: '/duplicates' },
+  { icon: Layers, label: 'Similar', path: '/near-duplicates' },
+  { icon: ListChecks, label: 'Jobs', path: '/jobs' },
 ];
 
 const footerMenuItems = [
@@ -8,6 +8,16 @@ export const photoSearchSchema = z.object({
     .nullable()
     .default(null)
     .transform((val) => (val === '' ? null : val)),
+  similar_to: z.coerce
+    .number()
+    .nullable()
+    .default(null)
+    .transform((val) => (val === 0 ? null : val)),
+  similar_to_image: z
+    .string()
+    .nullable()
+    .default(null)
+    .transform((val) => (val === '' ? null : val)),
   threshold: z.number().min(0).max(1).nullable().default(null),
   country: z.string().nullable().default(null),
   country_id: z.coerce
@@ -37,6 +47,7 @@ export const photoSearchSchema = z.object({
     .default(null)
     .transform((val) => (val && val.length === 0 ? null : val)),
   person_match_mode: z.enum(['All', 'Any']).nullable().default(null),
+  media_type: z.enum(['Photo', 'Video']).nullable().default(null),
   page: z.number().min(1).default(1),
   per_page: z.number().min(1).default(15),
 });
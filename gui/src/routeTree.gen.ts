@@ -11,6 +11,9 @@
 import { Route as rootRouteImport } from './routes/__root'
 import { Route as SearchRouteImport } from './routes/search'
 import { Route as PeopleRouteImport } from './routes/people'
+import { Route as DuplicatesRouteImport } from './routes/duplicates'
+import { Route as NearDuplicatesRouteImport } from './routes/near-duplicates'
+import { Route as JobsRouteImport } from './routes/jobs'
 import { Route as IndexRouteImport } from './routes/index'
 import { Route as PeopleIndexRouteImport } from './routes/people.index'
 import { Route as SearchGalleryRouteImport } from './routes/search.gallery'
@@ -28,6 +31,21 @@ const PeopleRoute = PeopleRouteImport.update({
   path: '/people',
   getParentRoute: () => rootRouteImport,
 } as any)
+const NearDuplicatesRoute = NearDuplicatesRouteImport.update({
+  id: '/near-duplicates',
+  path: '/near-duplicates',
+  getParentRoute: () => rootRouteImport,
+} as any)
+const JobsRoute = JobsRouteImport.update({
+  id: '/jobs',
+  path: '/jobs',
+  getParentRoute: () => rootRouteImport,
+} as any)
+const DuplicatesRoute = DuplicatesRouteImport.update({
+  id: '/duplicates',
+  path: '/duplicates',
+  getParentRoute: () => rootRouteImport,
+} as any)
 const IndexRoute = IndexRouteImport.update({
   id: '/',
   path: '/',
@@ -61,6 +79,9 @@ const PeopleIdGalleryRoute = PeopleIdGalleryRouteImport.update({
 
 export interface FileRoutesByFullPath {
   '/': typeof IndexRoute
+  '/duplicates': typeof DuplicatesRoute
+  '/jobs': typeof JobsRoute
+  '/near-duplicates': typeof NearDuplicatesRoute
   '/people': typeof PeopleRouteWithChildren
   '/search': typeof SearchRouteWithChildren
   '/people/$id': typeof PeopleIdRouteWithChildren
@@ -71,6 +92,9 @@ export interface FileRoutesByFullPath {
 }
 export interface FileRoutesByTo {
   '/': typeof IndexRoute
+  '/duplicates': typeof DuplicatesRoute
+  '/jobs': typeof JobsRoute
+  '/near-duplicates': typeof NearDuplicatesRoute
   '/search': typeof SearchRouteWithChildren
   '/people/$id': typeof PeopleIdRouteWithChildren
   '/photo/$id': typeof PhotoIdRoute
@@ -81,6 +105,9 @@ export interface FileRoutesByTo {
 export interface FileRoutesById {
   __root__: typeof rootRouteImport
   '/': typeof IndexRoute
+  '/duplicates': typeof DuplicatesRoute
+  '/jobs': typeof JobsRoute
+  '/near-duplicates': typeof NearDuplicatesRoute
   '/people': typeof PeopleRouteWithChildren
   '/search': typeof SearchRouteWithChildren
   '/people/$id': typeof PeopleIdRouteWithChildren
@@ -93,6 +120,9 @@ export interface FileRouteTypes {
   fileRoutesByFullPath: FileRoutesByFullPath
   fullPaths:
     | '/'
+    | '/duplicates'
+    | '/jobs'
+    | '/near-duplicates'
     | '/people'
     | '/search'
     | '/people/$id'
@@ -103,6 +133,9 @@ export interface FileRouteTypes {
   fileRoutesByTo: FileRoutesByTo
   to:
     | '/'
+    | '/duplicates'
+    | '/jobs'
+    | '/near-duplicates'
     | '/search'
     | '/people/$id'
     | '/photo/$id'
@@ -112,6 +145,9 @@ export interface FileRouteTypes {
   id:
     | '__root__'
     | '/'
+    | '/duplicates'
+    | '/jobs'
+    | '/near-duplicates'
     | '/people'
     | '/search'
     | '/people/$id'
@@ -123,6 +159,9 @@ export interface FileRouteTypes {
 }
 export interface RootRouteChildren {
   IndexRoute: typeof IndexRoute
+  DuplicatesRoute: typeof DuplicatesRoute
+  JobsRoute: typeof JobsRoute
+  NearDuplicatesRoute: typeof NearDuplicatesRoute
   PeopleRoute: typeof PeopleRouteWithChildren
   SearchRoute: typeof SearchRouteWithChildren
   PhotoIdRoute: typeof PhotoIdRoute
@@ -144,6 +183,27 @@ declare module '@tanstack/react-router' {
       preLoaderRoute: typeof PeopleRouteImport
       parentRoute: typeof rootRouteImport
     }
+    '/near-duplicates': {
+      id: '/near-duplicates'
+      path: '/near-duplicates'
+      fullPath: '/near-duplicates'
+      preLoaderRoute: typeof NearDuplicatesRouteImport
+      parentRoute: typeof rootRouteImport
+    }
+    '/jobs': {
+      id: '/jobs'
+      path: '/jobs'
+      fullPath: '/jobs'
+      preLoaderRoute: typeof JobsRouteImport
+      parentRoute: typeof rootRouteImport
+    }
+    '/duplicates': {
+      id: '/duplicates'
+      path: '/duplicates'
+      fullPath: '/duplicates'
+      preLoaderRoute: typeof DuplicatesRouteImport
+      parentRoute: typeof rootRouteImport
+    }
     '/': {
       id: '/'
       path: '/'
@@ -227,6 +287,9 @@ const SearchRouteWithChildren =
 
 const rootRouteChildren: RootRouteChildren = {
   IndexRoute: IndexRoute,
+  DuplicatesRoute: DuplicatesRoute,
+  JobsRoute: JobsRoute,
+  NearDuplicatesRoute: NearDuplicatesRoute,
   PeopleRoute: PeopleRouteWithChildren,
   SearchRoute: SearchRouteWithChildren,
   PhotoIdRoute: PhotoIdRoute,
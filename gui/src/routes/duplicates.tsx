@@ -0,0 +1,66 @@
+import { createFileRoute, Link } from '@tanstack/react-router';
+
+import { commands, DuplicateGroup, Photo } from '@/bindings';
+import { ErrorMessage } from '@/components/app/ErrorMessage';
+import { Photo as PhotoComponent } from '@/components/app/Photo';
+import { Badge } from '@/components/ui/badge';
+
+export const Route = createFileRoute('/duplicates')({
+  component: RouteComponent,
+  loader: async () => {
+    const groups = await commands.listDuplicateGroups();
+
+    if (groups.status === 'error') throw new Error(groups.error);
+
+    return {
+      breadcrumb: 'Duplicates',
+      groups: groups.data,
+    };
+  },
+  errorComponent: ErrorMessage,
+});
+
+function RouteComponent() {
+  const { groups } = Route.useLoaderData();
+
+  return (
+    <div>
+      <h4 className="scroll-m-20 text-xl font-semibold tracking-tight">Duplicates</h4>
+      {groups.length === 0 && <p className="text-gray-500">No duplicate photos found.</p>}
+      <div className="space-y-6 pt-2">
+        {groups.map((group) => (
+          <_DuplicateGroup key={group.hash} group={group} />
+        ))}
+      </div>
+    </div>
+  );
+}
+
+const _DuplicateGroup = ({ group }: { group: DuplicateGroup }) => {
+  return (
+    <div className="grid grid-cols-2 gap-4 md:grid-cols-4 lg:grid-cols-6 xl:grid-cols-8">
+      <_DuplicatePhoto photo={group.keeper} isKeeper />
+      {group.duplicates.map((photo) => (
+        <_DuplicatePhoto key={photo.id} photo={photo} />
+      ))}
+    </div>
+  );
+};
+
+const _DuplicatePhoto = ({ photo, isKeeper }: { photo: Photo; isKeeper?: boolean }) => {
+  return (
+    <div className="flex flex-col space-y-1">
+      <Link
+        to="/photo/$id"
+        params={{ id: photo.id.toString() }}
+        className="relative aspect-square cursor-pointer overflow-hidden rounded-lg shadow-sm transition-all duration-300 hover:scale-105 hover:shadow-lg"
+      >
+        <PhotoComponent photoId={photo.id} photoPath={photo.path} asThumbnail />
+        {isKeeper && <Badge className="absolute top-2 left-2">Keep</Badge>}
+      </Link>
+      <span className="text-muted-foreground truncate text-xs" title={photo.path}>
+        {photo.path}
+      </span>
+    </div>
+  );
+};
@@ -0,0 +1,72 @@
+import { createFileRoute, useRouter } from '@tanstack/react-router';
+import { toast } from 'sonner';
+
+import { commands } from '@/bindings';
+import { ErrorMessage } from '@/components/app/ErrorMessage';
+import { Button } from '@/components/ui/button';
+
+export const Route = createFileRoute('/jobs')({
+  component: RouteComponent,
+  loader: async () => {
+    const summaries = await commands.getJobSummaries();
+
+    if (summaries.status === 'error') throw new Error(summaries.error);
+
+    return {
+      breadcrumb: 'Jobs',
+      summaries: summaries.data,
+    };
+  },
+  errorComponent: ErrorMessage,
+});
+
+function RouteComponent() {
+  const { summaries } = Route.useLoaderData();
+  const router = useRouter();
+
+  const handleScanLibraries = async () => {
+    const result = await commands.enqueueLibraryScans(null);
+    if (result.status === 'ok') {
+      toast.success(`${result.data} library scans enqueued`);
+      router.invalidate();
+    } else {
+      toast.error(`Failed to enqueue library scans: ${result.error}`);
+    }
+  };
+
+  return (
+    <div>
+      <div className="flex items-center justify-between">
+        <h4 className="scroll-m-20 text-xl font-semibold tracking-tight">Jobs</h4>
+        <div className="space-x-2">
+          <Button variant="outline" onClick={() => router.invalidate()}>
+            Refresh
+          </Button>
+          <Button onClick={handleScanLibraries}>Scan libraries</Button>
+        </div>
+      </div>
+      <table className="mt-4 w-full text-sm">
+        <thead className="text-muted-foreground text-left">
+          <tr>
+            <th className="py-2">Stage</th>
+            <th className="py-2 text-right">Pending</th>
+            <th className="py-2 text-right">Running</th>
+            <th className="py-2 text-right">Done</th>
+            <th className="py-2 text-right">Failed</th>
+          </tr>
+        </thead>
+        <tbody>
+          {summaries.map((summary) => (
+            <tr key={summary.stage} className="border-t">
+              <td className="py-2">{summary.stage}</td>
+              <td className="py-2 text-right">{summary.pending}</td>
+              <td className="py-2 text-right">{summary.running}</td>
+              <td className="py-2 text-right">{summary.done}</td>
+              <td className="py-2 text-right">{summary.failed}</td>
+            </tr>
+          ))}
+        </tbody>
+      </table>
+    </div>
+  );
+}
@@ -0,0 +1,74 @@
+import { createFileRoute, Link } from '@tanstack/react-router';
+
+import { commands, NearDuplicateCluster, SimilarPhoto } from '@/bindings';
+import { ErrorMessage } from '@/components/app/ErrorMessage';
+import { Photo as PhotoComponent } from '@/components/app/Photo';
+import { Badge } from '@/components/ui/badge';
+
+export const Route = createFileRoute('/near-duplicates')({
+  component: RouteComponent,
+  loader: async () => {
+    const clusters = await commands.listNearDuplicateClusters();
+
+    if (clusters.status === 'error') throw new Error(clusters.error);
+
+    return {
+      breadcrumb: 'Similar photos',
+      clusters: clusters.data,
+    };
+  },
+  errorComponent: ErrorMessage,
+});
+
+function RouteComponent() {
+  const { clusters } = Route.useLoaderData();
+
+  return (
+    <div>
+      <h4 className="scroll-m-20 text-xl font-semibold tracking-tight">Similar photos</h4>
+      {clusters.length === 0 && (
+        <p className="text-gray-500">No near-duplicate photos or bursts found.</p>
+      )}
+      <div className="space-y-6 pt-2">
+        {clusters.map((cluster) => (
+          <_Cluster key={cluster.best_shot.id} cluster={cluster} />
+        ))}
+      </div>
+    </div>
+  );
+}
+
+const _Cluster = ({ cluster }: { cluster: NearDuplicateCluster }) => {
+  return (
+    <div className="space-y-2">
+      <Badge variant="secondary">
+        {cluster.kind === 'burst' ? 'Burst' : 'Near-duplicates'} · {cluster.others.length + 1}{' '}
+        photos
+      </Badge>
+      <div className="grid grid-cols-2 gap-4 md:grid-cols-4 lg:grid-cols-6 xl:grid-cols-8">
+        <_SimilarPhoto photo={cluster.best_shot} isBestShot />
+        {cluster.others.map((photo) => (
+          <_SimilarPhoto key={photo.id} photo={photo} />
+        ))}
+      </div>
+    </div>
+  );
+};
+
+const _SimilarPhoto = ({ photo, isBestShot }: { photo: SimilarPhoto; isBestShot?: boolean }) => {
+  return (
+    <div className="flex flex-col space-y-1">
+      <Link
+        to="/photo/$id"
+        params={{ id: photo.id.toString() }}
+        className="relative aspect-square cursor-pointer overflow-hidden rounded-lg shadow-sm transition-all duration-300 hover:scale-105 hover:shadow-lg"
+      >
+        <PhotoComponent photoId={photo.id} photoPath={photo.path} asThumbnail />
+        {isBestShot && <Badge className="absolute top-2 left-2">Best shot</Badge>}
+      </Link>
+      <span className="text-muted-foreground truncate text-xs" title={photo.path}>
+        {photo.path}
+      </span>
+    </div>
+  );
+};
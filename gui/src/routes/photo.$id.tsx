@@ -1,8 +1,10 @@
-import { createFileRoute } from '@tanstack/react-router';
+import { createFileRoute, Link } from '@tanstack/react-router';
 
 import { commands, Photo as PhotoType } from '@/bindings';
 import { ErrorMessage } from '@/components/app/ErrorMessage';
 import { Photo } from '@/components/app/Photo';
+import { Button } from '@/components/ui/button';
+import { DEFAULT_PHOTO_SEARCH } from '@/photoSearch';
 
 export const Route = createFileRoute('/photo/$id')({
   component: RouteComponent,
@@ -24,9 +26,17 @@ function RouteComponent() {
   return (
     <div className="flex h-[calc(100vh-6rem)]">
       <div className="flex-1/2">
-        <Photo photoPath={photo.photo.path} faces={photo.faces} />
+        <Photo photoId={photo.photo.id} photoPath={photo.photo.path} faces={photo.faces} />
       </div>
       <div className="flex-1 overflow-y-auto px-4">
+        <Button variant="outline" className="mb-4" asChild>
+          <Link
+            to="/search/gallery"
+            search={{ ...DEFAULT_PHOTO_SEARCH, similar_to: photo.photo.id }}
+          >
+            Similar photos
+          </Link>
+        </Button>
         <PhotoDescription photo={photo.photo} />
       </div>
     </div>
@@ -78,6 +88,15 @@ const PhotoDescription = ({ photo }: { photo: PhotoType }) => {
         <div>{photo.date_taken_utc ? new Date(photo.date_taken_utc).toLocaleString() : 'N/A'}</div>
       </div>
 
+      <div>
+        <div className="text-muted-foreground text-sm">Timezone</div>
+        <div>
+          {photo.date_taken_timezone
+            ? `${photo.date_taken_timezone} (${dateTakenSourceLabels[photo.date_taken_source ?? ''] ?? 'unknown source'})`
+            : 'N/A'}
+        </div>
+      </div>
+
       <div>
         <div className="text-muted-foreground text-sm">Image dimensions</div>
         <div>
@@ -106,6 +125,82 @@ const PhotoDescription = ({ photo }: { photo: PhotoType }) => {
         <div className="text-muted-foreground text-sm">Lens model</div>
         <div>{photo.lens_model || 'N/A'}</div>
       </div>
+
+      <div>
+        <div className="text-muted-foreground text-sm">Exposure</div>
+        <div>{formatExposure(photo)}</div>
+      </div>
+
+      <div>
+        <div className="text-muted-foreground text-sm">Focal length</div>
+        <div>
+          {photo.focal_length
+            ? `${photo.focal_length} mm${photo.focal_length_35mm ? ` (${photo.focal_length_35mm} mm in 35mm)` : ''}`
+            : 'N/A'}
+        </div>
+      </div>
+
+      <div>
+        <div className="text-muted-foreground text-sm">Flash</div>
+        <div>{photo.flash === null ? 'N/A' : photo.flash ? 'Fired' : 'Did not fire'}</div>
+      </div>
+
+      <div>
+        <div className="text-muted-foreground text-sm">Altitude</div>
+        <div>{photo.altitude !== null ? `${photo.altitude.toFixed(1)} m` : 'N/A'}</div>
+      </div>
+
+      <div>
+        <div className="text-muted-foreground text-sm">Heading</div>
+        <div>{photo.gps_heading !== null ? `${photo.gps_heading.toFixed(0)}°` : 'N/A'}</div>
+      </div>
+
+      <div>
+        <div className="text-muted-foreground text-sm">UTC offset</div>
+        <div>{photo.offset_time || 'N/A'}</div>
+      </div>
+
+      <div>
+        <div className="text-muted-foreground text-sm">Title</div>
+        <div>{photo.title || 'N/A'}</div>
+      </div>
+
+      <div>
+        <div className="text-muted-foreground text-sm">Caption</div>
+        <div>{photo.caption || 'N/A'}</div>
+      </div>
+
+      <div>
+        <div className="text-muted-foreground text-sm">Keywords</div>
+        <div>{photo.keywords.length > 0 ? photo.keywords.join(', ') : 'N/A'}</div>
+      </div>
+
+      <div>
+        <div className="text-muted-foreground text-sm">Rating</div>
+        <div>{photo.rating !== null ? `${photo.rating} / 5` : 'N/A'}</div>
+      </div>
     </div>
   );
 };
+
+const dateTakenSourceLabels: Record<string, string> = {
+  offset: 'from metadata',
+  gps_timezone: 'from location',
+  default_timezone: 'default',
+};
+
+const formatExposure = (photo: PhotoType) => {
+  const parts = [];
+
+  if (photo.aperture) parts.push(`f/${photo.aperture.toFixed(1)}`);
+  if (photo.exposure_time) {
+    parts.push(
+      photo.exposure_time < 1
+        ? `1/${Math.round(1 / photo.exposure_time)} s`
+        : `${photo.exposure_time} s`,
+    );
+  }
+  if (photo.iso) parts.push(`ISO ${photo.iso}`);
+
+  return parts.length > 0 ? parts.join(' · ') : 'N/A';
+};
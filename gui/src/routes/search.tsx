@@ -74,6 +74,11 @@ function SearchPage() {
       to: '/search/gallery',
       search: {
         ...photoSearchSchema.parse(values),
+        // Filters refine the search of similar photos until a text is searched
+        ...(!values.text && {
+          similar_to: search.similar_to,
+          similar_to_image: search.similar_to_image,
+        }),
       },
     });
   };
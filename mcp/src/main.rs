@@ -16,13 +16,18 @@ use std::{future::Future, sync::Arc};
 use picasa_core::{
     config::Config,
     database,
-    services::photo_search::{PhotoSearchService, PhotoSearchParams},
+    services::photo_search::{PhotoSearchService, PhotoSearchParams, SimilarTo},
     repositories::{
         PgPhotoRepository, PgGeoRepository, 
         face::repository::PgFaceRepository,
         person::repository::PgPersonRepository,
     },
-    services::embedders::ClipTextEmbedder,
+    services::embedders::{load_text_embedder, ClipImageEmbedder},
+    services::duplicates::{DuplicateService, KeeperPreference, KeeperStrategy},
+    models::{JobStage, NewJob},
+    repositories::{PgJobRepository, PgLibraryRepository},
+    services::{JobService, LibraryService},
+    utils::progress_reporter::NoOpProgressReporter,
 };
 
 #[derive(Clone)]
@@ -68,6 +73,14 @@ impl ServerHandler for PicasaServer {
                         "type": "string",
                         "description": "Semantic text search query (e.g., 'sunset over mountains')"
                     },
+                    "similar_to": {
+                        "type": "integer",
+                        "description": "Id of a photo whose similar photos are searched, instead of a text query"
+                    },
+                    "similar_to_image": {
+                        "type": "string",
+                        "description": "Path of an image file whose similar photos are searched, instead of a text query"
+                    },
                     "threshold": {
                         "type": "number",
                         "minimum": 0.0,
@@ -90,6 +103,11 @@ impl ServerHandler for PicasaServer {
                         "type": "string",
                         "description": "End date in ISO 8601 format (e.g., '2023-12-31T23:59:59Z')"
                     },
+                    "media_type": {
+                        "type": "string",
+                        "enum": ["photo", "video"],
+                        "description": "Filter by media type"
+                    },
                     "page": {
                         "type": "integer",
                         "minimum": 1,
@@ -112,13 +130,78 @@ impl ServerHandler for PicasaServer {
                 serde_json::Map::new()
             };
 
+            let duplicates_schema = serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "keep": {
+                        "type": "string",
+                        "enum": ["largest", "oldest"],
+                        "default": "largest",
+                        "description": "Rule used to suggest the photo to keep in each group"
+                    }
+                }
+            });
+
+            let duplicates_schema_map = if let serde_json::Value::Object(obj) = duplicates_schema {
+                obj
+            } else {
+                serde_json::Map::new()
+            };
+
+            let enqueue_scans_schema = serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "library": {
+                        "type": "string",
+                        "description": "Name of the library to scan, all enabled libraries when omitted"
+                    }
+                }
+            });
+
+            let enqueue_scans_schema_map = if let serde_json::Value::Object(obj) = enqueue_scans_schema {
+                obj
+            } else {
+                serde_json::Map::new()
+            };
+
+            let job_status_schema = serde_json::json!({
+                "type": "object",
+                "properties": {}
+            });
+
+            let job_status_schema_map = if let serde_json::Value::Object(obj) = job_status_schema {
+                obj
+            } else {
+                serde_json::Map::new()
+            };
+
             Ok(ListToolsResult {
-                tools: vec![Tool {
-                    name: "search_photos".into(),
-                    description: Some("Search for photos using text queries, location filters, date ranges, and person filters".into()),
-                    input_schema: Arc::new(input_schema_map),
-                    annotations: None,
-                }],
+                tools: vec![
+                    Tool {
+                        name: "search_photos".into(),
+                        description: Some("Search for photos using text queries or photos similar to a given photo, location filters, date ranges, and person filters".into()),
+                        input_schema: Arc::new(input_schema_map),
+                        annotations: None,
+                    },
+                    Tool {
+                        name: "find_duplicates".into(),
+                        description: Some("List groups of photos with identical content, with the photo suggested to keep in each group".into()),
+                        input_schema: Arc::new(duplicates_schema_map),
+                        annotations: None,
+                    },
+                    Tool {
+                        name: "enqueue_library_scans".into(),
+                        description: Some("Enqueue background jobs scanning libraries, followed by hashing, EXIF extraction, embedding and face detection of new and changed photos".into()),
+                        input_schema: Arc::new(enqueue_scans_schema_map),
+                        annotations: None,
+                    },
+                    Tool {
+                        name: "get_job_status".into(),
+                        description: Some("Count the background indexing jobs of each pipeline stage by status".into()),
+                        input_schema: Arc::new(job_status_schema_map),
+                        annotations: None,
+                    },
+                ],
                 next_cursor: None,
             })
         }
@@ -139,9 +222,28 @@ impl ServerHandler for PicasaServer {
                         ErrorData::invalid_params(format!("Invalid search parameters: {}", e), None)
                     })?;
 
+                    let media_type = search_params
+                        .media_type
+                        .as_deref()
+                        .map(str::parse)
+                        .transpose()
+                        .map_err(|e: anyhow::Error| {
+                            ErrorData::invalid_params(format!("Invalid media type: {}", e), None)
+                        })?;
+
+                    let similar_to = match (search_params.similar_to, search_params.similar_to_image.clone()) {
+                        (Some(_), Some(_)) => {
+                            return Err(ErrorData::invalid_params("Only one of similar_to and similar_to_image can be given", None));
+                        }
+                        (Some(id), None) => Some(SimilarTo::Photo(id)),
+                        (None, Some(path)) => Some(SimilarTo::Image(path)),
+                        (None, None) => None,
+                    };
+
                     // Convert our search params to the core PhotoSearchParams format
                     let core_params = PhotoSearchParams {
                         text: search_params.text.clone(),
+                        similar_to: similar_to.clone(),
                         threshold: search_params.threshold,
                         country: search_params.country.clone(),
                         country_id: None,
@@ -151,8 +253,10 @@ impl ServerHandler for PicasaServer {
                         date_to: search_params.date_to.clone(),
                         person_ids: None,
                         person_match_mode: None,
+                        media_type,
                         page: search_params.page.unwrap_or(1),
                         per_page: search_params.per_page.unwrap_or(10),
+                        ..Default::default()
                     };
 
                     // Set up repositories and search service
@@ -161,33 +265,29 @@ impl ServerHandler for PicasaServer {
                     let person_repository = PgPersonRepository::new(self.db_pool.clone());
                     let face_repository = PgFaceRepository::new(self.db_pool.clone());
                     
-                    // Initialize text embedder (this might fail if model files are not available)
-                    let text_embedder = match ClipTextEmbedder::new(&self.config.clip_model) {
-                        Ok(embedder) => embedder,
-                        Err(e) => {
-                            // If embedder fails to initialize, we can still search without semantic text search
-                            eprintln!("Warning: Failed to initialize text embedder: {}", e);
-                            return Ok(CallToolResult {
-                                content: vec![Annotated {
-                                    raw: RawContent::Text(RawTextContent {
-                                        text: format!("Warning: Text embedder not available. Error: {}\n\nFalling back to basic photo search without semantic text queries.", e),
-                                    }),
-                                    annotations: None,
-                                }],
-                                is_error: Some(false),
-                            });
-                        }
-                    };
-
                     // Create and use the search service
                     let mut search_service = PhotoSearchService::new(
                         photo_repository,
                         geo_repository,
                         person_repository,
                         face_repository,
-                        text_embedder,
+                        &self.config.embeddings.model,
                     );
 
+                    // Texts are only embedded when searching photos matching a text
+                    if search_params.text.is_some() {
+                        let text_embedder = load_text_embedder(&self.config.embeddings)
+                            .map_err(|e| ErrorData::internal_error(format!("Failed to initialize text embedder: {}", e), None))?;
+                        search_service = search_service.with_text_embedder(text_embedder);
+                    }
+
+                    // Images are only embedded when searching photos similar to a file
+                    if let Some(SimilarTo::Image(_)) = similar_to {
+                        let image_embedder = ClipImageEmbedder::new(&self.config.embeddings, &self.config.embeddings.model)
+                            .map_err(|e| ErrorData::internal_error(format!("Failed to initialize image embedder: {}", e), None))?;
+                        search_service = search_service.with_image_embedder(Box::new(image_embedder));
+                    }
+
                     let search_result = match search_service.search(core_params) {
                         Ok(results) => results,
                         Err(e) => {
@@ -216,7 +316,8 @@ impl ServerHandler for PicasaServer {
                             "face_detection_completed": photo.face_detection_completed,
                             "country_id": photo.country_id,
                             "city_id": photo.city_id,
-                            "indexed_at": photo.indexed_at
+                            "indexed_at": photo.indexed_at,
+                            "media_type": photo.media_type.as_str()
                         }))
                         .collect();
                     
@@ -244,6 +345,153 @@ impl ServerHandler for PicasaServer {
                         is_error: Some(false),
                     })
                 }
+                "find_duplicates" => {
+                    let duplicates_params: FindDuplicatesParams = serde_json::from_value(
+                        serde_json::Value::Object(request.arguments.unwrap_or_default()),
+                    )
+                    .map_err(|e| {
+                        ErrorData::invalid_params(format!("Invalid duplicates parameters: {}", e), None)
+                    })?;
+
+                    let strategy: KeeperStrategy = duplicates_params
+                        .keep
+                        .as_deref()
+                        .map(str::parse)
+                        .transpose()
+                        .map_err(|e: anyhow::Error| {
+                            ErrorData::invalid_params(format!("Invalid keeper strategy: {}", e), None)
+                        })?
+                        .unwrap_or_default();
+
+                    let photo_repository = PgPhotoRepository::new(self.db_pool.clone());
+                    let mut duplicate_service = DuplicateService::new(photo_repository, NoOpProgressReporter);
+
+                    let groups = duplicate_service
+                        .find_groups(&KeeperPreference {
+                            strategy,
+                            preferred_library_id: None,
+                        })
+                        .map_err(|e| {
+                            ErrorData::internal_error(format!("Finding duplicates failed: {}", e), None)
+                        })?;
+
+                    let formatted_groups: Vec<serde_json::Value> = groups.iter()
+                        .map(|group| serde_json::json!({
+                            "hash": group.hash,
+                            "keeper": {
+                                "id": group.keeper.id,
+                                "path": group.keeper.path,
+                                "file_size": group.keeper.file_size
+                            },
+                            "duplicates": group.duplicates.iter()
+                                .map(|photo| serde_json::json!({
+                                    "id": photo.id,
+                                    "path": photo.path,
+                                    "file_size": photo.file_size
+                                }))
+                                .collect::<Vec<_>>()
+                        }))
+                        .collect();
+
+                    Ok(CallToolResult {
+                        content: vec![Annotated {
+                            raw: RawContent::Text(RawTextContent {
+                                text: format!(
+                                    "Found {} groups of duplicate photos:\n{}",
+                                    groups.len(),
+                                    serde_json::to_string_pretty(&formatted_groups).unwrap()
+                                ),
+                            }),
+                            annotations: None,
+                        }],
+                        is_error: Some(false),
+                    })
+                }
+                "enqueue_library_scans" => {
+                    let enqueue_params: EnqueueLibraryScansParams = serde_json::from_value(
+                        serde_json::Value::Object(request.arguments.unwrap_or_default()),
+                    )
+                    .map_err(|e| {
+                        ErrorData::invalid_params(format!("Invalid enqueue parameters: {}", e), None)
+                    })?;
+
+                    let mut library_service =
+                        LibraryService::new(PgLibraryRepository::new(self.db_pool.clone()));
+                    let libraries = match &enqueue_params.library {
+                        Some(name) => library_service.get(name).map(|library| vec![library]),
+                        None => library_service.list().map(|libraries| {
+                            libraries.into_iter().filter(|library| library.enabled).collect()
+                        }),
+                    }
+                    .map_err(|e| {
+                        ErrorData::invalid_params(format!("Invalid library: {}", e), None)
+                    })?;
+
+                    let mut job_service = JobService::new(
+                        PgJobRepository::new(self.db_pool.clone()),
+                        NoOpProgressReporter,
+                        &self.config.jobs,
+                    );
+
+                    let count = job_service
+                        .enqueue(
+                            libraries
+                                .iter()
+                                .map(|library| NewJob::library(JobStage::Scan, library.id))
+                                .collect(),
+                        )
+                        .map_err(|e| {
+                            ErrorData::internal_error(format!("Enqueuing scans failed: {}", e), None)
+                        })?;
+
+                    Ok(CallToolResult {
+                        content: vec![Annotated {
+                            raw: RawContent::Text(RawTextContent {
+                                text: format!(
+                                    "Enqueued {} scan jobs for libraries: {}",
+                                    count,
+                                    libraries.iter().map(|library| library.name.as_str()).collect::<Vec<_>>().join(", ")
+                                ),
+                            }),
+                            annotations: None,
+                        }],
+                        is_error: Some(false),
+                    })
+                }
+                "get_job_status" => {
+                    let mut job_service = JobService::new(
+                        PgJobRepository::new(self.db_pool.clone()),
+                        NoOpProgressReporter,
+                        &self.config.jobs,
+                    );
+
+                    let summaries = job_service.summarize().map_err(|e| {
+                        ErrorData::internal_error(format!("Counting jobs failed: {}", e), None)
+                    })?;
+
+                    let formatted_summaries: Vec<serde_json::Value> = summaries.iter()
+                        .map(|summary| serde_json::json!({
+                            "stage": summary.stage.as_str(),
+                            "pending": summary.pending,
+                            "running": summary.running,
+                            "done": summary.done,
+                            "failed": summary.failed
+                        }))
+                        .collect();
+
+                    Ok(CallToolResult {
+                        content: vec![Annotated {
+                            raw: RawContent::Text(RawTextContent {
+                                text: format!(
+                                    "Indexing jobs by stage:\n{}",
+                                    serde_json::to_string_pretty(&formatted_summaries).unwrap()
+                                ),
+                            }),
+                            annotations: None,
+                        }],
+                        is_error: Some(false),
+                    })
+                }
                 _ => Err(ErrorData::method_not_found::<CallToolRequestMethod>()),
             }
         }
@@ -253,15 +501,28 @@ impl ServerHandler for PicasaServer {
 #[derive(Debug, Deserialize, Serialize)]
 struct SearchPhotoParams {
     text: Option<String>,
+    similar_to: Option<i32>,
+    similar_to_image: Option<String>,
     threshold: Option<f32>,
     country: Option<String>,
     city: Option<String>,
     date_from: Option<String>,
     date_to: Option<String>,
+    media_type: Option<String>,
     page: Option<u32>,
     per_page: Option<u32>,
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+struct FindDuplicatesParams {
+    keep: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct EnqueueLibraryScansParams {
+    library: Option<String>,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let server = PicasaServer::new()
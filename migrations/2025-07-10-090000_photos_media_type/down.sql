@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS photos_media_type_idx;
+
+ALTER TABLE photos DROP COLUMN IF EXISTS media_type;
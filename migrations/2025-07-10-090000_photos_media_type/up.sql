@@ -0,0 +1,3 @@
+ALTER TABLE photos ADD COLUMN IF NOT EXISTS media_type TEXT NOT NULL DEFAULT 'photo' CHECK (media_type IN ('photo', 'video'));
+
+CREATE INDEX IF NOT EXISTS photos_media_type_idx ON photos(media_type);
@@ -0,0 +1,3 @@
+DROP TRIGGER IF EXISTS update_libraries_updated_at_trigger ON libraries;
+
+DROP TABLE IF EXISTS libraries;
@@ -0,0 +1,24 @@
+CREATE TABLE IF NOT EXISTS libraries (
+    id SERIAL PRIMARY KEY,
+
+    name TEXT NOT NULL UNIQUE,
+
+    -- Absolute path of the library root, without trailing separator
+    root_path TEXT NOT NULL UNIQUE,
+
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+
+    -- Scan options
+    with_exif BOOLEAN NOT NULL DEFAULT FALSE,
+    with_hash BOOLEAN NOT NULL DEFAULT FALSE,
+    include TEXT[] NOT NULL DEFAULT '{}',
+    exclude TEXT[] NOT NULL DEFAULT '{}',
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER update_libraries_updated_at_trigger
+    BEFORE UPDATE ON libraries
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
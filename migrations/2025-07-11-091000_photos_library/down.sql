@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS photos_library_id_idx;
+
+ALTER TABLE photos DROP COLUMN IF EXISTS relative_path;
+ALTER TABLE photos DROP COLUMN IF EXISTS library_id;
@@ -0,0 +1,4 @@
+ALTER TABLE photos ADD COLUMN IF NOT EXISTS library_id INTEGER REFERENCES libraries(id) ON DELETE CASCADE;
+ALTER TABLE photos ADD COLUMN IF NOT EXISTS relative_path TEXT;
+
+CREATE INDEX IF NOT EXISTS photos_library_id_idx ON photos(library_id);
@@ -0,0 +1,3 @@
+DROP TRIGGER IF EXISTS relocate_library_photos_trigger ON libraries;
+
+DROP FUNCTION IF EXISTS relocate_library_photos();
@@ -0,0 +1,22 @@
+DROP TRIGGER IF EXISTS relocate_library_photos_trigger ON libraries;
+
+DROP FUNCTION IF EXISTS relocate_library_photos();
+
+-- Roots ending with a separator, such as the filesystem root, would build paths with "//"
+CREATE OR REPLACE FUNCTION relocate_library_photos()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF OLD.root_path IS DISTINCT FROM NEW.root_path THEN
+        UPDATE photos
+        SET path = rtrim(NEW.root_path, '/') || '/' || relative_path
+        WHERE library_id = NEW.id AND relative_path IS NOT NULL;
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER relocate_library_photos_trigger
+    AFTER UPDATE ON libraries
+    FOR EACH ROW
+    EXECUTE FUNCTION relocate_library_photos();
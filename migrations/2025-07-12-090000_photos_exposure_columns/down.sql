@@ -0,0 +1,9 @@
+ALTER TABLE photos DROP COLUMN IF EXISTS offset_time;
+ALTER TABLE photos DROP COLUMN IF EXISTS gps_heading;
+ALTER TABLE photos DROP COLUMN IF EXISTS altitude;
+ALTER TABLE photos DROP COLUMN IF EXISTS flash;
+ALTER TABLE photos DROP COLUMN IF EXISTS focal_length_35mm;
+ALTER TABLE photos DROP COLUMN IF EXISTS focal_length;
+ALTER TABLE photos DROP COLUMN IF EXISTS iso;
+ALTER TABLE photos DROP COLUMN IF EXISTS exposure_time;
+ALTER TABLE photos DROP COLUMN IF EXISTS aperture;
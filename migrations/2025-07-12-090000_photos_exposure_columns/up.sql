@@ -0,0 +1,14 @@
+-- Exposure settings
+ALTER TABLE photos ADD COLUMN IF NOT EXISTS aperture REAL;
+ALTER TABLE photos ADD COLUMN IF NOT EXISTS exposure_time REAL;
+ALTER TABLE photos ADD COLUMN IF NOT EXISTS iso INTEGER;
+ALTER TABLE photos ADD COLUMN IF NOT EXISTS focal_length REAL;
+ALTER TABLE photos ADD COLUMN IF NOT EXISTS focal_length_35mm INTEGER;
+ALTER TABLE photos ADD COLUMN IF NOT EXISTS flash BOOLEAN;
+
+-- GPS altitude in meters, negative below sea level, and heading in degrees
+ALTER TABLE photos ADD COLUMN IF NOT EXISTS altitude REAL;
+ALTER TABLE photos ADD COLUMN IF NOT EXISTS gps_heading REAL;
+
+-- UTC offset of the original date, such as +02:00
+ALTER TABLE photos ADD COLUMN IF NOT EXISTS offset_time TEXT;
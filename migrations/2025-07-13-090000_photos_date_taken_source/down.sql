@@ -0,0 +1,2 @@
+ALTER TABLE photos DROP COLUMN IF EXISTS date_taken_source;
+ALTER TABLE photos DROP COLUMN IF EXISTS date_taken_timezone;
@@ -0,0 +1,6 @@
+-- Timezone used to convert the local capture time to UTC, and where it comes from
+ALTER TABLE photos ADD COLUMN IF NOT EXISTS date_taken_timezone TEXT;
+ALTER TABLE photos ADD COLUMN IF NOT EXISTS date_taken_source TEXT CHECK (date_taken_source IN ('offset', 'gps_timezone', 'default_timezone'));
+
+-- Photos indexed so far only had a UTC capture time when their metadata recorded an offset
+UPDATE photos SET date_taken_source = 'offset' WHERE date_taken_utc IS NOT NULL;
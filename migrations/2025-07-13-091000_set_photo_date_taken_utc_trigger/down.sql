@@ -0,0 +1,3 @@
+DROP TRIGGER IF EXISTS set_photo_date_taken_utc_trigger ON photos;
+
+DROP FUNCTION IF EXISTS set_photo_date_taken_utc();
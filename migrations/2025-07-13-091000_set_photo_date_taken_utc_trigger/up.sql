@@ -0,0 +1,38 @@
+DROP TRIGGER IF EXISTS set_photo_date_taken_utc_trigger ON photos;
+
+DROP FUNCTION IF EXISTS set_photo_date_taken_utc();
+CREATE OR REPLACE FUNCTION set_photo_date_taken_utc()
+RETURNS TRIGGER AS $$
+DECLARE
+    city_timezone TEXT;
+BEGIN
+    -- Capture times with an offset recorded in the metadata are already in UTC
+    IF NEW.date_taken_local IS NULL OR NEW.date_taken_source = 'offset' THEN
+        RETURN NEW;
+    END IF;
+
+    IF NEW.city_id IS NOT NULL THEN
+        SELECT NULLIF(cities.timezone, '')
+        INTO city_timezone
+        FROM cities
+        WHERE cities.geonameid = NEW.city_id;
+    END IF;
+
+    IF city_timezone IS NOT NULL THEN
+        NEW.date_taken_timezone := city_timezone;
+        NEW.date_taken_source := 'gps_timezone';
+    END IF;
+
+    IF NEW.date_taken_timezone IS NOT NULL THEN
+        NEW.date_taken_utc := NEW.date_taken_local AT TIME ZONE NEW.date_taken_timezone;
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Named to run after set_photo_city_trigger, which matches the city of the GPS point
+CREATE TRIGGER set_photo_date_taken_utc_trigger
+    BEFORE INSERT OR UPDATE ON photos
+    FOR EACH ROW
+    EXECUTE FUNCTION set_photo_date_taken_utc();
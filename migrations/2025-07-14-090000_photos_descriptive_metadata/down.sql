@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS photos_keywords_idx;
+
+ALTER TABLE photos DROP COLUMN IF EXISTS face_regions;
+ALTER TABLE photos DROP COLUMN IF EXISTS caption;
+ALTER TABLE photos DROP COLUMN IF EXISTS title;
+ALTER TABLE photos DROP COLUMN IF EXISTS rating;
+ALTER TABLE photos DROP COLUMN IF EXISTS keywords;
@@ -0,0 +1,10 @@
+-- Descriptive metadata read from XMP sidecars and embedded XMP/IPTC
+ALTER TABLE photos ADD COLUMN IF NOT EXISTS keywords TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE photos ADD COLUMN IF NOT EXISTS rating INTEGER;
+ALTER TABLE photos ADD COLUMN IF NOT EXISTS title TEXT;
+ALTER TABLE photos ADD COLUMN IF NOT EXISTS caption TEXT;
+
+-- Named face regions, in pixels: [{"name", "x", "y", "width", "height"}]
+ALTER TABLE photos ADD COLUMN IF NOT EXISTS face_regions JSONB;
+
+CREATE INDEX IF NOT EXISTS photos_keywords_idx ON photos USING gin (keywords);
@@ -0,0 +1,2 @@
+-- Faces are either found by face detection or imported from metadata regions
+ALTER TABLE faces ADD COLUMN IF NOT EXISTS source TEXT NOT NULL DEFAULT 'detection' CHECK (source IN ('detection', 'xmp'));
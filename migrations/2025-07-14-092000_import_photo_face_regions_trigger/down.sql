@@ -0,0 +1,3 @@
+DROP TRIGGER IF EXISTS import_photo_face_regions_trigger ON photos;
+
+DROP FUNCTION IF EXISTS import_photo_face_regions();
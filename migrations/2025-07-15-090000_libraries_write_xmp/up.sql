@@ -0,0 +1,2 @@
+-- Opt-in to writing curated metadata back to the XMP sidecars of the library photos
+ALTER TABLE libraries ADD COLUMN IF NOT EXISTS write_xmp BOOLEAN NOT NULL DEFAULT FALSE;
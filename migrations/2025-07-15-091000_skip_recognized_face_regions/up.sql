@@ -0,0 +1,52 @@
+CREATE OR REPLACE FUNCTION import_photo_face_regions()
+RETURNS TRIGGER AS $$
+DECLARE
+    region JSONB;
+    region_person_id INTEGER;
+BEGIN
+    IF TG_OP = 'UPDATE' AND NEW.face_regions IS NOT DISTINCT FROM OLD.face_regions THEN
+        RETURN NULL;
+    END IF;
+
+    DELETE FROM faces WHERE faces.photo_id = NEW.id AND faces.source = 'xmp';
+
+    FOR region IN SELECT jsonb_array_elements(COALESCE(NEW.face_regions, '[]'::JSONB))
+    LOOP
+        SELECT people.id
+        INTO region_person_id
+        FROM people
+        WHERE people.name = region->>'name'
+        ORDER BY people.id
+        LIMIT 1;
+
+        IF region_person_id IS NULL THEN
+            INSERT INTO people (name)
+            VALUES (region->>'name')
+            RETURNING id INTO region_person_id;
+        END IF;
+
+        -- Regions written back from faces recognized in picasa-rs are already known
+        CONTINUE WHEN EXISTS (
+            SELECT 1
+            FROM faces
+            WHERE faces.photo_id = NEW.id
+                AND faces.person_id = region_person_id
+                AND faces.source = 'detection'
+        );
+
+        INSERT INTO faces (photo_id, person_id, bbox_x, bbox_y, bbox_width, bbox_height, confidence, source)
+        VALUES (
+            NEW.id,
+            region_person_id,
+            (region->>'x')::INTEGER,
+            (region->>'y')::INTEGER,
+            (region->>'width')::INTEGER,
+            (region->>'height')::INTEGER,
+            1.0,
+            'xmp'
+        );
+    END LOOP;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
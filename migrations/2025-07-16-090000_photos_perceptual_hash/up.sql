@@ -0,0 +1,2 @@
+-- 64-bit difference hash (dHash) of the image, to find resized or re-encoded copies
+ALTER TABLE photos ADD COLUMN IF NOT EXISTS perceptual_hash BIGINT;
@@ -0,0 +1,3 @@
+DROP TRIGGER IF EXISTS update_jobs_updated_at_trigger ON jobs;
+
+DROP TABLE IF EXISTS jobs;
@@ -0,0 +1,34 @@
+CREATE TABLE IF NOT EXISTS jobs (
+    id SERIAL PRIMARY KEY,
+
+    -- Pipeline stage: scan, hash, exif, embed, detect_faces or recognize
+    stage TEXT NOT NULL,
+
+    -- Target of the job: a library for scans, a photo for per-photo stages, none for recognition
+    library_id INTEGER REFERENCES libraries(id) ON DELETE CASCADE,
+    photo_id INTEGER REFERENCES photos(id) ON DELETE CASCADE,
+
+    -- pending, running, done or failed
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+
+    -- Pending jobs are not claimed before this time, to delay retries
+    run_after TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- A single job per stage and target, enqueued again in place
+CREATE UNIQUE INDEX IF NOT EXISTS jobs_stage_target_idx
+    ON jobs (stage, COALESCE(library_id, 0), COALESCE(photo_id, 0));
+
+CREATE INDEX IF NOT EXISTS jobs_pending_idx ON jobs (stage, run_after) WHERE status = 'pending';
+
+CREATE INDEX IF NOT EXISTS jobs_photo_id_idx ON jobs (photo_id);
+
+CREATE TRIGGER update_jobs_updated_at_trigger
+    BEFORE UPDATE ON jobs
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS photo_errors (
+    id SERIAL PRIMARY KEY,
+    photo_id INTEGER NOT NULL REFERENCES photos(id) ON DELETE CASCADE,
+
+    -- Pipeline stage which failed: hash, exif, embed or detect_faces
+    stage TEXT NOT NULL,
+
+    -- Error of the last failed attempt
+    error TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 1,
+    last_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE (photo_id, stage)
+);
+
+CREATE INDEX IF NOT EXISTS photo_errors_stage_idx ON photo_errors (stage);
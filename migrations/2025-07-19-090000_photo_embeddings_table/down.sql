@@ -0,0 +1,33 @@
+DROP TRIGGER IF EXISTS delete_embeddings_on_hash_change_trigger ON photos;
+
+DROP FUNCTION IF EXISTS delete_embeddings_on_hash_change ();
+
+ALTER TABLE photos ADD COLUMN embedding VECTOR(512);
+
+UPDATE photos
+SET embedding = photo_embeddings.embedding
+FROM photo_embeddings
+WHERE photo_embeddings.photo_id = photos.id
+  AND photo_embeddings.model = 'clip-vit-base-patch32';
+
+CREATE INDEX IF NOT EXISTS photos_embedding_cosine_idx ON photos USING hnsw (embedding vector_cosine_ops) WITH (m = 16, ef_construction = 64);
+
+CREATE OR REPLACE FUNCTION clear_embedding_on_hash_change()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF OLD.hash IS NOT NULL AND
+       NEW.hash IS NOT NULL AND
+       OLD.hash IS DISTINCT FROM NEW.hash THEN
+        NEW.embedding := NULL;
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER clear_embedding_on_hash_change_trigger
+    BEFORE UPDATE ON photos
+    FOR EACH ROW
+    EXECUTE FUNCTION clear_embedding_on_hash_change();
+
+DROP TABLE IF EXISTS photo_embeddings;
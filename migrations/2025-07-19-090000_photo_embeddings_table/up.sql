@@ -0,0 +1,49 @@
+-- Embeddings are stored per model, so that a new model can be backfilled while the
+-- previous one keeps serving searches.
+CREATE TABLE IF NOT EXISTS photo_embeddings (
+    photo_id INTEGER NOT NULL REFERENCES photos(id) ON DELETE CASCADE,
+    model TEXT NOT NULL,
+    embedding VECTOR NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (photo_id, model)
+);
+
+CREATE INDEX IF NOT EXISTS photo_embeddings_model_idx ON photo_embeddings (model);
+
+-- Embeddings of a model share the same dimensions, so each model gets a partial HNSW index
+-- over its embeddings cast to them. Searches cast embeddings the same way to use the index.
+-- Indexes of the models registered later are created by `embed` runs.
+CREATE INDEX IF NOT EXISTS photo_embeddings_clip_vit_base_patch32_idx ON photo_embeddings
+USING hnsw ((embedding::vector(512)) vector_cosine_ops)
+WHERE model = 'clip-vit-base-patch32';
+
+INSERT INTO photo_embeddings (photo_id, model, embedding)
+SELECT id, 'clip-vit-base-patch32', embedding
+FROM photos
+WHERE embedding IS NOT NULL;
+
+DROP TRIGGER IF EXISTS clear_embedding_on_hash_change_trigger ON photos;
+
+DROP FUNCTION IF EXISTS clear_embedding_on_hash_change ();
+
+DROP INDEX IF EXISTS photos_embedding_cosine_idx;
+
+ALTER TABLE photos DROP COLUMN embedding;
+
+CREATE OR REPLACE FUNCTION delete_embeddings_on_hash_change()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF OLD.hash IS NOT NULL AND
+       NEW.hash IS NOT NULL AND
+       OLD.hash IS DISTINCT FROM NEW.hash THEN
+        DELETE FROM photo_embeddings WHERE photo_id = NEW.id;
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER delete_embeddings_on_hash_change_trigger
+    AFTER UPDATE OF hash ON photos
+    FOR EACH ROW
+    EXECUTE FUNCTION delete_embeddings_on_hash_change();
@@ -0,0 +1,2 @@
+-- Opt-in to removing photos whose file disappeared when the library is scanned by the job queue
+ALTER TABLE libraries ADD COLUMN IF NOT EXISTS prune BOOLEAN NOT NULL DEFAULT FALSE;
@@ -1,8 +1,8 @@
+import base64
 from typing import List
 
 import cv2
 import numpy as np
-import pillow_heif
 from insightface.app import FaceAnalysis
 
 from models import BoundingBox, Face
@@ -15,18 +15,12 @@ class FaceDetectionService:
         )  # Use 'CUDAExecutionProvider' for GPU
         self.app.prepare(ctx_id=-1)  # ctx_id=-1 for CPU, 0 for GPU
 
-    def detect_faces(self, image_path: str) -> List[Face]:
-        img = None
-        if image_path.lower().endswith(".heic"):
-            heif_file = pillow_heif.open_heif(
-                image_path, convert_hdr_to_8bit=False, bgr_mode=True
-            )
-            img = np.asarray(heif_file[0])
-        else:
-            img = cv2.imread(image_path)
+    def detect_faces(self, image_data: str) -> List[Face]:
+        buffer = np.frombuffer(base64.b64decode(image_data), dtype=np.uint8)
+        img = cv2.imdecode(buffer, cv2.IMREAD_COLOR)
 
         if img is None:
-            raise ValueError(f"Could not read image: {image_path}")
+            raise ValueError("Could not decode image")
 
         faces = self.app.get(img)
 
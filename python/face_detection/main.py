@@ -11,7 +11,7 @@ face_detection_service = FaceDetectionService()
 @app.post("/detect-faces", response_model=DetectFacesResponse)
 async def detect_faces(request: DetectFacesRequest):
     try:
-        detected_faces = face_detection_service.detect_faces(request.image_path)
+        detected_faces = face_detection_service.detect_faces(request.image_data)
         return DetectFacesResponse(faces=detected_faces)
     except ValueError as e:
         raise HTTPException(status_code=400, detail=str(e))
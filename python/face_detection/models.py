@@ -4,7 +4,8 @@ from pydantic import BaseModel
 
 
 class DetectFacesRequest(BaseModel):
-    image_path: str
+    # Base64-encoded JPEG, already in its displayed orientation
+    image_data: str
 
 
 class BoundingBox(BaseModel):